@@ -0,0 +1,231 @@
+//! Build contents report for `takobull about --features`
+//!
+//! Lists the Cargo features compiled into this binary, the provider and
+//! channel backends they provide, and approximate memory footprints of the
+//! major subsystems, so fleet operators can tell exactly what a build holds.
+//! Footprints are estimates derived from the subsystems' fixed capacities;
+//! the binary size and resident memory are measured.
+
+use crate::channels::outbox;
+use crate::llm::client::PROVIDERS;
+use crate::logging::buffer;
+use crate::runtime::RuntimeConfig;
+use crate::scheduler::history::{DEFAULT_HISTORY_RUNS, MAX_OUTPUT_CHARS};
+use serde::Serialize;
+use std::path::Path;
+
+/// Assumed size of a formatted log line
+const LOG_LINE_BYTES: u64 = 256;
+
+/// Assumed size of an outbox entry, including the message
+const OUTBOX_ENTRY_BYTES: u64 = 1024;
+
+/// Assumed size of a loaded BPE rank (byte vector, rank, and map overhead)
+const TOKEN_ENTRY_BYTES: u64 = 56;
+
+/// A Cargo feature and whether this build has it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureFlag {
+    pub name: &'static str,
+    pub enabled: bool,
+}
+
+/// Approximate memory held by a subsystem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Footprint {
+    pub subsystem: &'static str,
+    pub bytes: u64,
+    /// How the figure was arrived at
+    pub basis: String,
+}
+
+/// What this build contains
+#[derive(Debug, Clone, Serialize)]
+pub struct AboutReport {
+    pub version: &'static str,
+    /// `<arch>-<os>`
+    pub target: String,
+    /// `release` or `debug`
+    pub profile: &'static str,
+    pub features: Vec<FeatureFlag>,
+    pub providers: Vec<&'static str>,
+    /// Channel integrations compiled in
+    pub channels: Vec<&'static str>,
+    pub footprints: Vec<Footprint>,
+    /// Size of the running executable
+    pub binary_bytes: Option<u64>,
+    /// Resident memory of this process (Linux only)
+    pub rss_bytes: Option<u64>,
+}
+
+macro_rules! features {
+    ($($name:literal),* $(,)?) => {
+        vec![$(FeatureFlag { name: $name, enabled: cfg!(feature = $name) }),*]
+    };
+}
+
+/// Every Cargo feature of the crate and whether it is compiled in
+pub fn features() -> Vec<FeatureFlag> {
+    features![
+        "channels-telegram",
+        "channels-discord",
+        "channels-dingtalk",
+        "channels-line",
+        "channels-qq",
+        "channels-whatsapp",
+        "providers-openrouter",
+        "providers-claude",
+        "providers-openai",
+        "providers-gemini",
+        "providers-zhipu",
+        "providers-deepseek",
+        "providers-groq",
+        "tools-web-search",
+        "tools-filesystem",
+        "tools-shell",
+        "tools-web-access",
+        "tools-hardware",
+        "tools-message",
+        "tools-cron",
+        "webhooks",
+        "mdns",
+        "native-tls",
+        "rustls",
+        "low-footprint",
+        "pi-zero",
+    ]
+}
+
+/// Footprints of the bounded subsystems, plus tokenizer tables loaded from
+/// `tokenizer_dir` if given
+pub fn footprints(tokenizer_dir: Option<&Path>) -> Vec<Footprint> {
+    let runtime = RuntimeConfig::default();
+    let workers = runtime.worker_threads as u64;
+    let stack = runtime.stack_size as u64;
+    let mut footprints = vec![
+        Footprint {
+            subsystem: "runtime workers",
+            bytes: workers * stack,
+            basis: format!(
+                "{} threads × {} KiB stack reserved{}",
+                workers,
+                stack / 1024,
+                if runtime.is_low_footprint() { " (low-footprint)" } else { "" }
+            ),
+        },
+        Footprint {
+            subsystem: "log buffer",
+            bytes: buffer::DEFAULT_CAPACITY as u64 * LOG_LINE_BYTES,
+            basis: format!("{} lines × ~{} B", buffer::DEFAULT_CAPACITY, LOG_LINE_BYTES),
+        },
+        Footprint {
+            subsystem: "job history",
+            bytes: (DEFAULT_HISTORY_RUNS * MAX_OUTPUT_CHARS * 4) as u64,
+            basis: format!(
+                "{} runs × {} chars per job while loaded",
+                DEFAULT_HISTORY_RUNS, MAX_OUTPUT_CHARS
+            ),
+        },
+        Footprint {
+            subsystem: "delivery outbox",
+            bytes: outbox::MAX_ENTRIES as u64 * OUTBOX_ENTRY_BYTES,
+            basis: format!("{} entries × ~{} KiB while loaded", outbox::MAX_ENTRIES, OUTBOX_ENTRY_BYTES / 1024),
+        },
+    ];
+    if let Some(dir) = tokenizer_dir {
+        let tokens: u64 = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "tiktoken"))
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .map(|contents| contents.lines().filter(|line| !line.trim().is_empty()).count() as u64)
+            .sum();
+        if tokens > 0 {
+            footprints.push(Footprint {
+                subsystem: "tokenizer tables",
+                bytes: tokens * TOKEN_ENTRY_BYTES,
+                basis: format!("{} tokens × ~{} B", tokens, TOKEN_ENTRY_BYTES),
+            });
+        }
+    }
+    footprints
+}
+
+impl AboutReport {
+    /// Describe the running build
+    pub fn collect(tokenizer_dir: Option<&Path>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+            profile: if cfg!(debug_assertions) { "debug" } else { "release" },
+            features: features(),
+            providers: PROVIDERS.to_vec(),
+            channels: features()
+                .into_iter()
+                .filter(|f| f.enabled)
+                .filter_map(|f| f.name.strip_prefix("channels-"))
+                .collect(),
+            footprints: footprints(tokenizer_dir),
+            binary_bytes: std::env::current_exe()
+                .and_then(std::fs::metadata)
+                .map(|meta| meta.len())
+                .ok(),
+            rss_bytes: rss_bytes(),
+        }
+    }
+}
+
+/// Resident set size from `/proc/self/status`
+fn rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Human-readable size, e.g. `1.5 MiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_reflects_build() {
+        let report = AboutReport::collect(None);
+        let webhooks = report.features.iter().find(|f| f.name == "webhooks").unwrap();
+        assert_eq!(webhooks.enabled, cfg!(feature = "webhooks"));
+        assert_eq!(report.channels.contains(&"telegram"), cfg!(feature = "channels-telegram"));
+        assert!(report.providers.contains(&"anthropic"));
+        assert!(report.footprints.iter().any(|f| f.subsystem == "log buffer"));
+        assert!(report.binary_bytes.is_some());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("cl100k_base.tiktoken"), "IQ== 0\nIg== 1\n").unwrap();
+        let tokenizer = footprints(Some(dir.path())).pop().unwrap();
+        assert_eq!(tokenizer.subsystem, "tokenizer tables");
+        assert_eq!(tokenizer.bytes, 2 * TOKEN_ENTRY_BYTES);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
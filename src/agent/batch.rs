@@ -0,0 +1,166 @@
+//! Batch mode: run many prompts through the agent
+//!
+//! Prompts are read from JSONL (one `{"id": ..., "prompt": ...}` object per
+//! line) and executed with bounded concurrency on a [`TaskPool`]. Results are
+//! emitted in input order so the output file lines up with the input. Each
+//! prompt runs on its own, without earlier history.
+
+use super::executor::AgentExecutor;
+use crate::error::{Error, Result};
+use crate::runtime::TaskPool;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// One prompt from a batch input file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchItem {
+    /// Identifier copied to the result; defaults to the input line number
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(alias = "message")]
+    pub prompt: String,
+    /// User whose preferences apply (e.g. reply language)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+/// Outcome of one batch prompt, written as a line of the output file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<String>,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub duration_ms: u64,
+}
+
+/// Parse batch input, skipping blank lines
+pub fn parse_items(input: &str) -> Result<Vec<BatchItem>> {
+    let mut items = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut item: BatchItem = serde_json::from_str(line)
+            .map_err(|e| Error::serialization(format!("Batch line {}: {}", i + 1, e)))?;
+        if item.id.is_none() {
+            item.id = Some((i + 1).to_string());
+        }
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Run `items` through `executor`, at most `concurrency` at a time, passing
+/// each result to `on_result` in input order
+pub async fn run_batch<F>(
+    executor: Arc<AgentExecutor>,
+    items: Vec<BatchItem>,
+    concurrency: usize,
+    mut on_result: F,
+) -> Result<()>
+where
+    F: FnMut(BatchResult) -> Result<()>,
+{
+    let pool = TaskPool::new(concurrency.max(1));
+    let mut pending = VecDeque::new();
+
+    for item in items {
+        // The oldest task finishing frees its slot, so waiting on it keeps output ordered
+        while !pool.can_accept_task() {
+            match pending.pop_front() {
+                Some(handle) => on_result(join(handle).await?)?,
+                None => break,
+            }
+        }
+        let executor = Arc::clone(&executor);
+        pending.push_back(pool.spawn_task(run_item(executor, item))?);
+    }
+    for handle in pending {
+        on_result(join(handle).await?)?;
+    }
+    pool.shutdown(std::time::Duration::from_secs(1)).await
+}
+
+async fn join(handle: tokio::task::JoinHandle<BatchResult>) -> Result<BatchResult> {
+    handle
+        .await
+        .map_err(|e| Error::runtime(format!("Batch task failed: {}", e)))
+}
+
+async fn run_item(executor: Arc<AgentExecutor>, item: BatchItem) -> BatchResult {
+    let started = Instant::now();
+    let mut result = BatchResult {
+        id: item.id.unwrap_or_default(),
+        ..Default::default()
+    };
+    match executor
+        .execute_turn(&item.prompt, item.user_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+    {
+        Ok(turn) => {
+            result.response = Some(turn.response);
+            result.tool_calls = turn.tool_calls;
+            result.input_tokens = turn.input_tokens;
+            result.output_tokens = turn.output_tokens;
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LlmClient;
+    use crate::tools::ToolRegistry;
+
+    #[test]
+    fn test_parse_items() {
+        let input = "{\"id\": \"a\", \"prompt\": \"one\"}\n\n{\"message\": \"two\"}\n";
+        let items = parse_items(input).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id.as_deref(), Some("a"));
+        assert_eq!(items[1].id.as_deref(), Some("3"));
+        assert_eq!(items[1].prompt, "two");
+
+        let err = parse_items("{\"prompt\": \"ok\"}\nnot json").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_keeps_input_order() {
+        let executor = AgentExecutor::new(
+            LlmClient::new("openai", "test-model", "key", "http://127.0.0.1:9"),
+            ToolRegistry::new(),
+        );
+        let items = (1..=5)
+            .map(|i| BatchItem {
+                id: Some(i.to_string()),
+                prompt: format!("prompt {}", i),
+                user_id: None,
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        run_batch(Arc::new(executor), items, 2, |result| {
+            results.push(result);
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4", "5"]);
+        assert!(results.iter().all(|r| r.error.is_some() && r.response.is_none()));
+    }
+}
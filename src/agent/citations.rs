@@ -0,0 +1,46 @@
+//! Citation footers for answers built from web-sourced tool results
+
+/// Add `url` to `sources` unless it is already there
+pub fn add_source(sources: &mut Vec<String>, url: &str) {
+    let url = url.trim();
+    if (url.starts_with("http://") || url.starts_with("https://")) && !sources.iter().any(|s| s == url) {
+        sources.push(url.to_string());
+    }
+}
+
+/// Numbered sources footer for `response`, listing at most `max` sources the
+/// response does not already link
+pub fn footer(response: &str, sources: &[String], max: usize) -> Option<String> {
+    let uncited: Vec<&String> = sources
+        .iter()
+        .filter(|url| !response.contains(url.as_str()))
+        .take(max)
+        .collect();
+    if uncited.is_empty() {
+        return None;
+    }
+    let mut footer = String::from("\n\nSources:");
+    for (i, url) in uncited.iter().enumerate() {
+        footer.push_str(&format!("\n[{}] {}", i + 1, url));
+    }
+    Some(footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_footer_lists_new_sources() {
+        let mut sources = Vec::new();
+        for url in ["https://a.example/x", "not a url", "https://b.example/y", "https://a.example/x"] {
+            add_source(&mut sources, url);
+        }
+        assert_eq!(sources, vec!["https://a.example/x", "https://b.example/y"]);
+
+        let cited = footer("Rain is expected (see https://b.example/y).", &sources, 5).unwrap();
+        assert_eq!(cited, "\n\nSources:\n[1] https://a.example/x");
+        assert_eq!(footer("answer", &sources, 1).unwrap().matches("\n[").count(), 1);
+        assert_eq!(footer("answer", &[], 5), None);
+    }
+}
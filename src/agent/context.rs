@@ -36,3 +36,43 @@ pub struct AgentContext {
     pub available_tools: Vec<String>,
     pub metadata: ContextMetadata,
 }
+
+/// System prompt assembled from independent sections for a single request
+#[derive(Debug, Clone, Default)]
+pub struct SystemPrompt {
+    sections: Vec<String>,
+}
+
+impl SystemPrompt {
+    /// Create an empty system prompt
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a section
+    pub fn push(&mut self, section: impl Into<String>) {
+        let section = section.into();
+        if !section.trim().is_empty() {
+            self.sections.push(section);
+        }
+    }
+
+    /// Append all sections of `other`
+    pub fn extend(&mut self, other: SystemPrompt) {
+        self.sections.extend(other.sections);
+    }
+
+    /// Whether no sections have been added
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Render the sections separated by blank lines, or `None` if empty
+    pub fn render(&self) -> Option<String> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.sections.join("\n\n"))
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Council mode: several models answer, the best answer wins
+//!
+//! [`Council::ask`] sends the prompt to every member model concurrently.
+//! With a judge configured, the judge reads all answers and writes the final
+//! one. Without a judge, or when the judge fails, the answer sharing the most
+//! words with the other answers is kept, on the theory that independent
+//! models agreeing are more likely to be right.
+
+use crate::error::{Error, Result};
+use crate::llm::{GenerationOptions, LlmClient};
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+/// Fewest member models a council can have
+pub const MIN_MEMBERS: usize = 2;
+
+/// Most member models a council can have
+pub const MAX_MEMBERS: usize = 3;
+
+const JUDGE_INSTRUCTION: &str = "Several assistants answered the same question. \
+Write the single best final answer to the question: keep what the answers get right, \
+resolve disagreements in favor of the best-supported claim, and fix any mistakes. \
+Reply with the final answer only, without mentioning the assistants.";
+
+/// One member's answer, or why it has none
+#[derive(Debug, Clone)]
+pub struct MemberAnswer {
+    /// `provider/model`
+    pub member: String,
+    pub answer: std::result::Result<String, String>,
+}
+
+/// How the council arrived at its response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// The judge model (`provider/model`) wrote the response
+    Judged(String),
+    /// The answer at this index was kept by agreement with the others
+    Agreement(usize),
+}
+
+/// Outcome of a council run
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    pub response: String,
+    pub decision: Decision,
+    /// Answers in member order
+    pub answers: Vec<MemberAnswer>,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl Verdict {
+    /// One-line account, e.g. `3 of 3 members answered; synthesized by openai/gpt-4o`
+    pub fn summary(&self) -> String {
+        let answered = self.answers.iter().filter(|a| a.answer.is_ok()).count();
+        let decision = match &self.decision {
+            Decision::Judged(judge) => format!("synthesized by {}", judge),
+            Decision::Agreement(index) => format!("kept {} by agreement", self.answers[*index].member),
+        };
+        format!("{} of {} members answered; {}", answered, self.answers.len(), decision)
+    }
+}
+
+/// Models that answer together, with an optional judge
+pub struct Council {
+    members: Vec<LlmClient>,
+    judge: Option<LlmClient>,
+}
+
+impl Council {
+    /// Council of `members`, which must number [`MIN_MEMBERS`] to [`MAX_MEMBERS`]
+    pub fn new(members: Vec<LlmClient>) -> Result<Self> {
+        if !(MIN_MEMBERS..=MAX_MEMBERS).contains(&members.len()) {
+            return Err(Error::config(format!(
+                "Council mode needs {} to {} models under agents.council.members, found {}",
+                MIN_MEMBERS,
+                MAX_MEMBERS,
+                members.len()
+            )));
+        }
+        Ok(Self { members, judge: None })
+    }
+
+    /// Let `judge` write the final answer from the members' answers
+    pub fn with_judge(mut self, judge: LlmClient) -> Self {
+        self.judge = Some(judge);
+        self
+    }
+
+    /// Ask every member concurrently and settle on one response
+    pub async fn ask(&self, system: Option<&str>, message: &str, options: &GenerationOptions) -> Result<Verdict> {
+        let replies = futures::future::join_all(
+            self.members
+                .iter()
+                .map(|client| client.chat_with_options(system, &[], message, Vec::new(), options)),
+        )
+        .await;
+
+        let mut input_tokens = 0;
+        let mut output_tokens = 0;
+        let mut answers = Vec::new();
+        for (client, reply) in self.members.iter().zip(replies) {
+            let member = label(client);
+            let answer = match reply {
+                Ok(response) => {
+                    input_tokens += response.usage.input_tokens;
+                    output_tokens += response.usage.output_tokens;
+                    if response.content.trim().is_empty() {
+                        Err("empty answer".to_string())
+                    } else {
+                        Ok(response.content)
+                    }
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(reason) = &answer {
+                warn!("Council member {} did not answer: {}", member, reason);
+            }
+            answers.push(MemberAnswer { member, answer });
+        }
+
+        let Some(index) = most_agreed(&answers) else {
+            return Err(Error::llm_provider("No council member produced an answer"));
+        };
+
+        if let Some(judge) = &self.judge {
+            let prompt = judge_prompt(message, &answers);
+            match judge
+                .chat_with_options(Some(JUDGE_INSTRUCTION), &[], &prompt, Vec::new(), options)
+                .await
+            {
+                Ok(response) if !response.content.trim().is_empty() => {
+                    info!("Council answer synthesized by {}", label(judge));
+                    return Ok(Verdict {
+                        response: response.content,
+                        decision: Decision::Judged(label(judge)),
+                        answers,
+                        input_tokens: input_tokens + response.usage.input_tokens,
+                        output_tokens: output_tokens + response.usage.output_tokens,
+                    });
+                }
+                Ok(_) => warn!("Council judge {} returned an empty answer", label(judge)),
+                Err(e) => warn!("Council judge {} failed: {}", label(judge), e),
+            }
+        }
+
+        info!("Council kept the answer from {} by agreement", answers[index].member);
+        Ok(Verdict {
+            response: answers[index].answer.clone().unwrap_or_default(),
+            decision: Decision::Agreement(index),
+            answers,
+            input_tokens,
+            output_tokens,
+        })
+    }
+}
+
+fn label(client: &LlmClient) -> String {
+    format!("{}/{}", client.provider(), client.model())
+}
+
+fn judge_prompt(question: &str, answers: &[MemberAnswer]) -> String {
+    let mut prompt = format!("Question:\n{}", question);
+    for (i, answer) in answers.iter().filter_map(|a| a.answer.as_ref().ok()).enumerate() {
+        prompt.push_str(&format!("\n\nAnswer {}:\n{}", i + 1, answer));
+    }
+    prompt
+}
+
+/// Index of the answer sharing the most words with the other answers,
+/// preferring earlier members on ties
+fn most_agreed(answers: &[MemberAnswer]) -> Option<usize> {
+    let words: Vec<Option<HashSet<String>>> = answers
+        .iter()
+        .map(|a| {
+            a.answer.as_ref().ok().map(|text| {
+                text.split(|c: char| !c.is_alphanumeric())
+                    .filter(|w| !w.is_empty())
+                    .map(str::to_lowercase)
+                    .collect()
+            })
+        })
+        .collect();
+    let mut best: Option<(usize, f64)> = None;
+    for (i, own) in words.iter().enumerate() {
+        let Some(own) = own else { continue };
+        let score: f64 = words
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .filter_map(|(_, other)| other.as_ref())
+            .map(|other| {
+                let union = own.union(other).count();
+                if union == 0 {
+                    0.0
+                } else {
+                    own.intersection(other).count() as f64 / union as f64
+                }
+            })
+            .sum();
+        if best.is_none_or(|(_, top)| score > top) {
+            best = Some((i, score));
+        }
+    }
+    best.map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(member: &str, text: Option<&str>) -> MemberAnswer {
+        MemberAnswer {
+            member: member.to_string(),
+            answer: text.map(str::to_string).ok_or_else(|| "timeout".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_most_agreed_answer_wins() {
+        let answers = vec![
+            answer("a/odd", Some("The capital of Australia is Sydney.")),
+            answer("b/down", None),
+            answer("c/one", Some("Canberra is the capital of Australia.")),
+            answer("d/two", Some("The capital of Australia is Canberra, not Sydney.")),
+        ];
+        assert_eq!(most_agreed(&answers), Some(3));
+        assert_eq!(most_agreed(&answers[1..2]), None);
+
+        let verdict = Verdict {
+            response: String::new(),
+            decision: Decision::Agreement(3),
+            answers,
+            input_tokens: 0,
+            output_tokens: 0,
+        };
+        assert_eq!(verdict.summary(), "3 of 4 members answered; kept d/two by agreement");
+    }
+
+    #[test]
+    fn test_member_count_is_checked() {
+        let client = || LlmClient::new("openai", "m", "key", "http://127.0.0.1:9");
+        assert!(Council::new(vec![client()]).is_err());
+        assert!(Council::new(vec![client(), client()]).is_ok());
+        assert!(Council::new(vec![client(), client(), client(), client()]).is_err());
+    }
+}
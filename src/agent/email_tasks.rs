@@ -0,0 +1,430 @@
+//! Email-to-task pipeline
+//!
+//! New mail in the configured Maildir is classified on the summary route into
+//! the categories declared under `email_tasks.categories`. Mail in a category
+//! with `file: true` becomes a todo, and mail in a category with `digest: true`
+//! is listed in a daily digest sent at `digest_time` through the alert
+//! channel. The model only picks a category and phrases the task; what
+//! happens to each category is fixed by config. Mail the model cannot
+//! classify is listed in the digest as unclassified rather than dropped.
+
+use crate::channels::email::{Email, Maildir};
+use crate::config::{EmailCategory, EmailTasksConfig};
+use crate::error::{Error, Result};
+use crate::llm::ModelRouter;
+use crate::scheduler::JobNotifier;
+use crate::tools::TodoStore;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Pipeline state, relative to the workspace
+pub const STATE_FILE: &str = "state/email_tasks.json";
+
+/// Category of mail the model could not classify
+pub const UNCLASSIFIED: &str = "unclassified";
+
+/// An email waiting for the next digest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestEntry {
+    pub from: String,
+    pub subject: String,
+    pub category: String,
+    /// Summary of the todo filed for the email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todo: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestState {
+    last_digest: Option<NaiveDate>,
+    #[serde(default)]
+    pending: Vec<DigestEntry>,
+}
+
+/// The summary model's verdict on one email
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Classification {
+    category: String,
+    #[serde(default)]
+    task: Option<String>,
+    #[serde(default)]
+    due: Option<String>,
+}
+
+/// Classifies incoming mail, files todos, and sends the daily digest
+pub struct EmailTasks {
+    config: EmailTasksConfig,
+    inbox: Maildir,
+    router: ModelRouter,
+    todos: Arc<TodoStore>,
+    state_path: PathBuf,
+    digest_time: NaiveTime,
+}
+
+impl EmailTasks {
+    pub fn new(
+        workspace: impl AsRef<Path>,
+        config: EmailTasksConfig,
+        router: ModelRouter,
+        todos: Arc<TodoStore>,
+    ) -> Result<Self> {
+        let Some(maildir) = &config.maildir else {
+            return Err(Error::config("email_tasks.maildir is required"));
+        };
+        if config.categories.is_empty() {
+            return Err(Error::config("email_tasks.categories is empty"));
+        }
+        let digest_time = NaiveTime::parse_from_str(&config.digest_time, "%H:%M").map_err(|_| {
+            Error::config(format!(
+                "Invalid email_tasks.digest_time '{}', expected HH:MM",
+                config.digest_time
+            ))
+        })?;
+        Ok(Self {
+            inbox: Maildir::new(maildir),
+            config,
+            router,
+            todos,
+            state_path: workspace.as_ref().join(STATE_FILE),
+            digest_time,
+        })
+    }
+
+    /// Classify new mail and file todos, returning what was queued for the digest
+    pub async fn process(&self, today: NaiveDate) -> Result<Vec<DigestEntry>> {
+        let mut queued = Vec::new();
+        for email in self.inbox.take_new().await? {
+            let from = email.from.to_lowercase();
+            if self
+                .config
+                .ignore_senders
+                .iter()
+                .any(|sender| from.contains(&sender.to_lowercase()))
+            {
+                debug!("Ignoring email {} from {}", email.id, email.from);
+                continue;
+            }
+
+            let classification = self.classify(&email, today).await;
+            let category = self.category(&classification.category);
+            let mut todo = None;
+            if category.map(|c| c.file).unwrap_or(false) {
+                let title = classification
+                    .task
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|task| !task.is_empty())
+                    .unwrap_or(&email.subject);
+                let due = classification
+                    .due
+                    .as_deref()
+                    .and_then(|due| NaiveDate::parse_from_str(due, "%Y-%m-%d").ok());
+                let item = self
+                    .todos
+                    .add(title, Some(&email.subject), due, Some(&email.from))
+                    .await?;
+                info!("Filed todo {} from email {}", item.id, email.id);
+                todo = Some(item.summary());
+            }
+            if category.map(|c| c.digest).unwrap_or(true) {
+                queued.push(DigestEntry {
+                    from: email.from,
+                    subject: email.subject,
+                    category: category.map(|c| c.name.clone()).unwrap_or_else(|| UNCLASSIFIED.to_string()),
+                    todo,
+                });
+            }
+        }
+
+        if !queued.is_empty() {
+            let mut state = self.load_state().await?;
+            state.pending.extend(queued.iter().cloned());
+            self.save_state(&state).await?;
+        }
+        Ok(queued)
+    }
+
+    /// The digest, if one is due at `now`; pending entries are cleared once
+    /// it is taken, and at most one digest is taken per day
+    pub async fn take_digest(&self, now: NaiveDateTime) -> Result<Option<String>> {
+        if now.time() < self.digest_time {
+            return Ok(None);
+        }
+        let mut state = self.load_state().await?;
+        if state.last_digest == Some(now.date()) {
+            return Ok(None);
+        }
+        let digest = (!state.pending.is_empty()).then(|| format_digest(&state.pending, &self.config.categories));
+        state.last_digest = Some(now.date());
+        state.pending.clear();
+        self.save_state(&state).await?;
+        Ok(digest)
+    }
+
+    /// Check for mail every `check_interval_secs` until shutdown, sending
+    /// digests to `notifier`
+    pub async fn run(self, notifier: Arc<dyn JobNotifier>, mut shutdown: broadcast::Receiver<()>) {
+        let interval = Duration::from_secs(self.config.check_interval_secs.max(1));
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let now = Local::now().naive_local();
+                    if let Err(e) = self.process(now.date()).await {
+                        warn!("Failed to process incoming email: {}", e);
+                    }
+                    match self.take_digest(now).await {
+                        Ok(Some(digest)) => notifier.notify(&digest).await,
+                        Ok(None) => {}
+                        Err(e) => warn!("Failed to prepare the email digest: {}", e),
+                    }
+                }
+                _ = shutdown.recv() => {
+                    debug!("Email pipeline stopping");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn category(&self, name: &str) -> Option<&EmailCategory> {
+        self.config
+            .categories
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    async fn classify(&self, email: &Email, today: NaiveDate) -> Classification {
+        let text = format!(
+            "From: {}\nSubject: {}\nDate: {}\n\n{}",
+            email.from,
+            email.subject,
+            email.date.as_deref().unwrap_or("unknown"),
+            email.body
+        );
+        let reply = match self.router.summarize(&self.instruction(today), &text).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                warn!("Failed to classify email {}: {}", email.id, e);
+                return unclassified();
+            }
+        };
+        parse_classification(&reply).unwrap_or_else(|| {
+            warn!("Unreadable classification for email {}: {}", email.id, reply);
+            unclassified()
+        })
+    }
+
+    fn instruction(&self, today: NaiveDate) -> String {
+        let categories: Vec<String> = self
+            .config
+            .categories
+            .iter()
+            .map(|c| {
+                let task = if c.file { " (task)" } else { "" };
+                format!("- {}{}: {}", c.name, task, c.description)
+            })
+            .collect();
+        format!(
+            "Classify the email below into exactly one of these categories:\n{}\n\n\
+             For a category marked (task), also phrase what the email asks of the reader as a short \
+             imperative task, and give its due date if the email states one. Today is {}.\n\
+             Reply with JSON only: {{\"category\": \"<name>\", \"task\": \"<task or null>\", \
+             \"due\": \"<YYYY-MM-DD or null>\"}}",
+            categories.join("\n"),
+            today
+        )
+    }
+
+    async fn load_state(&self) -> Result<DigestState> {
+        if !self.state_path.exists() {
+            return Ok(DigestState::default());
+        }
+        let contents = tokio::fs::read_to_string(&self.state_path).await?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    async fn save_state(&self, state: &DigestState) -> Result<()> {
+        if let Some(parent) = self.state_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = self.state_path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string(state)?).await?;
+        tokio::fs::rename(&tmp_path, &self.state_path).await?;
+        Ok(())
+    }
+}
+
+fn unclassified() -> Classification {
+    Classification {
+        category: UNCLASSIFIED.to_string(),
+        task: None,
+        due: None,
+    }
+}
+
+/// The JSON object in a model reply, tolerating surrounding prose or fences
+fn parse_classification(reply: &str) -> Option<Classification> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+/// Digest text, grouped by category in config order with unclassified mail last
+fn format_digest(entries: &[DigestEntry], categories: &[EmailCategory]) -> String {
+    let mut text = format!(
+        "Email digest: {} new email{}",
+        entries.len(),
+        if entries.len() == 1 { "" } else { "s" }
+    );
+    let names = categories
+        .iter()
+        .map(|c| c.name.as_str())
+        .chain(std::iter::once(UNCLASSIFIED));
+    for name in names {
+        let listed: Vec<&DigestEntry> = entries.iter().filter(|e| e.category == name).collect();
+        if listed.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n\n{}:", name));
+        for entry in listed {
+            text.push_str(&format!("\n- \"{}\" from {}", entry.subject, entry.from));
+            if let Some(todo) = &entry.todo {
+                text.push_str(&format!("\n  todo {}", todo));
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_classification() {
+        let parsed = parse_classification("```json\n{\"category\": \"action\", \"task\": \"Pay\", \"due\": null}\n```");
+        assert_eq!(
+            parsed,
+            Some(Classification {
+                category: "action".to_string(),
+                task: Some("Pay".to_string()),
+                due: None,
+            })
+        );
+        assert_eq!(parse_classification("I think this is spam"), None);
+    }
+
+    #[test]
+    fn test_format_digest_groups_by_category() {
+        let entry = |subject: &str, category: &str, todo: Option<&str>| DigestEntry {
+            from: "a@example.org".to_string(),
+            subject: subject.to_string(),
+            category: category.to_string(),
+            todo: todo.map(str::to_string),
+        };
+        let digest = format_digest(
+            &[
+                entry("Lunch?", UNCLASSIFIED, None),
+                entry("Newsletter", "info", None),
+                entry("Invoice", "action", Some("[1] Pay the invoice")),
+            ],
+            &EmailTasksConfig::default().categories,
+        );
+        assert_eq!(
+            digest,
+            "Email digest: 3 new emails\n\n\
+             action:\n- \"Invoice\" from a@example.org\n  todo [1] Pay the invoice\n\n\
+             info:\n- \"Newsletter\" from a@example.org\n\n\
+             unclassified:\n- \"Lunch?\" from a@example.org"
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_bad_config() {
+        let router = || ModelRouter::new(crate::llm::LlmClient::new("openai", "m", "key", "http://127.0.0.1:9"));
+        let todos = Arc::new(TodoStore::new("/tmp"));
+        let missing = EmailTasks::new("/tmp", EmailTasksConfig::default(), router(), todos.clone());
+        assert!(missing.is_err());
+        let config = EmailTasksConfig {
+            maildir: Some("/tmp/mail".into()),
+            digest_time: "8am".to_string(),
+            ..Default::default()
+        };
+        assert!(EmailTasks::new("/tmp", config, router(), todos).is_err());
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_email_becomes_todo_and_digest() {
+        // Classifies by subject, the way a summary model would
+        let model = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(|axum::Json(body): axum::Json<serde_json::Value>| async move {
+                let prompt = body["messages"][0]["content"].as_str().unwrap_or("").to_string();
+                let reply = if prompt.contains("Subject: Your invoice") {
+                    r#"{"category": "action", "task": "Pay the electricity bill", "due": "2026-10-20"}"#
+                } else if prompt.contains("Subject: Weekly deals") {
+                    r#"{"category": "noise", "task": null, "due": null}"#
+                } else {
+                    "Not sure, sorry"
+                };
+                axum::Json(serde_json::json!({"choices": [{"message": {"content": reply}}]}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, model).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let maildir = dir.path().join("Maildir");
+        std::fs::create_dir_all(maildir.join("new")).unwrap();
+        let mail = |name: &str, from: &str, subject: &str| {
+            std::fs::write(
+                maildir.join("new").join(name),
+                format!("From: {}\nSubject: {}\n\nHello", from, subject),
+            )
+            .unwrap()
+        };
+        mail("1.host", "billing@power.example", "Your invoice");
+        mail("2.host", "deals@shop.example", "Weekly deals");
+        mail("3.host", "friend@example.org", "Lunch?");
+        mail("4.host", "alerts@bank.example", "Login from new device");
+
+        let todos = Arc::new(TodoStore::new(dir.path()));
+        let config = EmailTasksConfig {
+            enabled: true,
+            maildir: Some(maildir.clone()),
+            ignore_senders: vec!["@BANK.example".to_string()],
+            ..Default::default()
+        };
+        let router = ModelRouter::new(crate::llm::LlmClient::new("openai", "test-model", "key", &api_base));
+        let pipeline = EmailTasks::new(dir.path(), config, router, todos.clone()).unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let queued = pipeline.process(day).await.unwrap();
+        let categories: Vec<&str> = queued.iter().map(|e| e.category.as_str()).collect();
+        assert_eq!(categories, vec!["action", UNCLASSIFIED]);
+
+        let items = todos.items().await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Pay the electricity bill");
+        assert_eq!(items[0].due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(items[0].source.as_deref(), Some("billing@power.example"));
+        assert!(std::fs::read_dir(maildir.join("new")).unwrap().next().is_none());
+
+        let at = |h: u32| day.and_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(pipeline.take_digest(at(7)).await.unwrap(), None);
+        let digest = pipeline.take_digest(at(8)).await.unwrap().unwrap();
+        assert!(digest.starts_with("Email digest: 2 new emails"));
+        assert!(digest.contains("Pay the electricity bill (due 2026-10-20)"));
+        assert!(digest.contains("\"Lunch?\" from friend@example.org"));
+        assert!(!digest.contains("Weekly deals"));
+        assert_eq!(pipeline.take_digest(at(9)).await.unwrap(), None);
+    }
+}
@@ -1,37 +1,447 @@
 //! Agent executor with tool execution loop
 
-use crate::llm::LlmClient;
-use crate::tools::ToolRegistry;
+use super::citations;
+use super::context::{Message, SystemPrompt};
+use super::council::{Council, Verdict};
+use super::experiment::Experiments;
+use super::stream::{emit, AgentEvent, AgentEvents};
+use super::language;
+use super::trace::{RunTrace, StepKind, TraceStore};
+use super::transcript;
+use crate::channels::observer::{Interaction, Observers, BACKGROUND_CHANNEL};
+use crate::channels::ChannelCapabilities;
+use crate::config::{CitationConfig, LanguageConfig, RecallConfig, TranscriptConfig};
+use crate::error::Error;
+use crate::llm::{count_message_tokens, GenerationOptions, LlmClient, ModelRoute, ModelRouter};
+use crate::memory::{MemoryStore, Namespace, Recalled};
+use crate::runtime::metrics;
+use crate::session::Session;
+use crate::tools::{ToolContext, ToolRegistry};
 use serde_json::json;
-use tracing::{info, debug};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, debug, warn};
+
+/// Outcome of one agent turn
+#[derive(Debug, Clone, Default)]
+pub struct AgentTurn {
+    pub response: String,
+    /// Tools the model called, in order
+    pub tool_calls: Vec<String>,
+    /// URLs tool results were drawn from, in order of first use
+    pub sources: Vec<String>,
+    /// Files tools read or wrote, in order of first use
+    pub files: Vec<String>,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    /// How the request was shrunk after a context-length error, if it was
+    pub context_degraded: Option<ContextDegradation>,
+    /// The run used up its tool iterations without a final answer
+    pub budget_exhausted: bool,
+    /// Id of the run's saved trace for `takobull trace`, empty when untraced
+    pub run_id: String,
+}
+
+/// How an oversized request was shrunk to fit the model's context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextDegradation {
+    /// The message was condensed on the summary route
+    Summarized,
+    /// The message was cut down, keeping its beginning and end
+    Truncated,
+}
+
+impl std::fmt::Display for ContextDegradation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextDegradation::Summarized => write!(f, "summarized"),
+            ContextDegradation::Truncated => write!(f, "truncated"),
+        }
+    }
+}
+
+/// Rounds of tool calls per run unless configured otherwise
+pub const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+/// Tokens of recent session messages sent with each turn unless configured otherwise
+pub const DEFAULT_HISTORY_TOKENS: usize = 2000;
+
+const CONDENSE_INSTRUCTION: &str = "The following request is too long for the model. \
+Rewrite it as concisely as possible while keeping every question, instruction, and detail needed to answer it.";
 
 pub struct AgentExecutor {
-    llm_client: LlmClient,
+    router: ModelRouter,
     tool_registry: ToolRegistry,
     max_iterations: usize,
+    history_tokens: usize,
+    language: LanguageConfig,
+    max_expensive_per_turn: Option<usize>,
+    dry_run: bool,
+    options: GenerationOptions,
+    memory: Option<Arc<MemoryStore>>,
+    recall: RecallConfig,
+    citations: CitationConfig,
+    transcripts: TranscriptConfig,
+    name: Option<String>,
+    observers: Arc<Observers>,
+    traces: Option<TraceStore>,
+    experiments: Arc<Experiments>,
+    channels: HashMap<String, ChannelCapabilities>,
 }
 
 impl AgentExecutor {
     pub fn new(llm_client: LlmClient, tool_registry: ToolRegistry) -> Self {
+        Self::with_router(ModelRouter::new(llm_client), tool_registry)
+    }
+
+    pub fn with_router(router: ModelRouter, tool_registry: ToolRegistry) -> Self {
         Self {
-            llm_client,
+            router,
             tool_registry,
-            max_iterations: 10,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            history_tokens: DEFAULT_HISTORY_TOKENS,
+            language: LanguageConfig::default(),
+            max_expensive_per_turn: None,
+            dry_run: false,
+            options: GenerationOptions::default(),
+            memory: None,
+            recall: RecallConfig::default(),
+            citations: CitationConfig::default(),
+            transcripts: TranscriptConfig::default(),
+            name: None,
+            observers: Arc::new(Observers::default()),
+            traces: None,
+            experiments: Arc::new(Experiments::default()),
+            channels: HashMap::new(),
         }
     }
 
+    /// Mirror every interaction to `observers`
+    pub fn with_observers(mut self, observers: Arc<Observers>) -> Self {
+        self.observers = observers;
+        self
+    }
+
+    pub fn observers(&self) -> &Observers {
+        &self.observers
+    }
+
+    /// Add each session's prompt experiment variants to its system prompt
+    pub fn with_experiments(mut self, experiments: Arc<Experiments>) -> Self {
+        self.experiments = experiments;
+        self
+    }
+
+    pub fn experiments(&self) -> &Experiments {
+        &self.experiments
+    }
+
+    /// Save a timing trace of every run to `traces`
+    pub fn with_traces(mut self, traces: TraceStore) -> Self {
+        self.traces = Some(traces);
+        self
+    }
+
+    /// Rounds of tool calls allowed per run before giving up
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    /// Tokens of recent session messages sent with each turn; 0 sends none
+    pub fn with_history_tokens(mut self, tokens: usize) -> Self {
+        self.history_tokens = tokens;
+        self
+    }
+
+    /// Soft limit on expensive tool calls per turn; further calls are skipped
+    pub fn with_expensive_tool_limit(mut self, limit: Option<usize>) -> Self {
+        self.max_expensive_per_turn = limit;
+        self
+    }
+
+    /// Set how the reply language is chosen
+    pub fn with_language(mut self, language: LanguageConfig) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Sampling overrides for this agent, taking precedence over the client defaults
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Name the agent, giving it a private memory namespace
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name.filter(|n| !n.is_empty());
+        self
+    }
+
+    /// Recall memories relevant to each message from `memory` into the context
+    pub fn with_memory(mut self, memory: Arc<MemoryStore>, recall: RecallConfig) -> Self {
+        self.memory = Some(memory);
+        self.recall = recall;
+        self
+    }
+
+    /// Set when answers get a footer citing the URLs tools drew on
+    pub fn with_citations(mut self, citations: CitationConfig) -> Self {
+        self.citations = citations;
+        self
+    }
+
+    /// Use `capabilities` for sessions on `channel` instead of its platform's
+    pub fn with_channel_capabilities(mut self, channel: impl Into<String>, capabilities: ChannelCapabilities) -> Self {
+        self.channels.insert(channel.into(), capabilities);
+        self
+    }
+
+    /// Append a "What I did" section to answers where `transcripts` enables it
+    pub fn with_transcripts(mut self, transcripts: TranscriptConfig) -> Self {
+        self.transcripts = transcripts;
+        self
+    }
+
+    /// Record tool calls without executing them, stopping after the first response
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn router(&self) -> &ModelRouter {
+        &self.router
+    }
+
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tool_registry
+    }
+
     pub async fn execute(&self, message: &str) -> Result<String, Box<dyn std::error::Error>> {
+        self.execute_as(message, None).await
+    }
+
+    /// Execute a message from `user_id`, applying that user's preferences
+    pub async fn execute_as(
+        &self,
+        message: &str,
+        user_id: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(self.execute_turn(message, user_id).await?.response)
+    }
+
+    /// Execute a message, returning the response along with tool calls and usage
+    pub async fn execute_turn(
+        &self,
+        message: &str,
+        user_id: Option<&str>,
+    ) -> Result<AgentTurn, Box<dyn std::error::Error>> {
+        self.run_turn(message, user_id, None, None).await
+    }
+
+    /// Execute a message in `session`, applying its system prompt override
+    /// and experiment variants and sending its recent messages as history
+    pub async fn execute_in_session(
+        &self,
+        message: &str,
+        session: &Session,
+    ) -> Result<AgentTurn, Box<dyn std::error::Error>> {
+        self.run_turn(message, Some(&session.user_id), Some(session), None).await
+    }
+
+    /// Like [`AgentExecutor::execute_in_session`], sending the reply text and
+    /// tool progress to `events` as the turn runs
+    pub async fn execute_streaming(
+        &self,
+        message: &str,
+        session: &Session,
+        events: &AgentEvents,
+    ) -> Result<AgentTurn, Box<dyn std::error::Error>> {
+        self.run_turn(message, Some(&session.user_id), Some(session), Some(events)).await
+    }
+
+    /// Put a message in `session` to `council` instead of the chat model,
+    /// with the system prompt a normal run would get
+    pub async fn execute_council(
+        &self,
+        council: &Council,
+        message: &str,
+        session: &Session,
+    ) -> Result<Verdict, Box<dyn std::error::Error>> {
+        let channel = Some(session.metadata.channel.as_str());
+        let system = self
+            .full_system_prompt(message, Some(&session.user_id), channel, self.session_prompt(session).as_deref())
+            .await;
+        let result = council.ask(system.as_deref(), message, &self.options).await;
+        match &result {
+            Ok(verdict) => self.observe(message, Some(&session.user_id), channel, Ok(&verdict.response), &[]),
+            Err(e) => self.observe(message, Some(&session.user_id), channel, Err(e.to_string()), &[]),
+        }
+        Ok(result?)
+    }
+
+    /// The most recent `messages` that fit in the history token budget, oldest first
+    fn recent_history<'a>(&self, messages: &'a [Message]) -> &'a [Message] {
+        let model = self.router.client(ModelRoute::Chat).model();
+        let mut used = 0;
+        let start = messages
+            .iter()
+            .rposition(|m| {
+                used += count_message_tokens(model, [m.content.as_str()]);
+                used > self.history_tokens
+            })
+            .map_or(0, |index| index + 1);
+        &messages[start..]
+    }
+
+    /// The session's system prompt override followed by its experiment variants
+    fn session_prompt(&self, session: &Session) -> Option<String> {
+        match (session.metadata.system_prompt.as_deref(), self.experiments.prompt_for(session)) {
+            (Some(prompt), Some(variants)) => Some(format!("{}\n\n{}", prompt, variants)),
+            (prompt, variants) => variants.or(prompt.map(str::to_string)),
+        }
+    }
+
+    /// What `channel` supports; unnamed runs get the default
+    fn capabilities(&self, channel: Option<&str>) -> ChannelCapabilities {
+        let Some(channel) = channel else {
+            return ChannelCapabilities::default();
+        };
+        self.channels
+            .get(channel)
+            .copied()
+            .unwrap_or_else(|| ChannelCapabilities::for_channel(channel))
+    }
+
+    /// Session override, reply language, channel limits, and recalled
+    /// memories, rendered
+    async fn full_system_prompt(
+        &self,
+        message: &str,
+        user_id: Option<&str>,
+        channel: Option<&str>,
+        system_override: Option<&str>,
+    ) -> Option<String> {
+        let mut system = SystemPrompt::new();
+        if let Some(prompt) = system_override {
+            system.push(prompt);
+        }
+        system.extend(self.system_prompt(message, user_id));
+        if let Some(max) = self.capabilities(channel).max_message_length {
+            system.push(format!(
+                "Messages on this channel hold at most {} characters; longer replies are split \
+into several messages, so keep answers well under that.",
+                max
+            ));
+        }
+        if let Some(recalled) = self.recall_memories(message, user_id).await {
+            system.push(recalled);
+        }
+        system.render()
+    }
+
+    /// Run the agent loop, recording the outcome in the global metrics ring
+    /// and, unless this is a dry run, a trace in the trace store
+    async fn run_turn(
+        &self,
+        message: &str,
+        user_id: Option<&str>,
+        session: Option<&Session>,
+        events: Option<&AgentEvents>,
+    ) -> Result<AgentTurn, Box<dyn std::error::Error>> {
+        let started = std::time::Instant::now();
+        let session_id = session.map(|s| s.id.as_str());
+        let channel = session.map(|s| s.metadata.channel.as_str());
+        let mut trace = match &self.traces {
+            Some(traces) => traces.start(message, session_id, channel),
+            None => RunTrace::start(message, session_id, channel),
+        };
+        let mut result = self.run_loop(message, user_id, session, events, &mut trace).await;
+        metrics::global().record(chrono::Utc::now(), started.elapsed(), result.is_ok());
+        trace.finish(result.as_ref().err().map(|e| e.to_string()));
+        if let Some(traces) = self.traces.as_ref().filter(|_| !self.dry_run) {
+            match traces.save(&trace) {
+                Ok(()) => {
+                    debug!("Saved trace for run {}", trace.id);
+                    if let Ok(turn) = &mut result {
+                        turn.run_id = trace.id;
+                    }
+                }
+                Err(e) => warn!("Failed to save trace for run {}: {}", trace.id, e),
+            }
+        }
+        match &result {
+            Ok(turn) => self.observe(message, user_id, channel, Ok(&turn.response), &turn.tool_calls),
+            Err(e) => self.observe(message, user_id, channel, Err(e.to_string()), &[]),
+        }
+        result
+    }
+
+    /// Send a copy of an interaction to the observers, unless this is a dry run
+    fn observe(
+        &self,
+        message: &str,
+        user_id: Option<&str>,
+        channel: Option<&str>,
+        outcome: std::result::Result<&str, String>,
+        tool_calls: &[String],
+    ) {
+        if self.dry_run || self.observers.is_empty() {
+            return;
+        }
+        let (response, error) = match outcome {
+            Ok(response) => (Some(response.to_string()), None),
+            Err(error) => (None, Some(error)),
+        };
+        self.observers.publish(Interaction {
+            at: chrono::Utc::now(),
+            channel: channel.filter(|c| !c.is_empty()).unwrap_or(BACKGROUND_CHANNEL).to_string(),
+            user_id: user_id.map(str::to_string),
+            message: message.to_string(),
+            response,
+            error,
+            tool_calls: tool_calls.to_vec(),
+        });
+    }
+
+    async fn run_loop(
+        &self,
+        message: &str,
+        user_id: Option<&str>,
+        session: Option<&Session>,
+        events: Option<&AgentEvents>,
+        trace: &mut RunTrace,
+    ) -> Result<AgentTurn, Box<dyn std::error::Error>> {
         info!("Starting agent execution loop");
+        let started = std::time::Instant::now();
+        let channel = session.map(|s| s.metadata.channel.as_str());
+        let system_override = session.and_then(|s| self.session_prompt(s));
+        let system = self
+            .full_system_prompt(message, user_id, channel, system_override.as_deref())
+            .await;
+        let mut history = session.map_or(&[][..], |s| self.recent_history(&s.messages));
+        trace.step(StepKind::Prompt, started, None);
+        // Partial text is only worth sending where the message can be updated in place
+        let text_events = events.filter(|_| self.capabilities(channel).editing);
+        let mut message = std::borrow::Cow::Borrowed(message);
 
         let mut iteration = 0;
-        let mut final_response = String::new();
+        let mut turn = AgentTurn::default();
+        let mut expensive_calls = 0;
 
         loop {
             iteration += 1;
             debug!("Agent iteration: {}", iteration);
 
             if iteration > self.max_iterations {
-                info!("Max iterations reached");
+                warn!("Iteration budget of {} exhausted", self.max_iterations);
+                turn.budget_exhausted = true;
+                turn.response = format!(
+                    "Iteration budget exhausted: stopped after {} rounds of tool calls without a final answer. \
+Ask again to continue, or raise agents.defaults.max_tool_iterations.",
+                    self.max_iterations
+                );
                 break;
             }
 
@@ -51,15 +461,64 @@ impl AgentExecutor {
                 })
                 .collect();
 
-            // Call LLM with tools
-            let response = self
-                .llm_client
-                .chat_with_tools(message, tools_json)
-                .await?;
+            // Call LLM with tools, shrinking the request once if it overflows the context window
+            let client = self.router.client(ModelRoute::Chat);
+            let started = std::time::Instant::now();
+            let result = match text_events {
+                Some(events) => {
+                    let on_text = |text: &str| emit(Some(events), AgentEvent::Delta { text: text.to_string() });
+                    client
+                        .chat_streaming(system.as_deref(), history, &message, tools_json, &self.options, on_text)
+                        .await
+                }
+                None => {
+                    client
+                        .chat_with_options(system.as_deref(), history, &message, tools_json, &self.options)
+                        .await
+                }
+            };
+            let (input_tokens, output_tokens, tool_calls) = match &result {
+                Ok(response) => (response.usage.input_tokens, response.usage.output_tokens, response.tool_calls.len()),
+                Err(_) => (0, 0, 0),
+            };
+            trace.step(
+                StepKind::Llm {
+                    iteration,
+                    model: client.model().to_string(),
+                    input_tokens,
+                    output_tokens,
+                    tool_calls,
+                },
+                started,
+                result.as_ref().err().map(|e| e.to_string()),
+            );
+            let response = match result {
+                Err(Error::ContextLength(detail)) if turn.context_degraded.is_none() => {
+                    warn!("Context length exceeded, retrying with a reduced request: {}", detail);
+                    let started = std::time::Instant::now();
+                    let (reduced, degradation) = self.reduce_message(&message).await;
+                    trace.step(StepKind::Reduce, started, None);
+                    message = std::borrow::Cow::Owned(reduced);
+                    history = &[];
+                    turn.context_degraded = Some(degradation);
+                    iteration -= 1;
+                    continue;
+                }
+                Err(Error::ContextLength(_)) => {
+                    return Err(format!(
+                        "The request is too long for {} even after shortening it; try a shorter message or a model with a larger context window",
+                        self.router.client(ModelRoute::Chat).model()
+                    )
+                    .into());
+                }
+                result => result?,
+            };
+            turn.input_tokens += response.usage.input_tokens;
+            turn.output_tokens += response.usage.output_tokens;
 
             // If no tool calls, we're done
             if response.tool_calls.is_empty() {
-                final_response = response.content;
+                turn.response = response.content;
                 info!("LLM response without tool calls (iteration: {})", iteration);
                 break;
             }
@@ -67,27 +526,465 @@ impl AgentExecutor {
             // Log tool calls
             let tool_names: Vec<&str> = response.tool_calls.iter().map(|tc| tc.name.as_str()).collect();
             info!("LLM requested tool calls: {:?} (iteration: {})", tool_names, iteration);
+            turn.tool_calls.extend(tool_names.iter().map(|name| name.to_string()));
+
+            if self.dry_run {
+                info!("Dry run: not executing tool calls");
+                turn.response = response.content;
+                break;
+            }
 
             // Execute tools
             for tool_call in &response.tool_calls {
                 debug!("Executing tool: {}", tool_call.name);
 
-                let result = self
+                let expensive = self
                     .tool_registry
-                    .execute(&tool_call.name, tool_call.arguments.clone())
-                    .await;
+                    .cost_hint(&tool_call.name)
+                    .await
+                    .is_some_and(|hint| hint.is_expensive());
+                if expensive {
+                    expensive_calls += 1;
+                }
+                emit(
+                    events,
+                    AgentEvent::ToolStart {
+                        name: tool_call.name.clone(),
+                        iteration,
+                    },
+                );
+                let tool_started = std::time::Instant::now();
+                let result = match self.max_expensive_per_turn {
+                    Some(limit) if expensive && expensive_calls > limit => {
+                        info!("Skipping expensive tool {} (limit {} per turn)", tool_call.name, limit);
+                        crate::tools::ToolResult::error(format!(
+                            "Skipped: at most {} expensive tool calls per turn",
+                            limit
+                        ))
+                    }
+                    _ => {
+                        // Progress goes to the event stream as the tool produces
+                        // it, and is dropped without one (e.g. in the gateway's
+                        // background runs); the channel closes when the call
+                        // finishes and drops `ctx`
+                        let (output, mut progress) = tokio::sync::mpsc::unbounded_channel::<String>();
+                        let ctx = ToolContext::for_user(user_id)
+                            .with_agent(self.name.as_deref())
+                            .with_output(output);
+                        let forward = async {
+                            while let Some(text) = progress.recv().await {
+                                emit(
+                                    events,
+                                    AgentEvent::ToolOutput {
+                                        name: tool_call.name.clone(),
+                                        text,
+                                    },
+                                );
+                            }
+                        };
+                        let started = std::time::Instant::now();
+                        let (result, ()) = tokio::join!(
+                            self.tool_registry
+                                .execute_in(&tool_call.name, tool_call.arguments.clone(), ctx),
+                            forward
+                        );
+                        trace.step(
+                            StepKind::Tool {
+                                iteration,
+                                name: tool_call.name.clone(),
+                                ok: !result.is_error,
+                            },
+                            started,
+                            None,
+                        );
+                        result
+                    }
+                };
+                emit(
+                    events,
+                    AgentEvent::ToolEnd {
+                        name: tool_call.name.clone(),
+                        ok: !result.is_error,
+                        duration_ms: tool_started.elapsed().as_millis() as u64,
+                    },
+                );
 
                 if result.is_error {
                     info!("Tool failed: {} - {}", tool_call.name, result.for_llm);
                 } else {
                     info!("Tool succeeded: {}", tool_call.name);
+                    for url in &result.sources {
+                        citations::add_source(&mut turn.sources, url);
+                    }
+                    for file in &result.files {
+                        if !turn.files.contains(file) {
+                            turn.files.push(file.clone());
+                        }
+                    }
                     if let Some(user_content) = &result.for_user {
                         println!("{}", user_content);
+                        emit(
+                            events,
+                            AgentEvent::ToolOutput {
+                                name: tool_call.name.clone(),
+                                text: user_content.clone(),
+                            },
+                        );
                     }
                 }
             }
         }
 
-        Ok(final_response)
+        if self.citations.enabled_for(channel) {
+            if let Some(footer) = citations::footer(&turn.response, &turn.sources, self.citations.max_sources) {
+                turn.response.push_str(&footer);
+            }
+        }
+        if !self.dry_run && self.transcripts.enabled_for(user_id, channel) {
+            if let Some(section) = transcript::section(&turn.tool_calls, &turn.files, &turn.sources) {
+                turn.response.push_str(&section);
+            }
+        }
+
+        info!(
+            "Agent execution finished: {} iterations, {} input tokens, {} output tokens",
+            iteration.min(self.max_iterations),
+            turn.input_tokens,
+            turn.output_tokens
+        );
+
+        Ok(turn)
+    }
+
+    /// Memories visible to `user_id` relevant to `message`, formatted as a labeled context section
+    async fn recall_memories(&self, message: &str, user_id: Option<&str>) -> Option<String> {
+        let memory = self.memory.as_ref().filter(|_| self.recall.enabled)?;
+        let namespaces = Namespace::visible(user_id, self.name.as_deref());
+        let recalled = match memory
+            .recall(&namespaces, message, self.recall.top_k, self.recall.threshold)
+            .await
+        {
+            Ok(recalled) => recalled,
+            Err(e) => {
+                warn!("Memory recall failed: {}", e);
+                return None;
+            }
+        };
+        if recalled.is_empty() {
+            return None;
+        }
+        debug!("Recalled {} memories", recalled.len());
+        Some(format_recalled(&recalled))
+    }
+
+    /// Condense a message on the summary route, falling back to truncation
+    async fn reduce_message(&self, message: &str) -> (String, ContextDegradation) {
+        match self.router.summarize(CONDENSE_INSTRUCTION, message).await {
+            Ok(condensed) if !condensed.trim().is_empty() && condensed.len() < message.len() => {
+                (condensed, ContextDegradation::Summarized)
+            }
+            Ok(_) => (truncate_middle(message, message.len() / 2), ContextDegradation::Truncated),
+            Err(e) => {
+                warn!("Failed to condense message, truncating instead: {}", e);
+                (truncate_middle(message, message.len() / 2), ContextDegradation::Truncated)
+            }
+        }
+    }
+
+    /// Assemble the system prompt for a message
+    pub fn system_prompt(&self, message: &str, user_id: Option<&str>) -> SystemPrompt {
+        let mut prompt = SystemPrompt::new();
+        if let Some(reply_language) = language::reply_language(&self.language, user_id, message) {
+            debug!("Reply language: {}", reply_language);
+            prompt.push(language::reply_instruction(&reply_language));
+        }
+        prompt
+    }
+}
+
+/// Label recalled memories so the model treats them as background, not instructions
+fn format_recalled(recalled: &[Recalled]) -> String {
+    let mut section = String::from(
+        "Recalled memories (retrieved automatically from long-term memory; they may be outdated or irrelevant, so use them only if they help):",
+    );
+    for memory in recalled {
+        section.push_str(&format!(
+            "\n- {} ({}, saved {})",
+            memory.entry.text,
+            memory.namespace.label(),
+            memory.entry.created_at.format("%Y-%m-%d")
+        ));
+    }
+    section
+}
+
+/// Keep the beginning and end of `text` within roughly `max_len` bytes
+fn truncate_middle(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let half = max_len / 2;
+    let mut head = half;
+    while !text.is_char_boundary(head) {
+        head -= 1;
+    }
+    let mut tail = text.len() - half;
+    while !text.is_char_boundary(tail) {
+        tail += 1;
+    }
+    format!("{}\n[… {} bytes omitted …]\n{}", &text[..head], tail - head, &text[tail..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolRegistry;
+
+    #[tokio::test]
+    async fn test_recall_memories_respects_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory = Arc::new(MemoryStore::new(
+            dir.path(),
+            Arc::new(crate::memory::HashEmbedder::default()),
+        ));
+        memory
+            .remember(&Namespace::Shared, "The garden sprinkler runs at 6am")
+            .await
+            .unwrap();
+        memory
+            .remember(&Namespace::User("bob".to_string()), "Bob waters the sprinkler beds")
+            .await
+            .unwrap();
+        let executor = |recall: RecallConfig| {
+            AgentExecutor::new(
+                LlmClient::new("openai", "test-model", "key", "http://127.0.0.1:9"),
+                ToolRegistry::new(),
+            )
+            .with_memory(memory.clone(), recall)
+        };
+
+        let recalled = executor(RecallConfig::default())
+            .recall_memories("When does the sprinkler run?", Some("alice"))
+            .await
+            .unwrap();
+        assert!(recalled.starts_with("Recalled memories"));
+        assert!(recalled.contains("- The garden sprinkler runs at 6am (household, saved "));
+        assert!(!recalled.contains("Bob"));
+
+        let disabled = RecallConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(executor(disabled).recall_memories("sprinkler", None).await.is_none());
+        let strict = RecallConfig {
+            threshold: 0.99,
+            ..Default::default()
+        };
+        assert!(executor(strict).recall_memories("sprinkler", None).await.is_none());
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_iteration_budget_exhausted() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // A model that asks for another tool call on every round
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let model = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {
+                    axum::Json(json!({"choices": [{"message": {"content": "", "tool_calls": [
+                        {"id": "1", "type": "function", "function": {"name": "lookup", "arguments": "{}"}}
+                    ]}}]}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, model).await });
+
+        let executor = AgentExecutor::new(LlmClient::new("openai", "test-model", "key", &api_base), ToolRegistry::new())
+            .with_max_iterations(3);
+        let turn = executor.execute_turn("loop forever", None).await.unwrap();
+        assert!(turn.budget_exhausted);
+        assert!(turn.response.starts_with("Iteration budget exhausted: stopped after 3 rounds"));
+        assert_eq!(turn.tool_calls.len(), 3);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_execute_streaming_sends_events() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // First round streams a tool call, the second streams the answer
+        let requests = Arc::new(AtomicUsize::new(0));
+        let model = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move || {
+                let round = requests.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let chunks = if round == 0 {
+                        vec![
+                            json!({"choices": [{"delta": {"content": "Checking"}}]}),
+                            json!({"choices": [{"delta": {"tool_calls": [
+                                {"index": 0, "id": "1", "function": {"name": "exec", "arguments": "{\"command\": \"echo 21C\"}"}}
+                            ]}}]}),
+                        ]
+                    } else {
+                        vec![
+                            json!({"choices": [{"delta": {"content": "Sunny "}}]}),
+                            json!({"choices": [{"delta": {"content": "today"}}]}),
+                        ]
+                    };
+                    let mut body: String = chunks.iter().map(|chunk| format!("data: {}\n\n", chunk)).collect();
+                    body.push_str("data: [DONE]\n\n");
+                    ([(axum::http::header::CONTENT_TYPE, "text/event-stream")], body)
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, model).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = Arc::new(crate::tools::JobManager::new(dir.path()));
+        let tools = ToolRegistry::new();
+        tools
+            .register(Arc::new(crate::tools::ShellTool::new(dir.path(), jobs, Default::default())))
+            .await;
+        let executor = AgentExecutor::new(LlmClient::new("openai", "test-model", "key", &api_base), tools);
+        let session = Session {
+            id: "s1".to_string(),
+            user_id: "web".to_string(),
+            created_at: std::time::SystemTime::now(),
+            last_activity: std::time::SystemTime::now(),
+            messages: Vec::new(),
+            metadata: crate::session::store::SessionMetadata {
+                channel: "dashboard".to_string(),
+                tags: Vec::new(),
+                custom_data: Default::default(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        };
+        let (events, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let turn = executor.execute_streaming("weather?", &session, &events).await.unwrap();
+        drop(events);
+        assert_eq!(turn.response, "Sunny today");
+
+        let mut received = Vec::new();
+        while let Some(event) = rx.recv().await {
+            received.push(match event {
+                AgentEvent::ToolEnd { name, ok, .. } => format!("end {} {}", name, ok),
+                AgentEvent::ToolStart { name, .. } => format!("start {}", name),
+                AgentEvent::ToolOutput { name, text } => format!("{}: {}", name, text),
+                AgentEvent::Delta { text } => text,
+                other => panic!("unexpected event {:?}", other),
+            });
+        }
+        // Command output arrives while the call runs, before it ends
+        assert_eq!(
+            received,
+            vec!["Checking", "start exec", "exec: 21C", "end exec true", "Sunny ", "today"]
+        );
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_session_history_sent_with_turn() {
+        use crate::agent::context::MessageRole;
+
+        // A model that records the messages of each request
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let model = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| {
+                recorder.lock().unwrap().push(request["messages"].clone());
+                async { axum::Json(json!({"choices": [{"message": {"content": "Twice a week"}}]})) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, model).await });
+
+        let executor = AgentExecutor::new(LlmClient::new("openai", "test-model", "key", &api_base), ToolRegistry::new());
+        let mut session = Session {
+            id: "s1".to_string(),
+            user_id: "web".to_string(),
+            created_at: std::time::SystemTime::now(),
+            last_activity: std::time::SystemTime::now(),
+            messages: Vec::new(),
+            metadata: crate::session::store::SessionMetadata {
+                channel: "dashboard".to_string(),
+                tags: Vec::new(),
+                custom_data: Default::default(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        };
+        for i in 0..15 {
+            session.add_message(MessageRole::User, format!("question {}", i));
+            session.add_message(MessageRole::Assistant, format!("answer {}", i));
+        }
+
+        executor.execute_in_session("How often?", &session).await.unwrap();
+        executor.execute_turn("How often?", None).await.unwrap();
+        // A budget that fits exactly the last two exchanges
+        let budget = count_message_tokens("test-model", ["question 13", "answer 13", "question 14", "answer 14"]);
+        let executor = executor.with_history_tokens(budget);
+        executor.execute_in_session("How often?", &session).await.unwrap();
+        let executor = executor.with_history_tokens(0);
+        executor.execute_in_session("How often?", &session).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        let conversation = |request: &serde_json::Value| -> Vec<serde_json::Value> {
+            request.as_array().unwrap().iter().filter(|m| m["role"] != "system").cloned().collect()
+        };
+        let messages = conversation(&seen[0]);
+        assert_eq!(messages.len(), 31);
+        assert_eq!(messages[0]["content"], "question 0");
+        assert_eq!(messages[30]["content"], "How often?");
+        assert_eq!(conversation(&seen[1]).len(), 1);
+        let messages = conversation(&seen[2]);
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[0]["content"], "question 13");
+        assert_eq!(messages[3]["role"], "assistant");
+        assert_eq!(conversation(&seen[3]).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_mentions_channel_length_limit() {
+        let unlimited = ChannelCapabilities::default();
+        let executor = AgentExecutor::new(
+            LlmClient::new("openai", "test-model", "key", "http://127.0.0.1:9"),
+            ToolRegistry::new(),
+        )
+        .with_channel_capabilities("line", unlimited);
+
+        let prompt = executor.full_system_prompt("hi", None, Some("discord"), None).await.unwrap();
+        assert!(prompt.contains("at most 2000 characters"), "{}", prompt);
+        assert!(executor.full_system_prompt("hi", None, Some("dashboard"), None).await.is_none());
+        assert!(executor.full_system_prompt("hi", None, Some("line"), None).await.is_none());
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_both_ends() {
+        let text = format!("{}{}", "a".repeat(100), "é".repeat(50));
+        let truncated = truncate_middle(&text, 60);
+        assert!(truncated.starts_with("aaaa"));
+        assert!(truncated.ends_with("éé"));
+        assert!(truncated.contains("bytes omitted"));
+        assert_eq!(truncate_middle("short", 60), "short");
     }
 }
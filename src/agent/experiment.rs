@@ -0,0 +1,400 @@
+//! Prompt A/B experiments and the `/feedback` chat command
+//!
+//! An experiment from the `experiments` list in config.yaml has two or more
+//! variants, each adding instructions or a persona to the system prompt.
+//! Sessions are bucketed by a hash of the experiment name and session id,
+//! weighted by each variant's share, and the bucket is recorded in the
+//! session so it sticks even if the variants change later. Users rate
+//! replies with `/feedback up` or `/feedback down`. Assignments and ratings
+//! are appended to `workspace/experiments/events.jsonl`, and
+//! [`Experiments::results`] totals them per variant.
+
+use super::persona;
+use crate::config::{ExperimentConfig, ExperimentVariant};
+use crate::error::{Error, Result};
+use crate::session::Session;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Event log, relative to the workspace
+pub const EVENTS_FILE: &str = "experiments/events.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// The session was bucketed into the variant
+    Assigned,
+    Up,
+    Down,
+}
+
+/// One line of the event log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentEvent {
+    pub at: DateTime<Utc>,
+    pub experiment: String,
+    pub variant: String,
+    pub session_id: String,
+    pub kind: EventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Totals for one variant
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VariantResults {
+    pub experiment: String,
+    pub variant: String,
+    pub sessions: usize,
+    pub up: usize,
+    pub down: usize,
+}
+
+impl VariantResults {
+    /// Share of ratings that were positive, if there are any
+    pub fn approval(&self) -> Option<f64> {
+        let rated = self.up + self.down;
+        (rated > 0).then(|| self.up as f64 / rated as f64)
+    }
+}
+
+/// The configured experiments
+#[derive(Debug, Default)]
+pub struct Experiments {
+    experiments: Vec<ExperimentConfig>,
+    workspace: PathBuf,
+}
+
+impl Experiments {
+    /// Experiments from config, with personas and the event log in `workspace`
+    pub fn new(experiments: Vec<ExperimentConfig>, workspace: impl Into<PathBuf>) -> Result<Self> {
+        for (i, experiment) in experiments.iter().enumerate() {
+            if experiments[..i].iter().any(|e| e.name == experiment.name) {
+                return Err(Error::config(format!("Duplicate experiment '{}'", experiment.name)));
+            }
+            if experiment.variants.len() < 2 {
+                return Err(Error::config(format!(
+                    "Experiment '{}' needs at least two variants",
+                    experiment.name
+                )));
+            }
+            for (j, variant) in experiment.variants.iter().enumerate() {
+                if experiment.variants[..j].iter().any(|v| v.name == variant.name) {
+                    return Err(Error::config(format!(
+                        "Duplicate variant '{}' in experiment '{}'",
+                        variant.name, experiment.name
+                    )));
+                }
+                if variant.prompt.is_some() == variant.persona.is_some() {
+                    return Err(Error::config(format!(
+                        "Variant '{}' of experiment '{}' needs either a prompt or a persona",
+                        variant.name, experiment.name
+                    )));
+                }
+            }
+            if experiment.variants.iter().all(|v| v.weight == 0) {
+                return Err(Error::config(format!(
+                    "Experiment '{}' has no variant with a weight above 0",
+                    experiment.name
+                )));
+            }
+        }
+        Ok(Self {
+            experiments,
+            workspace: workspace.into(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.experiments.is_empty()
+    }
+
+    /// Whether experiment `name` is assigning new sessions
+    pub fn is_running(&self, name: &str) -> bool {
+        self.running().any(|e| e.name == name)
+    }
+
+    fn running(&self) -> impl Iterator<Item = &ExperimentConfig> {
+        self.experiments.iter().filter(|e| e.enabled)
+    }
+
+    /// Variant of `experiment` for `session`: the recorded one if it still
+    /// exists, otherwise its bucket
+    fn variant<'a>(&self, experiment: &'a ExperimentConfig, session: &Session) -> &'a ExperimentVariant {
+        session
+            .metadata
+            .experiments
+            .get(&experiment.name)
+            .and_then(|name| experiment.variants.iter().find(|v| &v.name == name))
+            .unwrap_or_else(|| bucket(experiment, &session.id))
+    }
+
+    /// Record `session`'s variant of every running experiment it is not yet
+    /// in, returning whether the session changed. The caller saves it.
+    pub fn assign(&self, session: &mut Session) -> bool {
+        let mut changed = false;
+        for experiment in self.running() {
+            let variant = self.variant(experiment, session).name.clone();
+            if session.metadata.experiments.get(&experiment.name) == Some(&variant) {
+                continue;
+            }
+            info!("Session {} joins experiment {} as {}", session.id, experiment.name, variant);
+            if let Err(e) = self.log(&experiment.name, &variant, &session.id, EventKind::Assigned, None) {
+                warn!("Failed to record experiment assignment: {}", e);
+            }
+            session.metadata.experiments.insert(experiment.name.clone(), variant);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Instructions `session`'s variants add to the system prompt
+    pub fn prompt_for(&self, session: &Session) -> Option<String> {
+        let prompts: Vec<String> = self
+            .running()
+            .filter_map(|experiment| {
+                let variant = self.variant(experiment, session);
+                match (&variant.prompt, &variant.persona) {
+                    // An empty prompt makes a control variant
+                    (Some(prompt), _) => Some(prompt.clone()).filter(|p| !p.trim().is_empty()),
+                    (None, Some(name)) => persona::load_persona(&self.workspace, name)
+                        .map_err(|e| warn!("Experiment {} variant {}: {}", experiment.name, variant.name, e))
+                        .ok(),
+                    (None, None) => None,
+                }
+            })
+            .collect();
+        (!prompts.is_empty()).then(|| prompts.join("\n\n"))
+    }
+
+    /// Rate the latest reply in `session` for each running experiment it is
+    /// in, returning how many ratings were recorded
+    pub fn rate(&self, session: &Session, up: bool, note: Option<&str>) -> Result<usize> {
+        let kind = if up { EventKind::Up } else { EventKind::Down };
+        let mut recorded = 0;
+        for experiment in self.running() {
+            if let Some(variant) = session.metadata.experiments.get(&experiment.name) {
+                self.log(&experiment.name, variant, &session.id, kind, note)?;
+                recorded += 1;
+            }
+        }
+        Ok(recorded)
+    }
+
+    /// Totals for every configured variant, in config order
+    pub fn results(&self) -> Result<Vec<VariantResults>> {
+        let mut results: Vec<VariantResults> = self
+            .experiments
+            .iter()
+            .flat_map(|experiment| {
+                experiment.variants.iter().map(|variant| VariantResults {
+                    experiment: experiment.name.clone(),
+                    variant: variant.name.clone(),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let path = self.workspace.join(EVENTS_FILE);
+        if !path.exists() {
+            return Ok(results);
+        }
+
+        let mut sessions = HashSet::new();
+        for line in std::fs::read_to_string(&path)?.lines().filter(|l| !l.trim().is_empty()) {
+            let event: ExperimentEvent = match serde_json::from_str(line) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Skipping invalid experiment event: {}", e);
+                    continue;
+                }
+            };
+            let Some(totals) = results
+                .iter_mut()
+                .find(|r| r.experiment == event.experiment && r.variant == event.variant)
+            else {
+                continue;
+            };
+            match event.kind {
+                EventKind::Assigned => {
+                    if sessions.insert((event.experiment, event.variant, event.session_id)) {
+                        totals.sessions += 1;
+                    }
+                }
+                EventKind::Up => totals.up += 1,
+                EventKind::Down => totals.down += 1,
+            }
+        }
+        Ok(results)
+    }
+
+    fn log(&self, experiment: &str, variant: &str, session_id: &str, kind: EventKind, note: Option<&str>) -> Result<()> {
+        let event = ExperimentEvent {
+            at: Utc::now(),
+            experiment: experiment.to_string(),
+            variant: variant.to_string(),
+            session_id: session_id.to_string(),
+            kind,
+            note: note.map(str::to_string),
+        };
+        let path = self.workspace.join(EVENTS_FILE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&event)?)?;
+        Ok(())
+    }
+}
+
+/// Weighted variant for `session_id`, stable across runs and releases
+fn bucket<'a>(experiment: &'a ExperimentConfig, session_id: &str) -> &'a ExperimentVariant {
+    let digest = Sha256::digest(format!("{}:{}", experiment.name, session_id).as_bytes());
+    let total: u64 = experiment.variants.iter().map(|v| u64::from(v.weight)).sum();
+    let mut point = u64::from_be_bytes(digest[..8].try_into().expect("digest has 8 bytes")) % total.max(1);
+    for variant in &experiment.variants {
+        if point < u64::from(variant.weight) {
+            return variant;
+        }
+        point -= u64::from(variant.weight);
+    }
+    &experiment.variants[0]
+}
+
+/// Handle `/feedback up|down [note]`, rating the session's latest reply
+///
+/// Returns `None` if `text` is not a feedback command, otherwise the reply
+/// to show the user.
+pub fn handle_command(experiments: &Experiments, session: &Session, text: &str) -> Option<Result<String>> {
+    let text = text.trim();
+    let rest = text.strip_prefix("/feedback")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim();
+    let (rating, note) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let up = match rating {
+        "up" | "good" | "👍" => true,
+        "down" | "bad" | "👎" => false,
+        _ => return Some(Err(Error::config("Usage: /feedback up|down [note]"))),
+    };
+    let note = Some(note.trim()).filter(|n| !n.is_empty());
+    Some(experiments.rate(session, up, note).map(|recorded| {
+        if recorded == 0 {
+            "Thanks! This session is not part of an experiment, so the rating was not recorded.".to_string()
+        } else {
+            "Thanks for the feedback!".to_string()
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::store::SessionMetadata;
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    fn session(id: &str) -> Session {
+        Session {
+            id: id.to_string(),
+            user_id: "cli".to_string(),
+            created_at: SystemTime::now(),
+            last_activity: SystemTime::now(),
+            messages: Vec::new(),
+            metadata: SessionMetadata {
+                channel: "cli".to_string(),
+                tags: Vec::new(),
+                custom_data: HashMap::new(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        }
+    }
+
+    fn variant(name: &str, prompt: &str) -> ExperimentVariant {
+        ExperimentVariant {
+            name: name.to_string(),
+            prompt: Some(prompt.to_string()),
+            persona: None,
+            weight: 1,
+        }
+    }
+
+    fn tone() -> ExperimentConfig {
+        ExperimentConfig {
+            name: "tone".to_string(),
+            enabled: true,
+            variants: vec![variant("brief", "Be brief."), variant("warm", "Be warm.")],
+        }
+    }
+
+    #[test]
+    fn test_sticky_bucketing() {
+        let dir = tempfile::tempdir().unwrap();
+        let experiments = Experiments::new(vec![tone()], dir.path()).unwrap();
+
+        // Both variants get sessions, and a session always lands in the same one
+        let prompts: HashSet<String> = (0..20)
+            .map(|i| experiments.prompt_for(&session(&format!("s{}", i))).unwrap())
+            .collect();
+        assert_eq!(prompts.len(), 2);
+        let mut s = session("s1");
+        let before = experiments.prompt_for(&s);
+        assert!(experiments.assign(&mut s));
+        assert!(!experiments.assign(&mut s));
+        assert_eq!(experiments.prompt_for(&s), before);
+
+        // The recorded variant wins over a changed bucketing
+        let mut reweighted = tone();
+        reweighted.variants[0].weight = 0;
+        let reweighted = Experiments::new(vec![reweighted], dir.path()).unwrap();
+        assert_eq!(reweighted.prompt_for(&s), before);
+        assert_eq!(reweighted.prompt_for(&session("new")).as_deref(), Some("Be warm."));
+    }
+
+    #[test]
+    fn test_feedback_is_totalled_per_variant() {
+        let dir = tempfile::tempdir().unwrap();
+        let experiments = Experiments::new(vec![tone()], dir.path()).unwrap();
+        let mut s = session("s1");
+        assert!(handle_command(&experiments, &s, "/feedback up")
+            .unwrap()
+            .unwrap()
+            .contains("not part of an experiment"));
+
+        experiments.assign(&mut s);
+        let variant = s.metadata.experiments["tone"].clone();
+        assert_eq!(handle_command(&experiments, &s, "/feedback up").unwrap().unwrap(), "Thanks for the feedback!");
+        handle_command(&experiments, &s, "/feedback 👎 too long").unwrap().unwrap();
+        assert!(handle_command(&experiments, &s, "/feedback meh").unwrap().is_err());
+        assert!(handle_command(&experiments, &s, "/feedbackup").is_none());
+        assert!(handle_command(&experiments, &s, "hello").is_none());
+
+        let results = experiments.results().unwrap();
+        let totals = results.iter().find(|r| r.variant == variant).unwrap();
+        assert_eq!((totals.sessions, totals.up, totals.down), (1, 1, 1));
+        assert_eq!(totals.approval(), Some(0.5));
+        let other = results.iter().find(|r| r.variant != variant).unwrap();
+        assert_eq!((other.sessions, other.approval()), (0, None));
+    }
+
+    #[test]
+    fn test_invalid_experiments_are_rejected() {
+        let mut single = tone();
+        single.variants.pop();
+        let mut both = tone();
+        both.variants[0].persona = Some("pirate".to_string());
+        for experiment in [single, both] {
+            assert!(Experiments::new(vec![experiment], "/tmp").is_err());
+        }
+        assert!(Experiments::new(vec![tone(), tone()], "/tmp").is_err());
+    }
+}
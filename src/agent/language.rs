@@ -0,0 +1,217 @@
+//! Conversation language detection
+//!
+//! Detects the language of an incoming message so context assembly can tell
+//! the model which language to reply in, instead of hoping it notices.
+//! Non-Latin scripts are identified by character ranges; Latin-script
+//! languages by common function words and diacritics.
+
+use crate::config::LanguageConfig;
+
+/// A detected or configured reply language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// ISO 639-1 code
+    pub code: &'static str,
+    /// English name, used in the reply instruction
+    pub name: &'static str,
+}
+
+const fn lang(code: &'static str, name: &'static str) -> Language {
+    Language { code, name }
+}
+
+pub const ENGLISH: Language = lang("en", "English");
+pub const SPANISH: Language = lang("es", "Spanish");
+pub const FRENCH: Language = lang("fr", "French");
+pub const GERMAN: Language = lang("de", "German");
+pub const PORTUGUESE: Language = lang("pt", "Portuguese");
+pub const ITALIAN: Language = lang("it", "Italian");
+pub const DUTCH: Language = lang("nl", "Dutch");
+pub const CHINESE: Language = lang("zh", "Chinese");
+pub const JAPANESE: Language = lang("ja", "Japanese");
+pub const KOREAN: Language = lang("ko", "Korean");
+pub const RUSSIAN: Language = lang("ru", "Russian");
+pub const UKRAINIAN: Language = lang("uk", "Ukrainian");
+pub const ARABIC: Language = lang("ar", "Arabic");
+pub const HEBREW: Language = lang("he", "Hebrew");
+pub const GREEK: Language = lang("el", "Greek");
+pub const THAI: Language = lang("th", "Thai");
+pub const HINDI: Language = lang("hi", "Hindi");
+
+const ALL: &[Language] = &[
+    ENGLISH, SPANISH, FRENCH, GERMAN, PORTUGUESE, ITALIAN, DUTCH, CHINESE, JAPANESE, KOREAN,
+    RUSSIAN, UKRAINIAN, ARABIC, HEBREW, GREEK, THAI, HINDI,
+];
+
+/// Common function words for Latin-script languages
+const STOPWORDS: &[(Language, &[&str])] = &[
+    (ENGLISH, &["the", "and", "is", "are", "you", "what", "how", "to", "of", "it", "this", "that", "with", "for", "can", "my", "do", "please"]),
+    (SPANISH, &["el", "la", "los", "las", "es", "y", "que", "de", "en", "por", "para", "con", "qué", "cómo", "está", "una", "puedes", "mi"]),
+    (FRENCH, &["le", "la", "les", "est", "et", "que", "de", "des", "une", "pour", "avec", "je", "vous", "tu", "pas", "qu'est-ce", "mon", "c'est"]),
+    (GERMAN, &["der", "die", "das", "und", "ist", "ich", "du", "nicht", "mit", "ein", "eine", "wie", "was", "für", "auf", "kannst", "mein", "bitte"]),
+    (PORTUGUESE, &["o", "os", "as", "é", "e", "que", "de", "não", "uma", "para", "com", "você", "como", "está", "meu", "do", "da", "por favor"]),
+    (ITALIAN, &["il", "lo", "gli", "è", "e", "che", "di", "non", "una", "per", "con", "sono", "come", "cosa", "mio", "della", "puoi", "perché"]),
+    (DUTCH, &["de", "het", "een", "en", "is", "ik", "je", "niet", "van", "wat", "hoe", "met", "voor", "op", "mijn", "kun", "alsjeblieft", "dat"]),
+];
+
+/// Minimum letters before a non-Latin script is trusted
+const MIN_SCRIPT_LETTERS: usize = 2;
+
+/// Look up a language by ISO code or English name (case-insensitive)
+pub fn lookup(code_or_name: &str) -> Option<Language> {
+    let wanted = code_or_name.trim().to_lowercase();
+    ALL.iter()
+        .find(|l| l.code == wanted || l.name.to_lowercase() == wanted)
+        .copied()
+}
+
+/// Detect the language of `text`, or `None` if it is too short or ambiguous
+pub fn detect(text: &str) -> Option<Language> {
+    let mut counts = [0usize; 10];
+    let mut latin = 0;
+    for c in text.chars() {
+        match c as u32 {
+            0x3040..=0x30FF => counts[0] += 1,                   // Kana
+            0xAC00..=0xD7AF | 0x1100..=0x11FF => counts[1] += 1, // Hangul
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => counts[2] += 1, // Han
+            0x0400..=0x04FF => counts[3] += 1,                   // Cyrillic
+            0x0600..=0x06FF => counts[4] += 1,                   // Arabic
+            0x0590..=0x05FF => counts[5] += 1,                   // Hebrew
+            0x0370..=0x03FF => counts[6] += 1,                   // Greek
+            0x0E00..=0x0E7F => counts[7] += 1,                   // Thai
+            0x0900..=0x097F => counts[8] += 1,                   // Devanagari
+            _ if c.is_alphabetic() => latin += 1,
+            _ => {}
+        }
+    }
+
+    // Japanese mixes kana with Han, so any kana wins over Chinese
+    if counts[0] > 0 && counts[0] + counts[2] >= MIN_SCRIPT_LETTERS {
+        return Some(JAPANESE);
+    }
+    let (script, &count) = counts.iter().enumerate().max_by_key(|(_, n)| **n)?;
+    if count >= MIN_SCRIPT_LETTERS && count >= latin {
+        return Some(match script {
+            1 => KOREAN,
+            2 => CHINESE,
+            3 if text.contains(['і', 'ї', 'є', 'ґ']) => UKRAINIAN,
+            3 => RUSSIAN,
+            4 => ARABIC,
+            5 => HEBREW,
+            6 => GREEK,
+            7 => THAI,
+            _ => HINDI,
+        });
+    }
+
+    detect_latin(text)
+}
+
+fn detect_latin(text: &str) -> Option<Language> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !(c.is_alphabetic() || c == '\''))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut scores: Vec<(Language, usize)> = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words.iter().filter(|w| stopwords.contains(w)).count();
+            (*language, hits * 2 + diacritic_hints(*language, &lower))
+        })
+        .collect();
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+    match (scores.first(), scores.get(1)) {
+        (Some(&(best, score)), Some(&(_, runner_up))) if score >= 2 && score > runner_up => Some(best),
+        _ => None,
+    }
+}
+
+/// Extra score for letters that are characteristic of a language
+fn diacritic_hints(language: Language, text: &str) -> usize {
+    let hints: &[char] = match language.code {
+        "es" => &['ñ', '¿', '¡'],
+        "fr" => &['ç', 'è', 'ê', 'à', 'ù', 'œ'],
+        "de" => &['ß', 'ä', 'ö', 'ü'],
+        "pt" => &['ã', 'õ', 'ç'],
+        "it" => &['ò', 'ì'],
+        _ => &[],
+    };
+    text.chars().filter(|c| hints.contains(c)).count()
+}
+
+/// Language to reply in for a message from `user_id`, following `config`
+///
+/// A per-user preference wins, then detection, then the configured default.
+pub fn reply_language(config: &LanguageConfig, user_id: Option<&str>, text: &str) -> Option<String> {
+    if let Some(preferred) = user_id.and_then(|id| config.users.get(id)) {
+        return Some(display_name(preferred));
+    }
+    if config.detect {
+        if let Some(language) = detect(text) {
+            return Some(language.name.to_string());
+        }
+    }
+    config.default.as_deref().map(display_name)
+}
+
+fn display_name(code_or_name: &str) -> String {
+    lookup(code_or_name)
+        .map(|l| l.name.to_string())
+        .unwrap_or_else(|| code_or_name.to_string())
+}
+
+/// System prompt instruction to reply in `language`
+pub fn reply_instruction(language: &str) -> String {
+    format!(
+        "Reply in {}, the language the user is writing in, unless they explicitly ask for another language.",
+        language
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_detects_scripts() {
+        assert_eq!(detect("今日の天気はどうですか"), Some(JAPANESE));
+        assert_eq!(detect("今天天气怎么样"), Some(CHINESE));
+        assert_eq!(detect("오늘 날씨 어때요?"), Some(KOREAN));
+        assert_eq!(detect("Какая сегодня погода?"), Some(RUSSIAN));
+        assert_eq!(detect("Яка сьогодні погода? Дякую, і все"), Some(UKRAINIAN));
+        assert_eq!(detect("كيف الطقس اليوم"), Some(ARABIC));
+    }
+
+    #[test]
+    fn test_detects_latin_languages() {
+        assert_eq!(detect("What is the weather like today?"), Some(ENGLISH));
+        assert_eq!(detect("¿Qué tiempo hace hoy en la ciudad?"), Some(SPANISH));
+        assert_eq!(detect("Wie ist das Wetter heute? Ich gehe spazieren."), Some(GERMAN));
+        assert_eq!(detect("Est-ce que tu peux allumer la lumière pour moi ?"), Some(FRENCH));
+        assert_eq!(detect("ok"), None);
+    }
+
+    #[test]
+    fn test_user_preference_overrides_detection() {
+        let mut config = LanguageConfig {
+            users: HashMap::from([("telegram:42".to_string(), "de".to_string())]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            reply_language(&config, Some("telegram:42"), "What time is it?").as_deref(),
+            Some("German")
+        );
+        assert_eq!(
+            reply_language(&config, Some("telegram:7"), "What time is it?").as_deref(),
+            Some("English")
+        );
+
+        config.default = Some("fr".to_string());
+        assert_eq!(reply_language(&config, None, "ok").as_deref(), Some("French"));
+    }
+}
@@ -1,11 +1,27 @@
 //! Agent loop and context management
 
+pub mod batch;
+pub mod citations;
 pub mod context;
+pub mod council;
+pub mod email_tasks;
+pub mod experiment;
+pub mod language;
 pub mod loop_impl;
 pub mod memory;
+pub mod persona;
+pub mod replay;
+pub mod stream;
+pub mod trace;
+pub mod transcript;
 pub mod executor;
 
 pub use context::AgentContext;
+pub use council::Council;
+pub use email_tasks::EmailTasks;
+pub use experiment::Experiments;
 pub use loop_impl::AgentLoop;
 pub use memory::MemoryManager;
-pub use executor::AgentExecutor;
+pub use executor::{AgentExecutor, AgentTurn, ContextDegradation};
+pub use stream::{AgentEvent, AgentEvents};
+pub use trace::{RunTrace, TraceStore};
@@ -0,0 +1,140 @@
+//! Named personas and the `/persona` chat command
+//!
+//! Personas are Markdown files in `workspace/personas/<name>.md`. Selecting
+//! one copies its prompt into the session metadata, overriding the system
+//! prompt for that session only.
+
+use crate::error::{Error, Result};
+use crate::session::Session;
+use std::path::{Path, PathBuf};
+
+/// Workspace subdirectory holding persona prompts
+pub const PERSONAS_DIR: &str = "personas";
+
+fn persona_path(workspace: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(Error::config(format!("Invalid persona name: {}", name)));
+    }
+    Ok(workspace.join(PERSONAS_DIR).join(format!("{}.md", name)))
+}
+
+/// Read a persona's prompt
+pub fn load_persona(workspace: &Path, name: &str) -> Result<String> {
+    let path = persona_path(workspace, name)?;
+    if !path.exists() {
+        return Err(Error::config(format!("Unknown persona: {}", name)));
+    }
+    Ok(std::fs::read_to_string(path)?.trim().to_string())
+}
+
+/// Names of available personas, sorted
+pub fn list_personas(workspace: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(workspace.join(PERSONAS_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "md" {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Handle `/persona [name|off]`, updating `session`
+///
+/// Returns `None` if `text` is not a persona command, otherwise the reply
+/// to show the user. The caller saves the session.
+pub fn handle_command(workspace: &Path, session: &mut Session, text: &str) -> Option<Result<String>> {
+    let mut words = text.split_whitespace();
+    if words.next() != Some("/persona") {
+        return None;
+    }
+
+    let reply = match words.next() {
+        None => {
+            let available = list_personas(workspace);
+            let current = session.metadata.persona.as_deref().unwrap_or(match session.metadata.system_prompt {
+                Some(_) => "custom",
+                None => "default",
+            });
+            Ok(if available.is_empty() {
+                format!(
+                    "Current persona: {}. Add personas as {}/<name>.md in the workspace.",
+                    current, PERSONAS_DIR
+                )
+            } else {
+                format!("Current persona: {}. Available: {}", current, available.join(", "))
+            })
+        }
+        Some("off") | Some("default") => {
+            session.set_system_prompt(None);
+            Ok("Persona cleared; using the default system prompt.".to_string())
+        }
+        Some(name) => load_persona(workspace, name).map(|prompt| {
+            session.set_persona(name, prompt);
+            format!("Switched to persona: {}", name)
+        }),
+    };
+    Some(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::store::SessionMetadata;
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    fn session() -> Session {
+        Session {
+            id: "s1".to_string(),
+            user_id: "cli".to_string(),
+            created_at: SystemTime::now(),
+            last_activity: SystemTime::now(),
+            messages: Vec::new(),
+            metadata: SessionMetadata {
+                channel: "cli".to_string(),
+                tags: Vec::new(),
+                custom_data: HashMap::new(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_persona_command() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(PERSONAS_DIR)).unwrap();
+        std::fs::write(dir.path().join("personas/pirate.md"), "Talk like a pirate.\n").unwrap();
+        let mut session = session();
+
+        assert!(handle_command(dir.path(), &mut session, "hello").is_none());
+        assert!(handle_command(dir.path(), &mut session, "/persona")
+            .unwrap()
+            .unwrap()
+            .contains("pirate"));
+
+        handle_command(dir.path(), &mut session, "/persona pirate").unwrap().unwrap();
+        assert_eq!(session.metadata.persona.as_deref(), Some("pirate"));
+        assert_eq!(session.metadata.system_prompt.as_deref(), Some("Talk like a pirate."));
+
+        assert!(handle_command(dir.path(), &mut session, "/persona ../secret").unwrap().is_err());
+        assert!(handle_command(dir.path(), &mut session, "/persona ghost").unwrap().is_err());
+
+        handle_command(dir.path(), &mut session, "/persona off").unwrap().unwrap();
+        assert!(session.metadata.system_prompt.is_none());
+    }
+}
@@ -0,0 +1,184 @@
+//! Replay stored sessions against the current configuration
+//!
+//! Each user message is sent again through a dry-run executor (tools are
+//! not executed) and the new response is diffed against the recorded one,
+//! so prompt or model changes can be evaluated without side effects. Each
+//! turn is sent with the recorded conversation that preceded it.
+
+use super::context::MessageRole;
+use super::executor::AgentExecutor;
+use crate::error::{Error, Result};
+use crate::session::Session;
+
+/// One replayed user turn
+#[derive(Debug, Clone)]
+pub struct ReplayTurn {
+    pub user: String,
+    /// Assistant reply recorded in the session, if any
+    pub recorded: Option<String>,
+    pub replayed: String,
+    /// Tools the model would have called
+    pub tool_calls: Vec<String>,
+}
+
+impl ReplayTurn {
+    /// Whether the replayed response differs from the recorded one
+    pub fn changed(&self) -> bool {
+        self.recorded.as_deref().map(str::trim) != Some(self.replayed.trim())
+    }
+
+    /// Line diff from the recorded to the replayed response
+    pub fn diff(&self) -> Vec<DiffLine> {
+        diff_lines(self.recorded.as_deref().unwrap_or(""), &self.replayed)
+    }
+}
+
+/// A line in a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+impl std::fmt::Display for DiffLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffLine::Same(line) => write!(f, "  {}", line),
+            DiffLine::Removed(line) => write!(f, "- {}", line),
+            DiffLine::Added(line) => write!(f, "+ {}", line),
+        }
+    }
+}
+
+/// User messages paired with the assistant reply that followed each
+pub fn user_turns(session: &Session) -> Vec<(String, Option<String>)> {
+    let mut turns: Vec<(String, Option<String>)> = Vec::new();
+    for message in &session.messages {
+        match message.role {
+            MessageRole::User => turns.push((message.content.clone(), None)),
+            MessageRole::Assistant => {
+                if let Some((_, reply @ None)) = turns.last_mut() {
+                    *reply = Some(message.content.clone());
+                }
+            }
+            MessageRole::System => {}
+        }
+    }
+    turns
+}
+
+/// Replay every user turn of `session` through a dry-run `executor`
+pub async fn replay_session(executor: &AgentExecutor, session: &Session) -> Result<Vec<ReplayTurn>> {
+    let mut replayed = Vec::new();
+    let starts = session
+        .messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.role == MessageRole::User)
+        .map(|(index, _)| index);
+    let mut earlier = session.clone();
+    for ((user, recorded), start) in user_turns(session).into_iter().zip(starts) {
+        earlier.messages = session.messages[..start].to_vec();
+        let turn = executor
+            .execute_in_session(&user, &earlier)
+            .await
+            .map_err(|e| Error::llm_provider(e.to_string()))?;
+        replayed.push(ReplayTurn {
+            user,
+            recorded,
+            replayed: turn.response,
+            tool_calls: turn.tool_calls,
+        });
+    }
+    Ok(replayed)
+}
+
+/// Longest-common-subsequence line diff
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    diff.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::store::SessionMetadata;
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc\nd");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Added("x".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+        assert!(diff_lines("same", "same").iter().all(|l| matches!(l, DiffLine::Same(_))));
+    }
+
+    #[test]
+    fn test_user_turns_pair_replies() {
+        let mut session = Session {
+            id: "s1".to_string(),
+            user_id: "cli".to_string(),
+            created_at: SystemTime::now(),
+            last_activity: SystemTime::now(),
+            messages: Vec::new(),
+            metadata: SessionMetadata {
+                channel: "cli".to_string(),
+                tags: Vec::new(),
+                custom_data: HashMap::new(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        };
+        session.add_message(MessageRole::User, "hi");
+        session.add_message(MessageRole::Assistant, "hello");
+        session.add_message(MessageRole::User, "unanswered");
+
+        let turns = user_turns(&session);
+        assert_eq!(turns[0], ("hi".to_string(), Some("hello".to_string())));
+        assert_eq!(turns[1], ("unanswered".to_string(), None));
+    }
+}
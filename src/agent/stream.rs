@@ -0,0 +1,53 @@
+//! Progress events from a running agent turn
+//!
+//! A caller that wants incremental output passes an [`AgentEvents`] sender
+//! to [`AgentExecutor::execute_streaming`](super::AgentExecutor::execute_streaming)
+//! and receives the reply text as it is generated, interleaved with tool
+//! progress. Text streamed before a `tool_start` was the model thinking
+//! aloud on its way to a tool call; the `done` event carries the final reply.
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Sender for the events of one turn
+pub type AgentEvents = UnboundedSender<AgentEvent>;
+
+/// One step of a streamed agent turn, serialized with a `type` tag
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    /// A piece of reply text
+    Delta { text: String },
+    /// A tool call is starting
+    ToolStart { name: String, iteration: usize },
+    /// Content a tool produced for the user, e.g. a rendered notification
+    ToolOutput { name: String, text: String },
+    /// A tool call finished
+    ToolEnd { name: String, ok: bool, duration_ms: u64 },
+    /// The final reply; the last event of a successful turn
+    Done { response: String },
+    /// The turn failed; the last event of a failed turn
+    Error { message: String },
+}
+
+impl AgentEvent {
+    /// The `type` tag, e.g. `tool_start`, used as the SSE event name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AgentEvent::Delta { .. } => "delta",
+            AgentEvent::ToolStart { .. } => "tool_start",
+            AgentEvent::ToolOutput { .. } => "tool_output",
+            AgentEvent::ToolEnd { .. } => "tool_end",
+            AgentEvent::Done { .. } => "done",
+            AgentEvent::Error { .. } => "error",
+        }
+    }
+}
+
+/// Send `event` if there is anyone listening
+pub(crate) fn emit(events: Option<&AgentEvents>, event: AgentEvent) {
+    if let Some(events) = events {
+        // A closed receiver means the client went away; the turn carries on
+        let _ = events.send(event);
+    }
+}
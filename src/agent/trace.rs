@@ -0,0 +1,435 @@
+//! Per-run traces with step timings
+//!
+//! Every agent run records a [`RunTrace`]: how long the system prompt took
+//! to assemble (memory recall included), each LLM call with its model and
+//! token counts, and each tool's latency. [`TraceStore`] keeps them under
+//! `workspace/traces/<session>/<run>.json`, the most recent
+//! [`MAX_TRACES_PER_SESSION`] per session, for `takobull trace <run-id>`.
+//! With a [`Redactor`], the message preview and errors are redacted like
+//! stored sessions are.
+
+use crate::error::{Error, Result};
+use crate::session::Redactor;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Traces directory, relative to the workspace
+pub const TRACES_DIR: &str = "traces";
+
+/// Directory for runs outside a session, such as jobs and alert prompts
+pub const NO_SESSION_DIR: &str = "_background";
+
+/// Traces kept per session; older ones are deleted
+pub const MAX_TRACES_PER_SESSION: usize = 50;
+
+/// Characters of the message kept in a trace
+const MESSAGE_PREVIEW_CHARS: usize = 120;
+
+/// One timed step of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StepKind {
+    /// System prompt assembly, including memory recall
+    Prompt,
+    /// A chat completion
+    Llm {
+        iteration: usize,
+        model: String,
+        input_tokens: usize,
+        output_tokens: usize,
+        /// Tool calls the model asked for
+        tool_calls: usize,
+    },
+    /// A tool execution
+    Tool { iteration: usize, name: String, ok: bool },
+    /// Shrinking a message that overflowed the context window
+    Reduce,
+}
+
+/// A timed step, relative to the start of the run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    #[serde(flatten)]
+    pub kind: StepKind,
+    pub offset_ms: u64,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TraceStep {
+    /// Short description, e.g. `llm #2 gpt-4o` or `tool web_search`
+    pub fn label(&self) -> String {
+        match &self.kind {
+            StepKind::Prompt => "prompt".to_string(),
+            StepKind::Llm { iteration, model, .. } => format!("llm #{} {}", iteration, model),
+            StepKind::Tool { name, .. } => format!("tool {}", name),
+            StepKind::Reduce => "reduce".to_string(),
+        }
+    }
+}
+
+/// Timings and usage of one agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTrace {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Start of the message, for listings
+    pub message: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub iterations: usize,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub steps: Vec<TraceStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip)]
+    clock: Option<Instant>,
+}
+
+impl RunTrace {
+    /// Start timing a run of `message`
+    pub fn start(message: &str, session_id: Option<&str>, channel: Option<&str>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            session_id: session_id.map(str::to_string),
+            channel: channel.filter(|c| !c.is_empty()).map(str::to_string),
+            message: message.chars().take(MESSAGE_PREVIEW_CHARS).collect(),
+            started_at: Utc::now(),
+            duration_ms: 0,
+            iterations: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            steps: Vec::new(),
+            error: None,
+            clock: Some(Instant::now()),
+        }
+    }
+
+    /// Record a step that began at `started` and has just ended
+    pub fn step(&mut self, kind: StepKind, started: Instant, error: Option<String>) {
+        if let StepKind::Llm {
+            iteration,
+            input_tokens,
+            output_tokens,
+            ..
+        } = &kind
+        {
+            self.iterations = self.iterations.max(*iteration);
+            self.input_tokens += input_tokens;
+            self.output_tokens += output_tokens;
+        }
+        let offset = self.clock.map(|clock| started.saturating_duration_since(clock)).unwrap_or_default();
+        self.steps.push(TraceStep {
+            kind,
+            offset_ms: millis(offset),
+            duration_ms: millis(started.elapsed()),
+            error,
+        });
+    }
+
+    /// Record the end of the run
+    pub fn finish(&mut self, error: Option<String>) {
+        self.duration_ms = self.clock.map(|clock| millis(clock.elapsed())).unwrap_or(self.duration_ms);
+        self.error = error;
+    }
+
+    /// The step that took longest
+    pub fn slowest(&self) -> Option<&TraceStep> {
+        self.steps.iter().max_by_key(|step| step.duration_ms)
+    }
+
+    /// Multi-line timeline for the terminal
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "Run {} · {}{} · {}\n",
+            self.id,
+            self.session_id.as_deref().map(|s| format!("session {}", s)).unwrap_or_else(|| "no session".to_string()),
+            self.channel.as_deref().map(|c| format!(" ({})", c)).unwrap_or_default(),
+            self.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        text.push_str(&format!("> {}\n", self.message));
+        text.push_str(&format!(
+            "Total {} · {} iteration{} · {} in / {} out tokens\n\n",
+            seconds(self.duration_ms),
+            self.iterations,
+            if self.iterations == 1 { "" } else { "s" },
+            self.input_tokens,
+            self.output_tokens
+        ));
+        for step in &self.steps {
+            let detail = match &step.kind {
+                StepKind::Llm {
+                    input_tokens,
+                    output_tokens,
+                    tool_calls,
+                    ..
+                } => {
+                    let mut detail = format!("{} in / {} out", input_tokens, output_tokens);
+                    if *tool_calls > 0 {
+                        detail.push_str(&format!(" → {} tool call{}", tool_calls, if *tool_calls == 1 { "" } else { "s" }));
+                    }
+                    detail
+                }
+                StepKind::Tool { ok, .. } => if *ok { "ok" } else { "failed" }.to_string(),
+                _ => String::new(),
+            };
+            let mut line = format!(
+                "  +{:>8}  {:<28} {:>8}  {}",
+                seconds(step.offset_ms),
+                step.label(),
+                seconds(step.duration_ms),
+                detail
+            );
+            if let Some(error) = &step.error {
+                line.push_str(&format!("  error: {}", error));
+            }
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        if let Some(slowest) = self.slowest().filter(|_| self.duration_ms > 0) {
+            text.push_str(&format!(
+                "\nSlowest: {} ({}, {}% of the run)\n",
+                slowest.label(),
+                seconds(slowest.duration_ms),
+                slowest.duration_ms * 100 / self.duration_ms
+            ));
+        }
+        if let Some(error) = &self.error {
+            text.push_str(&format!("Failed: {}\n", error));
+        }
+        text
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().min(u64::MAX as u128) as u64
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.2}s", ms as f64 / 1000.0)
+}
+
+/// Run traces kept in the workspace
+#[derive(Debug, Clone)]
+pub struct TraceStore {
+    dir: PathBuf,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl TraceStore {
+    /// Traces under `workspace/traces`
+    pub fn new(workspace: impl AsRef<Path>) -> Self {
+        Self {
+            dir: workspace.as_ref().join(TRACES_DIR),
+            redactor: None,
+        }
+    }
+
+    /// Redact message previews and errors before they are saved
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Start timing a run of `message` to be saved here
+    ///
+    /// The whole message is redacted before the preview is cut, so a number
+    /// split by the cut is still caught.
+    pub fn start(&self, message: &str, session_id: Option<&str>, channel: Option<&str>) -> RunTrace {
+        match &self.redactor {
+            Some(redactor) => RunTrace::start(&redactor.redact(message), session_id, channel),
+            None => RunTrace::start(message, session_id, channel),
+        }
+    }
+
+    fn session_dir(&self, session_id: Option<&str>) -> PathBuf {
+        self.dir.join(session_id.unwrap_or(NO_SESSION_DIR))
+    }
+
+    /// Save `trace` and drop the session's oldest traces beyond the limit
+    pub fn save(&self, trace: &RunTrace) -> Result<()> {
+        let dir = self.session_dir(trace.session_id.as_deref());
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.json", trace.id));
+        let tmp_path = path.with_extension("json.tmp");
+        let json = match &self.redactor {
+            Some(redactor) => {
+                let mut redacted = trace.clone();
+                redacted.error = redacted.error.map(|e| redactor.redact(&e));
+                for step in &mut redacted.steps {
+                    step.error = step.error.as_ref().map(|e| redactor.redact(e));
+                }
+                serde_json::to_string_pretty(&redacted)?
+            }
+            None => serde_json::to_string_pretty(trace)?,
+        };
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, &path)?;
+
+        let mut traces = read_dir(&dir)?;
+        if traces.len() > MAX_TRACES_PER_SESSION {
+            traces.sort_by_key(|t| std::cmp::Reverse(t.started_at));
+            for old in &traces[MAX_TRACES_PER_SESSION..] {
+                let _ = std::fs::remove_file(dir.join(format!("{}.json", old.id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The trace of run `id`, from any session
+    pub fn load(&self, id: &str) -> Result<RunTrace> {
+        check_id(id, "run")?;
+        let file = format!("{}.json", id);
+        if self.dir.exists() {
+            for session in std::fs::read_dir(&self.dir)? {
+                let path = session?.path().join(&file);
+                if path.exists() {
+                    return Ok(serde_json::from_str(&std::fs::read_to_string(&path)?)?);
+                }
+            }
+        }
+        Err(Error::session(format!("No trace for run {}", id)))
+    }
+
+    /// Up to `limit` traces, newest first, optionally from one session only
+    pub fn recent(&self, session_id: Option<&str>, limit: usize) -> Result<Vec<RunTrace>> {
+        let mut traces = Vec::new();
+        if let Some(session_id) = session_id {
+            check_id(session_id, "session")?;
+            traces = read_dir(&self.session_dir(Some(session_id)))?;
+        } else if self.dir.exists() {
+            for session in std::fs::read_dir(&self.dir)? {
+                traces.extend(read_dir(&session?.path())?);
+            }
+        }
+        traces.sort_by_key(|t| std::cmp::Reverse(t.started_at));
+        traces.truncate(limit);
+        Ok(traces)
+    }
+}
+
+fn check_id(id: &str, what: &str) -> Result<()> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::session(format!("Invalid {} id: {}", what, id)));
+    }
+    Ok(())
+}
+
+/// Traces in one session directory, skipping unreadable files
+fn read_dir(dir: &Path) -> Result<Vec<RunTrace>> {
+    let mut traces = Vec::new();
+    if !dir.is_dir() {
+        return Ok(traces);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match serde_json::from_str(&std::fs::read_to_string(&path)?) {
+            Ok(trace) => traces.push(trace),
+            Err(e) => warn!("Skipping unreadable trace {:?}: {}", path, e),
+        }
+    }
+    Ok(traces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(session: Option<&str>) -> RunTrace {
+        let mut trace = RunTrace::start("why is the porch light on?", session, Some("cli"));
+        let started = Instant::now();
+        trace.step(
+            StepKind::Llm {
+                iteration: 1,
+                model: "gpt-4o".to_string(),
+                input_tokens: 900,
+                output_tokens: 40,
+                tool_calls: 1,
+            },
+            started,
+            None,
+        );
+        trace.step(
+            StepKind::Tool {
+                iteration: 1,
+                name: "device".to_string(),
+                ok: false,
+            },
+            started,
+            Some("timeout".to_string()),
+        );
+        trace.finish(None);
+        trace
+    }
+
+    #[test]
+    fn test_trace_totals_and_render() {
+        let trace = trace(Some("s1"));
+        assert_eq!(trace.iterations, 1);
+        assert_eq!(trace.input_tokens, 900);
+        assert_eq!(trace.steps.len(), 2);
+
+        let text = trace.render();
+        assert!(text.contains("session s1 (cli)"));
+        assert!(text.contains("llm #1 gpt-4o"));
+        assert!(text.contains("900 in / 40 out → 1 tool call"));
+        assert!(text.contains("tool device"));
+        assert!(text.contains("error: timeout"));
+    }
+
+    #[test]
+    fn test_store_finds_runs_and_keeps_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path());
+        let background = trace(None);
+        store.save(&background).unwrap();
+        let mut in_session = Vec::new();
+        for i in 0..MAX_TRACES_PER_SESSION + 2 {
+            let mut trace = trace(Some("s1"));
+            trace.started_at += chrono::Duration::seconds(i as i64);
+            store.save(&trace).unwrap();
+            in_session.push(trace);
+        }
+
+        assert_eq!(store.load(&background.id).unwrap().steps, background.steps);
+        assert!(store.load(&in_session[0].id).is_err());
+        assert!(store.load("../x").is_err());
+
+        let recent = store.recent(Some("s1"), 100).unwrap();
+        assert_eq!(recent.len(), MAX_TRACES_PER_SESSION);
+        assert_eq!(recent[0].id, in_session.last().unwrap().id);
+        assert_eq!(store.recent(None, 3).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_redacts_preview_before_cutting_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = crate::config::RedactionConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let store = TraceStore::new(dir.path()).with_redactor(Arc::new(Redactor::new(&config).unwrap()));
+        // The card number straddles the preview cut
+        let message = format!("{} 4111 1111 1111 1111", "x".repeat(MESSAGE_PREVIEW_CHARS - 8));
+        let mut trace = store.start(&message, Some("s1"), None);
+        trace.finish(Some("card 4111111111111111 declined".to_string()));
+        store.save(&trace).unwrap();
+
+        let saved = std::fs::read_to_string(dir.path().join(TRACES_DIR).join("s1").join(format!("{}.json", trace.id)))
+            .unwrap();
+        assert!(!saved.contains("4111"), "{}", saved);
+        assert!(saved.contains("[REDACTED:credit_card]"));
+    }
+}
@@ -0,0 +1,92 @@
+//! "What I did" sections summarizing a run's tool use
+//!
+//! Built from what the executor recorded (the tools it ran, the files those
+//! tools reported reading or writing, and the URLs their results came from),
+//! so the summary cannot drift from what happened the way a model's own
+//! account of its actions can.
+
+/// Items listed per line before the rest are counted
+const MAX_LISTED: usize = 5;
+
+/// "What I did" section for a run that called `tool_calls`, touched
+/// `files`, and fetched `urls`; `None` when no tools ran
+pub fn section(tool_calls: &[String], files: &[String], urls: &[String]) -> Option<String> {
+    if tool_calls.is_empty() {
+        return None;
+    }
+
+    // Repeated calls are folded into a count, keeping first-call order
+    let mut tools: Vec<(&str, usize)> = Vec::new();
+    for name in tool_calls {
+        match tools.iter_mut().find(|(tool, _)| tool == name) {
+            Some((_, count)) => *count += 1,
+            None => tools.push((name, 1)),
+        }
+    }
+    let tools: Vec<String> = tools
+        .into_iter()
+        .map(|(tool, count)| match count {
+            1 => tool.to_string(),
+            n => format!("{} ×{}", tool, n),
+        })
+        .collect();
+
+    let mut section = String::from("\n\nWhat I did:");
+    for (label, items) in [("Tools", &tools[..]), ("Files", files), ("URLs", urls)] {
+        if items.is_empty() {
+            continue;
+        }
+        section.push_str(&format!("\n- {}: {}", label, list(items)));
+    }
+    Some(section)
+}
+
+/// Comma-separated `items`, with any past [`MAX_LISTED`] counted
+fn list(items: &[String]) -> String {
+    let mut text = items[..items.len().min(MAX_LISTED)].join(", ");
+    if items.len() > MAX_LISTED {
+        text.push_str(&format!(" and {} more", items.len() - MAX_LISTED));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TranscriptConfig;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_section_lists_tools_files_and_urls() {
+        let tools = strings(&["read_file", "web_fetch", "read_file", "write_file"]);
+        let files = strings(&["notes/todo.md", "notes/done.md"]);
+        let urls: Vec<String> = (1..=7).map(|i| format!("https://example.com/{}", i)).collect();
+
+        let section = section(&tools, &files, &urls).unwrap();
+        let lines: Vec<&str> = section.lines().collect();
+        assert_eq!(lines[2], "What I did:");
+        assert_eq!(lines[3], "- Tools: read_file ×2, web_fetch, write_file");
+        assert_eq!(lines[4], "- Files: notes/todo.md, notes/done.md");
+        assert!(lines[5].starts_with("- URLs: https://example.com/1, "));
+        assert!(lines[5].ends_with("https://example.com/5 and 2 more"));
+
+        assert_eq!(super::section(&[], &files, &urls), None);
+        let tools_only = super::section(&strings(&["exec"]), &[], &[]).unwrap();
+        assert_eq!(tools_only, "\n\nWhat I did:\n- Tools: exec");
+    }
+
+    #[test]
+    fn test_user_setting_wins_over_channel() {
+        let config: TranscriptConfig = serde_yaml::from_str(
+            "channels: {telegram: true}\nusers: {alice: false}",
+        )
+        .unwrap();
+        assert!(!config.enabled_for(None, None));
+        assert!(config.enabled_for(Some("bob"), Some("telegram")));
+        assert!(!config.enabled_for(Some("alice"), Some("telegram")));
+        assert!(!config.enabled_for(Some("bob"), Some("discord")));
+    }
+}
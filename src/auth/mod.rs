@@ -1,9 +1,11 @@
-//! Authentication system for TakoBull (OAuth2 and PKCE)
+//! Authentication system for TakoBull (OAuth2, PKCE, and secrets)
 
 pub mod oauth2;
 pub mod pkce;
+pub mod secrets;
 pub mod token_storage;
 
 pub use oauth2::OAuthConfig;
 pub use pkce::PkceChallenge;
+pub use secrets::SecretStore;
 pub use token_storage::TokenPair;
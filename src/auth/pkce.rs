@@ -28,7 +28,7 @@ impl PkceChallenge {
         let mut hasher = Sha256::new();
         hasher.update(code_verifier.as_bytes());
         let hash = hasher.finalize();
-        let code_challenge = URL_SAFE_NO_PAD.encode(&hash);
+        let code_challenge = URL_SAFE_NO_PAD.encode(hash);
 
         PkceChallenge {
             code_verifier,
@@ -41,14 +41,14 @@ impl PkceChallenge {
         let mut hasher = Sha256::new();
         hasher.update(self.code_verifier.as_bytes());
         let hash = hasher.finalize();
-        let computed_challenge = URL_SAFE_NO_PAD.encode(&hash);
+        let computed_challenge = URL_SAFE_NO_PAD.encode(hash);
         computed_challenge == challenge
     }
 
     /// Check if the code verifier is valid (43-128 characters, URL-safe base64)
     pub fn is_valid_verifier(&self) -> bool {
         let len = self.code_verifier.len();
-        len >= 43 && len <= 128 && self.code_verifier.chars().all(|c| {
+        (43..=128).contains(&len) && self.code_verifier.chars().all(|c| {
             c.is_ascii_alphanumeric() || c == '-' || c == '_'
         })
     }
@@ -76,7 +76,7 @@ mod tests {
     fn test_pkce_verifier_length() {
         let challenge = PkceChallenge::generate();
         let len = challenge.code_verifier.len();
-        assert!(len >= 43 && len <= 128);
+        assert!((43..=128).contains(&len));
     }
 
     #[test]
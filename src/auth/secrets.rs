@@ -0,0 +1,147 @@
+//! Secret store for credentials injected into tools
+//!
+//! Secrets live in `~/.takobull/secrets.yaml` (mode 0600) as a flat map and
+//! are read at use time, so edits apply without a restart and values are
+//! never held in config structs that might be logged.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Prefix referencing a secret store entry, e.g. `secret:github_token`
+pub const SECRET_PREFIX: &str = "secret:";
+/// Prefix referencing a gateway environment variable, e.g. `env:GITHUB_TOKEN`
+pub const ENV_PREFIX: &str = "env:";
+
+/// File-backed secret store
+pub struct SecretStore {
+    path: PathBuf,
+}
+
+impl SecretStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Store path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        if contents.trim().is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        Ok(serde_yaml::from_str(&contents)?)
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("yaml.tmp");
+        // Left behind by an interrupted save; it may not be 0600
+        match std::fs::remove_file(&tmp_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        // Created 0600 so the secrets are never readable by others, even briefly
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp_path)?;
+        std::io::Write::write_all(&mut file, serde_yaml::to_string(secrets)?.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Look up a secret
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.load()?.remove(name))
+    }
+
+    /// Add or replace a secret
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        let mut secrets = self.load()?;
+        secrets.insert(name.to_string(), value.to_string());
+        self.save(&secrets)
+    }
+
+    /// Remove a secret, returning whether it existed
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let mut secrets = self.load()?;
+        let existed = secrets.remove(name).is_some();
+        if existed {
+            self.save(&secrets)?;
+        }
+        Ok(existed)
+    }
+
+    /// Names of stored secrets
+    pub fn names(&self) -> Result<Vec<String>> {
+        Ok(self.load()?.into_keys().collect())
+    }
+
+    /// Resolve a value reference: `secret:NAME`, `env:NAME`, or a literal
+    pub fn resolve(&self, reference: &str) -> Result<String> {
+        if let Some(name) = reference.strip_prefix(SECRET_PREFIX) {
+            self.get(name)?
+                .ok_or_else(|| Error::config(format!("Secret not found: {}", name)))
+        } else if let Some(name) = reference.strip_prefix(ENV_PREFIX) {
+            std::env::var(name)
+                .map_err(|_| Error::config(format!("Environment variable not set: {}", name)))
+        } else {
+            Ok(reference.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::new(dir.path().join("secrets.yaml"));
+        assert!(store.get("github_token").unwrap().is_none());
+        // A stale temporary file from an interrupted save is replaced
+        std::fs::write(dir.path().join("secrets.yaml.tmp"), "stale").unwrap();
+
+        store.set("github_token", "ghp_123").unwrap();
+        store.set("api_key", "k").unwrap();
+        assert_eq!(store.get("github_token").unwrap().as_deref(), Some("ghp_123"));
+        assert_eq!(store.names().unwrap(), vec!["api_key", "github_token"]);
+
+        assert!(store.remove("api_key").unwrap());
+        assert!(!store.remove("api_key").unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(store.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::new(dir.path().join("secrets.yaml"));
+        store.set("token", "s3cret").unwrap();
+
+        assert_eq!(store.resolve("secret:token").unwrap(), "s3cret");
+        assert_eq!(store.resolve("plain").unwrap(), "plain");
+        assert!(store.resolve("secret:missing").is_err());
+        assert!(store.resolve("env:TAKOBULL_TEST_UNSET_VAR").is_err());
+    }
+}
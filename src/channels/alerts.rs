@@ -0,0 +1,217 @@
+//! The alert channel
+//!
+//! Telemetry alerts, failing-job notices, low-disk warnings, and the daily
+//! email digest raised by the gateway go to the destination under
+//! `channels.alerts`: notices in a Matrix room, or JSON POSTed to a webhook. The channel only sends, so
+//! nothing posted at the destination reaches the agent. Notices go through
+//! the [`Outbox`], so failed deliveries are retried and show in status output.
+
+use super::framework::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use super::observer::send_matrix_notice;
+use super::outbox::{DeliveryState, Outbox};
+use crate::config::ObserverTarget;
+use crate::error::{Error, Result};
+use crate::scheduler::JobNotifier;
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+/// Channel id of alert notices
+pub const ALERT_CHANNEL: &str = "alerts";
+
+/// Send-only channel to the configured alert destination
+pub struct AlertChannel {
+    target: ObserverTarget,
+}
+
+impl AlertChannel {
+    pub fn new(target: ObserverTarget) -> Self {
+        Self { target }
+    }
+}
+
+#[async_trait]
+impl Channel for AlertChannel {
+    async fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn receive_message(&mut self) -> Result<Option<IncomingMessage>> {
+        Ok(None)
+    }
+
+    async fn send_message(&self, msg: OutgoingMessage) -> Result<()> {
+        match &self.target {
+            ObserverTarget::Matrix {
+                homeserver,
+                access_token,
+                room_id,
+            } => send_matrix_notice(homeserver, access_token, room_id, &msg.content).await,
+            ObserverTarget::Webhook { url, token } => {
+                let body = serde_json::json!({ "text": msg.content, "at": chrono::Utc::now() });
+                let mut request = reqwest::Client::new().post(url).json(&body);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| Error::channel(format!("Webhook request failed: {}", e)))?;
+                if !response.status().is_success() {
+                    return Err(Error::channel(format!("Webhook returned {}", response.status())));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        match self.target {
+            ObserverTarget::Matrix { .. } => ChannelType::Matrix,
+            ObserverTarget::Webhook { .. } => ChannelType::Webhook,
+        }
+    }
+}
+
+/// Delivers notices through the alert channel, if one is configured
+#[derive(Default)]
+pub struct AlertNotifier {
+    channel: Option<AlertChannel>,
+    outbox: Option<Outbox>,
+}
+
+impl AlertNotifier {
+    pub fn new(channel: Option<AlertChannel>) -> Self {
+        Self { channel, outbox: None }
+    }
+
+    /// Send notices through `outbox`, retrying and recording delivery
+    pub fn with_outbox(mut self, outbox: Outbox) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+}
+
+#[async_trait]
+impl JobNotifier for AlertNotifier {
+    async fn notify(&self, message: &str) {
+        let Some(channel) = &self.channel else {
+            warn!("No alert channel configured (channels.alerts), dropping notice: {}", message);
+            return;
+        };
+        let notice = OutgoingMessage {
+            channel_id: ALERT_CHANNEL.to_string(),
+            user_id: ALERT_CHANNEL.to_string(),
+            content: message.to_string(),
+            chat_id: None,
+            thread_id: None,
+            reply_to: None,
+        };
+        let Some(outbox) = &self.outbox else {
+            match channel.send_message(notice).await {
+                Ok(()) => debug!("Sent notice to the alert channel"),
+                Err(e) => warn!("Alert channel did not receive notice: {}", e),
+            }
+            return;
+        };
+        match outbox.send(channel, notice).await {
+            Ok(entry) if entry.state == DeliveryState::Sent => debug!("Sent notice {} to the alert channel", entry.id),
+            // The outbox has already logged each failed attempt
+            Ok(_) => {}
+            Err(e) => warn!("Could not record alert notice in the outbox: {}", e),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "webhooks"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notices_reach_matrix_and_webhook() {
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let seen = Arc::new(Mutex::new(Vec::<String>::new()));
+        let (matrix_seen, hook_seen) = (seen.clone(), seen.clone());
+        let server = axum::Router::new()
+            .route(
+                "/_matrix/client/v3/rooms/:room/send/m.room.message/:txn",
+                axum::routing::put(move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    matrix_seen.lock().push(format!("{} {}", body["msgtype"], body["body"]));
+                    axum::Json(serde_json::json!({ "event_id": "$1" }))
+                }),
+            )
+            .route(
+                "/alerts",
+                axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    hook_seen.lock().push(body["text"].to_string());
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, server).await });
+
+        let matrix = AlertNotifier::new(Some(AlertChannel::new(ObserverTarget::Matrix {
+            homeserver: base.clone(),
+            access_token: "token".to_string(),
+            room_id: "!alerts:example.org".to_string(),
+        })));
+        matrix.notify("CPU temperature is 81°C").await;
+        let webhook = AlertNotifier::new(Some(AlertChannel::new(ObserverTarget::Webhook {
+            url: format!("{}/alerts", base),
+            token: None,
+        })));
+        webhook.notify("Job backup failed 3 times in a row").await;
+        AlertNotifier::default().notify("nowhere to go").await;
+
+        assert_eq!(
+            *seen.lock(),
+            vec![
+                "\"m.notice\" \"CPU temperature is 81°C\"",
+                "\"Job backup failed 3 times in a row\""
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_notices_are_recorded_in_outbox() {
+        use std::time::Duration;
+
+        let server = axum::Router::new()
+            .route("/ok", axum::routing::post(|| async {}))
+            .route(
+                "/expired",
+                axum::routing::post(|| async { axum::http::StatusCode::UNAUTHORIZED }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, server).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = || Outbox::new(dir.path()).with_retries(2, Duration::from_millis(1));
+        let webhook = |path: &str| {
+            Some(AlertChannel::new(ObserverTarget::Webhook {
+                url: format!("{}/{}", base, path),
+                token: None,
+            }))
+        };
+        AlertNotifier::new(webhook("ok"))
+            .with_outbox(outbox())
+            .notify("Disk space is low")
+            .await;
+        AlertNotifier::new(webhook("expired"))
+            .with_outbox(outbox())
+            .notify("CPU temperature is 81°C")
+            .await;
+
+        let stats = outbox().stats().await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].channel_id, ALERT_CHANNEL);
+        assert_eq!((stats[0].sent, stats[0].failed, stats[0].retried), (1, 1, 1));
+        assert_eq!(stats[0].last_error.as_deref(), Some("Channel error: Webhook returned 401 Unauthorized"));
+    }
+}
@@ -0,0 +1,287 @@
+//! Handling for edited messages
+//!
+//! When a user edits their last message within the grace window, the agent
+//! run for the original text is cancelled and the edited text is processed
+//! in its place. Edits to older messages, or after the window, are ignored.
+//!
+//! Any channel can handle edits the same way: register each message with
+//! [`EditTracker::begin`], [`MessageRun::prepare`] the session, drive the
+//! agent with [`MessageRun::run`], and [`MessageRun::record`] the exchange.
+
+use super::framework::IncomingMessage;
+use crate::agent::context::MessageRole;
+use crate::session::Session;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// Default window after a message during which an edit replaces it
+pub const DEFAULT_EDIT_GRACE: Duration = Duration::from_secs(120);
+
+struct LastMessage {
+    message_id: String,
+    received_at: Instant,
+    token: CancellationToken,
+    /// Set once some version of the message has been recorded in its session
+    recorded: Arc<AtomicBool>,
+}
+
+/// An agent run started for an incoming message
+#[derive(Debug, Clone)]
+pub struct MessageRun {
+    token: CancellationToken,
+    recorded: Arc<AtomicBool>,
+    /// Whether this run replaces the run for the message's original text
+    pub supersedes: bool,
+}
+
+impl MessageRun {
+    /// Whether a later edit has cancelled this run
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Drive `future` to completion unless the run is cancelled first
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            _ = self.token.cancelled() => None,
+            output = future => Some(output),
+        }
+    }
+
+    /// Drop the exchange an edit replaces from `session` before running it
+    ///
+    /// Once the original text has been recorded the model would otherwise
+    /// see both versions. Call this after [`EditTracker::begin`].
+    pub fn prepare(&self, session: &mut Session) {
+        if !self.supersedes || !self.recorded.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Some(index) = session.messages.iter().rposition(|m| m.role == MessageRole::User) {
+            session.messages.truncate(index);
+        }
+    }
+
+    /// Record the message and the agent's reply in `session`
+    ///
+    /// An edit of an already recorded message corrects it in place. Returns
+    /// false, leaving the session alone, if a later edit has cancelled this
+    /// run. Callers must serialize calls for the same session.
+    pub fn record(&self, session: &mut Session, text: &str, reply: impl Into<String>) -> bool {
+        if self.is_cancelled() {
+            return false;
+        }
+        if !(self.supersedes
+            && self.recorded.load(Ordering::SeqCst)
+            && session.correct_last_user_message(text))
+        {
+            session.add_message(MessageRole::User, text);
+        }
+        session.add_message(MessageRole::Assistant, reply);
+        self.recorded.store(true, Ordering::SeqCst);
+        true
+    }
+}
+
+/// Channel, chat, thread, and author of a message
+type ConversationKey = (String, String, Option<String>, String);
+
+/// Tracks each user's last message per conversation so edits can supersede its run
+pub struct EditTracker {
+    grace: Duration,
+    last: Mutex<HashMap<ConversationKey, LastMessage>>,
+}
+
+impl EditTracker {
+    /// Create a tracker accepting edits for `grace` after the original message
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            grace,
+            last: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register an incoming message, returning the run to process it with
+    ///
+    /// Returns `None` for edits that should be ignored: edits of anything but
+    /// the conversation's last message, or edits after the grace window.
+    pub fn begin(&self, message: &IncomingMessage) -> Option<MessageRun> {
+        self.begin_at(message, Instant::now())
+    }
+
+    fn begin_at(&self, message: &IncomingMessage, now: Instant) -> Option<MessageRun> {
+        let key = (
+            message.channel_id.clone(),
+            message.chat().to_string(),
+            message.thread_id.clone(),
+            message.user_id.clone(),
+        );
+        let mut last = self.last.lock();
+        let token = CancellationToken::new();
+
+        if !message.edited {
+            let recorded = Arc::new(AtomicBool::new(false));
+            if let Some(message_id) = &message.message_id {
+                last.insert(
+                    key,
+                    LastMessage {
+                        message_id: message_id.clone(),
+                        received_at: now,
+                        token: token.clone(),
+                        recorded: recorded.clone(),
+                    },
+                );
+            }
+            return Some(MessageRun {
+                token,
+                recorded,
+                supersedes: false,
+            });
+        }
+
+        let previous = last.get_mut(&key)?;
+        if message.message_id.as_deref() != Some(previous.message_id.as_str()) {
+            debug!("Ignoring edit of an earlier message in {}", message.channel_id);
+            return None;
+        }
+        if now.duration_since(previous.received_at) > self.grace {
+            debug!("Ignoring edit outside the grace window in {}", message.channel_id);
+            return None;
+        }
+
+        previous.token.cancel();
+        previous.token = token.clone();
+        Some(MessageRun {
+            token,
+            recorded: previous.recorded.clone(),
+            supersedes: true,
+        })
+    }
+}
+
+impl Default for EditTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_EDIT_GRACE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn message(id: &str, edited: bool) -> IncomingMessage {
+        IncomingMessage {
+            channel_id: "chat-1".to_string(),
+            user_id: "user-1".to_string(),
+            content: "hello".to_string(),
+            timestamp: SystemTime::now(),
+            message_id: Some(id.to_string()),
+            edited,
+            chat_id: None,
+            thread_id: None,
+            reply_to: None,
+        }
+    }
+
+    #[test]
+    fn test_edit_cancels_superseded_run() {
+        let tracker = EditTracker::default();
+        let now = Instant::now();
+        let original = tracker.begin_at(&message("1", false), now).unwrap();
+        let edit = tracker
+            .begin_at(&message("1", true), now + Duration::from_secs(10))
+            .unwrap();
+
+        assert!(original.is_cancelled());
+        assert!(!edit.is_cancelled());
+        assert!(edit.supersedes);
+    }
+
+    #[test]
+    fn test_ignores_late_and_stale_edits() {
+        let tracker = EditTracker::new(Duration::from_secs(60));
+        let now = Instant::now();
+        tracker.begin_at(&message("1", false), now).unwrap();
+        tracker.begin_at(&message("2", false), now).unwrap();
+
+        assert!(tracker.begin_at(&message("1", true), now).is_none());
+        assert!(tracker
+            .begin_at(&message("2", true), now + Duration::from_secs(61))
+            .is_none());
+    }
+
+    #[test]
+    fn test_edits_are_tracked_per_chat() {
+        let tracker = EditTracker::default();
+        let in_group = |id: &str, edited: bool| IncomingMessage {
+            chat_id: Some("group-1".to_string()),
+            ..message(id, edited)
+        };
+        let direct = tracker.begin(&message("1", false)).unwrap();
+        tracker.begin(&in_group("2", false)).unwrap();
+
+        assert!(tracker.begin(&message("1", true)).is_some());
+        assert!(direct.is_cancelled());
+        assert!(tracker.begin(&in_group("2", true)).is_some());
+    }
+
+    async fn session(dir: &std::path::Path) -> Session {
+        crate::session::SessionManager::new(dir)
+            .create_session("user-1")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_edit_after_reply_corrects_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = session(dir.path()).await;
+        let tracker = EditTracker::default();
+        let original = tracker.begin(&message("1", false)).unwrap();
+        assert!(original.record(&mut session, "whats the wether", "Which city?"));
+
+        let edit = tracker.begin(&message("1", true)).unwrap();
+        let mut context = session.clone();
+        edit.prepare(&mut context);
+        assert!(context.messages.is_empty());
+
+        assert!(edit.record(&mut session, "what's the weather in Oslo", "Sunny"));
+        let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["what's the weather in Oslo", "Sunny"]);
+    }
+
+    #[tokio::test]
+    async fn test_superseded_run_is_not_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = session(dir.path()).await;
+        session.add_message(MessageRole::User, "earlier");
+        session.add_message(MessageRole::Assistant, "reply");
+        let tracker = EditTracker::default();
+        let original = tracker.begin(&message("1", false)).unwrap();
+        let edit = tracker.begin(&message("1", true)).unwrap();
+
+        let mut context = session.clone();
+        edit.prepare(&mut context);
+        assert_eq!(context.messages.len(), 2);
+
+        assert!(!original.record(&mut session, "whats the wether", "Which city?"));
+        assert!(edit.record(&mut session, "what's the weather in Oslo", "Sunny"));
+        let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["earlier", "reply", "what's the weather in Oslo", "Sunny"]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_yields_nothing() {
+        let tracker = EditTracker::default();
+        let original = tracker.begin(&message("1", false)).unwrap();
+        tracker.begin(&message("1", true)).unwrap();
+        assert_eq!(original.run(async { 42 }).await, None);
+    }
+}
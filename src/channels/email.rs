@@ -0,0 +1,348 @@
+//! Inbound email from a local Maildir
+//!
+//! Mail is fetched by the system's own tools (fetchmail, mbsync, or a local
+//! MTA) into a Maildir, so the assistant needs no IMAP credentials. New mail
+//! is read from `new/`, parsed down to sender, subject, and plain-text body,
+//! and moved to `cur/` marked as seen so each message is handled once.
+
+use crate::error::{Error, Result};
+use base64::Engine;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Longest body kept per email, in characters
+pub const MAX_BODY_CHARS: usize = 4000;
+
+/// A parsed email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    /// Message-ID, or the Maildir file name when the header is missing
+    pub id: String,
+    pub from: String,
+    pub subject: String,
+    /// Date header as sent
+    pub date: Option<String>,
+    /// Plain-text body, truncated to [`MAX_BODY_CHARS`]
+    pub body: String,
+}
+
+/// A Maildir delivered to by an external mail fetcher
+pub struct Maildir {
+    dir: PathBuf,
+}
+
+impl Maildir {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Parse every message in `new/`, oldest first, and move it to `cur/`
+    pub async fn take_new(&self) -> Result<Vec<Email>> {
+        let new_dir = self.dir.join("new");
+        if !new_dir.exists() {
+            return Err(Error::channel(format!("No Maildir at {:?}", self.dir)));
+        }
+        let cur_dir = self.dir.join("cur");
+        tokio::fs::create_dir_all(&cur_dir).await?;
+
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(&new_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                files.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        // Maildir names start with the delivery time
+        files.sort();
+
+        let mut emails = Vec::new();
+        for name in files {
+            let path = new_dir.join(&name);
+            let raw = match tokio::fs::read(&path).await {
+                Ok(raw) => raw,
+                Err(e) => {
+                    warn!("Skipping unreadable email {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let mut email = parse_email(&String::from_utf8_lossy(&raw));
+            if email.id.is_empty() {
+                email.id = name.clone();
+            }
+            let seen = match name.split_once(":2,") {
+                Some((base, flags)) if !flags.contains('S') => format!("{}:2,{}S", base, flags),
+                Some(_) => name.clone(),
+                None => format!("{}:2,S", name),
+            };
+            tokio::fs::rename(&path, cur_dir.join(seen)).await?;
+            debug!("Read email {} from {}", email.id, email.from);
+            emails.push(email);
+        }
+        Ok(emails)
+    }
+}
+
+/// Parse a raw RFC 5322 message
+pub fn parse_email(raw: &str) -> Email {
+    let (headers, body) = split_message(raw);
+    let header = |name: &str| header_value(&headers, name).map(|v| decode_words(&v));
+    let body = text_body(&headers, body);
+    Email {
+        id: header("message-id")
+            .map(|id| id.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+            .unwrap_or_default(),
+        from: header("from").unwrap_or_default(),
+        subject: header("subject").unwrap_or_default(),
+        date: header("date"),
+        body: truncate(body.trim(), MAX_BODY_CHARS),
+    }
+}
+
+/// Unfolded `(name, value)` headers and the body
+fn split_message(raw: &str) -> (Vec<(String, String)>, &str) {
+    let raw = raw.trim_start_matches(['\r', '\n']);
+    let (head, body) = match (raw.find("\r\n\r\n"), raw.find("\n\n")) {
+        (Some(crlf), Some(lf)) if lf < crlf => (&raw[..lf], &raw[lf + 2..]),
+        (Some(crlf), _) => (&raw[..crlf], &raw[crlf + 4..]),
+        (None, Some(lf)) => (&raw[..lf], &raw[lf + 2..]),
+        (None, None) => (raw, ""),
+    };
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header_value(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+}
+
+/// Parameter of a structured header, e.g. `boundary` of Content-Type
+fn header_param(value: &str, param: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|part| {
+        let (name, value) = part.split_once('=')?;
+        (name.trim().eq_ignore_ascii_case(param)).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Plain-text content of a part, preferring text/plain among alternatives
+fn text_body(headers: &[(String, String)], body: &str) -> String {
+    let content_type = header_value(headers, "content-type").unwrap_or_else(|| "text/plain".to_string());
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+
+    if mime.starts_with("multipart/") {
+        let Some(boundary) = header_param(&content_type, "boundary") else {
+            return body.to_string();
+        };
+        let parts: Vec<(Vec<(String, String)>, &str)> = body
+            .split(&format!("--{}", boundary))
+            .skip(1)
+            .take_while(|part| !part.starts_with("--"))
+            .map(split_message)
+            .collect();
+        let is_plain = |headers: &[(String, String)]| {
+            header_value(headers, "content-type")
+                .map(|t| t.to_lowercase().starts_with("text/plain"))
+                .unwrap_or(true)
+        };
+        return parts
+            .iter()
+            .find(|(headers, _)| is_plain(headers))
+            .or_else(|| parts.first())
+            .map(|(headers, body)| text_body(headers, body))
+            .unwrap_or_default();
+    }
+
+    let encoding = header_value(headers, "content-transfer-encoding")
+        .unwrap_or_default()
+        .to_lowercase();
+    let text = match encoding.as_str() {
+        "base64" => {
+            let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+            match base64::engine::general_purpose::STANDARD.decode(compact) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                Err(_) => body.to_string(),
+            }
+        }
+        "quoted-printable" => decode_quoted_printable(body, false),
+        _ => body.to_string(),
+    };
+    if mime == "text/html" {
+        strip_tags(&text)
+    } else {
+        text
+    }
+}
+
+/// Decode quoted-printable; in encoded words `_` stands for a space
+fn decode_quoted_printable(text: &str, encoded_word: bool) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let input = text.as_bytes();
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'=' if input[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if input[i + 1..].starts_with(b"\n") => i += 2,
+            b'=' => match std::str::from_utf8(input.get(i + 1..i + 3).unwrap_or_default())
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    bytes.push(byte);
+                    i += 3;
+                }
+                None => {
+                    bytes.push(b'=');
+                    i += 1;
+                }
+            },
+            b'_' if encoded_word => {
+                bytes.push(b' ');
+                i += 1;
+            }
+            byte => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Decode RFC 2047 encoded words, e.g. `=?UTF-8?B?...?=`
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let Some((encoding, text, len)) = encoded_word(&rest[start + 2..]) else {
+            break;
+        };
+        // Whitespace between adjacent encoded words is dropped
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        match encoding {
+            'B' => match base64::engine::general_purpose::STANDARD.decode(text) {
+                Ok(bytes) => out.push_str(&String::from_utf8_lossy(&bytes)),
+                Err(_) => out.push_str(text),
+            },
+            _ => out.push_str(&decode_quoted_printable(text, true)),
+        }
+        rest = &rest[start + 2 + len..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Encoding, text, and length of the encoded word following a `=?`
+fn encoded_word(word: &str) -> Option<(char, &str, usize)> {
+    let (_charset, rest) = word.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let end = rest.find("?=")?;
+    let len = word.len() - rest.len() + end + 2;
+    Some((encoding.chars().next()?.to_ascii_uppercase(), &rest[..end], len))
+}
+
+/// Text of an HTML body, without tags
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_email() {
+        let email = parse_email(
+            "Message-ID: <abc@example.org>\r\nFrom: Power Co <billing@power.example>\r\nSubject: Your invoice\r\n for October\r\nDate: Tue, 13 Oct 2026 09:00:00 +0000\r\n\r\nPlease pay 42 EUR by Friday.\r\n",
+        );
+        assert_eq!(email.id, "abc@example.org");
+        assert_eq!(email.from, "Power Co <billing@power.example>");
+        assert_eq!(email.subject, "Your invoice for October");
+        assert_eq!(email.date.as_deref(), Some("Tue, 13 Oct 2026 09:00:00 +0000"));
+        assert_eq!(email.body, "Please pay 42 EUR by Friday.");
+    }
+
+    #[test]
+    fn test_parse_multipart_email() {
+        let raw = "From: school@example.org\n\
+Subject: =?UTF-8?B?U2Nob29sIHRyaXA=?= =?UTF-8?Q?_on_Fri?=\n\
+Content-Type: multipart/alternative; boundary=\"b1\"\n\
+\n\
+--b1\n\
+Content-Type: text/html\n\
+\n\
+<p>ignored</p>\n\
+--b1\n\
+Content-Type: text/plain; charset=utf-8\n\
+Content-Transfer-Encoding: quoted-printable\n\
+\n\
+Bring a packed lunch =E2=82=AC5 and a coat. =\n\
+Thanks!\n\
+--b1--\n";
+        let email = parse_email(raw);
+        assert_eq!(email.subject, "School trip on Fri");
+        assert_eq!(email.body, "Bring a packed lunch €5 and a coat. Thanks!");
+        assert_eq!(email.id, "");
+    }
+
+    #[test]
+    fn test_parse_html_and_base64_bodies() {
+        let html = parse_email("Content-Type: text/html\n\n<p>Your <b>parcel</b> is on its way</p>");
+        assert_eq!(html.body, "Your parcel is on its way");
+        let encoded = parse_email("Content-Transfer-Encoding: base64\n\nUmVwbHkgYnkgTW9uZGF5\n");
+        assert_eq!(encoded.body, "Reply by Monday");
+    }
+
+    #[tokio::test]
+    async fn test_take_new_moves_mail_to_cur() {
+        let dir = tempfile::tempdir().unwrap();
+        let maildir = Maildir::new(dir.path());
+        assert!(maildir.take_new().await.is_err());
+
+        std::fs::create_dir_all(dir.path().join("new")).unwrap();
+        std::fs::write(dir.path().join("new/2.host"), "Subject: second\n\nb").unwrap();
+        std::fs::write(dir.path().join("new/1.host"), "Subject: first\n\na").unwrap();
+
+        let emails = maildir.take_new().await.unwrap();
+        let subjects: Vec<&str> = emails.iter().map(|e| e.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["first", "second"]);
+        assert_eq!(emails[0].id, "1.host");
+        assert!(dir.path().join("cur/1.host:2,S").exists());
+        assert!(maildir.take_new().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+//! Fitting outgoing messages to what a channel accepts
+//!
+//! A reply longer than the channel's [`max_message_length`] is split into
+//! several messages, breaking at a paragraph, line, or word where one falls
+//! in the second half of the allowed length, and mid-word only when none
+//! does.
+//!
+//! [`max_message_length`]: super::framework::ChannelCapabilities::max_message_length
+
+use super::framework::{ChannelCapabilities, OutgoingMessage};
+
+/// `message` as the messages to send on a channel with `capabilities`
+///
+/// Only the first part keeps `reply_to`, so the rest read as a continuation
+/// rather than several replies to the same message.
+pub fn fit(message: &OutgoingMessage, capabilities: &ChannelCapabilities) -> Vec<OutgoingMessage> {
+    let Some(max) = capabilities.max_message_length else {
+        return vec![message.clone()];
+    };
+    split_message(&message.content, max)
+        .into_iter()
+        .enumerate()
+        .map(|(i, content)| OutgoingMessage {
+            content,
+            reply_to: if i == 0 { message.reply_to.clone() } else { None },
+            ..message.clone()
+        })
+        .collect()
+}
+
+/// Split `text` into pieces of at most `max_chars` characters
+pub fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some((cut, _)) = rest.char_indices().nth(max_chars) {
+        let window = &rest[..cut];
+        let at = ["\n\n", "\n", " "]
+            .iter()
+            .filter_map(|separator| window.rfind(separator))
+            .find(|&at| at >= window.len() / 2 && at > 0)
+            .unwrap_or(cut);
+        let part = rest[..at].trim_end();
+        if !part.is_empty() {
+            parts.push(part.to_string());
+        }
+        rest = rest[at..].trim_start();
+    }
+    if !rest.is_empty() || parts.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_prefers_paragraphs_then_words() {
+        assert_eq!(split_message("short", 10), vec!["short"]);
+        assert_eq!(split_message("", 10), vec![""]);
+
+        let text = "First paragraph here.\n\nSecond one is longer than the rest.";
+        assert_eq!(
+            split_message(text, 30),
+            vec!["First paragraph here.", "Second one is longer than the", "rest."]
+        );
+
+        // No break in reach: cut mid-word, on a character boundary
+        let parts = split_message(&"é".repeat(25), 10);
+        assert_eq!(parts.iter().map(|p| p.chars().count()).collect::<Vec<_>>(), vec![10, 10, 5]);
+    }
+
+    #[test]
+    fn test_fit_keeps_reply_to_on_first_part() {
+        let message = OutgoingMessage {
+            channel_id: "discord".to_string(),
+            user_id: "alice".to_string(),
+            content: format!("{} {}", "a".repeat(1500), "b".repeat(1500)),
+            chat_id: Some("guild-1/general".to_string()),
+            thread_id: None,
+            reply_to: Some("m42".to_string()),
+        };
+        let parts = fit(&message, &ChannelCapabilities::for_channel("discord"));
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].reply_to.as_deref(), Some("m42"));
+        assert_eq!(parts[1].reply_to, None);
+        assert_eq!(parts[1].chat_id.as_deref(), Some("guild-1/general"));
+
+        assert_eq!(fit(&message, &ChannelCapabilities::default()).len(), 1);
+    }
+}
@@ -1,4 +1,9 @@
 //! Channel framework and abstractions
+//!
+//! Messages are addressed by `channel_id` (the integration), `chat_id` (a
+//! DM, group, or server channel on that platform), and an optional
+//! `thread_id`, independently of the `user_id` who wrote them, so group
+//! chats and threads on Discord, Slack, or Matrix can be represented.
 
 use async_trait::async_trait;
 use crate::error::Result;
@@ -12,6 +17,28 @@ pub struct IncomingMessage {
     pub user_id: String,
     pub content: String,
     pub timestamp: SystemTime,
+    /// Platform message id, used to match edits to the original message
+    #[serde(default)]
+    pub message_id: Option<String>,
+    /// Whether this is an edit of the message with `message_id`
+    #[serde(default)]
+    pub edited: bool,
+    /// Chat the message was posted in; `None` for a direct message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    /// Thread within the chat, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    /// Platform id of the message this one replies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+
+impl IncomingMessage {
+    /// Chat the message belongs to; a direct message's chat is its sender
+    pub fn chat(&self) -> &str {
+        self.chat_id.as_deref().unwrap_or(&self.user_id)
+    }
 }
 
 /// Outgoing message to a channel
@@ -20,6 +47,29 @@ pub struct OutgoingMessage {
     pub channel_id: String,
     pub user_id: String,
     pub content: String,
+    /// Chat to post in; `None` sends a direct message to `user_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    /// Thread to post in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    /// Platform id of the message being replied to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+
+impl OutgoingMessage {
+    /// Reply to `message` in the same chat and thread
+    pub fn reply(message: &IncomingMessage, content: impl Into<String>) -> Self {
+        Self {
+            channel_id: message.channel_id.clone(),
+            user_id: message.user_id.clone(),
+            content: content.into(),
+            chat_id: message.chat_id.clone(),
+            thread_id: message.thread_id.clone(),
+            reply_to: message.message_id.clone(),
+        }
+    }
 }
 
 /// Channel type enumeration
@@ -31,6 +81,107 @@ pub enum ChannelType {
     Line,
     QQ,
     WhatsApp,
+    /// A Matrix room, used as the alert channel
+    Matrix,
+    /// JSON POSTed to a URL, used as the alert channel
+    Webhook,
+}
+
+impl ChannelType {
+    /// Name used as the `channel_id` and session channel, e.g. `telegram`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChannelType::Telegram => "telegram",
+            ChannelType::Discord => "discord",
+            ChannelType::DingTalk => "dingtalk",
+            ChannelType::Line => "line",
+            ChannelType::QQ => "qq",
+            ChannelType::WhatsApp => "whatsapp",
+            ChannelType::Matrix => "matrix",
+            ChannelType::Webhook => "webhook",
+        }
+    }
+
+    /// Channel type with the given name, if it is one
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            ChannelType::Telegram,
+            ChannelType::Discord,
+            ChannelType::DingTalk,
+            ChannelType::Line,
+            ChannelType::QQ,
+            ChannelType::WhatsApp,
+            ChannelType::Matrix,
+            ChannelType::Webhook,
+        ]
+        .into_iter()
+        .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+
+    /// What the platform's bot API supports
+    pub fn capabilities(&self) -> ChannelCapabilities {
+        let (max_message_length, attachments, buttons, editing, typing) = match self {
+            ChannelType::Telegram => (Some(4096), true, true, true, true),
+            ChannelType::Discord => (Some(2000), true, true, true, true),
+            // 20000 bytes, mostly three-byte CJK characters
+            ChannelType::DingTalk => (Some(6000), true, true, false, false),
+            ChannelType::Line => (Some(5000), true, true, false, false),
+            ChannelType::QQ => (Some(2000), true, false, false, false),
+            ChannelType::WhatsApp => (Some(4096), true, true, false, true),
+            // Events are capped at 64 KiB, so allow for four-byte characters
+            ChannelType::Matrix => (Some(16000), true, false, true, true),
+            ChannelType::Webhook => (None, false, false, false, false),
+        };
+        ChannelCapabilities {
+            max_message_length,
+            attachments,
+            buttons,
+            editing,
+            typing,
+        }
+    }
+}
+
+/// What a channel can do with outgoing messages
+///
+/// Formatting and the executor consult this rather than matching on channel
+/// names. The default describes the CLI and dashboard: no length limit, and
+/// text that can be updated in place as it streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelCapabilities {
+    /// Longest message the platform accepts, in characters; longer replies
+    /// are split. `None` for no limit
+    pub max_message_length: Option<usize>,
+    /// Files and images can be sent
+    pub attachments: bool,
+    /// Inline buttons or quick replies can be attached to a message
+    pub buttons: bool,
+    /// A sent message can be edited, so replies can be streamed into it
+    pub editing: bool,
+    /// A typing indicator can be shown while the agent works
+    pub typing: bool,
+}
+
+impl ChannelCapabilities {
+    /// Capabilities of the channel named `name`; names that are not a
+    /// platform (`cli`, `dashboard`, background jobs) get the default
+    pub fn for_channel(name: &str) -> Self {
+        ChannelType::from_name(name)
+            .map(|t| t.capabilities())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for ChannelCapabilities {
+    fn default() -> Self {
+        Self {
+            max_message_length: None,
+            attachments: false,
+            buttons: false,
+            editing: true,
+            typing: true,
+        }
+    }
 }
 
 /// Channel trait for all channel implementations
@@ -50,4 +201,53 @@ pub trait Channel: Send + Sync {
 
     /// Get the channel type
     fn channel_type(&self) -> ChannelType;
+
+    /// What this channel supports; defaults to the platform's capabilities
+    fn capabilities(&self) -> ChannelCapabilities {
+        self.channel_type().capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_stays_in_chat_and_thread() {
+        let message: IncomingMessage = serde_json::from_value(serde_json::json!({
+            "channel_id": "discord",
+            "user_id": "alice",
+            "content": "lights off?",
+            "timestamp": SystemTime::UNIX_EPOCH,
+            "message_id": "m42",
+            "chat_id": "guild-1/general",
+            "thread_id": "t7"
+        }))
+        .unwrap();
+        assert_eq!(message.chat(), "guild-1/general");
+
+        let reply = OutgoingMessage::reply(&message, "Done");
+        assert_eq!(reply.chat_id.as_deref(), Some("guild-1/general"));
+        assert_eq!(reply.thread_id.as_deref(), Some("t7"));
+        assert_eq!(reply.reply_to.as_deref(), Some("m42"));
+
+        let direct = IncomingMessage {
+            chat_id: None,
+            ..message
+        };
+        assert_eq!(direct.chat(), "alice");
+    }
+
+    #[test]
+    fn test_capabilities_by_channel_name() {
+        let discord = ChannelCapabilities::for_channel("Discord");
+        assert_eq!(discord.max_message_length, Some(2000));
+        assert!(discord.editing);
+        assert!(!ChannelCapabilities::for_channel("line").editing);
+
+        let dashboard = ChannelCapabilities::for_channel("dashboard");
+        assert_eq!(dashboard, ChannelCapabilities::default());
+        assert_eq!(dashboard.max_message_length, None);
+        assert_eq!(ChannelType::from_name("whatsapp"), Some(ChannelType::WhatsApp));
+    }
 }
@@ -1,5 +1,18 @@
 //! Channel integrations for TakoBull
 
+pub mod alerts;
+pub mod edits;
+pub mod email;
+pub mod format;
 pub mod framework;
+pub mod observer;
+pub mod outbox;
+pub mod template;
 
-pub use framework::Channel;
+pub use alerts::{AlertChannel, AlertNotifier};
+pub use edits::{EditTracker, MessageRun};
+pub use email::{Email, Maildir};
+pub use framework::{Channel, ChannelCapabilities};
+pub use observer::{Interaction, Observer, Observers};
+pub use outbox::{ChannelDelivery, DeliveryState, Outbox, OutboxEntry};
+pub use template::Templates;
@@ -0,0 +1,320 @@
+//! Read-only observer channels
+//!
+//! An observer receives a copy of every agent interaction (the message, the
+//! reply or error, and the tools called) but is never read from, so nothing
+//! posted at the destination reaches the agent. Useful for parental
+//! oversight through a Matrix room or fleet auditing through a webhook.
+//! Delivery happens in the background and failures are only logged, so a
+//! slow or unreachable observer never delays a reply.
+
+use crate::config::{ObserverConfig, ObserverTarget};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Channel label for runs without a session, such as jobs and alert prompts
+pub const BACKGROUND_CHANNEL: &str = "background";
+
+/// One message and the agent's answer to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub at: DateTime<Utc>,
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<String>,
+}
+
+impl Interaction {
+    /// Plain-text rendering for chat destinations
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "[{}] {} via {}\n> {}",
+            self.at.format("%Y-%m-%d %H:%M:%S UTC"),
+            self.user_id.as_deref().unwrap_or("unknown user"),
+            self.channel,
+            self.message
+        );
+        if !self.tool_calls.is_empty() {
+            text.push_str(&format!("\nTools: {}", self.tool_calls.join(", ")));
+        }
+        match (&self.response, &self.error) {
+            (_, Some(error)) => text.push_str(&format!("\nError: {}", error)),
+            (Some(response), None) => text.push_str(&format!("\n{}", response)),
+            (None, None) => {}
+        }
+        text
+    }
+}
+
+/// A destination that receives interactions and nothing else
+#[async_trait]
+pub trait Observer: Send + Sync {
+    /// Deliver a copy of `interaction`
+    async fn observe(&self, interaction: &Interaction) -> Result<()>;
+}
+
+/// Posts interactions as notices to a Matrix room
+pub struct MatrixObserver {
+    homeserver: String,
+    access_token: String,
+    room_id: String,
+}
+
+impl MatrixObserver {
+    pub fn new(homeserver: impl Into<String>, access_token: impl Into<String>, room_id: impl Into<String>) -> Self {
+        Self {
+            homeserver: homeserver.into(),
+            access_token: access_token.into(),
+            room_id: room_id.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Observer for MatrixObserver {
+    async fn observe(&self, interaction: &Interaction) -> Result<()> {
+        send_matrix_notice(&self.homeserver, &self.access_token, &self.room_id, &interaction.render()).await
+    }
+}
+
+/// Post `body` to a Matrix room as an `m.notice`
+pub(crate) async fn send_matrix_notice(homeserver: &str, access_token: &str, room_id: &str, body: &str) -> Result<()> {
+    let mut url = reqwest::Url::parse(homeserver)
+        .map_err(|e| Error::config(format!("Invalid Matrix homeserver {}: {}", homeserver, e)))?;
+    let txn_id = uuid::Uuid::new_v4().simple().to_string();
+    url.path_segments_mut()
+        .map_err(|_| Error::config(format!("Invalid Matrix homeserver {}", homeserver)))?
+        .pop_if_empty()
+        .extend(["_matrix", "client", "v3", "rooms", room_id, "send", "m.room.message", &txn_id]);
+    // m.notice marks the message as automated so clients and bots do not reply
+    let response = reqwest::Client::new()
+        .put(url)
+        .bearer_auth(access_token)
+        .json(&serde_json::json!({ "msgtype": "m.notice", "body": body }))
+        .send()
+        .await
+        .map_err(|e| Error::channel(format!("Matrix request failed: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(Error::channel(format!("Matrix returned {}: {}", status, text)));
+    }
+    Ok(())
+}
+
+/// POSTs interactions as JSON to a URL
+pub struct WebhookObserver {
+    url: String,
+    token: Option<String>,
+}
+
+impl WebhookObserver {
+    pub fn new(url: impl Into<String>, token: Option<String>) -> Self {
+        Self { url: url.into(), token }
+    }
+}
+
+#[async_trait]
+impl Observer for WebhookObserver {
+    async fn observe(&self, interaction: &Interaction) -> Result<()> {
+        let mut request = reqwest::Client::new().post(&self.url).json(interaction);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::channel(format!("Webhook request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(Error::channel(format!("Webhook returned {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+struct Entry {
+    name: String,
+    channels: Vec<String>,
+    observer: Arc<dyn Observer>,
+}
+
+/// The configured observers
+#[derive(Default)]
+pub struct Observers {
+    entries: Vec<Entry>,
+    pending: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Observers {
+    /// Observers for the `observers` list in config.yaml
+    pub fn from_config(configs: &[ObserverConfig]) -> Self {
+        let mut observers = Self::default();
+        for config in configs {
+            let observer: Arc<dyn Observer> = match &config.target {
+                ObserverTarget::Matrix {
+                    homeserver,
+                    access_token,
+                    room_id,
+                } => Arc::new(MatrixObserver::new(homeserver, access_token, room_id)),
+                ObserverTarget::Webhook { url, token } => Arc::new(WebhookObserver::new(url, token.clone())),
+            };
+            observers = observers.with(&config.name, config.channels.clone(), observer);
+        }
+        observers
+    }
+
+    /// Add `observer` as `name`, mirroring only `channels` unless empty
+    pub fn with(mut self, name: impl Into<String>, channels: Vec<String>, observer: Arc<dyn Observer>) -> Self {
+        self.entries.push(Entry {
+            name: name.into(),
+            channels,
+            observer,
+        });
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Send `interaction` to every observer watching its channel, in the background
+    pub fn publish(&self, interaction: Interaction) {
+        let interaction = Arc::new(interaction);
+        for entry in &self.entries {
+            if !entry.channels.is_empty() && !entry.channels.contains(&interaction.channel) {
+                continue;
+            }
+            let name = entry.name.clone();
+            let observer = entry.observer.clone();
+            let interaction = interaction.clone();
+            let delivery = tokio::spawn(async move {
+                match observer.observe(&interaction).await {
+                    Ok(()) => debug!("Mirrored interaction to observer {}", name),
+                    Err(e) => warn!("Observer {} did not receive interaction: {}", name, e),
+                }
+            });
+            let mut pending = self.pending.lock();
+            pending.retain(|delivery| !delivery.is_finished());
+            pending.push(delivery);
+        }
+    }
+
+    /// Wait up to `timeout` for deliveries still in flight, e.g. before a
+    /// one-shot command exits
+    pub async fn flush(&self, timeout: Duration) {
+        let pending: Vec<JoinHandle<()>> = std::mem::take(&mut *self.pending.lock());
+        if pending.is_empty() {
+            return;
+        }
+        if tokio::time::timeout(timeout, futures::future::join_all(pending)).await.is_err() {
+            warn!("Gave up waiting for observers after {:?}", timeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Interaction>>);
+
+    #[async_trait]
+    impl Observer for Recorder {
+        async fn observe(&self, interaction: &Interaction) -> Result<()> {
+            self.0.lock().push(interaction.clone());
+            Ok(())
+        }
+    }
+
+    fn interaction(channel: &str) -> Interaction {
+        Interaction {
+            at: DateTime::parse_from_rfc3339("2026-06-01T08:30:00Z").unwrap().with_timezone(&Utc),
+            channel: channel.to_string(),
+            user_id: Some("kid".to_string()),
+            message: "how do volcanoes work?".to_string(),
+            response: Some("Magma rises...".to_string()),
+            error: None,
+            tool_calls: vec!["web_search".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_respects_channel_filter() {
+        let all = Arc::new(Recorder::default());
+        let cli_only = Arc::new(Recorder::default());
+        let observers = Observers::default()
+            .with("all", Vec::new(), all.clone())
+            .with("cli", vec!["cli".to_string()], cli_only.clone());
+
+        observers.publish(interaction("cli"));
+        observers.publish(interaction(BACKGROUND_CHANNEL));
+        observers.flush(Duration::from_secs(1)).await;
+        assert_eq!(all.0.lock().len(), 2);
+        assert_eq!(cli_only.0.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            interaction("cli").render(),
+            "[2026-06-01 08:30:00 UTC] kid via cli\n> how do volcanoes work?\nTools: web_search\nMagma rises..."
+        );
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_matrix_and_webhook_delivery() {
+        let seen = Arc::new(Mutex::new(Vec::<String>::new()));
+        let (matrix_seen, hook_seen) = (seen.clone(), seen.clone());
+        let server = axum::Router::new()
+            .route(
+                "/_matrix/client/v3/rooms/:room/send/m.room.message/:txn",
+                axum::routing::put(
+                    move |axum::extract::Path((room, _txn)): axum::extract::Path<(String, String)>,
+                          axum::Json(body): axum::Json<serde_json::Value>| async move {
+                        matrix_seen.lock().push(format!("{} {}", room, body["msgtype"]));
+                        axum::Json(serde_json::json!({ "event_id": "$1" }))
+                    },
+                ),
+            )
+            .route(
+                "/audit",
+                axum::routing::post(move |axum::Json(body): axum::Json<Interaction>| async move {
+                    hook_seen.lock().push(body.message);
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, server).await });
+
+        let matrix = MatrixObserver::new(format!("{}/", base), "token", "!room:example.org");
+        matrix.observe(&interaction("cli")).await.unwrap();
+        let hook = WebhookObserver::new(format!("{}/audit", base), Some("t".to_string()));
+        hook.observe(&interaction("cli")).await.unwrap();
+        let missing = WebhookObserver::new(format!("{}/nowhere", base), None);
+        assert!(missing.observe(&interaction("cli")).await.is_err());
+
+        assert_eq!(
+            *seen.lock(),
+            vec!["!room:example.org \"m.notice\"", "how do volcanoes work?"]
+        );
+    }
+}
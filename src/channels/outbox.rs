@@ -0,0 +1,324 @@
+//! Delivery tracking for outgoing messages
+//!
+//! Every reply sent through [`Outbox::send`] is recorded in
+//! `workspace/state/outbox.json` with its delivery state and attempts, so a
+//! channel whose token expired shows up as failures in status output instead
+//! of replies silently going nowhere. Only the most recent messages are kept.
+//! The alert channel sends its notices through the outbox.
+
+use super::format;
+use super::framework::{Channel, OutgoingMessage};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Outbox file, relative to the workspace
+pub const OUTBOX_FILE: &str = "state/outbox.json";
+
+/// Messages kept in the outbox
+pub const MAX_ENTRIES: usize = 200;
+
+/// Delivery state of an outgoing message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryState {
+    Pending,
+    Sent,
+    Failed,
+}
+
+/// A message in the outbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub message: OutgoingMessage,
+    pub state: DeliveryState,
+    /// Send attempts so far; more than one means the message was retried
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Delivery counts for one channel over the messages in the outbox
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChannelDelivery {
+    pub channel_id: String,
+    pub sent: usize,
+    pub failed: usize,
+    /// Messages that needed more than one attempt
+    pub retried: usize,
+    pub pending: usize,
+    /// Error of the most recent failed message
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
+impl ChannelDelivery {
+    /// One-line summary, e.g. `12 sent · 3 failed (1 retried) · last error: ...`
+    pub fn summary(&self) -> String {
+        let mut text = format!("{} sent · {} failed", self.sent, self.failed);
+        if self.retried > 0 {
+            text.push_str(&format!(" ({} retried)", self.retried));
+        }
+        if self.pending > 0 {
+            text.push_str(&format!(" · {} pending", self.pending));
+        }
+        if let Some(error) = &self.last_error {
+            text.push_str(&format!(" · last error: {}", error));
+        }
+        text
+    }
+}
+
+/// Sends messages with retries and records how delivery went
+pub struct Outbox {
+    path: PathBuf,
+    max_attempts: u32,
+    retry_delay: Duration,
+    lock: Mutex<()>,
+}
+
+impl Outbox {
+    /// Outbox kept in `workspace`, trying each message up to 3 times
+    pub fn new(workspace: impl AsRef<Path>) -> Self {
+        Self {
+            path: workspace.as_ref().join(OUTBOX_FILE),
+            max_attempts: 3,
+            retry_delay: Duration::from_secs(2),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Try each message up to `attempts` times, waiting `delay` (doubled each
+    /// time) between attempts
+    pub fn with_retries(mut self, attempts: u32, delay: Duration) -> Self {
+        self.max_attempts = attempts.max(1);
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Send `message` through `channel`, retrying failures, and record the outcome
+    ///
+    /// A message over the channel's length limit goes out in parts; a retry
+    /// resumes at the part that failed. Returns the final entry; a message
+    /// that never went out in full has state [`DeliveryState::Failed`].
+    pub async fn send(&self, channel: &dyn Channel, message: OutgoingMessage) -> Result<OutboxEntry> {
+        let now = Utc::now();
+        let mut entry = OutboxEntry {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            message,
+            state: DeliveryState::Pending,
+            attempts: 0,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.save(&entry).await?;
+
+        let parts = format::fit(&entry.message, &channel.capabilities());
+        let mut next = 0;
+        let mut delay = self.retry_delay;
+        while entry.attempts < self.max_attempts {
+            if entry.attempts > 0 {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            entry.attempts += 1;
+            match send_parts(channel, &parts[next..]).await {
+                Ok(()) => {
+                    entry.state = DeliveryState::Sent;
+                    break;
+                }
+                Err((sent, e)) => {
+                    next += sent;
+                    warn!(
+                        "Delivery {} to {} failed (attempt {}/{}): {}",
+                        entry.id, entry.message.channel_id, entry.attempts, self.max_attempts, e
+                    );
+                    entry.last_error = Some(e.to_string());
+                    entry.state = DeliveryState::Failed;
+                }
+            }
+        }
+        entry.updated_at = Utc::now();
+        self.save(&entry).await?;
+        Ok(entry)
+    }
+
+    /// Messages in the outbox, oldest first
+    pub async fn entries(&self) -> Result<Vec<OutboxEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&self.path).await?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Delivery counts per channel, in order of first appearance
+    pub async fn stats(&self) -> Result<Vec<ChannelDelivery>> {
+        let mut stats: Vec<ChannelDelivery> = Vec::new();
+        for entry in self.entries().await? {
+            let index = match stats.iter().position(|s| s.channel_id == entry.message.channel_id) {
+                Some(index) => index,
+                None => {
+                    stats.push(ChannelDelivery {
+                        channel_id: entry.message.channel_id.clone(),
+                        ..Default::default()
+                    });
+                    stats.len() - 1
+                }
+            };
+            let channel = &mut stats[index];
+            match entry.state {
+                DeliveryState::Sent => channel.sent += 1,
+                DeliveryState::Pending => channel.pending += 1,
+                DeliveryState::Failed => {
+                    channel.failed += 1;
+                    channel.last_error = entry.last_error.clone();
+                    channel.last_failure_at = Some(entry.updated_at);
+                }
+            }
+            if entry.attempts > 1 {
+                channel.retried += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Insert or update `entry`, dropping the oldest beyond the limit
+    async fn save(&self, entry: &OutboxEntry) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut entries = self.entries().await?;
+        match entries.iter_mut().find(|e| e.id == entry.id) {
+            Some(existing) => *existing = entry.clone(),
+            None => entries.push(entry.clone()),
+        }
+        let skip = entries.len().saturating_sub(MAX_ENTRIES);
+
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string(&entries[skip..])?).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+/// Send `parts` in order, stopping at the first failure with the number sent before it
+async fn send_parts(
+    channel: &dyn Channel,
+    parts: &[OutgoingMessage],
+) -> std::result::Result<(), (usize, Error)> {
+    for (i, part) in parts.iter().enumerate() {
+        channel.send_message(part.clone()).await.map_err(|e| (i, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::framework::{ChannelType, IncomingMessage};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails the first `failures` sends
+    struct FlakyChannel {
+        failures: u32,
+        calls: AtomicU32,
+        delivered: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl FlakyChannel {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures,
+                calls: AtomicU32::new(0),
+                delivered: Default::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Channel for FlakyChannel {
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive_message(&mut self) -> Result<Option<IncomingMessage>> {
+            Ok(None)
+        }
+
+        async fn send_message(&self, msg: OutgoingMessage) -> Result<()> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(Error::channel("401 Unauthorized: token expired"));
+            }
+            self.delivered.lock().unwrap().push(msg.content);
+            Ok(())
+        }
+
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::Telegram
+        }
+    }
+
+    fn message(text: &str) -> OutgoingMessage {
+        OutgoingMessage {
+            channel_id: "telegram".to_string(),
+            user_id: "alice".to_string(),
+            content: text.to_string(),
+            chat_id: None,
+            thread_id: None,
+            reply_to: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_states_and_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::new(dir.path()).with_retries(3, Duration::from_millis(1));
+
+        let flaky = FlakyChannel::new(1);
+        let entry = outbox.send(&flaky, message("retried")).await.unwrap();
+        assert_eq!((entry.state, entry.attempts), (DeliveryState::Sent, 2));
+
+        let broken = FlakyChannel::new(u32::MAX);
+        let entry = outbox.send(&broken, message("lost")).await.unwrap();
+        assert_eq!((entry.state, entry.attempts), (DeliveryState::Failed, 3));
+
+        let stats = outbox.stats().await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].sent, stats[0].failed, stats[0].retried), (1, 1, 2));
+        assert!(stats[0].last_error.as_deref().unwrap().contains("token expired"));
+        assert!(stats[0].summary().starts_with("1 sent · 1 failed (2 retried) · last error: "));
+        assert_eq!(outbox.entries().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_message_goes_out_in_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::new(dir.path()).with_retries(3, Duration::from_millis(1));
+
+        // Telegram takes 4096 characters; the first part fails once and is resent
+        let flaky = FlakyChannel::new(1);
+        let text = format!("{}\n\n{}", "a".repeat(4000), "b".repeat(100));
+        let entry = outbox.send(&flaky, message(&text)).await.unwrap();
+        assert_eq!((entry.state, entry.attempts), (DeliveryState::Sent, 2));
+        assert_eq!(entry.message.content, text);
+        let delivered = flaky.delivered.lock().unwrap();
+        assert_eq!(*delivered, vec!["a".repeat(4000), "b".repeat(100)]);
+    }
+}
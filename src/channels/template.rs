@@ -0,0 +1,238 @@
+//! Outbound message templates
+//!
+//! Recurring notifications such as a morning digest should read the same
+//! every time. A template fixes the wording and layout, and only its
+//! `{variable}` values change, whether they come from an alert rule, a
+//! scheduled job whose agent run answers with JSON, or the `notify` tool.
+
+use crate::config::MessageTemplateConfig;
+use crate::error::{Error, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// The templates from the `templates` section of config.yaml
+#[derive(Debug, Clone, Default)]
+pub struct Templates {
+    templates: BTreeMap<String, MessageTemplateConfig>,
+}
+
+impl Templates {
+    pub fn new(templates: HashMap<String, MessageTemplateConfig>) -> Self {
+        Self {
+            templates: templates.into_iter().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Template names in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&MessageTemplateConfig> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| Error::config(format!("Unknown message template '{}'", name)))
+    }
+
+    /// Variables used by template `name`, in order of first use
+    pub fn variables(&self, name: &str) -> Result<Vec<String>> {
+        let mut variables = Vec::new();
+        expand(&self.get(name)?.text, |variable| {
+            if !variables.iter().any(|v| v == variable) {
+                variables.push(variable.to_string());
+            }
+            None
+        });
+        Ok(variables)
+    }
+
+    /// Render template `name`, taking variables missing from `values` from
+    /// the template's defaults
+    pub fn render(&self, name: &str, values: &HashMap<String, String>) -> Result<String> {
+        let template = self.get(name)?;
+        let mut missing = Vec::new();
+        let text = expand(&template.text, |variable| {
+            let value = values.get(variable).or_else(|| template.defaults.get(variable)).cloned();
+            if value.is_none() && !missing.iter().any(|m| m == variable) {
+                missing.push(variable.to_string());
+            }
+            value
+        });
+        if !missing.is_empty() {
+            return Err(Error::channel(format!(
+                "Template '{}' is missing values for {}",
+                name,
+                missing.join(", ")
+            )));
+        }
+        Ok(text)
+    }
+
+    /// Prompt for an agent run that does `task` and answers with the values
+    /// for template `name` instead of free text
+    pub fn job_prompt(&self, name: &str, task: &str) -> Result<String> {
+        let template = self.get(name)?;
+        let keys: Vec<String> = self
+            .variables(name)?
+            .into_iter()
+            .map(|variable| {
+                if template.defaults.contains_key(&variable) {
+                    format!("{} (optional)", variable)
+                } else {
+                    variable
+                }
+            })
+            .collect();
+        Ok(format!(
+            "{}\n\nReply with only a JSON object with these keys: {}. Use strings, or arrays of strings for lists. \
+             The values are filled into a fixed message template, so do not add any other text.",
+            task,
+            keys.join(", ")
+        ))
+    }
+
+    /// Render template `name` from an agent reply containing a JSON object of
+    /// values, as requested by [`Templates::job_prompt`]
+    pub fn render_reply(&self, name: &str, reply: &str) -> Result<String> {
+        let json = match (reply.find('{'), reply.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &reply[start..=end],
+            _ => return Err(Error::serialization(format!("No JSON object in reply for template '{}'", name))),
+        };
+        let values: Value = serde_json::from_str(json)
+            .map_err(|e| Error::serialization(format!("Invalid JSON in reply for template '{}': {}", name, e)))?;
+        self.render(name, &values_from_json(&values))
+    }
+
+    /// One line per template for the agent, e.g. `- digest (date, items): Morning summary`
+    pub fn describe(&self) -> String {
+        self.templates
+            .iter()
+            .map(|(name, template)| {
+                let variables = self.variables(name).unwrap_or_default().join(", ");
+                match &template.description {
+                    Some(description) => format!("- {} ({}): {}", name, variables, description),
+                    None => format!("- {} ({})", name, variables),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Template values from a JSON object
+///
+/// Numbers and booleans are written out, and arrays become one `- item` line
+/// per element so a list variable can stand on its own lines.
+pub fn values_from_json(object: &Value) -> HashMap<String, String> {
+    let Some(object) = object.as_object() else {
+        return HashMap::new();
+    };
+    object
+        .iter()
+        .filter_map(|(key, value)| {
+            let text = match value {
+                Value::Null => return None,
+                Value::String(text) => text.clone(),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(text) => format!("- {}", text),
+                        other => format!("- {}", other),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                other => other.to_string(),
+            };
+            Some((key.clone(), text))
+        })
+        .collect()
+}
+
+/// Replace each `{variable}` in `text` with `value(variable)`, leaving it in
+/// place when that is `None`; `{{` and `}}` produce literal braces
+fn expand(text: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        if let Some(end) = rest.find('}').filter(|_| rest.starts_with('{')) {
+            let name = &rest[1..end];
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                match value(name) {
+                    Some(text) => out.push_str(&text),
+                    None => out.push_str(&rest[..=end]),
+                }
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        out.push_str(&rest[..1]);
+        rest = &rest[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates() -> Templates {
+        Templates::new(HashMap::from([(
+            "digest".to_string(),
+            MessageTemplateConfig {
+                text: "☀️ {date}: {{weather}} {weather}\n{items}\nSigned {signature}".to_string(),
+                description: Some("Morning digest".to_string()),
+                defaults: HashMap::from([("signature".to_string(), "TakoBull".to_string())]),
+            },
+        )]))
+    }
+
+    #[test]
+    fn test_render_with_defaults_and_escapes() {
+        let templates = templates();
+        assert_eq!(
+            templates.variables("digest").unwrap(),
+            vec!["date", "weather", "items", "signature"]
+        );
+        let values = HashMap::from([
+            ("date".to_string(), "Mon".to_string()),
+            ("weather".to_string(), "sunny".to_string()),
+            ("items".to_string(), "- bins out".to_string()),
+        ]);
+        assert_eq!(
+            templates.render("digest", &values).unwrap(),
+            "☀️ Mon: {weather} sunny\n- bins out\nSigned TakoBull"
+        );
+
+        let error = templates.render("digest", &HashMap::new()).unwrap_err().to_string();
+        assert!(error.contains("date, weather, items"), "{}", error);
+        assert!(templates.render("missing", &values).is_err());
+        assert_eq!(templates.describe(), "- digest (date, weather, items, signature): Morning digest");
+    }
+
+    #[test]
+    fn test_render_reply_from_job_output() {
+        let templates = templates();
+        let prompt = templates.job_prompt("digest", "Summarize today").unwrap();
+        assert!(prompt.starts_with("Summarize today\n\n"));
+        assert!(prompt.contains("date, weather, items, signature (optional)"));
+
+        let reply = "Here you go:\n{\"date\": \"Tue\", \"weather\": 21, \"items\": [\"dentist\", \"call mum\"]}";
+        assert_eq!(
+            templates.render_reply("digest", reply).unwrap(),
+            "☀️ Tue: {weather} 21\n- dentist\n- call mum\nSigned TakoBull"
+        );
+        assert!(templates.render_reply("digest", "No JSON today").is_err());
+    }
+}
@@ -1,7 +1,13 @@
 //! Configuration management for TacoBot
 
+use crate::device::SimulatedDeviceConfig;
+use crate::llm::GenerationOptions;
+use crate::telemetry::{AlertRule, SensorConfig};
+use crate::tools::{CostHint, SymlinkPolicy};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[cfg(test)]
 mod property_tests;
@@ -25,11 +31,824 @@ pub struct AgentConfig {
     pub memory_limit_mb: usize,
 }
 
+/// Agent defaults from the `agents.defaults` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentDefaults {
+    /// Agent name, giving the agent a private memory namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    #[serde(default = "default_model")]
+    pub model: String,
+    /// Provider for summarization; falls back to `provider` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_provider: Option<String>,
+    /// Cheap or local model used for summarization instead of the chat model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_model: Option<String>,
+    /// Default sampling, length, and stop settings for every request
+    #[serde(flatten)]
+    pub generation: GenerationOptions,
+    /// Memories recalled into the context before each run
+    #[serde(default)]
+    pub recall: RecallConfig,
+    /// Source citations appended to answers that used web-sourced tool results
+    #[serde(default)]
+    pub citations: CitationConfig,
+    /// "What I did" summaries appended to answers
+    #[serde(default)]
+    pub transcripts: TranscriptConfig,
+    /// Rounds of tool calls per run before giving up (default 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tool_iterations: Option<usize>,
+    /// Tokens of recent session messages sent with each turn (default 2000); 0 sends none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_tokens: Option<usize>,
+}
+
+fn default_provider() -> String {
+    "openrouter".to_string()
+}
+
+fn default_model() -> String {
+    "meta-llama/llama-2-70b-chat".to_string()
+}
+
+impl Default for AgentDefaults {
+    fn default() -> Self {
+        AgentDefaults {
+            name: None,
+            provider: default_provider(),
+            model: default_model(),
+            summary_provider: None,
+            summary_model: None,
+            generation: GenerationOptions::default(),
+            recall: RecallConfig::default(),
+            citations: CitationConfig::default(),
+            transcripts: TranscriptConfig::default(),
+            max_tool_iterations: None,
+            history_tokens: None,
+        }
+    }
+}
+
+/// A provider and model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRef {
+    pub provider: String,
+    pub model: String,
+}
+
+/// Models consulted by `agent --council`, from `agents.council`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CouncilConfig {
+    /// Two or three models that answer concurrently
+    #[serde(default)]
+    pub members: Vec<ModelRef>,
+    /// Model that writes the final answer from the members' answers; without
+    /// one the answer the members agree on most is kept
+    #[serde(default)]
+    pub judge: Option<ModelRef>,
+}
+
+/// Automatic memory recall from `agents.defaults.recall`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecallConfig {
+    /// Search memory with each incoming message and add matches to the context
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Most memories to add
+    #[serde(default = "default_recall_top_k")]
+    pub top_k: usize,
+    /// Minimum cosine similarity for a memory to be added
+    #[serde(default = "default_recall_threshold")]
+    pub threshold: f32,
+}
+
+fn default_recall_top_k() -> usize {
+    3
+}
+
+fn default_recall_threshold() -> f32 {
+    0.35
+}
+
+impl Default for RecallConfig {
+    fn default() -> Self {
+        RecallConfig {
+            enabled: true,
+            top_k: default_recall_top_k(),
+            threshold: default_recall_threshold(),
+        }
+    }
+}
+
+/// Citation footer settings from `agents.defaults.citations`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CitationConfig {
+    /// Append a sources footer when tools contributed URLs
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Most sources listed
+    #[serde(default = "default_max_citations")]
+    pub max_sources: usize,
+    /// Per-channel overrides of `enabled`, keyed by channel name (e.g. `telegram: false`)
+    #[serde(default)]
+    pub channels: HashMap<String, bool>,
+}
+
+fn default_max_citations() -> usize {
+    5
+}
+
+impl Default for CitationConfig {
+    fn default() -> Self {
+        CitationConfig {
+            enabled: true,
+            max_sources: default_max_citations(),
+            channels: HashMap::new(),
+        }
+    }
+}
+
+impl CitationConfig {
+    /// Whether answers on `channel` get a citations footer
+    pub fn enabled_for(&self, channel: Option<&str>) -> bool {
+        channel
+            .and_then(|channel| self.channels.get(channel).copied())
+            .unwrap_or(self.enabled)
+    }
+}
+
+/// Tool transcript settings from `agents.defaults.transcripts`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptConfig {
+    /// Append a "What I did" section listing tools called, files touched,
+    /// and URLs fetched
+    #[serde(default)]
+    pub enabled: bool,
+    /// Per-channel overrides of `enabled`, keyed by channel name
+    #[serde(default)]
+    pub channels: HashMap<String, bool>,
+    /// Per-user overrides of `enabled`, keyed by user id; these win over
+    /// channel overrides
+    #[serde(default)]
+    pub users: HashMap<String, bool>,
+}
+
+impl TranscriptConfig {
+    /// Whether answers to `user_id` on `channel` get a transcript
+    pub fn enabled_for(&self, user_id: Option<&str>, channel: Option<&str>) -> bool {
+        user_id
+            .and_then(|user| self.users.get(user).copied())
+            .or_else(|| channel.and_then(|channel| self.channels.get(channel).copied()))
+            .unwrap_or(self.enabled)
+    }
+}
+
+/// Memory storage settings from the `memory` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Provider for embeddings; falls back to `agents.defaults.provider`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_provider: Option<String>,
+    /// Embedding model; memories are embedded offline by hashing when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    /// Dimensions of the offline hashed embeddings
+    #[serde(default = "default_hash_dims")]
+    pub hash_dims: usize,
+}
+
+fn default_hash_dims() -> usize {
+    256
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        MemoryConfig {
+            embedding_provider: None,
+            embedding_model: None,
+            hash_dims: default_hash_dims(),
+        }
+    }
+}
+
+/// Reply language settings from the `agents.language` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    /// Detect the language of each message and ask the model to match it
+    #[serde(default = "default_true")]
+    pub detect: bool,
+    /// Language used when detection is inconclusive (code or English name)
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Per-user reply language overrides, keyed by user id
+    #[serde(default)]
+    pub users: HashMap<String, String>,
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        LanguageConfig {
+            detect: true,
+            default: None,
+            users: HashMap::new(),
+        }
+    }
+}
+
+/// Session storage settings from the `sessions` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    /// Days without activity before a session is moved to the archive
+    #[serde(default = "default_archive_after_days")]
+    pub archive_after_days: u64,
+    /// Background merging of near-duplicate old exchanges
+    #[serde(default)]
+    pub compression: CompressionConfig,
+}
+
+fn default_archive_after_days() -> u64 {
+    30
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        SessionsConfig {
+            archive_after_days: default_archive_after_days(),
+            compression: CompressionConfig::default(),
+        }
+    }
+}
+
+/// Session compression settings from `sessions.compression`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    /// Compress sessions periodically while the gateway runs
+    #[serde(default)]
+    pub enabled: bool,
+    /// Only exchanges older than this many days are merged
+    #[serde(default = "default_compress_after_days")]
+    pub after_days: u64,
+    /// Minimum cosine similarity (0-1) for two exchanges to be merged
+    #[serde(default = "default_compress_similarity")]
+    pub similarity: f32,
+    /// Hours between background passes
+    #[serde(default = "default_compress_interval_hours")]
+    pub interval_hours: u64,
+}
+
+fn default_compress_after_days() -> u64 {
+    7
+}
+
+fn default_compress_similarity() -> f32 {
+    0.85
+}
+
+fn default_compress_interval_hours() -> u64 {
+    24
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        CompressionConfig {
+            enabled: false,
+            after_days: default_compress_after_days(),
+            similarity: default_compress_similarity(),
+            interval_hours: default_compress_interval_hours(),
+        }
+    }
+}
+
+/// Scheduler settings from the `scheduler` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Most scheduled jobs and alert prompts running the agent at once
+    #[serde(default = "default_max_concurrent_runs")]
+    pub max_concurrent_runs: usize,
+    /// Runs kept per job in `workspace/cron/history/`
+    #[serde(default = "default_history_runs")]
+    pub history_runs: usize,
+    /// Backoff for retrying failed jobs and when to report them degraded
+    #[serde(default)]
+    pub retry: crate::scheduler::RetryPolicy,
+}
+
+fn default_history_runs() -> usize {
+    crate::scheduler::history::DEFAULT_HISTORY_RUNS
+}
+
+fn default_max_concurrent_runs() -> usize {
+    1
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            max_concurrent_runs: default_max_concurrent_runs(),
+            history_runs: default_history_runs(),
+            retry: Default::default(),
+        }
+    }
+}
+
+/// Telemetry settings from the `telemetry` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days of readings to keep
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+    #[serde(default)]
+    pub sensors: Vec<SensorConfig>,
+    /// Threshold alert rules evaluated on incoming readings
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+}
+
+fn default_retention_days() -> u32 {
+    14
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            enabled: false,
+            retention_days: default_retention_days(),
+            sensors: Vec::new(),
+            alerts: Vec::new(),
+        }
+    }
+}
+
+/// Watchdog settings from the `watchdog` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds between heartbeats
+    #[serde(default = "default_watchdog_interval_secs")]
+    pub interval_secs: u64,
+    /// Heartbeat file (default `workspace/state/heartbeat`)
+    #[serde(default)]
+    pub heartbeat_file: Option<PathBuf>,
+    /// Hardware watchdog device to pet, e.g. `/dev/watchdog`
+    #[serde(default)]
+    pub device: Option<PathBuf>,
+}
+
+fn default_watchdog_interval_secs() -> u64 {
+    15
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            enabled: false,
+            interval_secs: default_watchdog_interval_secs(),
+            heartbeat_file: None,
+            device: None,
+        }
+    }
+}
+
+/// Where an observer receives copies of agent interactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ObserverTarget {
+    /// Notices posted to a Matrix room through the client-server API
+    Matrix {
+        homeserver: String,
+        access_token: String,
+        room_id: String,
+    },
+    /// JSON POSTed to a URL
+    Webhook {
+        url: String,
+        /// Sent as a bearer token when set
+        #[serde(default)]
+        token: Option<String>,
+    },
+}
+
+/// A read-only destination from the `observers` list in config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObserverConfig {
+    pub name: String,
+    /// Only mirror interactions from these channels (e.g. `cli`, `dashboard`, `job`); empty mirrors all
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(flatten)]
+    pub target: ObserverTarget,
+}
+
+/// A named pattern whose matches are redacted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionPattern {
+    /// Shown in the placeholder, e.g. `[REDACTED:api_key]`
+    pub name: String,
+    pub regex: String,
+}
+
+/// Redaction of stored transcripts from the `redaction` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Redact card numbers that pass the Luhn check
+    #[serde(default = "default_true")]
+    pub credit_cards: bool,
+    /// Redact phone numbers of 9 to 15 digits
+    #[serde(default = "default_true")]
+    pub phone_numbers: bool,
+    #[serde(default)]
+    pub patterns: Vec<RedactionPattern>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        RedactionConfig {
+            enabled: false,
+            credit_cards: true,
+            phone_numbers: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// One arm of a prompt experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentVariant {
+    pub name: String,
+    /// Instructions added to the system prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Persona from `workspace/personas` whose prompt is added instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Relative share of sessions bucketed into this variant
+    #[serde(default = "default_variant_weight")]
+    pub weight: u32,
+}
+
+fn default_variant_weight() -> u32 {
+    1
+}
+
+/// A prompt A/B experiment from the `experiments` list in config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    pub name: String,
+    /// Stopped experiments assign no new sessions and add no prompts, but
+    /// keep their results
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub variants: Vec<ExperimentVariant>,
+}
+
+/// A named message template from the `templates` section of config.yaml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageTemplateConfig {
+    /// Text with `{variable}` placeholders; `{{` and `}}` are literal braces
+    pub text: String,
+    /// What the template is for, shown to the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Values for variables that are not supplied
+    #[serde(default)]
+    pub defaults: HashMap<String, String>,
+}
+
+/// Low disk space protection from the `disk` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Free space on the workspace filesystem below which cleanup runs
+    #[serde(default = "default_disk_min_free_mb")]
+    pub min_free_mb: u64,
+    /// Seconds between free space checks
+    #[serde(default = "default_disk_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// During cleanup, archive sessions idle for longer than this
+    #[serde(default = "default_disk_archive_after_days")]
+    pub archive_after_days: u64,
+    /// During cleanup, runs of history kept per job
+    #[serde(default = "default_disk_history_runs")]
+    pub history_runs: usize,
+    /// During cleanup, days of telemetry kept
+    #[serde(default = "default_disk_telemetry_days")]
+    pub telemetry_days: u32,
+}
+
+fn default_disk_min_free_mb() -> u64 {
+    100
+}
+
+fn default_disk_check_interval_secs() -> u64 {
+    300
+}
+
+fn default_disk_archive_after_days() -> u64 {
+    1
+}
+
+fn default_disk_history_runs() -> usize {
+    3
+}
+
+fn default_disk_telemetry_days() -> u32 {
+    2
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        DiskConfig {
+            enabled: true,
+            min_free_mb: default_disk_min_free_mb(),
+            check_interval_secs: default_disk_check_interval_secs(),
+            archive_after_days: default_disk_archive_after_days(),
+            history_runs: default_disk_history_runs(),
+            telemetry_days: default_disk_telemetry_days(),
+        }
+    }
+}
+
+/// Email-to-task pipeline from the `email_tasks` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTasksConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maildir an external fetcher (fetchmail, mbsync) delivers to
+    #[serde(default)]
+    pub maildir: Option<PathBuf>,
+    /// Seconds between checks for new mail
+    #[serde(default = "default_email_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// Local time the daily digest is sent, as HH:MM
+    #[serde(default = "default_email_digest_time")]
+    pub digest_time: String,
+    /// Mail from senders containing any of these is skipped unread
+    #[serde(default)]
+    pub ignore_senders: Vec<String>,
+    /// Categories the summary model sorts mail into
+    #[serde(default = "default_email_categories")]
+    pub categories: Vec<EmailCategory>,
+}
+
+/// A category of incoming email and what happens to mail in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailCategory {
+    pub name: String,
+    /// What belongs in the category, shown to the model
+    pub description: String,
+    /// File a todo for each email in this category
+    #[serde(default)]
+    pub file: bool,
+    /// List emails in this category in the daily digest
+    #[serde(default = "default_true")]
+    pub digest: bool,
+}
+
+fn default_email_check_interval_secs() -> u64 {
+    300
+}
+
+fn default_email_digest_time() -> String {
+    "08:00".to_string()
+}
+
+fn default_email_categories() -> Vec<EmailCategory> {
+    vec![
+        EmailCategory {
+            name: "action".to_string(),
+            description: "Asks the reader to do, pay, reply to, or decide something".to_string(),
+            file: true,
+            digest: true,
+        },
+        EmailCategory {
+            name: "info".to_string(),
+            description: "Worth knowing about but needs no action".to_string(),
+            file: false,
+            digest: true,
+        },
+        EmailCategory {
+            name: "noise".to_string(),
+            description: "Newsletters, promotions, and automated notifications".to_string(),
+            file: false,
+            digest: false,
+        },
+    ]
+}
+
+impl Default for EmailTasksConfig {
+    fn default() -> Self {
+        EmailTasksConfig {
+            enabled: false,
+            maildir: None,
+            check_interval_secs: default_email_check_interval_secs(),
+            digest_time: default_email_digest_time(),
+            ignore_senders: Vec::new(),
+            categories: default_email_categories(),
+        }
+    }
+}
+
+/// Which backend the device manager uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceBackendKind {
+    /// No hardware access
+    #[default]
+    None,
+    /// Virtual devices from `devices.simulated`
+    Simulated,
+}
+
+/// Device settings from the `devices` section of config.yaml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DevicesConfig {
+    #[serde(default)]
+    pub backend: DeviceBackendKind,
+    /// Virtual devices served by the simulated backend
+    #[serde(default)]
+    pub simulated: Vec<SimulatedDeviceConfig>,
+}
+
+/// Which engine turns speech into text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SttBackendKind {
+    /// Speech recognition disabled
+    #[default]
+    None,
+    /// Local whisper.cpp command-line binary
+    WhisperCpp,
+    /// OpenAI-compatible `/audio/transcriptions` API
+    Openai,
+}
+
+/// Which engine turns text into speech
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsBackendKind {
+    /// Speech synthesis disabled
+    #[default]
+    None,
+    /// Local Piper binary
+    Piper,
+    /// OpenAI-compatible `/audio/speech` API
+    Openai,
+}
+
+/// Settings for one speech engine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeechEngineConfig {
+    /// Local binary to run; defaults to `whisper-cli` or `piper`
+    #[serde(default)]
+    pub binary: Option<String>,
+    /// Model file for local engines, model name for APIs
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Spoken language hint, e.g. `en`
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Voice name for API synthesis
+    #[serde(default)]
+    pub voice: Option<String>,
+    /// API key; defaults to `providers.openai.api_key`
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// API base URL; defaults to `providers.openai.api_base`
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+/// Speech-to-text settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SttConfig {
+    #[serde(default)]
+    pub backend: SttBackendKind,
+    #[serde(flatten)]
+    pub engine: SpeechEngineConfig,
+}
+
+/// Text-to-speech settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TtsConfig {
+    #[serde(default)]
+    pub backend: TtsBackendKind,
+    #[serde(flatten)]
+    pub engine: SpeechEngineConfig,
+}
+
+/// Voice settings from the `speech` section of config.yaml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeechConfig {
+    #[serde(default)]
+    pub stt: SttConfig,
+    #[serde(default)]
+    pub tts: TtsConfig,
+}
+
+/// Gateway settings from the `gateway` section of config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayConfig {
+    #[serde(default = "default_gateway_host")]
+    pub host: String,
+    #[serde(default = "default_gateway_port")]
+    pub port: u16,
+    /// Serve the HTTP API
+    #[serde(default = "default_true")]
+    pub http_enabled: bool,
+    /// Serve the HTML dashboard at `/`
+    #[serde(default = "default_true")]
+    pub dashboard: bool,
+    /// Bearer token required by the HTTP API; required unless `host` is loopback
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// Host names the API answers to besides IP addresses, `localhost`, and
+    /// this machine's hostname (e.g. a DNS name in front of the device)
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Browser origins allowed to open the WebSocket besides `host`,
+    /// `localhost`, and `127.0.0.1` on `port` (e.g. `http://pi.local:18790`)
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Advertise the HTTP API via mDNS as `_tacobot._tcp`
+    #[serde(default = "default_true")]
+    pub mdns: bool,
+    /// Warn when gateway startup takes longer than this many milliseconds
+    #[serde(default)]
+    pub startup_budget_ms: Option<u64>,
+}
+
+fn default_gateway_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_gateway_port() -> u16 {
+    18790
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        GatewayConfig {
+            host: default_gateway_host(),
+            port: default_gateway_port(),
+            http_enabled: true,
+            dashboard: true,
+            auth_token: None,
+            allowed_hosts: Vec::new(),
+            allowed_origins: Vec::new(),
+            mdns: true,
+            startup_budget_ms: None,
+        }
+    }
+}
+
+/// Parse a human duration such as `90s`, `30m`, `8h`, or `2d` (bare numbers are seconds)
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    number.checked_mul(multiplier).map(Duration::from_secs)
+}
+
 /// Channels configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelsConfig {
     pub telegram: Option<ChannelConfig>,
     pub discord: Option<ChannelConfig>,
+    /// Seconds after a message during which an edit replaces it
+    #[serde(default = "default_edit_grace_secs")]
+    pub edit_grace_secs: u64,
+    /// Where gateway alerts and failing-job notices are sent
+    #[serde(default)]
+    pub alerts: Option<ObserverTarget>,
+}
+
+fn default_edit_grace_secs() -> u64 {
+    120
 }
 
 /// Individual channel configuration
@@ -51,14 +870,62 @@ pub struct LlmConfig {
 pub struct ProviderConfig {
     pub api_key: Option<String>,
     pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<OpenRouterRouting>,
+}
+
+/// OpenRouter routing preferences passed through with each request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenRouterRouting {
+    /// Upstream providers to try, in order
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// Whether OpenRouter may fall back to providers outside `order`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    /// Prompt transforms to apply (e.g. "middle-out")
+    #[serde(default)]
+    pub transforms: Vec<String>,
+    /// Models to try if the primary model is unavailable
+    #[serde(default)]
+    pub fallback_models: Vec<String>,
+}
+
+/// OpenRouter app attribution sent as `HTTP-Referer` and `X-Title` headers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenRouterAttribution {
+    /// URL identifying the app
+    #[serde(default)]
+    pub referer: Option<String>,
+    /// App name shown in OpenRouter rankings
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// HTTP settings from the `http` section of config.yaml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// User-Agent for LLM requests (default `takobull/<version>`)
+    #[serde(default)]
+    pub user_agent: Option<String>,
 }
 
 /// Tools configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolsConfig {
     pub web_search: Option<ToolConfig>,
-    pub filesystem: Option<ToolConfig>,
-    pub shell: Option<ToolConfig>,
+    pub filesystem: Option<FilesystemConfig>,
+    pub shell: Option<ShellConfig>,
+    /// Environment variables injected per tool name; values are
+    /// `secret:NAME`, `env:NAME`, or literals
+    #[serde(default)]
+    pub env: HashMap<String, HashMap<String, String>>,
+    /// Cost annotations overriding the tools' own, by tool name
+    #[serde(default)]
+    pub cost_hints: HashMap<String, CostHint>,
+    /// Expensive tool calls allowed per turn before further ones are skipped
+    #[serde(default)]
+    pub max_expensive_per_turn: Option<usize>,
 }
 
 /// Individual tool configuration
@@ -67,6 +934,121 @@ pub struct ToolConfig {
     pub enabled: bool,
 }
 
+/// Filesystem tool permissions
+///
+/// Relative paths resolve against the workspace and `~` expands to `$HOME`.
+/// The workspace itself is always readable and writable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesystemConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Paths tools may read but never write (takes precedence over `writable`)
+    #[serde(default)]
+    pub read_only: Vec<String>,
+    /// Writable paths in addition to the workspace
+    #[serde(default)]
+    pub writable: Vec<String>,
+    /// Globs that can never be read or written, e.g. `**/.env` or `*.key`
+    #[serde(default)]
+    pub deny: Vec<String>,
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+}
+
+impl Default for FilesystemConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            read_only: Vec::new(),
+            writable: Vec::new(),
+            deny: Vec::new(),
+            symlinks: SymlinkPolicy::default(),
+        }
+    }
+}
+
+/// Shell tool configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Default timeout for foreground commands
+    #[serde(default = "default_shell_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Stream output of foreground commands to the channel while they run
+    #[serde(default = "default_true")]
+    pub stream: bool,
+    /// Minimum time between streamed chunks
+    #[serde(default = "default_stream_interval_ms")]
+    pub stream_interval_ms: u64,
+    /// Largest streamed chunk; output beyond it within one interval is skipped
+    #[serde(default = "default_stream_chunk_chars")]
+    pub stream_chunk_chars: usize,
+    /// Maximum number of background jobs running at once
+    #[serde(default = "default_max_jobs")]
+    pub max_jobs: usize,
+}
+
+fn default_shell_timeout_secs() -> u64 {
+    60
+}
+
+fn default_stream_interval_ms() -> u64 {
+    2000
+}
+
+fn default_stream_chunk_chars() -> usize {
+    1500
+}
+
+fn default_max_jobs() -> usize {
+    4
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_secs: default_shell_timeout_secs(),
+            stream: true,
+            stream_interval_ms: default_stream_interval_ms(),
+            stream_chunk_chars: default_stream_chunk_chars(),
+            max_jobs: default_max_jobs(),
+        }
+    }
+}
+
+/// Tool approval settings from `tools.approval`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    /// Tools that only run once the call is approved or matches a rule;
+    /// `exec` unless configured otherwise, since the shell could otherwise
+    /// write approval rules of its own
+    #[serde(default = "default_approval_required")]
+    pub required: Vec<String>,
+    /// Approvals of the same tool and pattern before an auto-approve rule
+    /// is offered; 0 never offers one
+    #[serde(default = "default_suggest_after")]
+    pub suggest_after: usize,
+}
+
+fn default_approval_required() -> Vec<String> {
+    vec!["exec".to_string()]
+}
+
+fn default_suggest_after() -> usize {
+    3
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            required: default_approval_required(),
+            suggest_after: default_suggest_after(),
+        }
+    }
+}
+
 /// Authentication configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
@@ -91,6 +1073,8 @@ impl Default for Config {
             channels: ChannelsConfig {
                 telegram: None,
                 discord: None,
+                edit_grace_secs: default_edit_grace_secs(),
+                alerts: None,
             },
             llm: LlmConfig {
                 default_provider: "openrouter".to_string(),
@@ -100,6 +1084,9 @@ impl Default for Config {
                 web_search: None,
                 filesystem: None,
                 shell: None,
+                env: HashMap::new(),
+                cost_hints: HashMap::new(),
+                max_expensive_per_turn: None,
             },
             auth: AuthConfig {
                 oauth_enabled: true,
@@ -174,4 +1161,122 @@ max_context_size = "not_a_number"
         assert_eq!(deserialized.agent.timeout_ms, 10000);
         assert_eq!(deserialized.agent.memory_limit_mb, 20);
     }
+
+    #[test]
+    fn test_agent_defaults_fill_missing_fields() {
+        let yaml = r#"
+model: "gpt-4o"
+summary_model: "gpt-4o-mini"
+"#;
+        let defaults: AgentDefaults = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(defaults.provider, "openrouter");
+        assert_eq!(defaults.model, "gpt-4o");
+        assert_eq!(defaults.summary_model.as_deref(), Some("gpt-4o-mini"));
+        assert!(defaults.summary_provider.is_none());
+        assert!(defaults.generation.is_empty());
+    }
+
+    #[test]
+    fn test_agent_defaults_generation_options() {
+        let yaml = "model: \"gpt-4o\"\nmax_tokens: 8192\ntemperature: 1\ntop_p: 0.9\nseed: 42\n";
+        let defaults: AgentDefaults = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(defaults.generation.temperature, Some(1.0));
+        assert_eq!(defaults.generation.top_p, Some(0.9));
+        assert_eq!(defaults.generation.seed, Some(42));
+        assert_eq!(defaults.generation.max_tokens, Some(8192));
+        assert!(defaults.generation.stop.is_none());
+    }
+
+    #[test]
+    fn test_exec_needs_approval_unless_configured() {
+        let approval: ApprovalConfig = serde_yaml::from_str("suggest_after: 2\n").unwrap();
+        assert_eq!(approval.required, vec!["exec".to_string()]);
+        let approval: ApprovalConfig = serde_yaml::from_str("required: []\n").unwrap();
+        assert!(approval.required.is_empty());
+        assert!(serde_yaml::from_str::<ApprovalConfig>("required: exec\n").is_err());
+    }
+
+    #[test]
+    fn test_agent_defaults_recall() {
+        let defaults: AgentDefaults = serde_yaml::from_str("model: \"gpt-4o\"\n").unwrap();
+        assert_eq!(defaults.recall, RecallConfig::default());
+
+        let yaml = "temperature: 0.5\nrecall:\n  top_k: 5\n  threshold: 0.6\n";
+        let defaults: AgentDefaults = serde_yaml::from_str(yaml).unwrap();
+        assert!(defaults.recall.enabled);
+        assert_eq!(defaults.recall.top_k, 5);
+        assert_eq!(defaults.recall.threshold, 0.6);
+        assert_eq!(defaults.generation.temperature, Some(0.5));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("8h"), Some(Duration::from_secs(8 * 3600)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(2 * 86400)));
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("5y"), None);
+    }
+
+    #[test]
+    fn test_telemetry_sensors_from_yaml() {
+        let yaml = r#"
+enabled: true
+sensors:
+  - name: cpu_temp
+    unit: "°C"
+    source: file
+    path: /sys/class/thermal/thermal_zone0/temp
+    scale: 0.001
+  - name: load
+    source: command
+    command: "cut -d' ' -f1 /proc/loadavg"
+    interval_secs: 300
+"#;
+        let telemetry: TelemetryConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(telemetry.retention_days, 14);
+        assert_eq!(telemetry.sensors.len(), 2);
+        assert_eq!(telemetry.sensors[0].interval_secs, 60);
+        assert_eq!(telemetry.sensors[1].interval_secs, 300);
+    }
+
+    #[test]
+    fn test_gateway_config_defaults() {
+        let gateway: GatewayConfig = serde_yaml::from_str("host: \"0.0.0.0\"\nport: 18800\n").unwrap();
+        assert_eq!(gateway.host, "0.0.0.0");
+        assert_eq!(gateway.port, 18800);
+        assert!(gateway.http_enabled);
+        assert!(gateway.dashboard);
+        assert!(gateway.auth_token.is_none());
+        assert!(gateway.mdns);
+    }
+
+    #[test]
+    fn test_openrouter_routing_from_yaml() {
+        let yaml = r#"
+api_key: "sk-test"
+model: "anthropic/claude-3-haiku"
+routing:
+  order: ["anthropic", "together"]
+  allow_fallbacks: false
+  fallback_models: ["openai/gpt-4o-mini"]
+"#;
+        let provider: ProviderConfig = serde_yaml::from_str(yaml).unwrap();
+        let routing = provider.routing.unwrap();
+        assert_eq!(routing.order, vec!["anthropic", "together"]);
+        assert_eq!(routing.allow_fallbacks, Some(false));
+        assert!(routing.transforms.is_empty());
+        assert_eq!(routing.fallback_models, vec!["openai/gpt-4o-mini"]);
+    }
+
+    #[test]
+    fn test_filesystem_config_from_yaml() {
+        let yaml = "read_only: [\"~/Documents\"]\ndeny: [\"**/.env\"]\nsymlinks: deny\n";
+        let filesystem: FilesystemConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(filesystem.enabled);
+        assert_eq!(filesystem.read_only, vec!["~/Documents"]);
+        assert!(filesystem.writable.is_empty());
+        assert_eq!(filesystem.symlinks, SymlinkPolicy::Deny);
+    }
 }
@@ -22,6 +22,7 @@ mod tests {
     }
 
     /// Strategy for generating valid ChannelConfig values
+    #[allow(dead_code)]
     fn channel_config_strategy() -> impl Strategy<Value = ChannelConfig> {
         (any::<bool>(), ".*")
             .prop_map(|(enabled, token)| ChannelConfig {
@@ -35,6 +36,7 @@ mod tests {
     }
 
     /// Strategy for generating valid ProviderConfig values
+    #[allow(dead_code)]
     fn provider_config_strategy() -> impl Strategy<Value = ProviderConfig> {
         (any::<bool>(), ".*", ".*")
             .prop_map(|(has_key, key, model)| ProviderConfig {
@@ -44,6 +46,7 @@ mod tests {
                     None
                 },
                 model: Some(model.to_string()),
+                routing: None,
             })
     }
 
@@ -62,6 +65,7 @@ mod tests {
                     ProviderConfig {
                         api_key: Some("test_key".to_string()),
                         model: Some("test_model".to_string()),
+                        routing: None,
                     },
                 );
 
@@ -84,6 +88,8 @@ mod tests {
                         } else {
                             None
                         },
+                        edit_grace_secs: 120,
+                        alerts: None,
                     },
                     llm: LlmConfig {
                         default_provider: provider_name.to_string(),
@@ -93,6 +99,9 @@ mod tests {
                         web_search: None,
                         filesystem: None,
                         shell: None,
+                        env: HashMap::new(),
+                        cost_hints: HashMap::new(),
+                        max_expensive_per_turn: None,
                     },
                     auth: AuthConfig {
                         oauth_enabled: true,
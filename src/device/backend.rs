@@ -0,0 +1,21 @@
+//! Hardware access behind the device manager
+
+use super::manager::Device;
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Reads and drives devices on a board, or a stand-in for one
+///
+/// Values are numeric: GPIO pins read and write 0 or 1, sensors return
+/// their reading in display units.
+#[async_trait]
+pub trait DeviceBackend: Send + Sync {
+    /// Devices the backend can reach
+    async fn discover(&self) -> Result<Vec<Device>>;
+
+    /// Current value of device `id`
+    async fn read(&self, id: &str) -> Result<f64>;
+
+    /// Set device `id` to `value`
+    async fn write(&self, id: &str, value: f64) -> Result<()>;
+}
@@ -0,0 +1,254 @@
+//! First-run hardware capability detection
+//!
+//! [`detect`] looks for I2C buses and the chips bound on them, SPI devices,
+//! GPIO chips, cameras, and ALSA capture and playback devices by listing
+//! `/dev` and reading names from sysfs and procfs. Onboarding writes the
+//! result into the `hardware` section of config.yaml, with suggested entries
+//! that can be copied under `devices`.
+
+use super::manager::DeviceType;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A device node with a human-readable name where one is known
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedDevice {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// A device entry suggested from what was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuggestedDevice {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub device_type: DeviceType,
+    /// Bus device node, e.g. `/dev/i2c-1`
+    pub bus: String,
+    /// Address on the bus, e.g. `0x76`
+    pub address: String,
+}
+
+/// Hardware interfaces present on this machine
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HardwareCapabilities {
+    pub i2c_buses: Vec<String>,
+    pub spi_devices: Vec<String>,
+    pub gpio_chips: Vec<String>,
+    pub cameras: Vec<NamedDevice>,
+    pub microphones: Vec<NamedDevice>,
+    pub speakers: Vec<NamedDevice>,
+    pub suggested_devices: Vec<SuggestedDevice>,
+}
+
+impl HardwareCapabilities {
+    /// Whether nothing was found
+    pub fn is_empty(&self) -> bool {
+        self.i2c_buses.is_empty()
+            && self.spi_devices.is_empty()
+            && self.gpio_chips.is_empty()
+            && self.cameras.is_empty()
+            && self.microphones.is_empty()
+            && self.speakers.is_empty()
+    }
+
+    /// Counts of what was found, e.g. `2 I2C buses, 1 camera`
+    pub fn summary(&self) -> String {
+        let counts = [
+            (self.i2c_buses.len(), "I2C bus", "I2C buses"),
+            (self.spi_devices.len(), "SPI device", "SPI devices"),
+            (self.gpio_chips.len(), "GPIO chip", "GPIO chips"),
+            (self.cameras.len(), "camera", "cameras"),
+            (self.microphones.len(), "microphone", "microphones"),
+            (self.speakers.len(), "speaker", "speakers"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, one, many)| format!("{} {}", count, if *count == 1 { one } else { many }))
+            .collect();
+        if parts.is_empty() {
+            "no hardware interfaces".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// `hardware:` section to append to config.yaml
+    pub fn to_config_section(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct Section<'a> {
+            hardware: &'a HardwareCapabilities,
+        }
+        let yaml = serde_yaml::to_string(&Section { hardware: self })?;
+        Ok(format!(
+            "\n# Detected by `takobull onboard`. Copy suggested_devices entries under\n\
+             # devices to use them; delete this section and re-run onboard to re-detect.\n{}",
+            yaml
+        ))
+    }
+}
+
+/// Video nodes with these in their name are codecs or ISPs, not cameras
+const NON_CAMERA_NAMES: &[&str] = &["codec", "isp"];
+
+/// Probe the filesystem under `root` (`/` on a real system)
+pub fn detect(root: &Path) -> HardwareCapabilities {
+    let mut caps = HardwareCapabilities {
+        i2c_buses: dev_nodes(root, "dev", "i2c-"),
+        spi_devices: dev_nodes(root, "dev", "spidev"),
+        gpio_chips: dev_nodes(root, "dev", "gpiochip"),
+        ..Default::default()
+    };
+
+    for path in dev_nodes(root, "dev", "video") {
+        let node = path.trim_start_matches("/dev/");
+        let name = read_name(&root.join("sys/class/video4linux").join(node).join("name"));
+        let lower = name.as_deref().unwrap_or("").to_lowercase();
+        if NON_CAMERA_NAMES.iter().all(|skip| !lower.contains(skip)) {
+            caps.cameras.push(NamedDevice { path, name });
+        }
+    }
+
+    // ALSA PCM nodes are pcmC<card>D<device><c|p>
+    for path in dev_nodes(root, "dev/snd", "pcmC") {
+        let node = path.trim_start_matches("/dev/snd/");
+        let card = node["pcmC".len()..].split('D').next().unwrap_or_default();
+        let name = read_name(&root.join(format!("proc/asound/card{}/id", card)));
+        let kind = node.chars().last();
+        let device = NamedDevice { path, name };
+        match kind {
+            Some('c') => caps.microphones.push(device),
+            Some('p') => caps.speakers.push(device),
+            _ => {}
+        }
+    }
+
+    caps.suggested_devices = suggest(root, &caps);
+    caps
+}
+
+/// Entries for chips bound on I2C buses and for each SPI device
+fn suggest(root: &Path, caps: &HardwareCapabilities) -> Vec<SuggestedDevice> {
+    let mut suggested: Vec<SuggestedDevice> = Vec::new();
+    let mut add = |base: String, device_type: DeviceType, bus: String, address: String| {
+        let mut id = base.clone();
+        let mut n = 2;
+        while suggested.iter().any(|s| s.id == id) {
+            id = format!("{}_{}", base, n);
+            n += 1;
+        }
+        suggested.push(SuggestedDevice {
+            id,
+            device_type,
+            bus,
+            address,
+        });
+    };
+
+    // Bound I2C clients appear in sysfs as <bus>-<4 hex digit address>
+    let clients = root.join("sys/bus/i2c/devices");
+    for entry in sorted_names(&clients) {
+        let Some((bus, address)) = entry.split_once('-') else { continue };
+        let Ok(address) = u16::from_str_radix(address, 16) else { continue };
+        if bus.parse::<u32>().is_err() {
+            continue;
+        }
+        let name = read_name(&clients.join(&entry).join("name")).unwrap_or_else(|| "i2c".to_string());
+        let base = format!("{}_{:02x}", sanitize(&name), address);
+        add(base, DeviceType::I2C, format!("/dev/i2c-{}", bus), format!("0x{:02x}", address));
+    }
+    for path in &caps.spi_devices {
+        let address = path.trim_start_matches("/dev/spidev").to_string();
+        add(format!("spi{}", address.replace('.', "_")), DeviceType::SPI, path.clone(), address);
+    }
+    suggested
+}
+
+/// Paths of nodes in `dir` (e.g. `dev/snd`) whose names start with `prefix`,
+/// in numeric order, as they appear on the real system
+fn dev_nodes(root: &Path, dir: &str, prefix: &str) -> Vec<String> {
+    sorted_names(&root.join(dir))
+        .into_iter()
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| format!("/{}/{}", dir, name))
+        .collect()
+}
+
+/// Entry names in `dir`, shorter names first so `i2c-2` sorts before `i2c-10`
+fn sorted_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    names
+}
+
+fn read_name(path: &Path) -> Option<String> {
+    let name = std::fs::read_to_string(path).ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Lowercase id made of letters, digits, and underscores
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_detects_board_interfaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for node in ["i2c-10", "i2c-1", "spidev0.0", "gpiochip0", "video0", "video10", "snd/pcmC1D0c", "snd/pcmC0D0p"] {
+            touch(root, &format!("dev/{}", node), "");
+        }
+        touch(root, "sys/class/video4linux/video0/name", "Camera Module 3\n");
+        touch(root, "sys/class/video4linux/video10/name", "bcm2835-codec-decode\n");
+        touch(root, "proc/asound/card1/id", "USBMic\n");
+        touch(root, "proc/asound/card0/id", "Headphones\n");
+        touch(root, "sys/bus/i2c/devices/1-0076/name", "bme280\n");
+        touch(root, "sys/bus/i2c/devices/i2c-1/name", "bcm2835 I2C adapter\n");
+
+        let caps = detect(root);
+        assert_eq!(caps.i2c_buses, vec!["/dev/i2c-1", "/dev/i2c-10"]);
+        assert_eq!(caps.gpio_chips, vec!["/dev/gpiochip0"]);
+        assert_eq!(caps.cameras.len(), 1);
+        assert_eq!(caps.cameras[0].name.as_deref(), Some("Camera Module 3"));
+        assert_eq!(caps.microphones[0].path, "/dev/snd/pcmC1D0c");
+        assert_eq!(caps.microphones[0].name.as_deref(), Some("USBMic"));
+        assert_eq!(caps.speakers[0].name.as_deref(), Some("Headphones"));
+        assert_eq!(
+            caps.summary(),
+            "2 I2C buses, 1 SPI device, 1 GPIO chip, 1 camera, 1 microphone, 1 speaker"
+        );
+
+        let ids: Vec<&str> = caps.suggested_devices.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["bme280_76", "spi0_0"]);
+        assert_eq!(caps.suggested_devices[0].bus, "/dev/i2c-1");
+        assert_eq!(caps.suggested_devices[0].address, "0x76");
+
+        let section = caps.to_config_section().unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&section).unwrap();
+        assert_eq!(value["hardware"]["suggested_devices"][0]["type"], "i2c");
+
+        assert!(detect(&root.join("missing")).is_empty());
+    }
+}
@@ -1,10 +1,14 @@
 //! Device manager implementation
 
-use crate::error::Result;
+use super::backend::DeviceBackend;
+use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Device type enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DeviceType {
     I2C,
     SPI,
@@ -37,31 +41,73 @@ pub struct Device {
 
 /// Device manager for managing hardware devices
 pub struct DeviceManager {
-    // TODO: Add fields for device management
+    backend: Option<Arc<dyn DeviceBackend>>,
+    devices: HashMap<String, Device>,
 }
 
 impl DeviceManager {
     /// Create a new device manager
     pub fn new() -> Self {
-        DeviceManager {}
+        DeviceManager {
+            backend: None,
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Access hardware through `backend`
+    pub fn with_backend(mut self, backend: Arc<dyn DeviceBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    fn backend(&self) -> Result<&Arc<dyn DeviceBackend>> {
+        self.backend
+            .as_ref()
+            .ok_or_else(|| Error::device("No device backend configured"))
     }
 
     /// Discover available devices
     pub async fn discover_devices(&self) -> Result<Vec<Device>> {
-        // TODO: Implement device discovery
-        Ok(Vec::new())
+        match &self.backend {
+            Some(backend) => backend.discover().await,
+            None => Ok(Vec::new()),
+        }
     }
 
     /// Register a device
-    pub async fn register_device(&mut self, _device: Device) -> Result<()> {
-        // TODO: Implement device registration
+    pub async fn register_device(&mut self, device: Device) -> Result<()> {
+        self.devices.insert(device.id.clone(), device);
         Ok(())
     }
 
     /// Get a device by ID
-    pub fn get_device(&self, _id: &str) -> Result<Option<Device>> {
-        // TODO: Implement device retrieval
-        Ok(None)
+    pub fn get_device(&self, id: &str) -> Result<Option<Device>> {
+        Ok(self.devices.get(id).cloned())
+    }
+
+    /// Registered devices, sorted by ID
+    pub fn devices(&self) -> Vec<Device> {
+        let mut devices: Vec<Device> = self.devices.values().cloned().collect();
+        devices.sort_by(|a, b| a.id.cmp(&b.id));
+        devices
+    }
+
+    /// Read the current value of registered device `id`
+    pub async fn read(&self, id: &str) -> Result<f64> {
+        self.registered(id)?;
+        self.backend()?.read(id).await
+    }
+
+    /// Set registered device `id` to `value`
+    pub async fn write(&self, id: &str, value: f64) -> Result<()> {
+        self.registered(id)?;
+        self.backend()?.write(id, value).await
+    }
+
+    fn registered(&self, id: &str) -> Result<&Device> {
+        self.devices
+            .get(id)
+            .ok_or_else(|| Error::device(format!("Unknown device: {}", id)))
     }
 }
 
@@ -1,5 +1,16 @@
 //! Device management for hardware interfaces
+//!
+//! [`DeviceManager`] talks to hardware through a [`DeviceBackend`]. The
+//! [`SimulatedBackend`] stands in for a board so agent and tool behavior can
+//! be tried out on a laptop. [`detect`] finds the interfaces a board offers
+//! during onboarding.
 
+pub mod backend;
+pub mod detect;
 pub mod manager;
+pub mod simulated;
 
+pub use backend::DeviceBackend;
+pub use detect::HardwareCapabilities;
 pub use manager::DeviceManager;
+pub use simulated::{Signal, SimulatedBackend, SimulatedDeviceConfig};
@@ -0,0 +1,211 @@
+//! Simulated devices for development without a board
+//!
+//! Virtual GPIO pins and I2C/SPI sensors are declared under
+//! `devices.simulated` in config.yaml. Sensor values follow a signal: a
+//! constant, a scripted sequence, a sine wave, or a command printing a
+//! number. Writes are kept so tests can check what the agent switched.
+
+use super::backend::DeviceBackend;
+use super::manager::{Device, DeviceConfig, DeviceStatus, DeviceType};
+use crate::error::{Error, Result};
+use crate::telemetry::sensor::{build_source, SensorSourceConfig};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A simulated device from config.yaml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedDeviceConfig {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub device_type: DeviceType,
+    /// Pin number or bus address, e.g. `17` or `0x48`
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub signal: Signal,
+}
+
+/// How a simulated device's value evolves
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Signal {
+    Constant {
+        value: f64,
+    },
+    /// Values returned in turn, repeating from the start
+    Sequence {
+        values: Vec<f64>,
+    },
+    Sine {
+        min: f64,
+        max: f64,
+        period_secs: f64,
+    },
+    /// Shell command printing a number on stdout
+    Command {
+        command: String,
+    },
+}
+
+impl Default for Signal {
+    fn default() -> Self {
+        Signal::Constant { value: 0.0 }
+    }
+}
+
+struct SimulatedDevice {
+    config: SimulatedDeviceConfig,
+    /// Value set by the last write, overriding the signal
+    written: Option<f64>,
+    reads: usize,
+}
+
+/// Backend serving simulated devices
+pub struct SimulatedBackend {
+    devices: Mutex<Vec<SimulatedDevice>>,
+    writes: Mutex<Vec<(String, f64)>>,
+    started: Instant,
+}
+
+impl SimulatedBackend {
+    /// Backend with the devices in `configs`
+    pub fn new(configs: Vec<SimulatedDeviceConfig>) -> Self {
+        Self {
+            devices: Mutex::new(
+                configs
+                    .into_iter()
+                    .map(|config| SimulatedDevice {
+                        config,
+                        written: None,
+                        reads: 0,
+                    })
+                    .collect(),
+            ),
+            writes: Mutex::new(Vec::new()),
+            started: Instant::now(),
+        }
+    }
+
+    /// Writes made so far, oldest first
+    pub fn writes(&self) -> Vec<(String, f64)> {
+        self.writes.lock().unwrap().clone()
+    }
+
+    fn unknown(id: &str) -> Error {
+        Error::device(format!("Unknown device: {}", id))
+    }
+}
+
+#[async_trait]
+impl DeviceBackend for SimulatedBackend {
+    async fn discover(&self) -> Result<Vec<Device>> {
+        Ok(self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|device| Device {
+                id: device.config.id.clone(),
+                device_type: device.config.device_type,
+                status: DeviceStatus::Available,
+                config: DeviceConfig {
+                    address: device.config.address.clone(),
+                    parameters: HashMap::from([("backend".to_string(), "simulated".to_string())]),
+                },
+            })
+            .collect())
+    }
+
+    async fn read(&self, id: &str) -> Result<f64> {
+        let signal = {
+            let mut devices = self.devices.lock().unwrap();
+            let device = devices
+                .iter_mut()
+                .find(|d| d.config.id == id)
+                .ok_or_else(|| Self::unknown(id))?;
+            if let Some(value) = device.written {
+                return Ok(value);
+            }
+            device.reads += 1;
+            match &device.config.signal {
+                Signal::Constant { value } => return Ok(*value),
+                Signal::Sequence { values } if values.is_empty() => return Ok(0.0),
+                Signal::Sequence { values } => return Ok(values[(device.reads - 1) % values.len()]),
+                Signal::Sine {
+                    min,
+                    max,
+                    period_secs,
+                } => {
+                    let phase = self.started.elapsed().as_secs_f64() / period_secs.max(f64::EPSILON);
+                    let unit = (phase * std::f64::consts::TAU).sin() * 0.5 + 0.5;
+                    return Ok(min + (max - min) * unit);
+                }
+                Signal::Command { command } => SensorSourceConfig::Command {
+                    command: command.clone(),
+                },
+            }
+        };
+        build_source(&signal).read().await
+    }
+
+    async fn write(&self, id: &str, value: f64) -> Result<()> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices
+            .iter_mut()
+            .find(|d| d.config.id == id)
+            .ok_or_else(|| Self::unknown(id))?;
+        let value = match device.config.device_type {
+            DeviceType::GPIO if value != 0.0 => 1.0,
+            DeviceType::GPIO => 0.0,
+            _ => value,
+        };
+        device.written = Some(value);
+        self.writes.lock().unwrap().push((id.to_string(), value));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend() -> SimulatedBackend {
+        let yaml = r#"
+- id: porch_light
+  type: gpio
+  address: "17"
+- id: greenhouse_temp
+  type: i2c
+  address: "0x48"
+  signal: { kind: sequence, values: [18.5, 19.0] }
+- id: soil
+  type: spi
+  signal: { kind: command, command: "echo 42" }
+"#;
+        SimulatedBackend::new(serde_yaml::from_str(yaml).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_signals_and_writes() {
+        let backend = backend();
+        assert_eq!(backend.discover().await.unwrap().len(), 3);
+
+        let mut temps = Vec::new();
+        for _ in 0..3 {
+            temps.push(backend.read("greenhouse_temp").await.unwrap());
+        }
+        assert_eq!(temps, vec![18.5, 19.0, 18.5]);
+        assert_eq!(backend.read("soil").await.unwrap(), 42.0);
+
+        assert_eq!(backend.read("porch_light").await.unwrap(), 0.0);
+        backend.write("porch_light", 5.0).await.unwrap();
+        assert_eq!(backend.read("porch_light").await.unwrap(), 1.0);
+        assert_eq!(backend.writes(), vec![("porch_light".to_string(), 1.0)]);
+
+        assert!(backend.read("garage").await.is_err());
+        assert!(backend.write("garage", 1.0).await.is_err());
+    }
+}
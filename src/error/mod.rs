@@ -24,6 +24,9 @@ pub enum Error {
     #[error("LLM provider error: {0}")]
     LlmProvider(String),
 
+    #[error("Context length exceeded: {0}")]
+    ContextLength(String),
+
     #[error("Tool execution error: {0}")]
     Tool(String),
 
@@ -76,6 +79,11 @@ impl Error {
         Error::LlmProvider(msg.into())
     }
 
+    /// Create a context length error (the request does not fit the model's window)
+    pub fn context_length(msg: impl Into<String>) -> Self {
+        Error::ContextLength(msg.into())
+    }
+
     /// Create a tool execution error
     pub fn tool(msg: impl Into<String>) -> Self {
         Error::Tool(msg.into())
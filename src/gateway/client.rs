@@ -0,0 +1,111 @@
+//! Client for the HTTP API of a running gateway
+
+use super::{GatewayStatus, LogTail};
+use crate::error::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct SendResponse {
+    response: String,
+}
+
+/// Talks to a running gateway so the CLI doesn't start a second agent
+pub struct RemoteClient {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl RemoteClient {
+    /// Create a client for the gateway at `base_url` (e.g. `http://127.0.0.1:18790`)
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Authenticate with the gateway's `auth_token`
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    /// Base URL of the gateway
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Fetch gateway status
+    pub async fn status(&self) -> Result<GatewayStatus> {
+        self.send(self.http.get(self.url("/api/status"))).await
+    }
+
+    /// Send a message to the agent and return its response
+    ///
+    /// With `edit`, the message replaces the previous one sent to the gateway.
+    pub async fn send_message(&self, text: &str, edit: bool) -> Result<String> {
+        let request = self
+            .http
+            .post(self.url("/api/messages"))
+            .json(&serde_json::json!({ "text": text, "edit": edit }));
+        let response: SendResponse = self.send(request).await?;
+        Ok(response.response)
+    }
+
+    /// Ask the gateway to reload its configuration
+    pub async fn reload(&self) -> Result<()> {
+        let _: serde_json::Value = self.send(self.http.post(self.url("/api/reload"))).await?;
+        Ok(())
+    }
+
+    /// Start scheduled job `id` on the gateway
+    pub async fn run_job(&self, id: &str) -> Result<()> {
+        let url = self.url(&format!("/v1/jobs/{}/run", id));
+        let _: serde_json::Value = self.send(self.http.post(url)).await?;
+        Ok(())
+    }
+
+    /// Fetch the last `lines` log lines, or those after sequence `after`
+    pub async fn logs(&self, lines: usize, after: Option<u64>) -> Result<LogTail> {
+        let mut query = vec![("lines", lines.to_string())];
+        if let Some(after) = after {
+            query.push(("after", after.to_string()));
+        }
+        self.send(self.http.get(self.url("/api/logs")).query(&query))
+            .await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, mut request: reqwest::RequestBuilder) -> Result<T> {
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| {
+            Error::http(format!("Gateway not reachable at {}: {}", self.base_url, e))
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            let message = body["error"].as_str().unwrap_or(status.as_str()).to_string();
+            return Err(Error::http(format!("Gateway returned {}: {}", status, message)));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_url_is_normalized() {
+        let client = RemoteClient::new("http://127.0.0.1:18790/");
+        assert_eq!(client.url("/api/status"), "http://127.0.0.1:18790/api/status");
+    }
+}
@@ -0,0 +1,181 @@
+//! Single-page dashboard served at `/`
+//!
+//! Self-contained HTML with inline CSS and JavaScript so it works on an
+//! offline LAN. An auth token can be passed as `/?token=...`. Replies stream
+//! in over `/api/messages/stream` as they are generated.
+
+/// Dashboard page
+pub const DASHBOARD_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>TakoBull</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 0 auto; max-width: 56rem; padding: 1rem; color: #222; background: #fafafa; }
+h1 { font-size: 1.4rem; margin: 0 0 1rem; }
+h2 { font-size: 1rem; margin: 1.5rem 0 .5rem; text-transform: uppercase; color: #666; }
+section { background: #fff; border: 1px solid #ddd; border-radius: 6px; padding: .75rem 1rem; }
+table { border-collapse: collapse; width: 100%; }
+td { padding: .2rem .5rem .2rem 0; vertical-align: top; }
+#messages { max-height: 24rem; overflow-y: auto; }
+.msg { margin: .4rem 0; white-space: pre-wrap; }
+.msg .meta { color: #888; font-size: .8rem; }
+.user { color: #0a5; }
+.assistant { color: #05a; }
+.tool { color: #888; font-size: .8rem; }
+form { display: flex; gap: .5rem; margin-top: .75rem; }
+textarea { flex: 1; min-height: 3rem; font: inherit; }
+button { font: inherit; padding: 0 1rem; }
+#error { color: #b00; }
+</style>
+</head>
+<body>
+<h1>🐂 TakoBull</h1>
+<div id="error"></div>
+<h2>Status</h2>
+<section><table id="status"></table></section>
+<h2>Jobs</h2>
+<section><table id="jobs"><tr><td>No scheduled jobs</td></tr></table></section>
+<h2>Sensors</h2>
+<section><table id="sensors"><tr><td>No readings</td></tr></table></section>
+<h2>Messages</h2>
+<section>
+<div id="messages"></div>
+<form id="send">
+<textarea id="text" placeholder="Message the assistant"></textarea>
+<button type="submit">Send</button>
+</form>
+</section>
+<script>
+const token = new URLSearchParams(location.search).get("token");
+const headers = token ? { "Authorization": "Bearer " + token } : {};
+
+async function api(path, options) {
+  const res = await fetch("/api" + path, Object.assign({ headers: Object.assign({ "Content-Type": "application/json" }, headers) }, options));
+  const body = await res.json();
+  if (!res.ok) throw new Error(body.error || res.statusText);
+  return body;
+}
+
+function row(table, cells) {
+  const tr = table.insertRow();
+  for (const text of cells) tr.insertCell().textContent = text;
+}
+
+function time(ts) { return new Date(ts).toLocaleString(); }
+
+// Set while a reply is streaming, so refresh() leaves the message list alone
+let streaming = false;
+
+async function refresh() {
+  try {
+    const status = await api("/status");
+    const table = document.getElementById("status");
+    table.textContent = "";
+    row(table, ["Version", status.version]);
+    row(table, ["Uptime", Math.floor(status.uptime_secs / 60) + " min"]);
+    row(table, ["Model", status.provider + " / " + status.model]);
+    row(table, ["Sessions", status.sessions]);
+    if (status.safe_mode) row(table, ["Mode", "⚠️ Safe mode: mutating tools disabled, background jobs paused"]);
+    for (const d of status.delivery || []) {
+      let text = d.sent + " sent · " + d.failed + " failed";
+      if (d.retried) text += " (" + d.retried + " retried)";
+      if (d.pending) text += " · " + d.pending + " pending";
+      if (d.last_error) text += " · last error " + time(d.last_failure_at) + ": " + d.last_error;
+      row(table, ["Delivery " + d.channel_id, (d.failed ? "⚠️ " : "") + text]);
+    }
+
+    const jobTable = document.getElementById("jobs");
+    if (status.jobs.length) jobTable.textContent = "";
+    for (const j of status.jobs) {
+      let next = !j.enabled ? "disabled" : j.next_run ? (j.retrying ? "retry " : "next ") + time(j.next_run) : "no upcoming runs";
+      if (j.degraded) next = "DEGRADED · " + next;
+      let last = "never run";
+      if (j.last_run) {
+        const outcome = j.last_success === true ? "ok" : j.last_success === false ? "failed" : "skipped";
+        const took = j.last_success != null && j.last_duration_ms != null ? " in " + (j.last_duration_ms / 1000).toFixed(1) + "s" : "";
+        last = "last " + time(j.last_run) + " " + outcome + took;
+      }
+      row(jobTable, [j.description, j.expression, next, last]);
+    }
+
+    const sensors = await api("/sensors");
+    const sensorTable = document.getElementById("sensors");
+    if (sensors.length) sensorTable.textContent = "";
+    for (const s of sensors) row(sensorTable, [s.sensor, s.value, time(s.timestamp)]);
+
+    if (streaming) return;
+    const messages = await api("/messages?limit=50");
+    const list = document.getElementById("messages");
+    list.textContent = "";
+    for (const m of messages) {
+      const div = document.createElement("div");
+      div.className = "msg " + m.role;
+      const meta = document.createElement("div");
+      meta.className = "meta";
+      meta.textContent = m.role + " · " + m.channel + " · " + time(m.timestamp);
+      div.appendChild(meta);
+      div.appendChild(document.createTextNode(m.content));
+      list.appendChild(div);
+    }
+    list.scrollTop = list.scrollHeight;
+    document.getElementById("error").textContent = "";
+  } catch (e) {
+    document.getElementById("error").textContent = e.message;
+  }
+}
+
+document.getElementById("send").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const text = document.getElementById("text");
+  if (!text.value.trim()) return;
+  const button = event.target.querySelector("button");
+  button.disabled = true;
+  streaming = true;
+  const list = document.getElementById("messages");
+  const reply = document.createElement("div");
+  reply.className = "msg assistant";
+  list.appendChild(reply);
+  try {
+    const res = await fetch("/api/messages/stream", { method: "POST", headers: Object.assign({ "Content-Type": "application/json" }, headers), body: JSON.stringify({ text: text.value }) });
+    if (!res.ok) throw new Error((await res.json()).error || res.statusText);
+    text.value = "";
+    const reader = res.body.getReader();
+    const decoder = new TextDecoder();
+    let buffer = "";
+    for (;;) {
+      const { done, value } = await reader.read();
+      if (done) break;
+      buffer += decoder.decode(value, { stream: true });
+      let end;
+      while ((end = buffer.indexOf("\n\n")) >= 0) {
+        const data = buffer.slice(0, end).split("\n").filter(l => l.startsWith("data:")).map(l => l.slice(5)).join("\n");
+        buffer = buffer.slice(end + 2);
+        if (!data) continue;
+        const event = JSON.parse(data);
+        if (event.type === "delta") reply.appendChild(document.createTextNode(event.text));
+        else if (event.type === "tool_start") {
+          const tool = document.createElement("div");
+          tool.className = "tool";
+          tool.textContent = "⚙️ " + event.name + "…";
+          reply.appendChild(tool);
+        } else if (event.type === "tool_output") reply.appendChild(document.createTextNode(event.text + "\n"));
+        else if (event.type === "error") throw new Error(event.message);
+        list.scrollTop = list.scrollHeight;
+      }
+    }
+  } catch (e) {
+    document.getElementById("error").textContent = e.message;
+  }
+  streaming = false;
+  button.disabled = false;
+  refresh();
+});
+
+refresh();
+setInterval(refresh, 10000);
+</script>
+</body>
+</html>
+"#;
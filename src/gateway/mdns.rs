@@ -0,0 +1,100 @@
+//! mDNS/zeroconf advertisement of the gateway API
+//!
+//! Publishes `_tacobot._tcp` so companion apps can find the assistant on the
+//! LAN without knowing its IP address.
+
+use crate::error::{Error, Result};
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use std::net::SocketAddr;
+use tracing::{debug, info, warn};
+
+/// DNS-SD service type the gateway registers
+pub const SERVICE_TYPE: &str = "_tacobot._tcp.local.";
+
+/// Keeps the gateway registered via mDNS until dropped
+pub struct MdnsAdvertiser {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl MdnsAdvertiser {
+    /// Advertise the API listening on `addr` with the given TXT properties
+    pub fn advertise(instance: &str, addr: SocketAddr, properties: &[(&str, &str)]) -> Result<Self> {
+        let daemon = ServiceDaemon::new()
+            .map_err(|e| Error::runtime(format!("Failed to start mDNS daemon: {}", e)))?;
+        let host_name = format!("{}.local.", instance);
+        let ip = if addr.ip().is_unspecified() {
+            String::new()
+        } else {
+            addr.ip().to_string()
+        };
+        let mut service = ServiceInfo::new(SERVICE_TYPE, instance, &host_name, ip, addr.port(), properties)
+            .map_err(|e| Error::runtime(format!("Invalid mDNS service info: {}", e)))?;
+        if addr.ip().is_unspecified() {
+            service = service.enable_addr_auto();
+        }
+        let fullname = service.get_fullname().to_string();
+        daemon
+            .register(service)
+            .map_err(|e| Error::runtime(format!("Failed to register mDNS service: {}", e)))?;
+        info!("Advertising {} on port {} via mDNS", fullname, addr.port());
+        Ok(Self { daemon, fullname })
+    }
+}
+
+impl Drop for MdnsAdvertiser {
+    fn drop(&mut self) {
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            warn!("Failed to unregister mDNS service: {}", e);
+        }
+        let _ = self.daemon.shutdown();
+        debug!("Stopped mDNS advertisement");
+    }
+}
+
+/// Whether a listener on `addr` is reachable from other devices
+pub fn is_advertisable(addr: &SocketAddr) -> bool {
+    !addr.ip().is_loopback()
+}
+
+/// mDNS instance name derived from the system hostname
+pub fn instance_name() -> String {
+    let hostname = std::fs::read_to_string("/etc/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_default();
+    sanitize_instance_name(&hostname)
+}
+
+fn sanitize_instance_name(hostname: &str) -> String {
+    let name: String = hostname
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() {
+        "takobull".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_is_not_advertised() {
+        assert!(!is_advertisable(&"127.0.0.1:18790".parse().unwrap()));
+        assert!(is_advertisable(&"0.0.0.0:18790".parse().unwrap()));
+        assert!(is_advertisable(&"192.168.1.20:18790".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_sanitize_instance_name() {
+        assert_eq!(sanitize_instance_name("raspberrypi\n"), "raspberrypi");
+        assert_eq!(sanitize_instance_name("kitchen pi.lan"), "kitchen-pi-lan");
+        assert_eq!(sanitize_instance_name(""), "takobull");
+    }
+}
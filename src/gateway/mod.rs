@@ -0,0 +1,714 @@
+//! HTTP API and dashboard served by the gateway
+//!
+//! The listener is enabled with the `webhooks` feature and configured under
+//! the `gateway` section of config.yaml. With the `mdns` feature it is also
+//! advertised on the LAN. [`client::RemoteClient`] talks to a running gateway.
+//! Scheduled jobs can be started over `POST /v1/jobs/<id>/run` when an auth
+//! token is configured. Requests whose `Host` is not an IP address or a name
+//! of this gateway are refused, so a web page cannot reach the API through
+//! DNS rebinding. Replies can be streamed as they are generated, over
+//! server-sent events from `POST /api/messages/stream` or a WebSocket at
+//! `/api/ws`. A message sent with `"edit": true` replaces the previous one
+//! within `channels.edit_grace_secs`, cancelling its run if still going.
+
+pub mod client;
+#[cfg(feature = "webhooks")]
+pub mod dashboard;
+#[cfg(feature = "mdns")]
+pub mod mdns;
+#[cfg(feature = "webhooks")]
+pub mod server;
+
+use crate::agent::{experiment, persona, AgentEvents, AgentExecutor};
+use crate::channels::framework::IncomingMessage;
+use crate::channels::{ChannelDelivery, EditTracker, Outbox};
+use crate::error::{Error, Result};
+use crate::llm::router::ModelRoute;
+use crate::scheduler::{CronJob, JobRunner, JobStatus, JobStore, Scheduler};
+use crate::session::{title, Session, SessionManager};
+use crate::telemetry::SeriesStore;
+use chrono::{DateTime, Duration, Utc};
+use crate::logging::LogBuffer;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use tokio::sync::{Mutex, Notify};
+use tracing::warn;
+
+pub use client::RemoteClient;
+#[cfg(feature = "webhooks")]
+pub use server::{router, serve};
+
+/// Channel name recorded on sessions created from the dashboard
+pub const DASHBOARD_CHANNEL: &str = "dashboard";
+
+/// Gateway status reported by `/api/status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayStatus {
+    pub version: String,
+    pub uptime_secs: u64,
+    pub provider: String,
+    pub model: String,
+    pub sessions: usize,
+    pub sensors: usize,
+    /// Scheduled jobs with their next fire times
+    #[serde(default)]
+    pub jobs: Vec<JobStatus>,
+    /// Outgoing message delivery per channel
+    #[serde(default)]
+    pub delivery: Vec<ChannelDelivery>,
+    /// Started with `--safe-mode`: mutating tools off, background jobs paused
+    #[serde(default)]
+    pub safe_mode: bool,
+}
+
+/// A message from a recent session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentMessage {
+    pub session: String,
+    pub channel: String,
+    pub role: String,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest reading from a sensor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorSnapshot {
+    pub sensor: String,
+    pub value: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Log lines returned by `/api/logs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogTail {
+    pub lines: Vec<String>,
+    /// Sequence number to pass as `after` to fetch only newer lines
+    pub next: u64,
+}
+
+/// Outcome of asking the gateway to run a job
+#[derive(Debug)]
+pub enum JobStart {
+    /// The run was started in the background
+    Started(CronJob),
+    NotFound,
+    Disabled,
+    /// The gateway was started without a scheduler
+    NoScheduler,
+}
+
+/// State shared by the gateway's HTTP handlers
+pub struct GatewayState {
+    executor: Arc<AgentExecutor>,
+    sessions: Mutex<SessionManager>,
+    series: Arc<SeriesStore>,
+    auth_token: Option<String>,
+    /// Names accepted in the `Host` header besides IP addresses
+    allowed_hosts: Vec<String>,
+    /// Browser origins allowed to open the WebSocket
+    allowed_origins: Vec<String>,
+    started_at: Instant,
+    dashboard_session: Mutex<Option<String>>,
+    /// Lets a dashboard message be edited while or shortly after it runs
+    edits: EditTracker,
+    /// Id of the last dashboard message, which is the one an edit replaces
+    last_message_id: AtomicU64,
+    /// Workspace holding the sessions directory, used to find personas
+    workspace: PathBuf,
+    reload: Arc<Notify>,
+    logs: LogBuffer,
+    scheduler: Option<(Arc<Scheduler>, Arc<dyn JobRunner>)>,
+}
+
+impl GatewayState {
+    /// Create gateway state
+    pub fn new(executor: Arc<AgentExecutor>, sessions: SessionManager, series: Arc<SeriesStore>) -> Self {
+        let workspace = sessions
+            .sessions_dir()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        Self {
+            executor,
+            workspace,
+            sessions: Mutex::new(sessions),
+            series,
+            auth_token: None,
+            allowed_hosts: local_host_names(),
+            allowed_origins: Vec::new(),
+            started_at: Instant::now(),
+            dashboard_session: Mutex::new(None),
+            edits: EditTracker::default(),
+            last_message_id: AtomicU64::new(0),
+            reload: Arc::new(Notify::new()),
+            logs: crate::logging::buffer::global().clone(),
+            scheduler: None,
+        }
+    }
+
+    /// Allow jobs from `scheduler` to be started over HTTP, run by `runner`
+    pub fn with_scheduler(mut self, scheduler: Arc<Scheduler>, runner: Arc<dyn JobRunner>) -> Self {
+        self.scheduler = Some((scheduler, runner));
+        self
+    }
+
+    /// Notify `reload` when a config reload is requested
+    pub fn with_reload(mut self, reload: Arc<Notify>) -> Self {
+        self.reload = reload;
+        self
+    }
+
+    /// Serve log lines from `logs` instead of the global buffer
+    pub fn with_logs(mut self, logs: LogBuffer) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// Ask the gateway to reload its configuration
+    pub fn request_reload(&self) {
+        self.reload.notify_one();
+    }
+
+    /// Recent log lines, either the last `lines` or all lines after sequence `after`
+    pub fn log_tail(&self, lines: usize, after: Option<u64>) -> LogTail {
+        let entries = match after {
+            Some(seq) => self.logs.since(seq),
+            None => self.logs.tail(lines),
+        };
+        LogTail {
+            lines: entries.into_iter().map(|(_, line)| line).collect(),
+            next: self.logs.next_seq(),
+        }
+    }
+
+    /// Require `Authorization: Bearer <token>` on API requests
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    /// Also accept requests addressed to `hosts`
+    pub fn with_allowed_hosts(mut self, hosts: impl IntoIterator<Item = String>) -> Self {
+        self.allowed_hosts
+            .extend(hosts.into_iter().map(|h| h.to_lowercase()).filter(|h| !h.is_empty()));
+        self
+    }
+
+    /// Whether a request's `Host` header names this gateway
+    ///
+    /// IP addresses are always accepted: a rebound page is addressed by the
+    /// attacker's domain name, never by an address.
+    pub fn is_allowed_host(&self, host: Option<&str>) -> bool {
+        let Some(name) = host.map(host_name) else {
+            return false;
+        };
+        name.parse::<std::net::IpAddr>().is_ok() || self.allowed_hosts.contains(&name)
+    }
+
+    /// Accept edits of a dashboard message for `grace` after it was sent
+    pub fn with_edit_grace(mut self, grace: std::time::Duration) -> Self {
+        self.edits = EditTracker::new(grace);
+        self
+    }
+
+    /// Accept WebSocket connections from browser pages served from `origins`
+    pub fn with_allowed_origins(mut self, origins: impl IntoIterator<Item = String>) -> Self {
+        self.allowed_origins
+            .extend(origins.into_iter().map(|o| normalize_origin(&o)).filter(|o| !o.is_empty()));
+        self
+    }
+
+    /// Whether a request's `Origin`, if any, is one of the allowed origins
+    ///
+    /// Clients other than browsers send no `Origin` and are allowed. The
+    /// request's own `Host` is not trusted: under DNS rebinding it names the
+    /// attacker's domain just like the `Origin` does.
+    pub fn is_allowed_origin(&self, origin: Option<&str>) -> bool {
+        match origin {
+            Some(origin) => self.allowed_origins.contains(&normalize_origin(origin)),
+            None => true,
+        }
+    }
+
+    /// Whether API requests must present a token
+    pub fn requires_auth(&self) -> bool {
+        self.auth_token.is_some()
+    }
+
+    /// Whether a presented token grants access
+    pub fn is_authorized(&self, presented: Option<&str>) -> bool {
+        match &self.auth_token {
+            Some(token) => presented == Some(token.as_str()),
+            None => true,
+        }
+    }
+
+    /// Current gateway status
+    pub async fn status(&self) -> Result<GatewayStatus> {
+        let client = self.executor.router().client(ModelRoute::Chat);
+        Ok(GatewayStatus {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            provider: client.provider().to_string(),
+            model: client.model().to_string(),
+            sessions: self.sessions.lock().await.list_sessions().await?.len(),
+            sensors: self.series.sensors().await?.len(),
+            jobs: JobStore::new(self.workspace.join("cron"))
+                .load()
+                .await?
+                .iter()
+                .map(JobStatus::from_job)
+                .collect(),
+            delivery: Outbox::new(&self.workspace).stats().await?,
+            safe_mode: self.executor.tools().is_read_only(),
+        })
+    }
+
+    /// Start a run of job `id` in the background
+    pub async fn start_job(&self, id: &str) -> Result<JobStart> {
+        let Some((scheduler, runner)) = self.scheduler.clone() else {
+            return Ok(JobStart::NoScheduler);
+        };
+        let Some(job) = scheduler.store().get(id).await? else {
+            return Ok(JobStart::NotFound);
+        };
+        if !job.enabled {
+            return Ok(JobStart::Disabled);
+        }
+        let started = job.clone();
+        tokio::spawn(async move {
+            if let Err(e) = scheduler.trigger(runner.as_ref(), &job).await {
+                warn!("Failed to record triggered run of job {}: {}", job.id, e);
+            }
+        });
+        Ok(JobStart::Started(started))
+    }
+
+    /// Most recent messages across all sessions, oldest first
+    pub async fn recent_messages(&self, limit: usize) -> Result<Vec<RecentMessage>> {
+        let sessions = self.sessions.lock().await.list_sessions().await?;
+        let mut messages: Vec<RecentMessage> = sessions
+            .iter()
+            .flat_map(|session| {
+                session.messages.iter().map(|m| RecentMessage {
+                    session: session.display_name().to_string(),
+                    channel: session.metadata.channel.clone(),
+                    role: format!("{:?}", m.role).to_lowercase(),
+                    content: m.content.clone(),
+                    timestamp: m.timestamp.into(),
+                })
+            })
+            .collect();
+        messages.sort_by_key(|m| m.timestamp);
+        let skip = messages.len().saturating_sub(limit);
+        Ok(messages.split_off(skip))
+    }
+
+    /// Latest reading for each sensor seen in the last day
+    pub async fn sensor_readings(&self) -> Result<Vec<SensorSnapshot>> {
+        let now = Utc::now();
+        let mut snapshots = Vec::new();
+        for sensor in self.series.sensors().await? {
+            let readings = self.series.query(&sensor, now - Duration::days(1), now).await?;
+            if let Some(last) = readings.last() {
+                snapshots.push(SensorSnapshot {
+                    sensor,
+                    value: last.value,
+                    timestamp: last.timestamp,
+                });
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// Run a message from the dashboard through the agent and record it
+    ///
+    /// With `edit`, the text replaces the previous message: its run is
+    /// cancelled if still going, and otherwise its exchange is corrected.
+    pub async fn send_message(&self, text: &str, edit: bool) -> Result<String> {
+        self.send_message_streaming(text, edit, None).await
+    }
+
+    /// Like [`GatewayState::send_message`], sending the reply text and tool
+    /// progress to `events` while the agent runs
+    ///
+    /// Replies to chat commands such as `/persona` are not streamed; they
+    /// only come back as the result.
+    pub async fn send_message_streaming(
+        &self,
+        text: &str,
+        edit: bool,
+        events: Option<&AgentEvents>,
+    ) -> Result<String> {
+        let message_id = match edit {
+            true => self.last_message_id.load(Ordering::SeqCst),
+            false => self.last_message_id.fetch_add(1, Ordering::SeqCst) + 1,
+        };
+        let incoming = IncomingMessage {
+            channel_id: DASHBOARD_CHANNEL.to_string(),
+            user_id: DASHBOARD_CHANNEL.to_string(),
+            content: text.to_string(),
+            timestamp: SystemTime::now(),
+            message_id: Some(message_id.to_string()),
+            edited: edit,
+            chat_id: None,
+            thread_id: None,
+            reply_to: None,
+        };
+        let run = self
+            .edits
+            .begin(&incoming)
+            .ok_or_else(|| Error::channel("Only the last message can be edited, and only shortly after sending it"))?;
+        let superseded = || Error::channel("Superseded by an edited message");
+        let mut session = self.dashboard_session().await?;
+
+        if let Some(reply) = persona::handle_command(&self.workspace, &mut session, text) {
+            let reply = reply?;
+            self.sessions.lock().await.save_session(&session).await?;
+            return Ok(reply);
+        }
+        if let Some(reply) = experiment::handle_command(self.executor.experiments(), &session, text) {
+            return reply;
+        }
+
+        let mut context = session.clone();
+        run.prepare(&mut context);
+        let turn = run
+            .run(async {
+                match events {
+                    Some(events) => self.executor.execute_streaming(text, &context, events).await,
+                    None => self.executor.execute_in_session(text, &context).await,
+                }
+            })
+            .await
+            .ok_or_else(superseded)?
+            .map_err(|e| Error::llm_provider(e.to_string()))?;
+        let response = turn.response;
+
+        let sessions = self.sessions.lock().await;
+        // Reload so changes made while the agent ran (e.g. a persona switch) are kept
+        let mut session = sessions.load_session(&session.id).await.unwrap_or(session);
+        if let Some(degradation) = turn.context_degraded {
+            session.record_context_event(degradation);
+        }
+        self.executor.experiments().assign(&mut session);
+        if !run.record(&mut session, text, response.clone()) {
+            return Err(superseded());
+        }
+        sessions.save_session(&session).await?;
+        drop(sessions);
+
+        // Titled outside the lock, since it takes another model call
+        if title::needs_title(&session) {
+            let title = title::generate_title(self.executor.router(), &session).await?;
+            let sessions = self.sessions.lock().await;
+            let mut session = sessions.load_session(&session.id).await?;
+            if session.metadata.title.is_none() {
+                session.metadata.title = Some(title);
+                sessions.save_session(&session).await?;
+            }
+        }
+        Ok(response)
+    }
+
+    /// Load or create the persistent dashboard session
+    async fn dashboard_session(&self) -> Result<Session> {
+        let mut sessions = self.sessions.lock().await;
+        let mut session_id = self.dashboard_session.lock().await;
+        let mut session = match session_id.as_deref() {
+            Some(id) => sessions.load_session(id).await?,
+            None => {
+                let existing = sessions
+                    .list_sessions()
+                    .await?
+                    .into_iter()
+                    .find(|s| s.metadata.channel == DASHBOARD_CHANNEL);
+                match existing {
+                    Some(session) => session,
+                    None => sessions.create_session(DASHBOARD_CHANNEL).await?,
+                }
+            }
+        };
+        session.metadata.channel = DASHBOARD_CHANNEL.to_string();
+        *session_id = Some(session.id.clone());
+        Ok(session)
+    }
+}
+
+/// `localhost` and this machine's hostname, also under `.local` for mDNS
+fn local_host_names() -> Vec<String> {
+    let hostname = crate::runtime::lease::hostname().to_lowercase();
+    vec!["localhost".to_string(), format!("{}.local", hostname), hostname]
+}
+
+/// `origin` lowercased, without a trailing slash
+fn normalize_origin(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_lowercase()
+}
+
+/// Lowercased name from a `Host` header, without its port or IPv6 brackets
+fn host_name(host: &str) -> String {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.trim_end_matches('.').to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::context::MessageRole;
+    use crate::llm::LlmClient;
+    use crate::telemetry::Reading;
+    use crate::tools::ToolRegistry;
+
+    fn state(dir: &std::path::Path) -> GatewayState {
+        let executor = AgentExecutor::new(
+            LlmClient::new("openrouter", "test-model", "key", "http://localhost"),
+            ToolRegistry::new(),
+        );
+        GatewayState::new(
+            Arc::new(executor),
+            SessionManager::new(dir.join("sessions")),
+            Arc::new(SeriesStore::new(dir.join("telemetry"))),
+        )
+    }
+
+    #[test]
+    fn test_auth_token() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(state(dir.path()).is_authorized(None));
+
+        let state = state(dir.path()).with_auth_token(Some("secret".to_string()));
+        assert!(state.is_authorized(Some("secret")));
+        assert!(!state.is_authorized(Some("wrong")));
+        assert!(!state.is_authorized(None));
+    }
+
+    #[test]
+    fn test_rebound_host_names_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path()).with_allowed_hosts(["Pi.Example.org".to_string()]);
+        for host in ["127.0.0.1:18790", "192.168.1.20", "[::1]:18790", "localhost:18790", "pi.example.org:80"] {
+            assert!(state.is_allowed_host(Some(host)), "{} refused", host);
+        }
+        for host in ["evil.example:18790", "localhost.evil.example", "127.0.0.1.nip.io"] {
+            assert!(!state.is_allowed_host(Some(host)), "{} accepted", host);
+        }
+        assert!(!state.is_allowed_host(None));
+    }
+
+    #[test]
+    fn test_websocket_origin_allowlist() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path())
+            .with_allowed_origins(["http://127.0.0.1:18790".to_string(), "http://Pi.local:18790/".to_string()]);
+        assert!(state.is_allowed_origin(None));
+        assert!(state.is_allowed_origin(Some("http://127.0.0.1:18790")));
+        assert!(state.is_allowed_origin(Some("http://pi.local:18790")));
+        // A rebound page has matching Origin and Host, both the attacker's
+        assert!(!state.is_allowed_origin(Some("http://evil.example:18790")));
+        assert!(!state.is_allowed_origin(Some("http://127.0.0.1:9999")));
+        assert!(!state.is_allowed_origin(Some("null")));
+    }
+
+    #[test]
+    fn test_log_tail_follows_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = LogBuffer::new(10);
+        let state = state(dir.path()).with_logs(logs.clone());
+        logs.push("one");
+        logs.push("two");
+
+        let tail = state.log_tail(1, None);
+        assert_eq!(tail.lines, vec!["two"]);
+        logs.push("three");
+        assert_eq!(state.log_tail(10, Some(tail.next)).lines, vec!["three"]);
+    }
+
+    #[tokio::test]
+    async fn test_status_and_readings() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path());
+        let now = Utc::now();
+        for value in [1.0, 2.0] {
+            state
+                .series
+                .append("load", Reading { timestamp: now, value })
+                .await
+                .unwrap();
+        }
+
+        let status = state.status().await.unwrap();
+        assert_eq!(status.model, "test-model");
+        assert_eq!(status.sensors, 1);
+        assert!(status.jobs.is_empty());
+        assert!(status.delivery.is_empty());
+
+        let readings = state.sensor_readings().await.unwrap();
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[0].value, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_start_job() {
+        struct Runner;
+
+        #[async_trait::async_trait]
+        impl JobRunner for Runner {
+            async fn run_job(&self, _job: &CronJob) -> Result<String> {
+                Ok("done".to_string())
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            state(dir.path()).start_job("nope").await.unwrap(),
+            JobStart::NoScheduler
+        ));
+
+        let store = JobStore::new(dir.path().join("cron"));
+        let job = CronJob::new("0 9 * * *", "nightly digest").unwrap();
+        let mut disabled = CronJob::new("0 9 * * *", "paused").unwrap();
+        disabled.enabled = false;
+        store.add(job.clone()).await.unwrap();
+        store.add(disabled.clone()).await.unwrap();
+        let scheduler = Arc::new(Scheduler::new(store));
+        let state = state(dir.path()).with_scheduler(scheduler.clone(), Arc::new(Runner));
+
+        assert!(matches!(state.start_job("nope").await.unwrap(), JobStart::NotFound));
+        assert!(matches!(state.start_job(&disabled.id).await.unwrap(), JobStart::Disabled));
+        assert!(matches!(state.start_job(&job.id).await.unwrap(), JobStart::Started(_)));
+        for _ in 0..50 {
+            if scheduler.store().get(&job.id).await.unwrap().unwrap().last_success == Some(true) {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("triggered run was not recorded");
+    }
+
+    #[tokio::test]
+    async fn test_recent_messages_are_limited() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path());
+        {
+            let mut sessions = state.sessions.lock().await;
+            let mut session = sessions.create_session("cli").await.unwrap();
+            for i in 0..5 {
+                session.add_message(MessageRole::User, format!("message {}", i));
+            }
+            sessions.save_session(&session).await.unwrap();
+        }
+
+        let messages = state.recent_messages(3).await.unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2].content, "message 4");
+        assert_eq!(messages[0].role, "user");
+    }
+
+    #[tokio::test]
+    async fn test_persona_command_from_dashboard() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("personas")).unwrap();
+        std::fs::write(dir.path().join("personas/butler.md"), "Be formal.").unwrap();
+        let state = state(dir.path());
+
+        let reply = state.send_message("/persona butler", false).await.unwrap();
+        assert_eq!(reply, "Switched to persona: butler");
+
+        let session = state.dashboard_session().await.unwrap();
+        assert_eq!(session.metadata.system_prompt.as_deref(), Some("Be formal."));
+        assert!(session.messages.is_empty());
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_dashboard_session_gets_title() {
+        let model = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(|| async {
+                axum::Json(serde_json::json!({"choices": [{"message": {"content": "Garden watering plan"}}]}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, model).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let executor = AgentExecutor::new(LlmClient::new("openai", "test-model", "key", &api_base), ToolRegistry::new());
+        let state = GatewayState::new(
+            Arc::new(executor),
+            SessionManager::new(dir.path().join("sessions")),
+            Arc::new(SeriesStore::new(dir.path().join("telemetry"))),
+        );
+        state.send_message("when should I water the tomatoes?", false).await.unwrap();
+        assert_eq!(state.dashboard_session().await.unwrap().metadata.title, None);
+        state.send_message("and the basil?", false).await.unwrap();
+        let session = state.dashboard_session().await.unwrap();
+        assert_eq!(session.metadata.title.as_deref(), Some("Garden watering plan"));
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_edit_replaces_dashboard_message() {
+        use std::sync::atomic::AtomicUsize;
+
+        // Answers with the last user message and how many the model saw; the
+        // first request is slow so an edit can arrive while it runs
+        let requests = Arc::new(AtomicUsize::new(0));
+        let model = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let round = requests.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if round == 0 {
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    }
+                    let users: Vec<&str> = body["messages"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter(|m| m["role"] == "user")
+                        .filter_map(|m| m["content"].as_str())
+                        .collect();
+                    let reply = format!("{} ({})", users.last().unwrap(), users.len());
+                    axum::Json(serde_json::json!({"choices": [{"message": {"content": reply}}]}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, model).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let executor = AgentExecutor::new(LlmClient::new("openai", "test-model", "key", &api_base), ToolRegistry::new());
+        let state = Arc::new(GatewayState::new(
+            Arc::new(executor),
+            SessionManager::new(dir.path().join("sessions")),
+            Arc::new(SeriesStore::new(dir.path().join("telemetry"))),
+        ));
+        assert!(state.send_message("nothing to edit", true).await.is_err());
+
+        let original = tokio::spawn({
+            let state = state.clone();
+            async move { state.send_message("whats the wether", false).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let reply = state.send_message("weather in Oslo", true).await.unwrap();
+        assert_eq!(reply, "weather in Oslo (1)");
+        assert!(original.await.unwrap().is_err());
+
+        // Editing again after the reply corrects the recorded exchange
+        let reply = state.send_message("weather in Bergen", true).await.unwrap();
+        assert_eq!(reply, "weather in Bergen (1)");
+        let session = state.dashboard_session().await.unwrap();
+        let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["weather in Bergen", "weather in Bergen (1)"]);
+    }
+}
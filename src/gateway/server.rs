@@ -0,0 +1,305 @@
+//! HTTP routes for the gateway API and dashboard
+
+use super::dashboard::DASHBOARD_HTML;
+use super::{GatewayState, JobStart};
+use crate::agent::{AgentEvent, AgentEvents};
+use crate::error::{Error, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::Stream;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Messages returned by `/api/messages` when no limit is given
+const DEFAULT_MESSAGE_LIMIT: usize = 50;
+
+/// Log lines returned by `/api/logs` when no count is given
+const DEFAULT_LOG_LINES: usize = 100;
+
+/// Error returned from an API handler
+struct ApiError(StatusCode, String);
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+#[derive(Deserialize)]
+struct MessagesQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    lines: Option<usize>,
+    after: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct SendMessage {
+    text: String,
+    /// Replace the previous message instead of sending a new one
+    #[serde(default)]
+    edit: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Build the gateway router
+///
+/// `dashboard` controls whether the HTML page is served at `/`.
+pub fn router(state: Arc<GatewayState>, dashboard: bool) -> Router {
+    let api = Router::new()
+        .route("/status", get(status))
+        .route("/messages", get(messages).post(send_message))
+        .route("/messages/stream", post(stream_message))
+        .route("/sensors", get(sensors))
+        .route("/logs", get(logs))
+        .route("/reload", post(reload))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+    let v1 = Router::new()
+        .route("/jobs/:id/run", post(run_job))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+
+    // Browsers cannot set headers on a WebSocket, so it checks the token itself
+    let api = api.route("/ws", get(websocket));
+
+    let mut router = Router::new().nest("/api", api).nest("/v1", v1);
+    if dashboard {
+        router = router.route("/", get(|| async { Html(DASHBOARD_HTML) }));
+    }
+    router
+        .layer(middleware::from_fn_with_state(state.clone(), require_known_host))
+        .with_state(state)
+}
+
+/// Serve the gateway API on `addr` until `shutdown` resolves
+pub async fn serve(
+    addr: SocketAddr,
+    router: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Gateway HTTP listening on {}", addr);
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
+        .await
+        .map_err(|e| Error::http(format!("Gateway HTTP server failed: {}", e)))
+}
+
+/// Refuse requests addressed to a name other than this gateway's, which is
+/// how a DNS-rebound web page would reach it
+async fn require_known_host(
+    State(state): State<Arc<GatewayState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let host = request.headers().get(header::HOST).and_then(|v| v.to_str().ok());
+    if !state.is_allowed_host(host) {
+        warn!("Rejected request for unknown host {:?}; add it to gateway.allowed_hosts if it is this gateway", host);
+        return ApiError(StatusCode::MISDIRECTED_REQUEST, "Unknown host".to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+async fn require_token(
+    State(state): State<Arc<GatewayState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.is_authorized(bearer_token(request.headers())) {
+        warn!("Rejected unauthorized request to {}", request.uri());
+        return ApiError(StatusCode::UNAUTHORIZED, "Unauthorized".to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+async fn status(State(state): State<Arc<GatewayState>>) -> ApiResult<super::GatewayStatus> {
+    Ok(Json(state.status().await?))
+}
+
+async fn messages(
+    State(state): State<Arc<GatewayState>>,
+    Query(query): Query<MessagesQuery>,
+) -> ApiResult<Vec<super::RecentMessage>> {
+    let limit = query.limit.unwrap_or(DEFAULT_MESSAGE_LIMIT);
+    Ok(Json(state.recent_messages(limit).await?))
+}
+
+async fn sensors(State(state): State<Arc<GatewayState>>) -> ApiResult<Vec<super::SensorSnapshot>> {
+    Ok(Json(state.sensor_readings().await?))
+}
+
+async fn send_message(
+    State(state): State<Arc<GatewayState>>,
+    Json(body): Json<SendMessage>,
+) -> ApiResult<serde_json::Value> {
+    if body.text.trim().is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "Message text is empty".to_string()));
+    }
+    let response = state.send_message(&body.text, body.edit).await?;
+    Ok(Json(serde_json::json!({ "response": response })))
+}
+
+/// Send a message and stream the turn back as server-sent events
+///
+/// Each event is named after its `type` (`delta`, `tool_start`, ...) and
+/// carries the [`AgentEvent`] as JSON; the stream ends after `done` or `error`.
+async fn stream_message(
+    State(state): State<Arc<GatewayState>>,
+    Json(body): Json<SendMessage>,
+) -> std::result::Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>, ApiError> {
+    if body.text.trim().is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "Message text is empty".to_string()));
+    }
+    let (events, rx) = mpsc::unbounded_channel();
+    tokio::spawn(run_streamed(state, body, events));
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        let data = serde_json::to_string(&event).unwrap_or_default();
+        Some((Ok(Event::default().event(event.kind()).data(data)), rx))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Chat over a WebSocket: each `{"text": ...}` frame from the client runs a
+/// turn, answered by its [`AgentEvent`]s as JSON frames
+///
+/// The token may be given as `?token=` since browsers cannot set headers here.
+/// WebSockets are not covered by CORS, so browser connections from pages
+/// other than the allowed origins are refused: otherwise any site the user
+/// visits could run agent turns on a gateway without a token.
+async fn websocket(
+    State(state): State<Arc<GatewayState>>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let origin = headers.get(header::ORIGIN).map(|v| v.to_str().unwrap_or_default());
+    if !state.is_allowed_origin(origin) {
+        warn!("Rejected cross-origin WebSocket connection");
+        return ApiError(StatusCode::FORBIDDEN, "Cross-origin WebSocket connections are not allowed".to_string())
+            .into_response();
+    }
+    if !state.is_authorized(bearer_token(&headers).or(query.token.as_deref())) {
+        warn!("Rejected unauthorized WebSocket connection");
+        return ApiError(StatusCode::UNAUTHORIZED, "Unauthorized".to_string()).into_response();
+    }
+    upgrade.on_upgrade(move |socket| serve_websocket(state, socket))
+}
+
+async fn serve_websocket(state: Arc<GatewayState>, mut socket: WebSocket) {
+    debug!("WebSocket client connected");
+    while let Some(Ok(message)) = socket.recv().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let (events, mut rx) = mpsc::unbounded_channel();
+        let rejected = match serde_json::from_str::<SendMessage>(&text) {
+            Ok(body) if !body.text.trim().is_empty() => {
+                tokio::spawn(run_streamed(state.clone(), body, events));
+                None
+            }
+            Ok(_) => Some((events, "Message text is empty".to_string())),
+            Err(e) => Some((events, format!("Expected {{\"text\": ...}}: {}", e))),
+        };
+        if let Some((events, message)) = rejected {
+            let _ = events.send(AgentEvent::Error { message });
+        }
+        // The channel closes once the turn has sent its last event
+        while let Some(event) = rx.recv().await {
+            let frame = serde_json::to_string(&event).unwrap_or_default();
+            if socket.send(Message::Text(frame)).await.is_err() {
+                debug!("WebSocket client went away mid-turn");
+                return;
+            }
+        }
+    }
+    debug!("WebSocket client disconnected");
+}
+
+/// Run a dashboard message, finishing the event stream with `done` or
+/// `error`. The turn is recorded even if the client has gone away.
+async fn run_streamed(state: Arc<GatewayState>, body: SendMessage, events: AgentEvents) {
+    let last = match state.send_message_streaming(&body.text, body.edit, Some(&events)).await {
+        Ok(response) => AgentEvent::Done { response },
+        Err(e) => AgentEvent::Error { message: e.to_string() },
+    };
+    let _ = events.send(last);
+}
+
+async fn logs(
+    State(state): State<Arc<GatewayState>>,
+    Query(query): Query<LogsQuery>,
+) -> ApiResult<super::LogTail> {
+    let lines = query.lines.unwrap_or(DEFAULT_LOG_LINES);
+    Ok(Json(state.log_tail(lines, query.after)))
+}
+
+async fn reload(State(state): State<Arc<GatewayState>>) -> ApiResult<serde_json::Value> {
+    info!("Config reload requested over HTTP");
+    state.request_reload();
+    Ok(Json(serde_json::json!({ "reloading": true })))
+}
+
+/// Start a scheduled job from an external system (CI, Home Assistant)
+///
+/// Refused unless the gateway has an auth token, since it runs the agent.
+async fn run_job(
+    State(state): State<Arc<GatewayState>>,
+    Path(id): Path<String>,
+) -> std::result::Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    if !state.requires_auth() {
+        return Err(ApiError(
+            StatusCode::FORBIDDEN,
+            "Set gateway.auth_token to trigger jobs over HTTP".to_string(),
+        ));
+    }
+    match state.start_job(&id).await? {
+        JobStart::Started(job) => {
+            info!("Job {} triggered over HTTP", job.id);
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "job": job.id, "started": true })),
+            ))
+        }
+        JobStart::NotFound => Err(ApiError(StatusCode::NOT_FOUND, format!("Job not found: {}", id))),
+        JobStart::Disabled => Err(ApiError(StatusCode::CONFLICT, format!("Job {} is disabled", id))),
+        JobStart::NoScheduler => Err(ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The scheduler is not running".to_string(),
+        )),
+    }
+}
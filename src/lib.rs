@@ -9,19 +9,33 @@
 //! - LLM provider integrations
 //! - Tool framework for extensibility
 //! - Session and state management
+//! - Long-term memory with automatic recall
+//! - Scheduled jobs with missed-run catch-up
 //! - Device management for hardware interfaces
+//! - Pluggable speech recognition and synthesis
+//! - Gateway HTTP API and dashboard
+//! - Sensor telemetry collection and time-series logging
+//! - Workspace integrity checks and maintenance
+//! - Build contents and memory footprint report
 
+pub mod about;
 pub mod agent;
 pub mod auth;
 pub mod channels;
 pub mod config;
 pub mod device;
 pub mod error;
+pub mod gateway;
 pub mod llm;
 pub mod logging;
+pub mod memory;
 pub mod runtime;
+pub mod scheduler;
 pub mod session;
+pub mod speech;
+pub mod telemetry;
 pub mod tools;
+pub mod workspace;
 
 pub use error::{Error, Result};
 
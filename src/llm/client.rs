@@ -1,20 +1,45 @@
 //! Simple LLM client for making requests to various providers
 
 use serde_json::json;
+use crate::agent::context::{Message, MessageRole};
+use crate::config::{OpenRouterAttribution, OpenRouterRouting};
 use crate::error::{Error, Result};
 use crate::tools::ToolCall;
+use super::framework::{GenerationOptions, TokenUsage};
+use super::stream::StreamedReply;
+use super::tokenizer::{count_message_tokens, count_tokens};
+use futures::StreamExt;
 use std::collections::HashMap;
+use tracing::debug;
+
+/// Providers the client can talk to
+pub const PROVIDERS: &[&str] = &["openrouter", "openai", "anthropic"];
+
+/// User-Agent sent when none is configured
+pub const DEFAULT_USER_AGENT: &str = concat!("takobull/", env!("CARGO_PKG_VERSION"));
+
+/// Temperature sent to OpenAI-compatible providers when none is configured
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+/// Output token limit when none is configured (Anthropic requires one)
+const DEFAULT_MAX_TOKENS: usize = 2048;
 
 pub struct LlmResponse {
     pub content: String,
     pub tool_calls: Vec<ToolCall>,
+    pub usage: TokenUsage,
 }
 
+#[derive(Clone)]
 pub struct LlmClient {
     provider: String,
     model: String,
     api_key: String,
     api_base: String,
+    routing: Option<OpenRouterRouting>,
+    attribution: Option<OpenRouterAttribution>,
+    user_agent: String,
+    options: GenerationOptions,
 }
 
 impl LlmClient {
@@ -24,6 +49,92 @@ impl LlmClient {
             model: model.to_string(),
             api_key: api_key.to_string(),
             api_base: api_base.to_string(),
+            routing: None,
+            attribution: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            options: GenerationOptions::default(),
+        }
+    }
+
+    /// Provider name
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Model name
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Set OpenRouter routing preferences (ignored by other providers)
+    pub fn with_routing(mut self, routing: OpenRouterRouting) -> Self {
+        self.routing = Some(routing);
+        self
+    }
+
+    /// Send OpenRouter's `HTTP-Referer`/`X-Title` attribution headers (ignored by other providers)
+    pub fn with_attribution(mut self, attribution: OpenRouterAttribution) -> Self {
+        self.attribution = Some(attribution);
+        self
+    }
+
+    /// Set the User-Agent sent with every request
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Set default sampling options for every request
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Default sampling options
+    pub fn options(&self) -> &GenerationOptions {
+        &self.options
+    }
+
+    /// POST request to `url` carrying the User-Agent and, for OpenRouter, attribution headers
+    fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        let mut request = reqwest::Client::new()
+            .post(url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent);
+        if let (Some(attribution), "openrouter") = (&self.attribution, self.provider.as_str()) {
+            if let Some(referer) = &attribution.referer {
+                request = request.header("HTTP-Referer", referer);
+            }
+            if let Some(title) = &attribution.title {
+                request = request.header("X-Title", title);
+            }
+        }
+        request
+    }
+
+    /// Add OpenRouter provider preferences, transforms, and model fallbacks to a payload
+    fn apply_routing(&self, payload: &mut serde_json::Value) {
+        let routing = match &self.routing {
+            Some(r) => r,
+            None => return,
+        };
+
+        let mut provider = serde_json::Map::new();
+        if !routing.order.is_empty() {
+            provider.insert("order".to_string(), json!(routing.order));
+        }
+        if let Some(allow_fallbacks) = routing.allow_fallbacks {
+            provider.insert("allow_fallbacks".to_string(), json!(allow_fallbacks));
+        }
+        if !provider.is_empty() {
+            payload["provider"] = serde_json::Value::Object(provider);
+        }
+        if !routing.transforms.is_empty() {
+            payload["transforms"] = json!(routing.transforms);
+        }
+        if !routing.fallback_models.is_empty() {
+            let mut models = vec![self.model.clone()];
+            models.extend(routing.fallback_models.iter().cloned());
+            payload["models"] = json!(models);
         }
     }
 
@@ -39,15 +150,79 @@ impl LlmClient {
         }
     }
 
+    /// Embed `text` with the client's model via the OpenAI-compatible `/embeddings` endpoint
+    pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        if self.provider == "anthropic" {
+            return Err(Error::llm_provider("Anthropic does not provide an embeddings API"));
+        }
+        let url = format!("{}/embeddings", self.api_base);
+        let payload = json!({
+            "model": self.model,
+            "input": text,
+        });
+
+        let response = self
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| Error::http(format!("Request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        let data: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| Error::serialization(format!("Failed to parse response: {}", e)))?;
+
+        data["data"][0]["embedding"]
+            .as_array()
+            .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+            .ok_or_else(|| Error::llm_provider("No embedding in response".to_string()))
+    }
+
     pub async fn chat_with_tools(
         &self,
         message: &str,
         tools: Vec<serde_json::Value>,
     ) -> Result<LlmResponse> {
+        self.chat_with_system(None, message, tools).await
+    }
+
+    /// Chat with tools, prefixing the conversation with an optional system prompt
+    pub async fn chat_with_system(
+        &self,
+        system: Option<&str>,
+        message: &str,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<LlmResponse> {
+        self.chat_with_options(system, &[], message, tools, &GenerationOptions::default())
+            .await
+    }
+
+    /// Chat with tools, with `overrides` taking precedence over the client's options
+    ///
+    /// `history` holds earlier turns of the conversation, oldest first;
+    /// system messages in it are skipped.
+    pub async fn chat_with_options(
+        &self,
+        system: Option<&str>,
+        history: &[Message],
+        message: &str,
+        tools: Vec<serde_json::Value>,
+        overrides: &GenerationOptions,
+    ) -> Result<LlmResponse> {
+        let options = self.options.merged(overrides);
         match self.provider.as_str() {
-            "openrouter" => self.chat_openrouter_with_tools(message, tools).await,
-            "openai" => self.chat_openai_with_tools(message, tools).await,
-            "anthropic" => self.chat_anthropic_with_tools(message, tools).await,
+            "openrouter" => self.chat_openrouter_with_tools(system, history, message, tools, &options).await,
+            "openai" => self.chat_openai_with_tools(system, history, message, tools, &options).await,
+            "anthropic" => self.chat_anthropic_with_tools(system, history, message, tools, &options).await,
             _ => Err(Error::llm_provider(format!(
                 "Unsupported provider: {}",
                 self.provider
@@ -55,11 +230,93 @@ impl LlmClient {
         }
     }
 
+    /// Chat with tools like [`LlmClient::chat_with_options`], passing each
+    /// piece of the reply text to `on_text` as it arrives
+    ///
+    /// OpenAI-compatible providers stream the response. Anthropic is asked
+    /// for the whole response, whose text is passed on in one piece.
+    pub async fn chat_streaming(
+        &self,
+        system: Option<&str>,
+        history: &[Message],
+        message: &str,
+        tools: Vec<serde_json::Value>,
+        overrides: &GenerationOptions,
+        mut on_text: impl FnMut(&str) + Send,
+    ) -> Result<LlmResponse> {
+        match self.provider.as_str() {
+            "openrouter" | "openai" => {
+                let options = self.options.merged(overrides);
+                self.chat_openai_streaming(system, history, message, tools, &options, &mut on_text).await
+            }
+            _ => {
+                let response = self.chat_with_options(system, history, message, tools, overrides).await?;
+                if !response.content.is_empty() {
+                    on_text(&response.content);
+                }
+                Ok(response)
+            }
+        }
+    }
+
+    /// Content, tool calls, and usage from an OpenAI-style chat completion
+    fn parse_openai_response(&self, data: &serde_json::Value, prompt: &str) -> LlmResponse {
+        let content = data["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        let mut tool_calls = Vec::new();
+        if let Some(calls) = data["choices"][0]["message"]["tool_calls"].as_array() {
+            for call in calls {
+                if let (Some(id), Some(name), Some(args)) = (
+                    call["id"].as_str(),
+                    call["function"]["name"].as_str(),
+                    call["function"]["arguments"].as_str(),
+                ) {
+                    let arguments: HashMap<String, serde_json::Value> =
+                        serde_json::from_str(args).unwrap_or_default();
+                    tool_calls.push(ToolCall {
+                        id: id.to_string(),
+                        name: name.to_string(),
+                        arguments,
+                    });
+                }
+            }
+        }
+
+        let usage = self.parse_usage(data, prompt, &content);
+        LlmResponse {
+            content,
+            tool_calls,
+            usage,
+        }
+    }
+
+    /// Read token usage from a provider response, estimating any missing counts
+    fn parse_usage(&self, data: &serde_json::Value, prompt: &str, content: &str) -> TokenUsage {
+        let usage = &data["usage"];
+        let input_tokens = usage["prompt_tokens"]
+            .as_u64()
+            .or_else(|| usage["input_tokens"].as_u64())
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or_else(|| count_message_tokens(&self.model, [prompt]));
+        let output_tokens = usage["completion_tokens"]
+            .as_u64()
+            .or_else(|| usage["output_tokens"].as_u64())
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or_else(|| count_tokens(&self.model, content));
+
+        TokenUsage {
+            input_tokens,
+            output_tokens,
+        }
+    }
+
     async fn chat_openrouter(&self, message: &str) -> Result<String> {
-        let client = reqwest::Client::new();
         let url = format!("{}/chat/completions", self.api_base);
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
             "messages": [
                 {
@@ -67,11 +324,11 @@ impl LlmClient {
                     "content": message
                 }
             ],
-            "temperature": 0.7,
-            "max_tokens": 2048,
         });
+        apply_openai_options(&mut payload, &self.options);
+        self.apply_routing(&mut payload);
 
-        let response = client
+        let response = self
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
@@ -83,10 +340,7 @@ impl LlmClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(Error::llm_provider(format!(
-                "API error {}: {}",
-                status, text
-            )));
+            return Err(api_error(status, &text));
         }
 
         let data: serde_json::Value = response
@@ -102,27 +356,24 @@ impl LlmClient {
 
     async fn chat_openrouter_with_tools(
         &self,
+        system: Option<&str>,
+        history: &[Message],
         message: &str,
         tools: Vec<serde_json::Value>,
+        options: &GenerationOptions,
     ) -> Result<LlmResponse> {
-        let client = reqwest::Client::new();
         let url = format!("{}/chat/completions", self.api_base);
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": message
-                }
-            ],
+            "messages": chat_messages(system, history, message),
             "tools": tools,
             "tool_choice": "auto",
-            "temperature": 0.7,
-            "max_tokens": 2048,
         });
+        apply_openai_options(&mut payload, options);
+        self.apply_routing(&mut payload);
 
-        let response = client
+        let response = self
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
@@ -134,10 +385,7 @@ impl LlmClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(Error::llm_provider(format!(
-                "API error {}: {}",
-                status, text
-            )));
+            return Err(api_error(status, &text));
         }
 
         let data: serde_json::Value = response
@@ -145,41 +393,14 @@ impl LlmClient {
             .await
             .map_err(|e| Error::serialization(format!("Failed to parse response: {}", e)))?;
 
-        let content = data["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
-
-        let mut tool_calls = Vec::new();
-        if let Some(calls) = data["choices"][0]["message"]["tool_calls"].as_array() {
-            for call in calls {
-                if let (Some(id), Some(name), Some(args)) = (
-                    call["id"].as_str(),
-                    call["function"]["name"].as_str(),
-                    call["function"]["arguments"].as_str(),
-                ) {
-                    let arguments: HashMap<String, serde_json::Value> =
-                        serde_json::from_str(args).unwrap_or_default();
-                    tool_calls.push(ToolCall {
-                        id: id.to_string(),
-                        name: name.to_string(),
-                        arguments,
-                    });
-                }
-            }
-        }
-
-        Ok(LlmResponse {
-            content,
-            tool_calls,
-        })
+        let prompt = format!("{}{}", system.unwrap_or(""), message);
+        Ok(self.parse_openai_response(&data, &prompt))
     }
 
     async fn chat_openai(&self, message: &str) -> Result<String> {
-        let client = reqwest::Client::new();
         let url = format!("{}/chat/completions", self.api_base);
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
             "messages": [
                 {
@@ -187,11 +408,10 @@ impl LlmClient {
                     "content": message
                 }
             ],
-            "temperature": 0.7,
-            "max_tokens": 2048,
         });
+        apply_openai_options(&mut payload, &self.options);
 
-        let response = client
+        let response = self
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
@@ -203,10 +423,7 @@ impl LlmClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(Error::llm_provider(format!(
-                "API error {}: {}",
-                status, text
-            )));
+            return Err(api_error(status, &text));
         }
 
         let data: serde_json::Value = response
@@ -222,27 +439,23 @@ impl LlmClient {
 
     async fn chat_openai_with_tools(
         &self,
+        system: Option<&str>,
+        history: &[Message],
         message: &str,
         tools: Vec<serde_json::Value>,
+        options: &GenerationOptions,
     ) -> Result<LlmResponse> {
-        let client = reqwest::Client::new();
         let url = format!("{}/chat/completions", self.api_base);
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": message
-                }
-            ],
+            "messages": chat_messages(system, history, message),
             "tools": tools,
             "tool_choice": "auto",
-            "temperature": 0.7,
-            "max_tokens": 2048,
         });
+        apply_openai_options(&mut payload, options);
 
-        let response = client
+        let response = self
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
@@ -254,10 +467,7 @@ impl LlmClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(Error::llm_provider(format!(
-                "API error {}: {}",
-                status, text
-            )));
+            return Err(api_error(status, &text));
         }
 
         let data: serde_json::Value = response
@@ -265,43 +475,105 @@ impl LlmClient {
             .await
             .map_err(|e| Error::serialization(format!("Failed to parse response: {}", e)))?;
 
-        let content = data["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
+        let prompt = format!("{}{}", system.unwrap_or(""), message);
+        Ok(self.parse_openai_response(&data, &prompt))
+    }
 
-        let mut tool_calls = Vec::new();
-        if let Some(calls) = data["choices"][0]["message"]["tool_calls"].as_array() {
-            for call in calls {
-                if let (Some(id), Some(name), Some(args)) = (
-                    call["id"].as_str(),
-                    call["function"]["name"].as_str(),
-                    call["function"]["arguments"].as_str(),
-                ) {
-                    let arguments: HashMap<String, serde_json::Value> =
-                        serde_json::from_str(args).unwrap_or_default();
-                    tool_calls.push(ToolCall {
-                        id: id.to_string(),
-                        name: name.to_string(),
-                        arguments,
-                    });
+    /// Streamed chat completion for OpenAI and OpenRouter
+    async fn chat_openai_streaming(
+        &self,
+        system: Option<&str>,
+        history: &[Message],
+        message: &str,
+        tools: Vec<serde_json::Value>,
+        options: &GenerationOptions,
+        on_text: &mut (impl FnMut(&str) + Send),
+    ) -> Result<LlmResponse> {
+        let url = format!("{}/chat/completions", self.api_base);
+
+        let mut payload = json!({
+            "model": self.model,
+            "messages": chat_messages(system, history, message),
+            "tools": tools,
+            "tool_choice": "auto",
+            "stream": true,
+            "stream_options": { "include_usage": true },
+        });
+        apply_openai_options(&mut payload, options);
+        if self.provider == "openrouter" {
+            self.apply_routing(&mut payload);
+        }
+
+        let response = self
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| Error::http(format!("Request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        let prompt = format!("{}{}", system.unwrap_or(""), message);
+        let is_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if !is_stream {
+            // Some compatible servers ignore `stream` and answer in one piece
+            let data: serde_json::Value = response
+                .json()
+                .await
+                .map_err(|e| Error::serialization(format!("Failed to parse response: {}", e)))?;
+            let response = self.parse_openai_response(&data, &prompt);
+            if !response.content.is_empty() {
+                on_text(&response.content);
+            }
+            return Ok(response);
+        }
+
+        let mut reply = StreamedReply::default();
+        let mut pending: Vec<u8> = Vec::new();
+        let mut body = response.bytes_stream();
+        while let Some(bytes) = body.next().await {
+            let bytes = bytes.map_err(|e| Error::http(format!("Stream interrupted: {}", e)))?;
+            pending.extend_from_slice(&bytes);
+            // Split on whole lines only, so multi-byte characters are never cut
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                if let Some(text) = reply.push_line(String::from_utf8_lossy(&line).trim())? {
+                    on_text(&text);
                 }
             }
+            if reply.is_done() {
+                break;
+            }
+        }
+        if let Some(text) = reply.push_line(String::from_utf8_lossy(&pending).trim())? {
+            on_text(&text);
         }
 
+        let (content, tool_calls, usage) = reply.finish();
+        let usage = self.parse_usage(&json!({ "usage": usage }), &prompt, &content);
+
         Ok(LlmResponse {
             content,
             tool_calls,
+            usage,
         })
     }
 
     async fn chat_anthropic(&self, message: &str) -> Result<String> {
-        let client = reqwest::Client::new();
         let url = format!("{}/messages", self.api_base);
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
-            "max_tokens": 2048,
             "messages": [
                 {
                     "role": "user",
@@ -309,8 +581,9 @@ impl LlmClient {
                 }
             ],
         });
+        apply_anthropic_options(&mut payload, &self.options);
 
-        let response = client
+        let response = self
             .post(&url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
@@ -323,10 +596,7 @@ impl LlmClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(Error::llm_provider(format!(
-                "API error {}: {}",
-                status, text
-            )));
+            return Err(api_error(status, &text));
         }
 
         let data: serde_json::Value = response
@@ -342,25 +612,25 @@ impl LlmClient {
 
     async fn chat_anthropic_with_tools(
         &self,
+        system: Option<&str>,
+        history: &[Message],
         message: &str,
         tools: Vec<serde_json::Value>,
+        options: &GenerationOptions,
     ) -> Result<LlmResponse> {
-        let client = reqwest::Client::new();
         let url = format!("{}/messages", self.api_base);
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
-            "max_tokens": 2048,
             "tools": tools,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": message
-                }
-            ],
+            "messages": chat_messages(None, history, message),
         });
+        if let Some(system) = system {
+            payload["system"] = json!(system);
+        }
+        apply_anthropic_options(&mut payload, options);
 
-        let response = client
+        let response = self
             .post(&url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
@@ -373,10 +643,7 @@ impl LlmClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(Error::llm_provider(format!(
-                "API error {}: {}",
-                status, text
-            )));
+            return Err(api_error(status, &text));
         }
 
         let data: serde_json::Value = response
@@ -412,9 +679,216 @@ impl LlmClient {
             }
         }
 
+        let prompt = format!("{}{}", system.unwrap_or(""), message);
+        let usage = self.parse_usage(&data, &prompt, &content);
+
         Ok(LlmResponse {
             content,
             tool_calls,
+            usage,
         })
     }
 }
+
+/// Phrases providers use when a request exceeds the model's context window
+const CONTEXT_LENGTH_MARKERS: &[&str] = &[
+    "context_length_exceeded",
+    "maximum context length",
+    "context length",
+    "context window",
+    "prompt is too long",
+    "too many tokens",
+];
+
+/// Map an unsuccessful provider response to an error
+fn api_error(status: reqwest::StatusCode, text: &str) -> Error {
+    let lower = text.to_lowercase();
+    let client_error = status == reqwest::StatusCode::BAD_REQUEST
+        || status == reqwest::StatusCode::PAYLOAD_TOO_LARGE;
+    if client_error && CONTEXT_LENGTH_MARKERS.iter().any(|m| lower.contains(m)) {
+        return Error::context_length(format!("API error {}: {}", status, text));
+    }
+    Error::llm_provider(format!("API error {}: {}", status, text))
+}
+
+/// Add sampling options to an OpenAI-compatible payload (OpenAI, OpenRouter)
+fn apply_openai_options(payload: &mut serde_json::Value, options: &GenerationOptions) {
+    payload["temperature"] = json!(options.temperature.unwrap_or(DEFAULT_TEMPERATURE));
+    payload["max_tokens"] = json!(options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS));
+    if let Some(top_p) = options.top_p {
+        payload["top_p"] = json!(top_p);
+    }
+    if let Some(seed) = options.seed {
+        payload["seed"] = json!(seed);
+    }
+    if let Some(stop) = options.stop.as_ref().filter(|s| !s.is_empty()) {
+        payload["stop"] = json!(stop);
+    }
+    if let Some(penalty) = options.frequency_penalty {
+        payload["frequency_penalty"] = json!(penalty);
+    }
+    if let Some(penalty) = options.presence_penalty {
+        payload["presence_penalty"] = json!(penalty);
+    }
+}
+
+/// Add sampling options to an Anthropic payload, which has no seed or penalties
+fn apply_anthropic_options(payload: &mut serde_json::Value, options: &GenerationOptions) {
+    payload["max_tokens"] = json!(options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS));
+    if let Some(stop) = options.stop.as_ref().filter(|s| !s.is_empty()) {
+        payload["stop_sequences"] = json!(stop);
+    }
+    if let Some(temperature) = options.temperature {
+        payload["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = options.top_p {
+        payload["top_p"] = json!(top_p);
+    }
+    if options.seed.is_some() {
+        debug!("Anthropic does not support seeds; output may not be reproducible");
+    }
+    if options.frequency_penalty.is_some() || options.presence_penalty.is_some() {
+        debug!("Anthropic does not support frequency/presence penalties; ignoring them");
+    }
+}
+
+/// Build an OpenAI-style message list with an optional leading system
+/// message, then the user and assistant turns of `history`, then `message`
+fn chat_messages(system: Option<&str>, history: &[Message], message: &str) -> serde_json::Value {
+    let mut messages = Vec::new();
+    if let Some(system) = system {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+    for earlier in history {
+        let role = match earlier.role {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::System => continue,
+        };
+        messages.push(json!({ "role": role, "content": earlier.content }));
+    }
+    messages.push(json!({ "role": "user", "content": message }));
+    serde_json::Value::Array(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_agent_and_attribution_headers() {
+        let attribution = OpenRouterAttribution {
+            referer: Some("https://example.org/bot".to_string()),
+            title: Some("Garden Bot".to_string()),
+        };
+        let client = LlmClient::new("openrouter", "m", "k", "https://openrouter.ai/api/v1")
+            .with_user_agent("gardenbot/1.0")
+            .with_attribution(attribution.clone());
+        let request = client.post("https://openrouter.ai/api/v1/chat/completions").build().unwrap();
+        assert_eq!(request.headers()["user-agent"], "gardenbot/1.0");
+        assert_eq!(request.headers()["http-referer"], "https://example.org/bot");
+        assert_eq!(request.headers()["x-title"], "Garden Bot");
+
+        let client = LlmClient::new("openai", "m", "k", "https://api.openai.com/v1").with_attribution(attribution);
+        let request = client.post("https://api.openai.com/v1/chat/completions").build().unwrap();
+        assert_eq!(request.headers()["user-agent"], DEFAULT_USER_AGENT);
+        assert!(!request.headers().contains_key("x-title"));
+    }
+
+    #[test]
+    fn test_chat_messages_with_system_prompt() {
+        let messages = chat_messages(Some("Reply in German."), &[], "hallo");
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(messages[1]["content"], "hallo");
+        assert_eq!(chat_messages(None, &[], "hi").as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_chat_messages_with_history() {
+        let earlier = |role, content: &str| Message {
+            role,
+            content: content.to_string(),
+            timestamp: std::time::SystemTime::now(),
+        };
+        let history = [
+            earlier(MessageRole::User, "My tomatoes are yellowing"),
+            earlier(MessageRole::System, "Session renamed"),
+            earlier(MessageRole::Assistant, "Check the watering"),
+        ];
+        let messages = chat_messages(Some("Be brief."), &history, "How often?");
+        let roles: Vec<&str> = messages.as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "user"]);
+        assert_eq!(messages[1]["content"], "My tomatoes are yellowing");
+        assert_eq!(messages[3]["content"], "How often?");
+    }
+
+    #[test]
+    fn test_generation_options_in_payloads() {
+        let client_defaults = GenerationOptions {
+            temperature: Some(0.2),
+            top_p: Some(0.9),
+            ..Default::default()
+        };
+        let options = client_defaults.merged(&GenerationOptions {
+            seed: Some(42),
+            ..Default::default()
+        });
+
+        let mut openai = json!({});
+        apply_openai_options(&mut openai, &options);
+        assert_eq!(openai["seed"], 42);
+        assert_eq!(openai["top_p"].as_f64().unwrap() as f32, 0.9);
+        assert_eq!(openai["temperature"].as_f64().unwrap() as f32, 0.2);
+
+        let mut anthropic = json!({});
+        apply_anthropic_options(&mut anthropic, &options);
+        assert!(anthropic.get("seed").is_none());
+
+        let mut defaults = json!({});
+        apply_openai_options(&mut defaults, &GenerationOptions::default());
+        assert_eq!(defaults["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(defaults["temperature"].as_f64().unwrap() as f32, DEFAULT_TEMPERATURE);
+        assert!(defaults.get("seed").is_none());
+    }
+
+    #[test]
+    fn test_length_options_in_payloads() {
+        let options = GenerationOptions {
+            max_tokens: Some(512),
+            stop: Some(vec!["\nUser:".to_string()]),
+            frequency_penalty: Some(0.5),
+            ..Default::default()
+        };
+
+        let mut openai = json!({});
+        apply_openai_options(&mut openai, &options);
+        assert_eq!(openai["max_tokens"], 512);
+        assert_eq!(openai["stop"][0], "\nUser:");
+        assert_eq!(openai["frequency_penalty"], 0.5);
+        assert!(openai.get("presence_penalty").is_none());
+
+        let mut anthropic = json!({});
+        apply_anthropic_options(&mut anthropic, &options);
+        assert_eq!(anthropic["max_tokens"], 512);
+        assert_eq!(anthropic["stop_sequences"][0], "\nUser:");
+        assert!(anthropic.get("frequency_penalty").is_none());
+    }
+
+    #[test]
+    fn test_api_error_detects_context_length() {
+        let openai = r#"{"error":{"message":"This model's maximum context length is 8192 tokens","code":"context_length_exceeded"}}"#;
+        assert!(matches!(
+            api_error(reqwest::StatusCode::BAD_REQUEST, openai),
+            Error::ContextLength(_)
+        ));
+        let anthropic = r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#;
+        assert!(matches!(
+            api_error(reqwest::StatusCode::BAD_REQUEST, anthropic),
+            Error::ContextLength(_)
+        ));
+        assert!(matches!(
+            api_error(reqwest::StatusCode::UNAUTHORIZED, "invalid api key"),
+            Error::LlmProvider(_)
+        ));
+    }
+}
@@ -2,6 +2,11 @@
 
 pub mod framework;
 pub mod client;
+pub mod router;
+pub mod stream;
+pub mod tokenizer;
 
-pub use framework::LlmProvider;
+pub use framework::{GenerationOptions, LlmProvider};
 pub use client::{LlmClient, LlmResponse};
+pub use router::{ModelRoute, ModelRouter};
+pub use tokenizer::{count_message_tokens, count_tokens};
@@ -0,0 +1,58 @@
+//! Model routing for chat and auxiliary LLM tasks
+
+use super::client::LlmClient;
+use crate::error::Result;
+use tracing::debug;
+
+/// Purpose an LLM call is made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModelRoute {
+    /// Main conversational model
+    Chat,
+    /// Cheap model for memory consolidation, session and URL summarization
+    Summary,
+}
+
+/// Routes LLM calls to the client configured for each purpose
+#[derive(Clone)]
+pub struct ModelRouter {
+    chat: LlmClient,
+    summary: Option<LlmClient>,
+}
+
+impl ModelRouter {
+    /// Create a router where every route uses the chat client
+    pub fn new(chat: LlmClient) -> Self {
+        Self {
+            chat,
+            summary: None,
+        }
+    }
+
+    /// Use a dedicated client for the summary route
+    pub fn with_summary(mut self, client: LlmClient) -> Self {
+        self.summary = Some(client);
+        self
+    }
+
+    /// Get the client for a route, falling back to the chat client
+    pub fn client(&self, route: ModelRoute) -> &LlmClient {
+        match route {
+            ModelRoute::Chat => &self.chat,
+            ModelRoute::Summary => self.summary.as_ref().unwrap_or(&self.chat),
+        }
+    }
+
+    /// Summarize `text` on the summary route following `instruction`
+    pub async fn summarize(&self, instruction: &str, text: &str) -> Result<String> {
+        let client = self.client(ModelRoute::Summary);
+        debug!(
+            "Summarizing {} chars with {}/{}",
+            text.len(),
+            client.provider(),
+            client.model()
+        );
+        let prompt = format!("{}\n\n---\n{}", instruction, text);
+        client.chat(&prompt).await
+    }
+}
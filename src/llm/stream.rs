@@ -0,0 +1,145 @@
+//! Streamed chat completions
+//!
+//! OpenAI-compatible providers stream a completion as server-sent events,
+//! one `data:` line per chunk, ending with `data: [DONE]`. Text arrives as
+//! `delta.content` pieces, and tool calls as fragments keyed by index whose
+//! names and argument JSON are concatenated. With `include_usage` the last
+//! chunk carries the token counts.
+
+use crate::error::{Error, Result};
+use crate::tools::ToolCall;
+use serde_json::Value;
+
+/// Tool calls accepted in one response; a higher index is ignored rather
+/// than allocated for
+const MAX_TOOL_CALLS: usize = 64;
+
+/// A streamed response, assembled chunk by chunk
+#[derive(Debug, Default)]
+pub struct StreamedReply {
+    content: String,
+    tool_calls: Vec<PartialToolCall>,
+    usage: Value,
+    done: bool,
+}
+
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl StreamedReply {
+    /// Feed one line of the event stream, returning the text it adds
+    pub fn push_line(&mut self, line: &str) -> Result<Option<String>> {
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            // Comments, blank separators, and other fields carry nothing
+            return Ok(None);
+        };
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(None);
+        }
+        let chunk: Value = serde_json::from_str(data)
+            .map_err(|e| Error::serialization(format!("Invalid stream chunk: {}", e)))?;
+        if let Some(error) = chunk.get("error") {
+            let message = error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+            return Err(Error::llm_provider(format!("Stream failed: {}", message)));
+        }
+        if !chunk["usage"].is_null() {
+            self.usage = chunk["usage"].clone();
+        }
+
+        let delta = &chunk["choices"][0]["delta"];
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let index = call["index"].as_u64().unwrap_or(0);
+            let Some(index) = usize::try_from(index).ok().filter(|&i| i < MAX_TOOL_CALLS) else {
+                continue;
+            };
+            if self.tool_calls.len() <= index {
+                self.tool_calls.resize_with(index + 1, Default::default);
+            }
+            let partial = &mut self.tool_calls[index];
+            if let Some(id) = call["id"].as_str() {
+                partial.id.push_str(id);
+            }
+            if let Some(name) = call["function"]["name"].as_str() {
+                partial.name.push_str(name);
+            }
+            if let Some(arguments) = call["function"]["arguments"].as_str() {
+                partial.arguments.push_str(arguments);
+            }
+        }
+        match delta["content"].as_str() {
+            Some(text) if !text.is_empty() => {
+                self.content.push_str(text);
+                Ok(Some(text.to_string()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether the `[DONE]` marker has been seen
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The full text, the tool calls, and the provider's usage object
+    /// (null when it sent none)
+    pub fn finish(self) -> (String, Vec<ToolCall>, Value) {
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .filter(|call| !call.name.is_empty())
+            .map(|call| ToolCall {
+                id: call.id,
+                name: call.name,
+                arguments: serde_json::from_str(&call.arguments).unwrap_or_default(),
+            })
+            .collect();
+        (self.content, tool_calls, self.usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assembles_text_tool_calls_and_usage() {
+        let lines = [
+            r#"data: {"choices":[{"delta":{"role":"assistant","content":""}}]}"#,
+            "",
+            r#"data: {"choices":[{"delta":{"content":"Checking "}}]}"#,
+            ": keep-alive",
+            r#"data: {"choices":[{"delta":{"content":"now"}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"get_weather","arguments":"{\"ci"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"ty\": \"Oslo\"}"}}]}}]}"#,
+            r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7}}"#,
+            "data: [DONE]",
+        ];
+        let mut reply = StreamedReply::default();
+        let texts: Vec<String> = lines
+            .iter()
+            .filter_map(|line| reply.push_line(line).unwrap())
+            .collect();
+        assert_eq!(texts, vec!["Checking ", "now"]);
+        assert!(reply.is_done());
+
+        let (content, tool_calls, usage) = reply.finish();
+        assert_eq!(content, "Checking now");
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].id, "call_1");
+        assert_eq!(tool_calls[0].name, "get_weather");
+        assert_eq!(tool_calls[0].arguments["city"], "Oslo");
+        assert_eq!(usage["completion_tokens"], 7);
+
+        let mut failed = StreamedReply::default();
+        let error = failed
+            .push_line(r#"data: {"error":{"message":"overloaded"}}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("overloaded"), "{}", error);
+    }
+}
@@ -0,0 +1,317 @@
+//! Token counting for prompts and responses
+//!
+//! Provides a tiktoken-compatible byte pair encoder that loads `.tiktoken`
+//! rank files (e.g. `cl100k_base.tiktoken`) plus a heuristic fallback for
+//! models whose vocabulary is not available on the device.
+
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use parking_lot::RwLock;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, info};
+
+/// Pre-tokenization pattern modelled on cl100k_base.
+///
+/// The upstream pattern uses a `\s+(?!\S)` lookahead which the `regex` crate
+/// does not support, so trailing whitespace is attached slightly differently.
+/// Counts may drift by a token or two on whitespace-heavy input.
+const PRETOKENIZE_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+";
+
+/// Token overhead added per chat message (role markers, separators)
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Encoding used to count tokens for a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Cl100kBase,
+    O200kBase,
+    Heuristic,
+}
+
+impl Encoding {
+    /// Resolve the encoding for a model name (OpenRouter `vendor/model` names are accepted)
+    pub fn for_model(model: &str) -> Self {
+        let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+        if name.starts_with("gpt-4o")
+            || name.starts_with("gpt-4.1")
+            || name.starts_with("gpt-5")
+            || name.starts_with("o1")
+            || name.starts_with("o3")
+            || name.starts_with("o4")
+        {
+            Encoding::O200kBase
+        } else if name.starts_with("gpt-4")
+            || name.starts_with("gpt-3.5")
+            || name.starts_with("text-embedding")
+        {
+            Encoding::Cl100kBase
+        } else {
+            Encoding::Heuristic
+        }
+    }
+
+    /// Name of the encoding, matching the `.tiktoken` file stem
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Cl100kBase => "cl100k_base",
+            Encoding::O200kBase => "o200k_base",
+            Encoding::Heuristic => "heuristic",
+        }
+    }
+}
+
+/// Byte pair encoder compatible with tiktoken rank files
+pub struct BpeTokenizer {
+    ranks: HashMap<Vec<u8>, u32>,
+}
+
+impl BpeTokenizer {
+    /// Parse the contents of a `.tiktoken` file (`<base64 token> <rank>` per line)
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut ranks = HashMap::new();
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let (token, rank) = match (parts.next(), parts.next()) {
+                (Some(t), Some(r)) => (t, r),
+                _ => {
+                    return Err(Error::serialization(format!(
+                        "Invalid tiktoken line {}",
+                        line_no + 1
+                    )))
+                }
+            };
+            let bytes = STANDARD.decode(token).map_err(|e| {
+                Error::serialization(format!("Invalid token on line {}: {}", line_no + 1, e))
+            })?;
+            let rank: u32 = rank.parse().map_err(|e| {
+                Error::serialization(format!("Invalid rank on line {}: {}", line_no + 1, e))
+            })?;
+            ranks.insert(bytes, rank);
+        }
+        Ok(Self { ranks })
+    }
+
+    /// Load a `.tiktoken` rank file from disk
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    /// Number of tokens in the vocabulary
+    pub fn vocab_size(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// Count the tokens `text` encodes to
+    pub fn count(&self, text: &str) -> usize {
+        pretokenizer()
+            .find_iter(text)
+            .map(|piece| self.count_piece(piece.as_str().as_bytes()))
+            .sum()
+    }
+
+    fn count_piece(&self, piece: &[u8]) -> usize {
+        if piece.len() <= 1 || self.ranks.contains_key(piece) {
+            return 1;
+        }
+
+        // Boundaries between the current parts; merge the lowest-ranked pair until none remain
+        let mut bounds: Vec<usize> = (0..=piece.len()).collect();
+        loop {
+            let mut best: Option<(usize, u32)> = None;
+            for i in 0..bounds.len().saturating_sub(2) {
+                if let Some(&rank) = self.ranks.get(&piece[bounds[i]..bounds[i + 2]]) {
+                    if best.is_none_or(|(_, r)| rank < r) {
+                        best = Some((i, rank));
+                    }
+                }
+            }
+            match best {
+                Some((i, _)) => {
+                    bounds.remove(i + 1);
+                }
+                None => break,
+            }
+        }
+        bounds.len() - 1
+    }
+}
+
+fn pretokenizer() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(PRETOKENIZE_PATTERN).expect("valid pretokenize pattern"))
+}
+
+fn encodings() -> &'static RwLock<HashMap<&'static str, Arc<BpeTokenizer>>> {
+    static ENCODINGS: OnceLock<RwLock<HashMap<&'static str, Arc<BpeTokenizer>>>> = OnceLock::new();
+    ENCODINGS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register a loaded encoder so `count_tokens` uses it for matching models
+pub fn register_encoding(encoding: Encoding, tokenizer: BpeTokenizer) {
+    if encoding == Encoding::Heuristic {
+        return;
+    }
+    encodings().write().insert(encoding.name(), Arc::new(tokenizer));
+}
+
+/// Load any known `.tiktoken` files found in `dir`, returning how many were loaded
+pub fn load_encodings_from_dir(dir: &Path) -> Result<usize> {
+    let mut loaded = 0;
+    for encoding in [Encoding::Cl100kBase, Encoding::O200kBase] {
+        let path = dir.join(format!("{}.tiktoken", encoding.name()));
+        if !path.exists() {
+            continue;
+        }
+        let tokenizer = BpeTokenizer::from_file(&path)?;
+        info!(
+            "Loaded {} encoding ({} tokens) from {:?}",
+            encoding.name(),
+            tokenizer.vocab_size(),
+            path
+        );
+        register_encoding(encoding, tokenizer);
+        loaded += 1;
+    }
+    debug!("Loaded {} tokenizer encodings from {:?}", loaded, dir);
+    Ok(loaded)
+}
+
+/// Count the tokens in `text` for `model`
+///
+/// Uses the model's BPE encoding when its rank file has been loaded and
+/// falls back to a heuristic estimate otherwise.
+pub fn count_tokens(model: &str, text: &str) -> usize {
+    let encoding = Encoding::for_model(model);
+    if encoding != Encoding::Heuristic {
+        if let Some(tokenizer) = encodings().read().get(encoding.name()).cloned() {
+            return tokenizer.count(text);
+        }
+    }
+    heuristic_count(text)
+}
+
+/// Count the tokens for a sequence of chat message contents, including per-message overhead
+pub fn count_message_tokens<'a>(model: &str, contents: impl IntoIterator<Item = &'a str>) -> usize {
+    contents
+        .into_iter()
+        .map(|content| count_tokens(model, content) + MESSAGE_OVERHEAD_TOKENS)
+        .sum()
+}
+
+/// Estimate token count without a vocabulary
+///
+/// Words cost roughly one token per four ASCII characters (non-ASCII letters
+/// weigh double), CJK ideographs and kana cost one token each, and each
+/// punctuation symbol or line break costs one token.
+pub fn heuristic_count(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut word_units = 0;
+
+    let flush = |units: &mut usize, tokens: &mut usize| {
+        if *units > 0 {
+            *tokens += units.div_ceil(4);
+            *units = 0;
+        }
+    };
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush(&mut word_units, &mut tokens);
+            tokens += 1;
+        } else if c.is_alphanumeric() {
+            word_units += if c.is_ascii() { 1 } else { 2 };
+        } else if c.is_whitespace() {
+            flush(&mut word_units, &mut tokens);
+            if c == '\n' {
+                tokens += 1;
+            }
+        } else {
+            flush(&mut word_units, &mut tokens);
+            tokens += 1;
+        }
+    }
+    flush(&mut word_units, &mut tokens);
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF   // Hiragana, Katakana
+        | 0x3400..=0x4DBF // CJK Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF // Hangul Syllables
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_vocab() -> BpeTokenizer {
+        let mut contents = String::new();
+        let mut rank = 0;
+        for b in 0u8..=255 {
+            contents.push_str(&format!("{} {}\n", STANDARD.encode([b]), rank));
+            rank += 1;
+        }
+        for token in ["he", "ll", "hell", "hello", " w", " wor", " world"] {
+            contents.push_str(&format!("{} {}\n", STANDARD.encode(token), rank));
+            rank += 1;
+        }
+        BpeTokenizer::parse(&contents).unwrap()
+    }
+
+    #[test]
+    fn test_encoding_for_model() {
+        assert_eq!(Encoding::for_model("gpt-4o-mini"), Encoding::O200kBase);
+        assert_eq!(Encoding::for_model("openai/gpt-4-turbo"), Encoding::Cl100kBase);
+        assert_eq!(Encoding::for_model("gpt-3.5-turbo"), Encoding::Cl100kBase);
+        assert_eq!(Encoding::for_model("claude-3-haiku"), Encoding::Heuristic);
+        assert_eq!(Encoding::for_model("meta-llama/llama-2-70b-chat"), Encoding::Heuristic);
+    }
+
+    #[test]
+    fn test_bpe_merges_known_tokens() {
+        let tokenizer = tiny_vocab();
+        assert_eq!(tokenizer.count("hello world"), 2);
+        assert_eq!(tokenizer.count("help"), 3);
+        assert_eq!(tokenizer.count(""), 0);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_lines() {
+        assert!(BpeTokenizer::parse("aGVsbG8=\n").is_err());
+        assert!(BpeTokenizer::parse("!!! 1\n").is_err());
+        assert!(BpeTokenizer::parse("aGVsbG8= x\n").is_err());
+    }
+
+    #[test]
+    fn test_heuristic_count() {
+        assert_eq!(heuristic_count(""), 0);
+        assert_eq!(heuristic_count("hi"), 1);
+        assert_eq!(heuristic_count("hello world"), 4);
+        assert_eq!(heuristic_count("Hi!"), 2);
+        assert_eq!(heuristic_count("你好"), 2);
+    }
+
+    #[test]
+    fn test_count_tokens_falls_back_to_heuristic() {
+        let text = "The quick brown fox jumps over the lazy dog.";
+        assert_eq!(count_tokens("claude-3-haiku", text), heuristic_count(text));
+    }
+
+    #[test]
+    fn test_count_message_tokens_adds_overhead() {
+        let total = count_message_tokens("claude-3-haiku", ["hi", "hi"]);
+        assert_eq!(total, 2 * (1 + MESSAGE_OVERHEAD_TOKENS));
+    }
+}
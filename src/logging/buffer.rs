@@ -0,0 +1,139 @@
+//! In-memory ring buffer of recent log lines
+//!
+//! Lets a running gateway serve its own logs (e.g. to `takobull remote logs`)
+//! on devices where stdout goes nowhere useful.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, OnceLock};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Log lines kept by the global buffer
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+struct Inner {
+    lines: VecDeque<(u64, String)>,
+    next_seq: u64,
+    capacity: usize,
+}
+
+/// Bounded buffer of formatted log lines, each tagged with a sequence number
+#[derive(Clone)]
+pub struct LogBuffer {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl LogBuffer {
+    /// Create a buffer holding at most `capacity` lines
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                lines: VecDeque::with_capacity(capacity.min(DEFAULT_CAPACITY)),
+                next_seq: 0,
+                capacity,
+            })),
+        }
+    }
+
+    /// Append a line, evicting the oldest when full
+    pub fn push(&self, line: impl Into<String>) {
+        let mut inner = self.inner.lock();
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        if inner.lines.len() == inner.capacity {
+            inner.lines.pop_front();
+        }
+        inner.lines.push_back((seq, line.into()));
+    }
+
+    /// The last `count` lines
+    pub fn tail(&self, count: usize) -> Vec<(u64, String)> {
+        let inner = self.inner.lock();
+        let skip = inner.lines.len().saturating_sub(count);
+        inner.lines.iter().skip(skip).cloned().collect()
+    }
+
+    /// Lines with a sequence number of at least `seq`
+    pub fn since(&self, seq: u64) -> Vec<(u64, String)> {
+        let inner = self.inner.lock();
+        inner.lines.iter().filter(|(s, _)| *s >= seq).cloned().collect()
+    }
+
+    /// Sequence number the next line will get
+    pub fn next_seq(&self) -> u64 {
+        self.inner.lock().next_seq
+    }
+}
+
+/// Buffer that `init_logging` mirrors all log output into
+pub fn global() -> &'static LogBuffer {
+    static BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+    BUFFER.get_or_init(|| LogBuffer::new(DEFAULT_CAPACITY))
+}
+
+/// Writer for a single log event
+pub struct LineWriter {
+    buffer: LogBuffer,
+    pending: Vec<u8>,
+}
+
+impl io::Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LineWriter {
+    fn drop(&mut self) {
+        let text = String::from_utf8_lossy(&self.pending);
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            self.buffer.push(line);
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LineWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LineWriter {
+            buffer: self.clone(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_evicts_oldest_lines() {
+        let buffer = LogBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(format!("line {}", i));
+        }
+        let lines: Vec<String> = buffer.tail(10).into_iter().map(|(_, l)| l).collect();
+        assert_eq!(lines, vec!["line 2", "line 3", "line 4"]);
+        assert_eq!(buffer.tail(1)[0].0, 4);
+        assert_eq!(buffer.since(4).len(), 1);
+        assert_eq!(buffer.next_seq(), 5);
+    }
+
+    #[test]
+    fn test_writer_splits_lines() {
+        let buffer = LogBuffer::new(10);
+        {
+            let mut writer = buffer.make_writer();
+            write!(writer, "first\nsecond\n").unwrap();
+        }
+        assert_eq!(buffer.tail(10).len(), 2);
+    }
+}
@@ -1,3 +1,6 @@
 //! Logging and tracing setup for TakoBull
 
+pub mod buffer;
 pub mod setup;
+
+pub use buffer::LogBuffer;
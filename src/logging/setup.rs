@@ -4,6 +4,8 @@ use crate::error::Result;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 /// Initialize logging with the specified log level
+///
+/// Output is also mirrored into [`super::buffer::global`] for remote tailing.
 pub fn init_logging(log_level: &str) -> Result<()> {
     let env_filter = EnvFilter::try_from_default_env()
         .or_else(|_| EnvFilter::try_new(log_level))
@@ -12,6 +14,7 @@ pub fn init_logging(log_level: &str) -> Result<()> {
     tracing_subscriber::registry()
         .with(env_filter)
         .with(fmt::layer().with_writer(std::io::stdout))
+        .with(fmt::layer().with_ansi(false).with_writer(super::buffer::global().clone()))
         .init();
 
     Ok(())
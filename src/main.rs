@@ -36,18 +36,269 @@ enum Commands {
         /// Message to send to the agent
         #[arg(short, long)]
         message: Option<String>,
+        /// Session to continue (a new session is created if omitted)
+        #[arg(short, long)]
+        session: Option<String>,
+        /// Sampling temperature for this request
+        #[arg(long)]
+        temperature: Option<f32>,
+        /// Nucleus sampling cutoff for this request
+        #[arg(long)]
+        top_p: Option<f32>,
+        /// Sampling seed for reproducible output (OpenAI-compatible providers)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Maximum tokens in the response
+        #[arg(long)]
+        max_tokens: Option<usize>,
+        /// Rounds of tool calls allowed for this run (defaults to agents.defaults.max_tool_iterations)
+        #[arg(long)]
+        max_iterations: Option<usize>,
+        /// Ask the models in agents.council and keep the best answer
+        #[arg(long)]
+        council: bool,
     },
     /// Start the gateway for channel integrations
-    Gateway,
+    Gateway {
+        /// Recovery mode: disable mutating tools, pause background jobs, and
+        /// serve the API on loopback only
+        #[arg(long)]
+        safe_mode: bool,
+    },
     /// Show system status
-    Status,
+    Status {
+        /// Show message rate, latency, and error trends over this window (e.g. 1h, 30m)
+        #[arg(long)]
+        history: Option<String>,
+    },
     /// Manage scheduled cron jobs
     Cron {
         #[command(subcommand)]
         action: CronAction,
     },
+    /// Manage conversation sessions
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
+    /// Control a running gateway over its HTTP API
+    Remote {
+        /// Gateway URL (defaults to gateway.host and gateway.port from config)
+        #[arg(long)]
+        url: Option<String>,
+        /// API token (defaults to gateway.auth_token from config)
+        #[arg(long)]
+        token: Option<String>,
+        #[command(subcommand)]
+        action: RemoteAction,
+    },
+    /// Re-run a stored session against the current config (tools are not executed) and diff the responses
+    Replay {
+        /// Session id
+        session_id: String,
+    },
+    /// Run prompts from a JSONL file through the agent and write results as JSONL
+    Batch {
+        /// Input file with one {"id": ..., "prompt": ...} object per line
+        input: String,
+        /// Output file (defaults to <input>.results.jsonl)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Prompts to run at once
+        #[arg(short = 'j', long, default_value_t = 2)]
+        concurrency: usize,
+    },
+    /// Compare prompt experiment variants by session count and feedback
+    Experiments,
+    /// Show the trace of an agent run, or list recent runs
+    Trace {
+        /// Run id (lists recent runs when omitted)
+        run_id: Option<String>,
+        /// Only list runs from this session
+        #[arg(short, long)]
+        session: Option<String>,
+        /// Runs to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Manage long-term memory
+    Memory {
+        #[command(subcommand)]
+        action: MemoryAction,
+    },
+    /// Manage tool approval rules
+    Tools {
+        #[command(subcommand)]
+        action: ToolsAction,
+    },
+    /// Export or import the assistant's persona as a single file
+    Persona {
+        #[command(subcommand)]
+        action: PersonaAction,
+    },
+    /// Manage secrets injected into tools via tools.env
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+    /// Show version and build information
+    About {
+        /// List compiled-in features, backends, and approximate memory footprints
+        #[arg(long)]
+        features: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Initialize configuration and workspace
-    Onboard,
+    Onboard {
+        /// Template pack: home-automation, coding, journal, a directory, a manifest file, or a URL
+        #[arg(long)]
+        template: Option<String>,
+        /// Overwrite existing workspace files with the template's
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum RemoteAction {
+    /// Show gateway status
+    Status,
+    /// Send a message to the agent
+    Send {
+        /// Message text
+        message: String,
+        /// Replace the previous message, cancelling its run if still going
+        #[arg(long)]
+        edit: bool,
+    },
+    /// Reload config.yaml and restart gateway services
+    Reload,
+    /// Start a scheduled job now
+    RunJob {
+        /// Job id
+        id: String,
+    },
+    /// Show recent gateway log lines
+    Logs {
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
+        /// Keep printing new lines as they arrive
+        #[arg(short, long)]
+        follow: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionAction {
+    /// List stored sessions
+    List {
+        /// List archived sessions instead
+        #[arg(long)]
+        archived: bool,
+    },
+    /// Move idle sessions into compressed cold storage
+    Archive {
+        /// Days without activity before archiving (defaults to sessions.archive_after_days)
+        #[arg(long)]
+        idle_days: Option<u64>,
+    },
+    /// Merge near-duplicate old exchanges into summaries now
+    Compress {
+        /// Only merge exchanges older than this (defaults to sessions.compression.after_days)
+        #[arg(long)]
+        older_than_days: Option<u64>,
+    },
+    /// Search archived sessions by title or first message
+    Search {
+        /// Text to search for
+        query: String,
+    },
+    /// Override the system prompt for one session
+    SetSystem {
+        /// Session id
+        session_id: String,
+        /// System prompt text (omit with --clear)
+        #[arg(required_unless_present = "clear")]
+        text: Option<String>,
+        /// Restore the default system prompt
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Import conversations exported from another assistant
+    Import {
+        /// Export format (chatgpt, generic-jsonl)
+        #[arg(long)]
+        format: picoclaw::session::import::ImportFormat,
+        /// Exported file (conversations.json for ChatGPT)
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MemoryAction {
+    /// Delete remembered facts matching a query from the vector store and MEMORY.md
+    Forget {
+        /// Text identifying the facts to delete
+        #[arg(short, long)]
+        query: String,
+        /// List matching memories without deleting them
+        #[arg(long)]
+        dry_run: bool,
+        /// Minimum similarity for a memory to match (exact text matches always do)
+        #[arg(long, default_value_t = picoclaw::memory::DEFAULT_FORGET_THRESHOLD)]
+        threshold: f32,
+        /// Namespace to search: shared, user:<id>, or agent:<name> (repeatable; all by default)
+        #[arg(short, long)]
+        namespace: Vec<picoclaw::memory::Namespace>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ToolsAction {
+    /// List auto-approve rules for tools in tools.approval.required
+    Rules {
+        /// Remove the rule with this id
+        #[arg(long)]
+        revoke: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PersonaAction {
+    /// Write identity files, skills, personas, and message templates to a bundle
+    Export {
+        /// Bundle file to write
+        file: String,
+    },
+    /// Install a bundle into the workspace
+    Import {
+        /// Bundle file to read
+        file: String,
+        /// Overwrite existing workspace files with the bundle's
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SecretsAction {
+    /// List stored secret names
+    List,
+    /// Store a secret (reads the value from stdin if omitted)
+    Set {
+        /// Secret name, referenced as secret:NAME
+        name: String,
+        /// Secret value
+        value: Option<String>,
+    },
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        name: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -57,41 +308,127 @@ enum CronAction {
     /// Add a new scheduled job
     Add {
         /// Cron expression
-        #[arg(short, long)]
-        expression: String,
+        #[arg(short, long, required_unless_present = "at", conflicts_with = "at")]
+        expression: Option<String>,
+        /// Run once at this local time ("2025-07-01 09:00") instead of on a cron schedule
+        #[arg(long)]
+        at: Option<String>,
         /// Job description
         #[arg(short, long)]
         description: String,
+        /// Missed-run policy after downtime (skip, run-once, run-all)
+        #[arg(long, default_value = "run-once")]
+        catch_up: picoclaw::scheduler::CatchUpPolicy,
+        /// Delay each run by up to this many seconds to spread out a fleet's wakeups
+        #[arg(long, default_value_t = 0)]
+        jitter: u64,
+        /// Deliver the output through this message template from config.yaml
+        #[arg(long)]
+        template: Option<String>,
     },
+    /// Remove a scheduled job
+    Remove {
+        /// Job id
+        id: String,
+    },
+    /// Show a job's recent runs and their output
+    History {
+        /// Job id
+        id: String,
+        /// Number of runs to show, newest last
+        #[arg(short = 'n', long, default_value = "5")]
+        runs: usize,
+    },
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Sized for the board: a Pi Zero gets one worker and small stacks
+    let runtime = picoclaw::runtime::RuntimeConfig::default();
+    runtime.build()?.block_on(run(runtime))
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn run(runtime: picoclaw::runtime::RuntimeConfig) -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
     // Initialize logging
     picoclaw::logging::setup::init_logging(&args.log_level)?;
 
     info!("Starting TakoBull v{}", env!("CARGO_PKG_VERSION"));
+    if runtime.is_low_footprint() {
+        info!(
+            "Low-footprint runtime: {} worker thread, {} KB stacks",
+            runtime.worker_threads,
+            runtime.stack_size / 1024
+        );
+    }
     if let Some(config_path) = &args.config {
         info!("Configuration file: {:?}", config_path);
     }
 
     match args.command {
-        Some(Commands::Agent { message }) => {
-            handle_agent(message).await?;
+        Some(Commands::Agent {
+            message,
+            session,
+            temperature,
+            top_p,
+            seed,
+            max_tokens,
+            max_iterations,
+            council,
+        }) => {
+            let options = picoclaw::llm::GenerationOptions {
+                temperature,
+                top_p,
+                seed,
+                max_tokens,
+                ..Default::default()
+            };
+            handle_agent(message, session, options, max_iterations, council).await?;
         }
-        Some(Commands::Gateway) => {
-            handle_gateway().await?;
+        Some(Commands::Gateway { safe_mode }) => {
+            handle_gateway(safe_mode).await?;
         }
-        Some(Commands::Status) => {
-            handle_status().await?;
+        Some(Commands::About { features, json }) => {
+            handle_about(features, json)?;
+        }
+        Some(Commands::Status { history }) => {
+            handle_status(args.verbose, history).await?;
         }
         Some(Commands::Cron { action }) => {
             handle_cron(action).await?;
         }
-        Some(Commands::Onboard) => {
-            handle_onboard().await?;
+        Some(Commands::Session { action }) => {
+            handle_session(action).await?;
+        }
+        Some(Commands::Remote { url, token, action }) => {
+            handle_remote(url, token, action).await?;
+        }
+        Some(Commands::Replay { session_id }) => {
+            handle_replay(session_id).await?;
+        }
+        Some(Commands::Batch { input, output, concurrency }) => {
+            handle_batch(input, output, concurrency).await?;
+        }
+        Some(Commands::Experiments) => {
+            handle_experiments()?;
+        }
+        Some(Commands::Trace { run_id, session, limit }) => {
+            handle_trace(run_id, session, limit)?;
+        }
+        Some(Commands::Memory { action }) => {
+            handle_memory(action).await?;
+        }
+        Some(Commands::Tools { action }) => {
+            handle_tools(action).await?;
+        }
+        Some(Commands::Persona { action }) => {
+            handle_persona(action)?;
+        }
+        Some(Commands::Secrets { action }) => {
+            handle_secrets(action)?;
+        }
+        Some(Commands::Onboard { template, force }) => {
+            handle_onboard(template, force).await?;
         }
         None => {
             // Default: show help
@@ -103,6 +440,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("  gateway  Start the gateway for channel integrations");
             println!("  status   Show system status");
             println!("  cron     Manage scheduled cron jobs");
+            println!("  session  Manage conversation sessions");
+            println!("  remote   Control a running gateway");
+            println!("  replay   Replay a session against the current config");
+            println!("  batch    Run prompts from a JSONL file through the agent");
+            println!("  memory   Manage long-term memory");
+            println!("  tools    Manage tool approval rules");
+            println!("  persona  Export or import the assistant's persona");
+            println!("  secrets  Manage secrets injected into tools");
+            println!("  about    Show version and build information");
             println!("  onboard  Initialize configuration and workspace");
             println!("\nOptions:");
             println!("  -c, --config <FILE>          Path to configuration file");
@@ -118,84 +464,128 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn handle_agent(message: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+async fn handle_agent(
+    message: Option<String>,
+    session_id: Option<String>,
+    options: picoclaw::llm::GenerationOptions,
+    max_iterations: Option<usize>,
+    council: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting agent");
 
-    // Load config
     let home = std::env::var("HOME")?;
-    let config_path = format!("{}/.takobull/config.yaml", home);
     let workspace_path = format!("{}/.takobull/workspace", home);
-    
-    if !std::path::Path::new(&config_path).exists() {
-        eprintln!("❌ Config not found: {}", config_path);
-        eprintln!("Run 'takobull onboard' first to initialize");
-        return Err("Config file not found".into());
-    }
+    let config = load_required_config(&home)?;
 
-    let config_content = std::fs::read_to_string(&config_path)?;
-    info!("Loaded config from: {}", config_path);
+    // A running gateway checked the workspace when it took the lease; moving
+    // files out from under it would break its open sessions
+    match picoclaw::runtime::lease::current_holder(std::path::Path::new(&workspace_path))? {
+        Some(holder) => info!("Skipping workspace check, gateway is running ({})", holder.describe()),
+        None => check_workspace_integrity(&workspace_path),
+    }
 
     if let Some(msg) = message {
         info!("Processing message: {}", msg);
-        
-        // Parse YAML config
-        let config: serde_yaml::Value = serde_yaml::from_str(&config_content)?;
-        
-        let provider = config["agents"]["defaults"]["provider"]
-            .as_str()
-            .unwrap_or("openrouter")
-            .to_string();
-        
-        let model = config["agents"]["defaults"]["model"]
-            .as_str()
-            .unwrap_or("meta-llama/llama-2-70b-chat")
-            .to_string();
-        
-        // Get API key and base from provider config
-        let provider_config = &config["providers"][&provider];
-        let api_key = provider_config["api_key"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
-        
-        let api_base = provider_config["api_base"]
-            .as_str()
-            .unwrap_or("https://openrouter.ai/api/v1")
-            .to_string();
-        
-        info!("Using provider: {}, model: {}", provider, model);
-        
-        if api_key.is_empty() {
-            eprintln!("❌ API key not configured for provider: {}", provider);
-            eprintln!("Set the API key in ~/.takobull/config.yaml under providers.{}.api_key", provider);
-            return Err("API key not configured".into());
+
+        let mut session_manager =
+            picoclaw::session::SessionManager::new(format!("{}/sessions", workspace_path));
+        if let Some(redactor) = build_redactor(&config)? {
+            session_manager = session_manager.with_redactor(redactor);
         }
-        
-        // Create LLM client
-        let llm_client = picoclaw::llm::LlmClient::new(&provider, &model, &api_key, &api_base);
-        
-        // Create tool registry and register tools
-        let tool_registry = picoclaw::tools::ToolRegistry::new();
-        let write_file_tool = std::sync::Arc::new(
-            picoclaw::tools::WriteFileTool::new(workspace_path)
-        );
-        tool_registry.register(write_file_tool).await;
-        
-        // Create agent executor
-        let executor = picoclaw::agent::AgentExecutor::new(llm_client, tool_registry);
-        
+        let is_new = session_id.is_none();
+        let mut session = match session_id {
+            Some(id) => session_manager.load_session(&id).await?,
+            None => session_manager.create_session("cli").await?,
+        };
+        session.metadata.channel = "cli".to_string();
+
+        if let Some(reply) =
+            picoclaw::agent::persona::handle_command(std::path::Path::new(&workspace_path), &mut session, &msg)
+        {
+            println!("{}", reply?);
+            session_manager.save_session(&session).await?;
+            println!("Session: {}", session.id);
+            return Ok(());
+        }
+        let experiments = build_experiments(&config, &workspace_path)?;
+        if let Some(reply) = picoclaw::agent::experiment::handle_command(&experiments, &session, &msg) {
+            println!("{}", reply?);
+            if is_new {
+                session_manager.delete_session(&session.id).await?;
+            }
+            return Ok(());
+        }
+
+        let mut profile = picoclaw::runtime::startup::StartupProfile::start();
+        let mut executor = build_executor(&home, &config, false, &mut profile).await?.with_options(options);
+        if let Some(approvals) = executor.tools().approvals() {
+            if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+                approvals.set_approver(std::sync::Arc::new(TerminalApprover));
+            }
+        }
+        if let Some(max_iterations) = max_iterations {
+            executor = executor.with_max_iterations(max_iterations);
+        }
+        profile.finish(None);
+
         println!("🤖 Processing: {}", msg);
         
-        match executor.execute(&msg).await {
-            Ok(response) => {
-                println!("{}", response);
-                info!("Response: {}", response);
+        let result = if council {
+            match build_council(&config) {
+                Ok(council) => executor.execute_council(&council, &msg, &session).await.map(|verdict| {
+                    println!("🏛️  Council: {}", verdict.summary());
+                    picoclaw::agent::AgentTurn {
+                        response: verdict.response,
+                        input_tokens: verdict.input_tokens,
+                        output_tokens: verdict.output_tokens,
+                        ..Default::default()
+                    }
+                }),
+                Err(e) => Err(e),
+            }
+        } else {
+            executor.execute_in_session(&msg, &session).await
+        };
+        let response = match result {
+            Ok(turn) => {
+                if let Some(degradation) = turn.context_degraded {
+                    println!("⚠️  Message was too long for the model's context window and was {}", degradation);
+                    session.record_context_event(degradation);
+                }
+                if turn.budget_exhausted {
+                    println!("⚠️  {}", turn.response);
+                } else {
+                    println!("{}", turn.response);
+                }
+                info!("Response: {}", turn.response);
+                if !turn.run_id.is_empty() {
+                    info!("Run: {} (takobull trace {})", turn.run_id, turn.run_id);
+                }
+                turn.response
             }
             Err(e) => {
                 eprintln!("❌ Error: {}", e);
+                if is_new {
+                    let _ = session_manager.delete_session(&session.id).await;
+                }
+                executor.observers().flush(std::time::Duration::from_secs(10)).await;
                 return Err(e);
             }
+        };
+
+        executor.experiments().assign(&mut session);
+        // Record the exchange in the session
+        session.add_message(picoclaw::agent::context::MessageRole::User, msg);
+        session.add_message(picoclaw::agent::context::MessageRole::Assistant, response);
+
+        if picoclaw::session::title::needs_title(&session) {
+            let title = picoclaw::session::title::generate_title(executor.router(), &session).await?;
+            info!("Generated session title: {}", title);
+            session.metadata.title = Some(title);
         }
+        session_manager.save_session(&session).await?;
+        info!("Session: {}", session.id);
+        executor.observers().flush(std::time::Duration::from_secs(10)).await;
     } else {
         info!("Starting interactive agent mode");
         println!("🤖 TakoBull Interactive Mode");
@@ -208,42 +598,1551 @@ async fn handle_agent(message: Option<String>) -> Result<(), Box<dyn std::error:
     Ok(())
 }
 
-async fn handle_gateway() -> Result<(), Box<dyn std::error::Error>> {
-    info!("Starting gateway");
-    println!("Gateway mode (not yet implemented)");
+/// Read ~/.takobull/config.yaml, failing with onboarding instructions if it is missing
+fn load_required_config(home: &str) -> Result<serde_yaml::Value, Box<dyn std::error::Error>> {
+    let config_path = format!("{}/.takobull/config.yaml", home);
+    if !std::path::Path::new(&config_path).exists() {
+        eprintln!("❌ Config not found: {}", config_path);
+        eprintln!("Run 'takobull onboard' first to initialize");
+        return Err("Config file not found".into());
+    }
+
+    let config = serde_yaml::from_str(&std::fs::read_to_string(&config_path)?)?;
+    info!("Loaded config from: {}", config_path);
+    Ok(config)
+}
+
+/// Build the agent executor (LLM routes and tools) from config.yaml
+async fn build_executor(
+    home: &str,
+    config: &serde_yaml::Value,
+    safe_mode: bool,
+    profile: &mut picoclaw::runtime::startup::StartupProfile,
+) -> Result<picoclaw::agent::AgentExecutor, Box<dyn std::error::Error>> {
+    let workspace_path = format!("{}/.takobull/workspace", home);
+    let defaults: picoclaw::config::AgentDefaults =
+        serde_yaml::from_value(config["agents"]["defaults"].clone()).unwrap_or_default();
+
+    info!("Using provider: {}, model: {}", defaults.provider, defaults.model);
+
+    let tokenizer_dir = format!("{}/.takobull/tokenizers", home);
+    if std::path::Path::new(&tokenizer_dir).exists() {
+        picoclaw::llm::tokenizer::load_encodings_from_dir(std::path::Path::new(&tokenizer_dir))?;
+    }
+
+    // TLS certificate validation fails on a wrong clock; startup carries on
+    // either way and the time is recorded once NTP catches up
+    let clock = picoclaw::runtime::clock::TimeSanity::new(std::path::Path::new(&format!(
+        "{}/state",
+        workspace_path
+    )));
+    if clock.check().is_sane() {
+        clock.record_good_time()?;
+    } else {
+        eprintln!("⚠️  System clock looks wrong; HTTPS requests may fail until it is synchronized");
+        tokio::spawn(async move {
+            clock.wait_until_sane(std::time::Duration::from_secs(24 * 60 * 60)).await;
+        });
+    }
+    profile.phase("clock check");
+
+    let router = build_router(config, &defaults)?;
+    profile.phase("llm clients");
+
+    // Create tool registry and register tools
+    let tool_env: std::collections::HashMap<String, std::collections::HashMap<String, String>> =
+        serde_yaml::from_value(config["tools"]["env"].clone()).unwrap_or_default();
+    let cost_hints: std::collections::HashMap<String, picoclaw::tools::CostHint> =
+        serde_yaml::from_value(config["tools"]["cost_hints"].clone()).unwrap_or_default();
+    let approval = approval_config(config)?;
+    let tool_registry = picoclaw::tools::ToolRegistry::new()
+        .with_env(tool_env, std::sync::Arc::new(secret_store(home)))
+        .with_cost_hints(cost_hints)
+        .with_read_only(safe_mode)
+        .with_approvals(std::sync::Arc::new(picoclaw::tools::ApprovalGate::new(&workspace_path, approval)));
+    // A typo here must not silently drop deny globs or the symlink policy
+    let filesystem: picoclaw::config::FilesystemConfig = match &config["tools"]["filesystem"] {
+        serde_yaml::Value::Null => Default::default(),
+        filesystem => serde_yaml::from_value(filesystem.clone())?,
+    };
+    if filesystem.enabled {
+        let policy = std::sync::Arc::new(picoclaw::tools::PathPolicy::from_config(
+            &workspace_path,
+            &filesystem,
+        )?);
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::WriteFileTool::with_policy(policy.clone())))
+            .await;
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::ReadFileTool::new(policy)))
+            .await;
+    }
+    let shell: picoclaw::config::ShellConfig =
+        serde_yaml::from_value(config["tools"]["shell"].clone()).unwrap_or_default();
+    if shell.enabled {
+        let jobs = std::sync::Arc::new(
+            picoclaw::tools::JobManager::new(&workspace_path).with_max_running(shell.max_jobs),
+        );
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::ShellTool::new(
+                &workspace_path,
+                jobs.clone(),
+                shell,
+            )))
+            .await;
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::JobStatusTool::new(jobs.clone())))
+            .await;
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::JobKillTool::new(jobs)))
+            .await;
+    }
+    let series_store = std::sync::Arc::new(picoclaw::telemetry::SeriesStore::new(format!(
+        "{}/telemetry",
+        workspace_path
+    )));
+    let telemetry: picoclaw::config::TelemetryConfig =
+        serde_yaml::from_value(config["telemetry"].clone()).unwrap_or_default();
+    tool_registry
+        .register(std::sync::Arc::new(
+            picoclaw::tools::QueryMetricsTool::new(series_store).with_retention_days(telemetry.retention_days),
+        ))
+        .await;
+    let devices: picoclaw::config::DevicesConfig =
+        serde_yaml::from_value(config["devices"].clone()).unwrap_or_default();
+    if devices.backend == picoclaw::config::DeviceBackendKind::Simulated {
+        let backend = std::sync::Arc::new(picoclaw::device::SimulatedBackend::new(devices.simulated));
+        let mut manager = picoclaw::device::DeviceManager::new().with_backend(backend);
+        for device in manager.discover_devices().await? {
+            manager.register_device(device).await?;
+        }
+        info!("Using {} simulated devices", manager.devices().len());
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::DeviceTool::new(std::sync::Arc::new(manager))))
+            .await;
+    }
+    let embedder = build_embedder(config, &defaults)?;
+    let redactor = build_redactor(config)?;
+    let mut memory = picoclaw::memory::MemoryStore::new(&workspace_path, embedder.clone());
+    let mut notes = picoclaw::memory::NoteStore::new(&workspace_path, embedder);
+    let mut traces = picoclaw::agent::TraceStore::new(&workspace_path);
+    if let Some(redactor) = &redactor {
+        memory = memory.with_redactor(redactor.clone());
+        notes = notes.with_redactor(redactor.clone());
+        traces = traces.with_redactor(redactor.clone());
+    }
+    let memory = std::sync::Arc::new(memory);
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::RememberTool::new(memory.clone())))
+        .await;
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::ForgetTool::new(memory.clone())))
+        .await;
+    let notes = std::sync::Arc::new(notes);
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::NoteAddTool::new(notes.clone())))
+        .await;
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::NoteSearchTool::new(notes)))
+        .await;
+    let todos = std::sync::Arc::new(picoclaw::tools::TodoStore::new(&workspace_path));
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::TodoAddTool::new(todos.clone())))
+        .await;
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::TodoListTool::new(todos.clone())))
+        .await;
+    tool_registry
+        .register(std::sync::Arc::new(picoclaw::tools::TodoDoneTool::new(todos)))
+        .await;
+    let templates = build_templates(config)?;
+    if !templates.is_empty() {
+        tool_registry
+            .register(std::sync::Arc::new(picoclaw::tools::NotifyTool::new(std::sync::Arc::new(templates))))
+            .await;
+    }
+    profile.phase("tool registry");
+
+    let language: picoclaw::config::LanguageConfig =
+        serde_yaml::from_value(config["agents"]["language"].clone()).unwrap_or_default();
+
+    let max_expensive_per_turn: Option<usize> =
+        serde_yaml::from_value(config["tools"]["max_expensive_per_turn"].clone()).unwrap_or_default();
+
+    Ok(picoclaw::agent::AgentExecutor::with_router(router, tool_registry)
+        .with_max_iterations(
+            defaults
+                .max_tool_iterations
+                .unwrap_or(picoclaw::agent::executor::DEFAULT_MAX_ITERATIONS),
+        )
+        .with_history_tokens(
+            defaults
+                .history_tokens
+                .unwrap_or(picoclaw::agent::executor::DEFAULT_HISTORY_TOKENS),
+        )
+        .with_language(language)
+        .with_expensive_tool_limit(max_expensive_per_turn)
+        .with_memory(memory, defaults.recall)
+        .with_citations(defaults.citations)
+        .with_transcripts(defaults.transcripts)
+        .with_name(defaults.name)
+        .with_traces(traces)
+        .with_experiments(std::sync::Arc::new(build_experiments(config, &workspace_path)?))
+        .with_observers(std::sync::Arc::new(build_observers(config)?)))
+}
+
+/// LLM clients for the chat and summary routes
+fn build_router(
+    config: &serde_yaml::Value,
+    defaults: &picoclaw::config::AgentDefaults,
+) -> Result<picoclaw::llm::ModelRouter, Box<dyn std::error::Error>> {
+    let llm_client = build_llm_client(config, &defaults.provider, &defaults.model)?;
+    let mut router = picoclaw::llm::ModelRouter::new(llm_client);
+    if let Some(summary_model) = &defaults.summary_model {
+        let summary_provider = defaults
+            .summary_provider
+            .as_deref()
+            .unwrap_or(&defaults.provider);
+        info!("Using summary provider: {}, model: {}", summary_provider, summary_model);
+        router = router.with_summary(build_llm_client(config, summary_provider, summary_model)?);
+    }
+    Ok(router)
+}
+
+/// Read-only destinations from the `observers` list
+fn build_observers(config: &serde_yaml::Value) -> Result<picoclaw::channels::Observers, Box<dyn std::error::Error>> {
+    // A typo here must not silently switch oversight off
+    let configs: Vec<picoclaw::config::ObserverConfig> = if config["observers"].is_null() {
+        Vec::new()
+    } else {
+        serde_yaml::from_value(config["observers"].clone())?
+    };
+    Ok(picoclaw::channels::Observers::from_config(&configs))
+}
+
+/// Tool approval settings from `tools.approval`
+fn approval_config(config: &serde_yaml::Value) -> Result<picoclaw::config::ApprovalConfig, Box<dyn std::error::Error>> {
+    // A typo here must not silently switch approvals off
+    match &config["tools"]["approval"] {
+        serde_yaml::Value::Null => Ok(Default::default()),
+        approval => Ok(serde_yaml::from_value(approval.clone())?),
+    }
+}
+
+/// How long after a message an edit replaces it, from `channels.edit_grace_secs`
+fn edit_grace(config: &serde_yaml::Value) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+    let secs = match &config["channels"]["edit_grace_secs"] {
+        serde_yaml::Value::Null => picoclaw::channels::edits::DEFAULT_EDIT_GRACE.as_secs(),
+        secs => serde_yaml::from_value(secs.clone())?,
+    };
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// Prompt experiments from the `experiments` list in config.yaml
+fn build_experiments(
+    config: &serde_yaml::Value,
+    workspace_path: &str,
+) -> Result<picoclaw::agent::Experiments, Box<dyn std::error::Error>> {
+    let experiments = match &config["experiments"] {
+        serde_yaml::Value::Null => Vec::new(),
+        experiments => serde_yaml::from_value(experiments.clone())?,
+    };
+    Ok(picoclaw::agent::Experiments::new(experiments, workspace_path)?)
+}
+
+/// Message templates from the `templates` section of config.yaml
+fn build_templates(config: &serde_yaml::Value) -> Result<picoclaw::channels::Templates, Box<dyn std::error::Error>> {
+    let templates = match &config["templates"] {
+        serde_yaml::Value::Null => Default::default(),
+        templates => serde_yaml::from_value(templates.clone())?,
+    };
+    Ok(picoclaw::channels::Templates::new(templates))
+}
+
+/// Council of models from `agents.council`
+fn build_council(config: &serde_yaml::Value) -> Result<picoclaw::agent::Council, Box<dyn std::error::Error>> {
+    let council_config: picoclaw::config::CouncilConfig =
+        serde_yaml::from_value(config["agents"]["council"].clone()).unwrap_or_default();
+    let members = council_config
+        .members
+        .iter()
+        .map(|member| build_llm_client(config, &member.provider, &member.model))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut council = picoclaw::agent::Council::new(members)?;
+    if let Some(judge) = &council_config.judge {
+        council = council.with_judge(build_llm_client(config, &judge.provider, &judge.model)?);
+    }
+    Ok(council)
+}
+
+/// Redactor for stored sessions, memories, and notes, or `None` when the
+/// `redaction` section leaves it disabled
+fn build_redactor(
+    config: &serde_yaml::Value,
+) -> Result<Option<std::sync::Arc<picoclaw::session::Redactor>>, Box<dyn std::error::Error>> {
+    // A typo here must not silently store transcripts unredacted
+    let redaction: picoclaw::config::RedactionConfig = match &config["redaction"] {
+        serde_yaml::Value::Null => Default::default(),
+        redaction => serde_yaml::from_value(redaction.clone())?,
+    };
+    Ok(picoclaw::session::Redactor::from_config(&redaction)?.map(std::sync::Arc::new))
+}
+
+/// Embedder for memory and notes, as configured in the `memory` section
+fn build_embedder(
+    config: &serde_yaml::Value,
+    defaults: &picoclaw::config::AgentDefaults,
+) -> Result<std::sync::Arc<dyn picoclaw::memory::Embedder>, Box<dyn std::error::Error>> {
+    let memory: picoclaw::config::MemoryConfig =
+        serde_yaml::from_value(config["memory"].clone()).unwrap_or_default();
+    Ok(match &memory.embedding_model {
+        Some(model) => {
+            let provider = memory
+                .embedding_provider
+                .as_deref()
+                .unwrap_or(&defaults.provider);
+            info!("Using embedding provider: {}, model: {}", provider, model);
+            std::sync::Arc::new(picoclaw::memory::ApiEmbedder::new(build_llm_client(
+                config, provider, model,
+            )?))
+        }
+        None => std::sync::Arc::new(picoclaw::memory::HashEmbedder::new(memory.hash_dims)),
+    })
+}
+
+/// Build an LLM client for `provider` from the `providers` section of config.yaml
+fn build_llm_client(
+    config: &serde_yaml::Value,
+    provider: &str,
+    model: &str,
+) -> Result<picoclaw::llm::LlmClient, Box<dyn std::error::Error>> {
+    let provider_config = &config["providers"][provider];
+    let api_key = provider_config["api_key"].as_str().unwrap_or("");
+    let api_base = provider_config["api_base"]
+        .as_str()
+        .unwrap_or("https://openrouter.ai/api/v1");
+
+    if api_key.is_empty() {
+        eprintln!("❌ API key not configured for provider: {}", provider);
+        eprintln!("Set the API key in ~/.takobull/config.yaml under providers.{}.api_key", provider);
+        return Err("API key not configured".into());
+    }
+
+    let defaults: picoclaw::config::AgentDefaults =
+        serde_yaml::from_value(config["agents"]["defaults"].clone()).unwrap_or_default();
+    let mut llm_client = picoclaw::llm::LlmClient::new(provider, model, api_key, api_base)
+        .with_options(defaults.generation);
+    if provider == "openrouter" && !provider_config["routing"].is_null() {
+        let routing: picoclaw::config::OpenRouterRouting =
+            serde_yaml::from_value(provider_config["routing"].clone())?;
+        llm_client = llm_client.with_routing(routing);
+    }
+    if provider == "openrouter" && !provider_config["attribution"].is_null() {
+        let attribution: picoclaw::config::OpenRouterAttribution =
+            serde_yaml::from_value(provider_config["attribution"].clone())?;
+        llm_client = llm_client.with_attribution(attribution);
+    }
+    let http: picoclaw::config::HttpConfig =
+        serde_yaml::from_value(config["http"].clone()).unwrap_or_default();
+    if let Some(user_agent) = http.user_agent.filter(|ua| !ua.trim().is_empty()) {
+        llm_client = llm_client.with_user_agent(user_agent);
+    }
+
+    Ok(llm_client)
+}
+
+/// Runs scheduled jobs through the agent executor
+struct AgentJobRunner {
+    executor: std::sync::Arc<picoclaw::agent::AgentExecutor>,
+    templates: std::sync::Arc<picoclaw::channels::Templates>,
+}
+
+#[async_trait::async_trait]
+impl picoclaw::scheduler::JobRunner for AgentJobRunner {
+    async fn run_job(&self, job: &picoclaw::scheduler::CronJob) -> picoclaw::Result<String> {
+        let Some(template) = &job.template else {
+            return self
+                .executor
+                .execute(&job.description)
+                .await
+                .map_err(|e| picoclaw::Error::tool(e.to_string()));
+        };
+        // The agent only supplies the values, so every run reads the same
+        let reply = self
+            .executor
+            .execute(&self.templates.job_prompt(template, &job.description)?)
+            .await
+            .map_err(|e| picoclaw::Error::tool(e.to_string()))?;
+        self.templates.render_reply(template, &reply)
+    }
+}
+
+/// Delivers telemetry alerts and failing-job notices from the gateway
+/// through the alert channel
+struct GatewayAlertSink {
+    executor: std::sync::Arc<picoclaw::agent::AgentExecutor>,
+    /// Shared with the scheduler to cap background agent runs
+    background: std::sync::Arc<tokio::sync::Semaphore>,
+    notifier: picoclaw::channels::AlertNotifier,
+}
+
+#[async_trait::async_trait]
+impl picoclaw::telemetry::AlertSink for GatewayAlertSink {
+    async fn dispatch(&self, event: &picoclaw::telemetry::AlertEvent) {
+        use picoclaw::scheduler::JobNotifier;
+        use picoclaw::telemetry::alerts::AlertAction;
+        match &event.action {
+            AlertAction::Notify { message } => self.notifier.notify(message).await,
+            // Rendered into `Notify` by the alert engine
+            AlertAction::Template { template, .. } => {
+                self.notifier.notify(&format!("{} ({})", event.rule, template)).await
+            }
+            AlertAction::Prompt { prompt } => {
+                let _permit = self.background.acquire().await.ok();
+                let response = self.executor.execute(prompt).await.map_err(|e| e.to_string());
+                match response {
+                    Ok(response) => self.notifier.notify(&response).await,
+                    Err(e) => tracing::warn!("Alert {} prompt failed: {}", event.rule, e),
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl picoclaw::scheduler::JobNotifier for GatewayAlertSink {
+    async fn notify(&self, message: &str) {
+        self.notifier.notify(message).await;
+    }
+}
+
+/// The alert channel from `channels.alerts`, if configured
+fn alert_channel(config: &serde_yaml::Value) -> Result<Option<picoclaw::channels::AlertChannel>, Box<dyn std::error::Error>> {
+    // A typo here must not silently drop every alert
+    match &config["channels"]["alerts"] {
+        serde_yaml::Value::Null => Ok(None),
+        target => Ok(Some(picoclaw::channels::AlertChannel::new(serde_yaml::from_value(
+            target.clone(),
+        )?))),
+    }
+}
+
+/// Asks the user at the terminal to approve tool calls
+struct TerminalApprover;
+
+impl TerminalApprover {
+    async fn ask(prompt: String) -> bool {
+        tokio::task::spawn_blocking(move || {
+            use std::io::Write;
+            print!("{} [y/N] ", prompt);
+            let _ = std::io::stdout().flush();
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer).is_ok()
+                && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+        })
+        .await
+        .unwrap_or(false)
+    }
+}
+
+#[async_trait::async_trait]
+impl picoclaw::tools::Approver for TerminalApprover {
+    async fn approve(&self, request: &picoclaw::tools::ApprovalRequest) -> bool {
+        Self::ask(format!("🔐 Allow {} on `{}`?", request.tool, request.subject)).await
+    }
+
+    async fn offer_rule(&self, request: &picoclaw::tools::ApprovalRequest, approvals: usize) -> bool {
+        Self::ask(format!(
+            "🔐 You have approved {} on `{}` {} times. Always allow it for you?",
+            request.tool,
+            request.pattern.as_deref().unwrap_or(&request.subject),
+            approvals
+        ))
+        .await
+    }
+}
+
+async fn handle_gateway(safe_mode: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let workspace_path = format!("{}/.takobull/workspace", std::env::var("HOME")?);
+    // Held across config reloads so another gateway cannot start in between
+    let mut lease = picoclaw::runtime::lease::WorkspaceLease::acquire(std::path::Path::new(&workspace_path))?;
+    while run_gateway(safe_mode, &mut lease).await? {
+        info!("Restarting gateway with reloaded configuration");
+    }
+    Ok(())
+}
+
+/// Run the gateway until shutdown, returning true if a config reload was requested
+///
+/// In safe mode mutating tools are disabled, the scheduler and telemetry
+/// collector do not run, and the HTTP API listens on loopback only, so a
+/// prompt or skill that misbehaves on boot can be inspected and fixed.
+async fn run_gateway(
+    safe_mode: bool,
+    lease: &mut picoclaw::runtime::lease::WorkspaceLease,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    info!("Starting gateway{}", if safe_mode { " in safe mode" } else { "" });
+    let mut profile = picoclaw::runtime::startup::StartupProfile::start();
+    let home = std::env::var("HOME")?;
+    let workspace_path = format!("{}/.takobull/workspace", home);
+    let config = load_required_config(&home)?;
+    profile.phase("config load");
+    check_workspace_integrity(&workspace_path);
+    profile.phase("workspace check");
+
+    let executor = std::sync::Arc::new(build_executor(&home, &config, safe_mode, &mut profile).await?);
+    profile.phase("agent setup");
+    let templates = std::sync::Arc::new(build_templates(&config)?);
+    let runner = std::sync::Arc::new(AgentJobRunner {
+        executor: executor.clone(),
+        templates: templates.clone(),
+    });
     // TODO: Initialize channel connections
     // TODO: Start listening for messages
+
+    let scheduler_config: picoclaw::config::SchedulerConfig =
+        serde_yaml::from_value(config["scheduler"].clone()).unwrap_or_default();
+    let scheduler = picoclaw::scheduler::Scheduler::new(picoclaw::scheduler::JobStore::new(format!(
+        "{}/cron",
+        workspace_path
+    )))
+    .with_max_concurrent(scheduler_config.max_concurrent_runs)
+    .with_history_limit(scheduler_config.history_runs)
+    .with_retry(scheduler_config.retry);
+    let alert_sink = std::sync::Arc::new(GatewayAlertSink {
+        executor: executor.clone(),
+        background: scheduler.background_permits(),
+        notifier: picoclaw::channels::AlertNotifier::new(alert_channel(&config)?)
+            .with_outbox(picoclaw::channels::Outbox::new(&workspace_path)),
+    });
+    let scheduler = std::sync::Arc::new(scheduler.with_notifier(alert_sink.clone()));
+
+    let runtime = picoclaw::runtime::RuntimeManager::new();
+
+    let watchdog_config: picoclaw::config::WatchdogConfig =
+        serde_yaml::from_value(config["watchdog"].clone()).unwrap_or_default();
+    if watchdog_config.enabled {
+        let heartbeat_file = watchdog_config.heartbeat_file.clone().unwrap_or_else(|| {
+            std::path::Path::new(&workspace_path).join(picoclaw::runtime::watchdog::HEARTBEAT_FILE)
+        });
+        let mut watchdog = picoclaw::runtime::watchdog::Watchdog::new(&heartbeat_file);
+        if let Some(device) = &watchdog_config.device {
+            watchdog = watchdog.with_device(device).unwrap_or_else(|e| {
+                tracing::warn!("{}; writing the heartbeat file only", e);
+                picoclaw::runtime::watchdog::Watchdog::new(&heartbeat_file)
+            });
+        }
+        let interval = std::time::Duration::from_secs(watchdog_config.interval_secs.max(1));
+        let shutdown = runtime.shutdown_signal();
+        runtime.spawn_task(async move { watchdog.run(interval, shutdown).await });
+    }
+
+    let metrics = picoclaw::runtime::metrics::global().clone();
+    match picoclaw::runtime::metrics::load(std::path::Path::new(&workspace_path)).await {
+        Ok(samples) => metrics.restore(samples),
+        Err(e) => tracing::warn!("Failed to load saved metrics: {}", e),
+    }
+    let shutdown = runtime.shutdown_signal();
+    let metrics_dir = std::path::PathBuf::from(&workspace_path);
+    runtime.spawn_task(async move {
+        metrics
+            .persist(metrics_dir, std::time::Duration::from_secs(60), shutdown)
+            .await
+    });
+
+    let disk_config: picoclaw::config::DiskConfig =
+        serde_yaml::from_value(config["disk"].clone()).unwrap_or_default();
+    if disk_config.enabled {
+        let guard = picoclaw::workspace::DiskGuard::new(&workspace_path, disk_config).with_read_only(safe_mode);
+        let shutdown = runtime.shutdown_signal();
+        let notifier = alert_sink.clone();
+        runtime.spawn_task(async move { guard.run(notifier, shutdown).await });
+    }
+
+    // A typo here must not silently stop mail from being read
+    let email_config: picoclaw::config::EmailTasksConfig = match &config["email_tasks"] {
+        serde_yaml::Value::Null => Default::default(),
+        email => serde_yaml::from_value(email.clone())?,
+    };
+    if email_config.enabled && !safe_mode {
+        let pipeline = picoclaw::agent::EmailTasks::new(
+            &workspace_path,
+            email_config,
+            executor.router().clone(),
+            std::sync::Arc::new(picoclaw::tools::TodoStore::new(&workspace_path)),
+        )?;
+        let shutdown = runtime.shutdown_signal();
+        let notifier = alert_sink.clone();
+        runtime.spawn_task(async move { pipeline.run(notifier, shutdown).await });
+    }
+
+    let sessions_config: picoclaw::config::SessionsConfig =
+        serde_yaml::from_value(config["sessions"].clone()).unwrap_or_default();
+    if sessions_config.compression.enabled && !safe_mode {
+        let defaults: picoclaw::config::AgentDefaults =
+            serde_yaml::from_value(config["agents"]["defaults"].clone()).unwrap_or_default();
+        let compressor =
+            picoclaw::session::SessionCompressor::new(build_embedder(&config, &defaults)?, sessions_config.compression)
+                .with_router(executor.router().clone());
+        let mut sessions = picoclaw::session::SessionManager::new(format!("{}/sessions", workspace_path));
+        if let Some(redactor) = build_redactor(&config)? {
+            sessions = sessions.with_redactor(redactor);
+        }
+        let shutdown = runtime.shutdown_signal();
+        runtime.spawn_task(async move { compressor.run(sessions, shutdown).await });
+    }
+
+    let series_store = std::sync::Arc::new(picoclaw::telemetry::SeriesStore::new(format!(
+        "{}/telemetry",
+        workspace_path
+    )));
+    let telemetry_config: picoclaw::config::TelemetryConfig =
+        serde_yaml::from_value(config["telemetry"].clone()).unwrap_or_default();
+    if telemetry_config.enabled && !telemetry_config.sensors.is_empty() && !safe_mode {
+        let store = series_store.clone();
+        let alerts =
+            picoclaw::telemetry::AlertEngine::new(telemetry_config.alerts.clone()).with_templates(templates.clone());
+        let mut collector = picoclaw::telemetry::TelemetryCollector::from_config(&telemetry_config, store)
+            .with_alerts(alerts, alert_sink.clone());
+        let shutdown = runtime.shutdown_signal();
+        runtime.spawn_task(async move { collector.run(shutdown).await });
+    }
+    profile.phase("background tasks");
+
+    let mut gateway_config: picoclaw::config::GatewayConfig =
+        serde_yaml::from_value(config["gateway"].clone()).unwrap_or_default();
+    if safe_mode {
+        gateway_config.host = "127.0.0.1".to_string();
+        gateway_config.mdns = false;
+    }
+    let reload = std::sync::Arc::new(tokio::sync::Notify::new());
+    let mut sessions = picoclaw::session::SessionManager::new(format!("{}/sessions", workspace_path));
+    if let Some(redactor) = build_redactor(&config)? {
+        sessions = sessions.with_redactor(redactor);
+    }
+    start_gateway_http(
+        &runtime,
+        &gateway_config,
+        executor.clone(),
+        sessions,
+        series_store,
+        reload.clone(),
+        (!safe_mode).then(|| (scheduler.clone(), runner.clone())),
+        edit_grace(&config)?,
+    )?;
+    let _mdns = advertise_gateway(&gateway_config);
+    profile.phase("http api");
+    let report = profile.finish(gateway_config.startup_budget_ms);
+    if let Err(e) = report.save(std::path::Path::new(&workspace_path)).await {
+        tracing::warn!("Failed to save startup report: {}", e);
+    }
+
+    let shutdown = runtime.shutdown_signal();
+    if safe_mode {
+        println!("⚠️  Safe mode: mutating tools disabled, background jobs paused, API on loopback only");
+    }
+    if !executor.observers().is_empty() {
+        println!("✓ Mirroring interactions to {} observer(s)", executor.observers().len());
+    }
+    println!("✓ Gateway running (Ctrl+C to stop)");
+    // Missed-job catch-up is only meaningful once the clock is trustworthy,
+    // so the scheduler alone waits for it; everything above is already up
+    let clock = picoclaw::runtime::clock::TimeSanity::new(std::path::Path::new(&format!(
+        "{}/state",
+        workspace_path
+    )));
+    let jobs = async {
+        if safe_mode {
+            std::future::pending::<()>().await;
+        }
+        while !clock.wait_until_sane(std::time::Duration::from_secs(60)).await.is_sane() {
+            info!("Deferring scheduler start until the system clock is synchronized");
+        }
+        match scheduler.catch_up(runner.as_ref(), chrono::Utc::now()).await {
+            Ok(caught_up) => info!("Scheduler catch-up ran {} missed jobs", caught_up),
+            Err(e) => tracing::warn!("Scheduler catch-up failed: {}", e),
+        }
+        scheduler.run(runner.as_ref(), shutdown).await;
+    };
+    let mut reloading = false;
+    let mut lost_lease = None;
+    tokio::select! {
+        _ = jobs => {}
+        e = lease.hold() => {
+            eprintln!("❌ {}; stopping so the other gateway does not double-reply", e);
+            lost_lease = Some(e);
+        }
+        _ = tokio::signal::ctrl_c() => {
+            info!("Received Ctrl+C");
+        }
+        _ = reload.notified() => {
+            println!("↻ Reloading configuration");
+            reloading = true;
+        }
+    }
+    runtime.shutdown(std::time::Duration::from_secs(5)).await?;
+    if let Some(e) = lost_lease {
+        return Err(e.into());
+    }
+    Ok(reloading)
+}
+
+/// Serve the gateway HTTP API and dashboard until the runtime shuts down
+#[cfg(feature = "webhooks")]
+#[allow(clippy::too_many_arguments)]
+fn start_gateway_http(
+    runtime: &picoclaw::runtime::RuntimeManager,
+    gateway_config: &picoclaw::config::GatewayConfig,
+    executor: std::sync::Arc<picoclaw::agent::AgentExecutor>,
+    sessions: picoclaw::session::SessionManager,
+    series_store: std::sync::Arc<picoclaw::telemetry::SeriesStore>,
+    reload: std::sync::Arc<tokio::sync::Notify>,
+    scheduler: Option<(std::sync::Arc<picoclaw::scheduler::Scheduler>, std::sync::Arc<AgentJobRunner>)>,
+    edit_grace: std::time::Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !gateway_config.http_enabled {
+        return Ok(());
+    }
+    let addr: std::net::SocketAddr =
+        format!("{}:{}", gateway_config.host, gateway_config.port).parse()?;
+    // The API runs agent turns and tools, so only loopback is served without a token
+    if !addr.ip().is_loopback() && gateway_config.auth_token.as_deref().unwrap_or("").is_empty() {
+        return Err(picoclaw::error::Error::config(format!(
+            "gateway.auth_token must be set to serve the HTTP API on {}; bind to 127.0.0.1 or set a token",
+            addr
+        ))
+        .into());
+    }
+    let mut state = picoclaw::gateway::GatewayState::new(
+        executor,
+        sessions,
+        series_store,
+    )
+    .with_auth_token(gateway_config.auth_token.clone())
+    .with_allowed_hosts(
+        gateway_config
+            .allowed_hosts
+            .iter()
+            .chain([&gateway_config.host])
+            .cloned(),
+    )
+    .with_allowed_origins(
+        ["localhost", "127.0.0.1", "[::1]", gateway_config.host.as_str()]
+            .map(|name| format!("http://{}:{}", name, gateway_config.port))
+            .into_iter()
+            .chain(gateway_config.allowed_origins.iter().cloned()),
+    )
+    .with_edit_grace(edit_grace)
+    .with_reload(reload);
+    if let Some((scheduler, runner)) = scheduler {
+        state = state.with_scheduler(scheduler, runner);
+    }
+    let router = picoclaw::gateway::router(std::sync::Arc::new(state), gateway_config.dashboard);
+    let mut shutdown = runtime.shutdown_signal();
+    runtime.spawn_task(async move {
+        let stop = async move {
+            let _ = shutdown.recv().await;
+        };
+        if let Err(e) = picoclaw::gateway::serve(addr, router, stop).await {
+            tracing::error!("{}", e);
+        }
+    });
+    if gateway_config.dashboard {
+        println!("✓ Dashboard at http://{}/", addr);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "webhooks"))]
+#[allow(clippy::too_many_arguments)]
+fn start_gateway_http(
+    _runtime: &picoclaw::runtime::RuntimeManager,
+    _gateway_config: &picoclaw::config::GatewayConfig,
+    _executor: std::sync::Arc<picoclaw::agent::AgentExecutor>,
+    _sessions: picoclaw::session::SessionManager,
+    _series_store: std::sync::Arc<picoclaw::telemetry::SeriesStore>,
+    _reload: std::sync::Arc<tokio::sync::Notify>,
+    _scheduler: Option<(std::sync::Arc<picoclaw::scheduler::Scheduler>, std::sync::Arc<AgentJobRunner>)>,
+    _edit_grace: std::time::Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+
+/// Advertise the gateway API on the LAN, returning a guard that unregisters on drop
+#[cfg(feature = "mdns")]
+fn advertise_gateway(
+    gateway_config: &picoclaw::config::GatewayConfig,
+) -> Option<picoclaw::gateway::mdns::MdnsAdvertiser> {
+    use picoclaw::gateway::mdns;
+    if !gateway_config.http_enabled || !gateway_config.mdns {
+        return None;
+    }
+    let addr: std::net::SocketAddr = format!("{}:{}", gateway_config.host, gateway_config.port)
+        .parse()
+        .ok()?;
+    if !mdns::is_advertisable(&addr) {
+        info!("Gateway bound to loopback; skipping mDNS advertisement");
+        return None;
+    }
+    let auth = if gateway_config.auth_token.as_deref().unwrap_or("").is_empty() {
+        "none"
+    } else {
+        "token"
+    };
+    let properties = [
+        ("version", env!("CARGO_PKG_VERSION")),
+        ("api", "/api"),
+        ("auth", auth),
+    ];
+    match mdns::MdnsAdvertiser::advertise(&mdns::instance_name(), addr, &properties) {
+        Ok(advertiser) => Some(advertiser),
+        Err(e) => {
+            tracing::warn!("{}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "mdns"))]
+fn advertise_gateway(_gateway_config: &picoclaw::config::GatewayConfig) -> Option<()> {
+    None
+}
+
+fn handle_about(features: bool, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let tokenizer_dir = std::path::PathBuf::from(format!("{}/.takobull/tokenizers", home));
+    let report = picoclaw::about::AboutReport::collect(Some(&tokenizer_dir));
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("TakoBull v{} ({}, {})", report.version, report.target, report.profile);
+    if let Some(bytes) = report.binary_bytes {
+        println!("Binary:    {}", picoclaw::about::format_bytes(bytes));
+    }
+    if let Some(bytes) = report.rss_bytes {
+        println!("Resident:  {}", picoclaw::about::format_bytes(bytes));
+    }
+    if !features {
+        return Ok(());
+    }
+
+    let enabled: Vec<&str> = report.features.iter().filter(|f| f.enabled).map(|f| f.name).collect();
+    let disabled: Vec<&str> = report.features.iter().filter(|f| !f.enabled).map(|f| f.name).collect();
+    println!("Features:  {}", enabled.join(", "));
+    println!("Excluded:  {}", if disabled.is_empty() { "none".to_string() } else { disabled.join(", ") });
+    println!("Providers: {}", report.providers.join(", "));
+    println!("Channels:  {}", if report.channels.is_empty() { "none".to_string() } else { report.channels.join(", ") });
+    println!("Memory (approximate):");
+    for footprint in &report.footprints {
+        println!(
+            "  {:<18} {:>10}  {}",
+            footprint.subsystem,
+            picoclaw::about::format_bytes(footprint.bytes),
+            footprint.basis
+        );
+    }
     Ok(())
 }
 
-async fn handle_status() -> Result<(), Box<dyn std::error::Error>> {
+async fn handle_status(verbose: bool, history: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
     info!("Showing status");
     println!("TakoBull v{}", env!("CARGO_PKG_VERSION"));
     println!("Status: OK");
     // TODO: Show actual status information
+
+    let home = std::env::var("HOME")?;
+    let store = picoclaw::scheduler::JobStore::new(format!("{}/.takobull/workspace/cron", home));
+    print_jobs(&picoclaw::scheduler::Scheduler::new(store).statuses().await?);
+    let outbox = picoclaw::channels::Outbox::new(format!("{}/.takobull/workspace", home));
+    print_delivery(&outbox.stats().await?);
+    match picoclaw::runtime::lease::current_holder(std::path::Path::new(&format!("{}/.takobull/workspace", home)))? {
+        Some(holder) => println!("Gateway:  running ({})", holder.describe()),
+        None => println!("Gateway:  not running"),
+    }
+
+    let watchdog_config: picoclaw::config::WatchdogConfig =
+        serde_yaml::from_value(load_config_value(&home)?["watchdog"].clone()).unwrap_or_default();
+    if watchdog_config.enabled {
+        let heartbeat_file = watchdog_config.heartbeat_file.unwrap_or_else(|| {
+            std::path::Path::new(&format!("{}/.takobull/workspace", home))
+                .join(picoclaw::runtime::watchdog::HEARTBEAT_FILE)
+        });
+        match picoclaw::runtime::watchdog::last_heartbeat(&heartbeat_file).await? {
+            Some(at) => {
+                let age = (chrono::Utc::now() - at).num_seconds().max(0);
+                let stale = age as u64 > watchdog_config.interval_secs.max(1) * 3;
+                println!("Heartbeat: {}s ago{}", age, if stale { " (stale)" } else { "" });
+            }
+            None => println!("Heartbeat: none recorded"),
+        }
+    }
+
+    let disk_config: picoclaw::config::DiskConfig =
+        serde_yaml::from_value(load_config_value(&home)?["disk"].clone()).unwrap_or_default();
+    let workspace = format!("{}/.takobull/workspace", home);
+    if let Ok(free) = picoclaw::workspace::disk::free_bytes(std::path::Path::new(&workspace)).await {
+        let low = free < disk_config.min_free_mb * 1024 * 1024;
+        println!(
+            "Disk:     {} free{}",
+            picoclaw::about::format_bytes(free),
+            if low { " ⚠️  below disk.min_free_mb" } else { "" }
+        );
+    }
+
+    if verbose {
+        match picoclaw::runtime::startup::StartupReport::load(std::path::Path::new(&workspace)).await? {
+            Some(report) => {
+                let marker = if report.over_budget() { "⚠️ " } else { "" };
+                let started = report.started_at.with_timezone(&chrono::Local);
+                println!("Startup:  {}{} at {}", marker, report.summary(), started.format("%Y-%m-%d %H:%M:%S"));
+                for phase in &report.phases {
+                    println!("  {:<20} {:>6}ms", phase.name, phase.duration_ms);
+                }
+            }
+            None => println!("Startup:  no gateway startup recorded"),
+        }
+    }
+
+    if let Some(window) = history {
+        let window = picoclaw::config::parse_duration(&window)
+            .ok_or_else(|| format!("Invalid history window: {}", window))?;
+        let samples = picoclaw::runtime::metrics::load(std::path::Path::new(&workspace)).await?;
+        print_history(&samples, chrono::Duration::from_std(window)?);
+    }
+    Ok(())
+}
+
+/// Rows shown by `status --history`
+const HISTORY_ROWS: i64 = 12;
+
+/// Print agent run trends over the last `window`
+fn print_history(samples: &[picoclaw::runtime::metrics::MetricsSample], window: chrono::Duration) {
+    use chrono::DurationRound;
+    let minutes = window.num_minutes().max(1);
+    let step = chrono::Duration::minutes((minutes + HISTORY_ROWS - 1) / HISTORY_ROWS);
+    let now = chrono::Utc::now();
+    let to = now.duration_trunc(chrono::Duration::minutes(1)).unwrap_or(now) + chrono::Duration::minutes(1);
+    let intervals = picoclaw::runtime::metrics::aggregate(samples, to - window, to, step);
+
+    let (messages, errors) = intervals
+        .iter()
+        .fold((0, 0), |(m, e), i| (m + i.messages, e + i.errors));
+    println!(
+        "History:  {} messages, {} errors in the last {}m ({}m per row)",
+        messages,
+        errors,
+        minutes,
+        step.num_minutes()
+    );
+    if messages == 0 {
+        println!("  no agent runs recorded; metrics are saved by a running gateway");
+        return;
+    }
+    for interval in &intervals {
+        let rate = interval.messages as f64 / step.num_minutes() as f64;
+        println!(
+            "  {}  {:>4} msgs  {:>5.1}/min  {:>3} errors  avg {:>6}ms  max {:>6}ms",
+            interval.at.with_timezone(&chrono::Local).format("%H:%M"),
+            interval.messages,
+            rate,
+            interval.errors,
+            interval.avg_latency_ms(),
+            interval.max_latency_ms
+        );
+    }
+}
+
+/// Print scheduled jobs with their next and last runs
+fn print_jobs(jobs: &[picoclaw::scheduler::JobStatus]) {
+    if jobs.is_empty() {
+        println!("Jobs:     none scheduled");
+        return;
+    }
+    println!("Jobs:");
+    for job in jobs {
+        println!("  {}  {}", job.id, job.description);
+        println!("            {}", job.schedule_line());
+    }
+}
+
+/// Print outgoing message delivery per channel
+fn print_delivery(channels: &[picoclaw::channels::ChannelDelivery]) {
+    if channels.is_empty() {
+        return;
+    }
+    println!("Delivery:");
+    for channel in channels {
+        let marker = if channel.failed > 0 { "⚠️ " } else { "" };
+        println!("  {}{}  {}", marker, channel.channel_id, channel.summary());
+    }
+}
+
+async fn handle_remote(
+    url: Option<String>,
+    token: Option<String>,
+    action: RemoteAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let config = load_config_value(&home)?;
+    let gateway_config: picoclaw::config::GatewayConfig =
+        serde_yaml::from_value(config["gateway"].clone()).unwrap_or_default();
+    let url = url.unwrap_or_else(|| {
+        let host = match gateway_config.host.as_str() {
+            "0.0.0.0" | "::" => "127.0.0.1",
+            host => host,
+        };
+        format!("http://{}:{}", host, gateway_config.port)
+    });
+    let client = picoclaw::gateway::RemoteClient::new(url)
+        .with_token(token.or(gateway_config.auth_token));
+    info!("Connecting to gateway at {}", client.base_url());
+
+    match action {
+        RemoteAction::Status => {
+            let status = client.status().await?;
+            println!("TakoBull v{} at {}", status.version, client.base_url());
+            println!("Uptime:   {}s", status.uptime_secs);
+            println!("Model:    {}/{}", status.provider, status.model);
+            println!("Sessions: {}", status.sessions);
+            println!("Sensors:  {}", status.sensors);
+            if status.safe_mode {
+                println!("Mode:     ⚠️  safe mode (mutating tools disabled, background jobs paused)");
+            }
+            print_jobs(&status.jobs);
+            print_delivery(&status.delivery);
+        }
+        RemoteAction::Send { message, edit } => {
+            println!("{}", client.send_message(&message, edit).await?);
+        }
+        RemoteAction::Reload => {
+            client.reload().await?;
+            println!("✓ Gateway is reloading its configuration");
+        }
+        RemoteAction::RunJob { id } => {
+            client.run_job(&id).await?;
+            println!("✓ Started job {}", id);
+        }
+        RemoteAction::Logs { lines, follow } => {
+            let mut tail = client.logs(lines, None).await?;
+            loop {
+                for line in &tail.lines {
+                    println!("{}", line);
+                }
+                if !follow {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                tail = client.logs(lines, Some(tail.next)).await?;
+            }
+        }
+    }
     Ok(())
 }
 
 async fn handle_cron(action: CronAction) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let store = picoclaw::scheduler::JobStore::new(format!("{}/.takobull/workspace/cron", home));
+
     match action {
         CronAction::List => {
             info!("Listing cron jobs");
-            println!("Cron jobs (not yet implemented)");
-            // TODO: List scheduled jobs
+            let jobs = store.load().await?;
+            if jobs.is_empty() {
+                println!("No scheduled jobs");
+            }
+            for job in jobs {
+                println!(
+                    "{}  {:<15} {:<9} {}{}{}",
+                    job.id,
+                    job.schedule_label(),
+                    job.catch_up,
+                    job.description,
+                    job.template.as_deref().map(|t| format!(" → {}", t)).unwrap_or_default(),
+                    if job.enabled { "" } else { " (disabled)" }
+                );
+                println!(
+                    "          {}",
+                    picoclaw::scheduler::JobStatus::from_job(&job).schedule_line()
+                );
+            }
         }
         CronAction::Add {
             expression,
+            at,
             description,
+            catch_up,
+            jitter,
+            template,
         } => {
-            info!("Adding cron job: {} - {}", expression, description);
-            println!("Added cron job: {} - {}", expression, description);
-            // TODO: Add scheduled job
+            let mut job = match (expression, at) {
+                (Some(expression), None) => picoclaw::scheduler::CronJob::new(&expression, &description)?,
+                (None, Some(at)) => {
+                    picoclaw::scheduler::CronJob::once(picoclaw::scheduler::parse_at(&at)?, &description)?
+                }
+                _ => return Err("Give either --expression or --at".into()),
+            }
+            .with_catch_up(catch_up)
+            .with_jitter(jitter);
+            if let Some(template) = template {
+                build_templates(&load_config_value(&home)?)?.get(&template)?;
+                job = job.with_template(template);
+            }
+            info!("Adding cron job: {} - {}", job.schedule_label(), description);
+            let (id, schedule) = (job.id.clone(), job.schedule_label());
+            store.add(job).await?;
+            println!("Added cron job {}: {} - {}", id, schedule, description);
+        }
+        CronAction::Remove { id } => {
+            let job = store.remove(&id).await?;
+            picoclaw::scheduler::RunHistory::new(store.cron_dir()).clear(&job.id).await?;
+            println!("Removed cron job {}: {}", job.id, job.description);
+        }
+        CronAction::History { id, runs } => {
+            let history = picoclaw::scheduler::RunHistory::new(store.cron_dir()).load(&id).await?;
+            if history.is_empty() {
+                println!("No recorded runs for job {}", id);
+            }
+            for run in &history[history.len().saturating_sub(runs)..] {
+                println!(
+                    "{}  {:<6} {:.1}s",
+                    run.started_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"),
+                    if run.success { "ok" } else { "failed" },
+                    run.duration_ms as f64 / 1000.0
+                );
+                for line in run.output.lines() {
+                    println!("    {}", line);
+                }
+            }
         }
     }
     Ok(())
 }
 
-async fn handle_onboard() -> Result<(), Box<dyn std::error::Error>> {
+async fn handle_session(action: SessionAction) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let mut session_manager =
+        picoclaw::session::SessionManager::new(format!("{}/.takobull/workspace/sessions", home));
+    // Imported transcripts are stored like live ones
+    if let Some(redactor) = build_redactor(&load_config_value(&home)?)? {
+        session_manager = session_manager.with_redactor(redactor);
+    }
+    let archive = picoclaw::session::SessionArchive::for_sessions_dir(session_manager.sessions_dir());
+
+    match action {
+        SessionAction::List { archived: true } => {
+            info!("Listing archived sessions");
+            print_archive_entries(archive.entries().await?);
+        }
+        SessionAction::Search { query } => {
+            info!("Searching archived sessions: {}", query);
+            print_archive_entries(archive.search(&query).await?);
+        }
+        SessionAction::SetSystem { session_id, text, clear } => {
+            let mut session = session_manager.load_session(&session_id).await?;
+            let prompt = if clear { None } else { text };
+            let cleared = prompt.is_none();
+            session.set_system_prompt(prompt);
+            session_manager.save_session(&session).await?;
+            if cleared {
+                println!("✓ Restored the default system prompt for {}", session.id);
+            } else {
+                println!("✓ Set the system prompt for {}", session.id);
+            }
+        }
+        SessionAction::Import { format, file } => {
+            info!("Importing {:?} sessions from {:?}", format, file);
+            let imported = picoclaw::session::import::import_file(&session_manager, &file, format).await?;
+            for session in &imported {
+                println!("  {}  {} messages  {}", session.id, session.messages.len(), session.display_name());
+            }
+            println!("✓ Imported {} conversations", imported.len());
+        }
+        SessionAction::Archive { idle_days } => {
+            let config = load_config_value(&home)?;
+            let sessions_config: picoclaw::config::SessionsConfig =
+                serde_yaml::from_value(config["sessions"].clone()).unwrap_or_default();
+            let days = idle_days.unwrap_or(sessions_config.archive_after_days);
+            let max_idle = std::time::Duration::from_secs(days * 24 * 60 * 60);
+            let archived = archive.archive_idle(&session_manager, max_idle).await?;
+            println!("✓ Archived {} sessions idle for {}+ days", archived.len(), days);
+        }
+        SessionAction::Compress { older_than_days } => {
+            let config = load_required_config(&home)?;
+            let defaults: picoclaw::config::AgentDefaults =
+                serde_yaml::from_value(config["agents"]["defaults"].clone()).unwrap_or_default();
+            let sessions_config: picoclaw::config::SessionsConfig =
+                serde_yaml::from_value(config["sessions"].clone()).unwrap_or_default();
+            let mut compression = sessions_config.compression;
+            if let Some(days) = older_than_days {
+                compression.after_days = days;
+            }
+            let days = compression.after_days;
+            let compressor =
+                picoclaw::session::SessionCompressor::new(build_embedder(&config, &defaults)?, compression)
+                    .with_router(build_router(&config, &defaults)?);
+            let report = compressor.compress_all(&session_manager).await?;
+            println!(
+                "✓ Merged {} exchanges older than {} days in {} sessions",
+                report.exchanges_removed, days, report.sessions
+            );
+        }
+        SessionAction::List { archived: false } => {
+            info!("Listing sessions");
+            let sessions = session_manager.list_sessions().await?;
+            if sessions.is_empty() {
+                println!("No sessions");
+            }
+            for session in sessions {
+                let last_activity: chrono::DateTime<chrono::Local> = session.last_activity.into();
+                println!(
+                    "{}  {}  {} messages  {}",
+                    session.id,
+                    last_activity.format("%Y-%m-%d %H:%M"),
+                    session.messages.len(),
+                    session.display_name()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_experiments() -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let experiments = build_experiments(&load_config_value(&home)?, &format!("{}/.takobull/workspace", home))?;
+    if experiments.is_empty() {
+        println!("No experiments configured");
+        return Ok(());
+    }
+    let mut current = None;
+    for results in experiments.results()? {
+        if current.as_ref() != Some(&results.experiment) {
+            let state = if experiments.is_running(&results.experiment) { "running" } else { "stopped" };
+            println!("{} ({})", results.experiment, state);
+            current = Some(results.experiment.clone());
+        }
+        println!(
+            "  {:<16} {:>5} sessions   👍 {:<4} 👎 {:<4} {}",
+            results.variant,
+            results.sessions,
+            results.up,
+            results.down,
+            results
+                .approval()
+                .map(|approval| format!("{:.0}% positive", approval * 100.0))
+                .unwrap_or_else(|| "no ratings".to_string())
+        );
+    }
+    Ok(())
+}
+
+fn handle_trace(run_id: Option<String>, session: Option<String>, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let store = picoclaw::agent::TraceStore::new(format!("{}/.takobull/workspace", home));
+
+    if let Some(run_id) = run_id {
+        println!("{}", store.load(&run_id)?.render());
+        return Ok(());
+    }
+    let traces = store.recent(session.as_deref(), limit)?;
+    if traces.is_empty() {
+        println!("No traced runs");
+    }
+    for trace in traces {
+        println!(
+            "{}  {}  {:<12} {:>7}ms  {}{}",
+            trace.id,
+            trace.started_at.format("%Y-%m-%d %H:%M:%S"),
+            trace.session_id.as_deref().or(trace.channel.as_deref()).unwrap_or("-"),
+            trace.duration_ms,
+            trace.message,
+            if trace.error.is_some() { " (failed)" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+async fn handle_replay(session_id: String) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let config = load_required_config(&home)?;
+    let session_manager =
+        picoclaw::session::SessionManager::new(format!("{}/.takobull/workspace/sessions", home));
+    let session = session_manager.load_session(&session_id).await?;
+    let executor = build_executor(&home, &config, false, &mut picoclaw::runtime::startup::StartupProfile::start())
+        .await?
+        .with_dry_run(true);
+
+    println!("🔁 Replaying {} (dry run, tools are not executed)", session.display_name());
+    let turns = picoclaw::agent::replay::replay_session(&executor, &session).await?;
+    let mut changed = 0;
+    for (i, turn) in turns.iter().enumerate() {
+        println!("\n── Turn {}: {}", i + 1, turn.user);
+        if !turn.tool_calls.is_empty() {
+            println!("   would call: {}", turn.tool_calls.join(", "));
+        }
+        if turn.changed() {
+            changed += 1;
+            for line in turn.diff() {
+                println!("{}", line);
+            }
+        } else {
+            println!("✓ unchanged");
+        }
+    }
+    println!("\n{} of {} responses changed", changed, turns.len());
+    Ok(())
+}
+
+async fn handle_batch(
+    input: String,
+    output: Option<String>,
+    concurrency: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let home = std::env::var("HOME")?;
+    let config = load_required_config(&home)?;
+    let items = picoclaw::agent::batch::parse_items(&std::fs::read_to_string(&input)?)?;
+    let output = output.unwrap_or_else(|| {
+        let stem = input.strip_suffix(".jsonl").unwrap_or(&input);
+        format!("{}.results.jsonl", stem)
+    });
+    let mut profile = picoclaw::runtime::startup::StartupProfile::start();
+    let executor = std::sync::Arc::new(build_executor(&home, &config, false, &mut profile).await?);
+    profile.finish(None);
+
+    println!("📦 Running {} prompts ({} at a time) → {}", items.len(), concurrency.max(1), output);
+    let total = items.len();
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&output)?);
+    let (mut done, mut failed) = (0, 0);
+    picoclaw::agent::batch::run_batch(executor, items, concurrency, |result| {
+        done += 1;
+        match &result.error {
+            Some(e) => {
+                failed += 1;
+                println!("[{}/{}] {} ❌ {}", done, total, result.id, e);
+            }
+            None => println!("[{}/{}] {} ✓ {}ms", done, total, result.id, result.duration_ms),
+        }
+        serde_json::to_writer(&mut writer, &result)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    })
+    .await?;
+
+    println!("{} of {} prompts succeeded", total - failed, total);
+    Ok(())
+}
+
+async fn handle_memory(action: MemoryAction) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let config = load_required_config(&home)?;
+    let defaults: picoclaw::config::AgentDefaults =
+        serde_yaml::from_value(config["agents"]["defaults"].clone()).unwrap_or_default();
+    let memory = picoclaw::memory::MemoryStore::new(
+        format!("{}/.takobull/workspace", home),
+        build_embedder(&config, &defaults)?,
+    );
+
+    match action {
+        MemoryAction::Forget { query, dry_run, threshold, namespace } => {
+            let namespaces = if namespace.is_empty() {
+                memory.namespaces()?
+            } else {
+                namespace
+            };
+            let report = memory.forget(&namespaces, &query, threshold, dry_run).await?;
+            println!("{}", picoclaw::tools::memory::describe_forget(&report));
+            if dry_run && !report.is_empty() {
+                println!("\nRun again without --dry-run to delete them");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_tools(action: ToolsAction) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let config = load_config_value(&home)?;
+    let gate = picoclaw::tools::ApprovalGate::new(format!("{}/.takobull/workspace", home), approval_config(&config)?);
+
+    match action {
+        ToolsAction::Rules { revoke: Some(id) } => {
+            let rule = gate.revoke(&id).await?;
+            println!("✓ Revoked rule {}: {} `{}`", rule.id, rule.tool, rule.pattern);
+        }
+        ToolsAction::Rules { revoke: None } => {
+            let rules = gate.rules().await?;
+            if rules.is_empty() {
+                println!("No approval rules");
+            }
+            let audit = gate.audit().await?;
+            for rule in rules {
+                let uses = audit
+                    .iter()
+                    .filter(|r| {
+                        r.decision == picoclaw::tools::approval::ApprovalDecision::AutoApproved
+                            && r.rule.as_deref() == Some(rule.id.as_str())
+                    })
+                    .count();
+                println!(
+                    "{}  {:<12} `{}`  for {}  added {}  used {} times",
+                    rule.id,
+                    rule.tool,
+                    rule.pattern,
+                    rule.user_id.as_deref().unwrap_or("everyone"),
+                    rule.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d"),
+                    uses
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_persona(action: PersonaAction) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let workspace = std::path::PathBuf::from(format!("{}/.takobull/workspace", home));
+    let config = load_config_value(&home)?;
+    let templates: std::collections::BTreeMap<String, picoclaw::config::MessageTemplateConfig> =
+        match &config["templates"] {
+            serde_yaml::Value::Null => Default::default(),
+            templates => serde_yaml::from_value(templates.clone())?,
+        };
+
+    match action {
+        PersonaAction::Export { file } => {
+            let bundle = picoclaw::workspace::PersonaBundle::export(&workspace, templates)?;
+            bundle.save(std::path::Path::new(&file))?;
+            println!(
+                "✓ Exported {} files and {} message templates to {}",
+                bundle.files.len(),
+                bundle.templates.len(),
+                file
+            );
+        }
+        PersonaAction::Import { file, force } => {
+            let bundle = picoclaw::workspace::PersonaBundle::load(std::path::Path::new(&file))?;
+            let report = bundle.apply(&workspace, force)?;
+            for path in &report.written {
+                println!("✓ Wrote {}", path.display());
+            }
+            for path in &report.skipped {
+                println!("  Kept existing {}", path.display());
+            }
+            if !report.skipped.is_empty() {
+                println!("\nRun again with --force to replace the kept files");
+            }
+
+            // config.yaml is left alone so its comments and keys survive
+            let missing: std::collections::BTreeMap<_, _> = bundle
+                .templates
+                .iter()
+                .filter(|(name, template)| match templates.get(*name) {
+                    Some(configured) => force && configured.text != template.text,
+                    None => true,
+                })
+                .collect();
+            if !missing.is_empty() {
+                println!("\nCopy these message templates into the templates section of config.yaml:\n");
+                let yaml = serde_yaml::to_string(&serde_yaml::Mapping::from_iter([(
+                    "templates".into(),
+                    serde_yaml::to_value(&missing)?,
+                )]))?;
+                print!("{}", yaml);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Secret store at ~/.takobull/secrets.yaml
+fn secret_store(home: &str) -> picoclaw::auth::SecretStore {
+    picoclaw::auth::SecretStore::new(format!("{}/.takobull/secrets.yaml", home))
+}
+
+fn handle_secrets(action: SecretsAction) -> Result<(), Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")?;
+    let store = secret_store(&home);
+
+    match action {
+        SecretsAction::List => {
+            let names = store.names()?;
+            if names.is_empty() {
+                println!("No secrets");
+            }
+            for name in names {
+                println!("{}", name);
+            }
+        }
+        SecretsAction::Set { name, value } => {
+            let value = match value {
+                Some(v) => v,
+                None => {
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                    input.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            store.set(&name, &value)?;
+            println!("✓ Stored secret {}", name);
+        }
+        SecretsAction::Remove { name } => {
+            if store.remove(&name)? {
+                println!("✓ Removed secret {}", name);
+            } else {
+                println!("No secret named {}", name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_archive_entries(entries: Vec<picoclaw::session::archive::ArchiveEntry>) {
+    if entries.is_empty() {
+        println!("No archived sessions");
+    }
+    for entry in entries {
+        let last_activity: chrono::DateTime<chrono::Local> = entry.last_activity.into();
+        println!(
+            "{}  {}  {} messages  {}",
+            entry.id,
+            last_activity.format("%Y-%m-%d %H:%M"),
+            entry.message_count,
+            entry.title.as_deref().unwrap_or(&entry.snippet)
+        );
+    }
+}
+
+/// Quarantine corrupt workspace files so a bad file never prevents startup
+fn check_workspace_integrity(workspace_path: &str) {
+    let workspace = std::path::Path::new(workspace_path);
+    if !workspace.exists() {
+        return;
+    }
+    match picoclaw::workspace::check_workspace(workspace) {
+        Ok(report) => {
+            for (original, target) in &report.quarantined {
+                eprintln!("⚠️  Corrupt file moved aside: {:?} -> {:?}", original, target);
+            }
+        }
+        Err(e) => tracing::warn!("Workspace integrity check failed: {}", e),
+    }
+}
+
+/// Read ~/.takobull/config.yaml, returning an empty document if it does not exist
+fn load_config_value(home: &str) -> Result<serde_yaml::Value, Box<dyn std::error::Error>> {
+    let config_path = format!("{}/.takobull/config.yaml", home);
+    if !std::path::Path::new(&config_path).exists() {
+        return Ok(serde_yaml::Value::Null);
+    }
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(&config_path)?)?)
+}
+
+async fn handle_onboard(template: Option<String>, force: bool) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting onboard process");
     
     let home = std::env::var("HOME")?;
@@ -275,6 +2174,9 @@ agents:
     max_tokens: 8192
     temperature: 0.7
     max_tool_iterations: 20
+    # summary_model: "openai/gpt-4o-mini"
+  language:
+    detect: true
 
 channels:
   telegram:
@@ -311,6 +2213,34 @@ tools:
       enabled: true
       max_results: 5
 
+  filesystem:
+    enabled: true
+    read_only: []
+    writable: []
+    deny: ["**/.env", "*.key"]
+    symlinks: follow_within
+
+  shell:
+    enabled: false
+    timeout_secs: 60
+
+gateway:
+  host: "127.0.0.1"
+  port: 18790
+  http_enabled: true
+  dashboard: true
+  # Open the dashboard as /?token=<auth_token>
+  auth_token: "{auth_token}"
+  mdns: true
+
+sessions:
+  archive_after_days: 30
+
+telemetry:
+  enabled: false
+  retention_days: 14
+  sensors: []
+
 heartbeat:
   enabled: true
   interval: 30
@@ -319,12 +2249,42 @@ logging:
   level: "info"
   format: "json"
 "#;
-        std::fs::write(&config_path, default_config)?;
+        let auth_token = uuid::Uuid::new_v4().simple().to_string();
+        std::fs::write(&config_path, default_config.replace("{auth_token}", &auth_token))?;
         println!("✓ Created default config: {}", config_path);
     } else {
         println!("✓ Config already exists: {}", config_path);
     }
+
+    // Record what hardware this machine offers, once
+    let hardware = picoclaw::device::detect::detect(std::path::Path::new("/"));
+    println!("✓ Detected {}", hardware.summary());
+    for device in &hardware.suggested_devices {
+        println!("  suggested device {} ({:?} {} at {})", device.id, device.device_type, device.bus, device.address);
+    }
+    let existing = std::fs::read_to_string(&config_path)?;
+    let recorded = serde_yaml::from_str::<serde_yaml::Value>(&existing)
+        .map(|config| !config["hardware"].is_null())
+        .unwrap_or(false);
+    if recorded {
+        println!("  kept the existing hardware section in {}", config_path);
+    } else {
+        let mut config = existing;
+        config.push_str(&hardware.to_config_section()?);
+        std::fs::write(&config_path, config)?;
+        println!("✓ Wrote detected hardware to {}", config_path);
+    }
     
+    // Seed the template pack before the default files so its content wins
+    if let Some(spec) = template {
+        let pack = picoclaw::workspace::template::resolve(&spec).await?;
+        let report = pack.apply(std::path::Path::new(&workspace_dir), force)?;
+        println!("✓ Applied template '{}' ({} files)", pack.name, report.written.len());
+        for path in &report.skipped {
+            println!("  kept existing {} (use --force to overwrite)", path.display());
+        }
+    }
+
     // Create workspace files
     let workspace_files = vec![
         ("AGENTS.md", "# Agent Configuration\n\nConfigure agent behavior here.\n"),
@@ -0,0 +1,195 @@
+//! Text embedders for the vector store
+
+use crate::error::Result;
+use crate::llm::LlmClient;
+use async_trait::async_trait;
+
+/// Turns text into a vector for similarity search
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Embed `text`
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+
+    /// Identifies the embedding space; vectors from different embedders are never compared
+    fn name(&self) -> &str;
+}
+
+/// Words too common to say anything about relevance
+const STOPWORDS: &[&str] = &[
+    "a", "about", "am", "an", "and", "are", "as", "at", "be", "been", "but", "by", "can", "could",
+    "did", "do", "does", "for", "from", "had", "has", "have", "he", "her", "his", "how", "i", "if",
+    "in", "is", "it", "its", "me", "my", "not", "of", "on", "or", "our", "she", "should", "so",
+    "that", "the", "their", "them", "there", "they", "this", "to", "us", "was", "we", "were",
+    "what", "when", "where", "which", "who", "will", "with", "would", "you", "your",
+];
+
+/// Strip common English suffixes so "runs", "running", and "run" match
+fn stem(word: &str) -> &str {
+    for suffix in ["ing", "ed", "es", "s"] {
+        if let Some(stripped) = word.strip_suffix(suffix) {
+            if stripped.chars().count() >= 3 && !(suffix == "s" && stripped.ends_with('s')) {
+                return stripped;
+            }
+        }
+    }
+    word
+}
+
+/// Lowercased, stemmed words of `text` without stopwords
+pub(crate) fn terms(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(w))
+        .map(|w| stem(w).to_string())
+        .collect()
+}
+
+/// Offline embedder hashing words and word pairs into a fixed-size vector
+///
+/// Far weaker than a real embedding model, but needs no network or model
+/// files, which makes it the default on small boards.
+pub struct HashEmbedder {
+    dims: usize,
+    name: String,
+}
+
+impl HashEmbedder {
+    /// Create an embedder producing `dims`-dimensional vectors
+    pub fn new(dims: usize) -> Self {
+        let dims = dims.max(16);
+        Self {
+            dims,
+            name: format!("hash-{}", dims),
+        }
+    }
+
+    /// Vector index for a term, reduced in 64 bits so a 32-bit board
+    /// produces the same vectors as the machine that stored the memories
+    fn bucket(&self, bytes: &[u8]) -> usize {
+        (fnv1a(bytes) % self.dims as u64) as usize
+    }
+
+    /// Embed synchronously
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let words = terms(text);
+
+        let mut vector = vec![0.0f32; self.dims];
+        for word in &words {
+            vector[self.bucket(word.as_bytes())] += 1.0;
+        }
+        for pair in words.windows(2) {
+            let joined = format!("{} {}", pair[0], pair[1]);
+            vector[self.bucket(joined.as_bytes())] += 0.25;
+        }
+        normalize(&mut vector);
+        vector
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_text(text))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Embedder calling a provider's OpenAI-compatible `/embeddings` endpoint
+pub struct ApiEmbedder {
+    client: LlmClient,
+    name: String,
+}
+
+impl ApiEmbedder {
+    /// Embed with `client`, whose model must be an embedding model
+    pub fn new(client: LlmClient) -> Self {
+        let name = format!("{}/{}", client.provider(), client.model());
+        Self { client, name }
+    }
+}
+
+#[async_trait]
+impl Embedder for ApiEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vector = self.client.embed(text).await?;
+        normalize(&mut vector);
+        Ok(vector)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Cosine similarity of two vectors, 0.0 if their lengths differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// FNV-1a, stable across builds unlike the std hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stem() {
+        assert_eq!(stem("runs"), "run");
+        assert_eq!(stem("watering"), "water");
+        assert_eq!(stem("glass"), "glass");
+        assert_eq!(stem("is"), "is");
+    }
+
+    #[test]
+    fn test_buckets_do_not_depend_on_pointer_width() {
+        // Truncating the hash to a 32-bit usize first would give index 63
+        let vector = HashEmbedder::new(100).embed_text("glass");
+        let hot: Vec<usize> = (0..vector.len()).filter(|&i| vector[i] != 0.0).collect();
+        assert_eq!(hot, vec![3]);
+    }
+
+    #[test]
+    fn test_hash_embedder_similarity() {
+        let embedder = HashEmbedder::default();
+        let fact = embedder.embed_text("The garden sprinkler runs at 6am on weekdays");
+        let related = embedder.embed_text("When does the garden sprinkler run?");
+        let unrelated = embedder.embed_text("Alice is allergic to peanuts");
+
+        assert!(cosine_similarity(&fact, &related) > cosine_similarity(&fact, &unrelated));
+        assert!((cosine_similarity(&fact, &fact) - 1.0).abs() < 1e-5);
+        assert_eq!(cosine_similarity(&fact, &[1.0]), 0.0);
+    }
+}
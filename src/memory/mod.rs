@@ -0,0 +1,340 @@
+//! Long-term memory: remembered facts in MEMORY.md and a vector store
+//!
+//! Every fact is appended to a human-readable `MEMORY.md` and embedded into
+//! a JSONL vector store, which the executor searches before each run to
+//! recall relevant facts. Facts are partitioned into [`Namespace`]s so one
+//! user's facts are never recalled in another user's conversation.
+//! Embeddings come from a provider's `/embeddings` endpoint when
+//! `memory.embedding_model` is configured and from the offline
+//! [`HashEmbedder`] otherwise. [`MemoryStore::forget`] purges matching facts
+//! from both files. Deliberately captured [`notes`] share the embedder but
+//! are stored and searched separately.
+
+pub mod embed;
+pub mod namespace;
+pub mod notes;
+pub mod store;
+
+pub use embed::{cosine_similarity, ApiEmbedder, Embedder, HashEmbedder};
+pub use namespace::Namespace;
+pub use notes::{Note, NoteMatch, NoteStore};
+pub use store::{MemoryEntry, ScoredEntry, VectorStore};
+
+use crate::error::{Error, Result};
+use crate::session::Redactor;
+use chrono::Utc;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Shared namespace's memory file in the workspace
+pub const MEMORY_FILE: &str = "MEMORY.md";
+
+/// Shared namespace's vector store file, relative to the workspace
+pub const VECTORS_FILE: &str = "memory/vectors.jsonl";
+
+/// Similarity above which `forget` treats a memory as matching its query
+pub const DEFAULT_FORGET_THRESHOLD: f32 = 0.5;
+
+/// A memory recalled for a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recalled {
+    pub namespace: Namespace,
+    pub entry: MemoryEntry,
+    /// Cosine similarity to the query
+    pub score: f32,
+}
+
+/// Memories matched (and, unless a dry run, removed) by [`MemoryStore::forget`]
+#[derive(Debug, Clone, Default)]
+pub struct ForgetReport {
+    /// Matching vector store entries
+    pub entries: Vec<(Namespace, MemoryEntry)>,
+    /// Matching MEMORY.md lines
+    pub lines: Vec<(Namespace, String)>,
+    pub dry_run: bool,
+}
+
+impl ForgetReport {
+    /// Whether nothing matched
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.lines.is_empty()
+    }
+}
+
+/// Remembers facts and recalls them by similarity
+pub struct MemoryStore {
+    workspace: PathBuf,
+    embedder: Arc<dyn Embedder>,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl MemoryStore {
+    /// Memory kept in `workspace`, embedded with `embedder`
+    pub fn new(workspace: impl Into<PathBuf>, embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            workspace: workspace.into(),
+            embedder,
+            redactor: None,
+        }
+    }
+
+    /// Redact facts with `redactor` before they are stored
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Path of a namespace's MEMORY.md
+    pub fn memory_file(&self, namespace: &Namespace) -> PathBuf {
+        namespace.memory_file(&self.workspace)
+    }
+
+    /// A namespace's vector store
+    pub fn vectors(&self, namespace: &Namespace) -> VectorStore {
+        VectorStore::new(namespace.vectors_file(&self.workspace))
+    }
+
+    /// The shared namespace and every user and agent namespace holding memory
+    pub fn namespaces(&self) -> Result<Vec<Namespace>> {
+        let mut namespaces = vec![Namespace::Shared];
+        namespaces.extend(Namespace::existing(&self.workspace)?);
+        Ok(namespaces)
+    }
+
+    /// Record a fact in a namespace's MEMORY.md and vector store
+    pub async fn remember(&self, namespace: &Namespace, text: &str) -> Result<MemoryEntry> {
+        let mut text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return Err(Error::tool("Nothing to remember"));
+        }
+        if let Some(redactor) = &self.redactor {
+            text = redactor.redact(&text);
+        }
+
+        let entry = MemoryEntry {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            embedding: self.embedder.embed(&text).await?,
+            embedder: self.embedder.name().to_string(),
+            text,
+            created_at: Utc::now(),
+        };
+        append_line(&self.memory_file(namespace), &entry.text)?;
+        self.vectors(namespace).add(&entry)?;
+        Ok(entry)
+    }
+
+    /// Up to `top_k` memories from `namespaces` similar to `query` with a
+    /// score of at least `threshold`, best first
+    pub async fn recall(
+        &self,
+        namespaces: &[Namespace],
+        query: &str,
+        top_k: usize,
+        threshold: f32,
+    ) -> Result<Vec<Recalled>> {
+        if top_k == 0 || namespaces.is_empty() {
+            return Ok(Vec::new());
+        }
+        let embedding = self.embedder.embed(query).await?;
+        let mut recalled = Vec::new();
+        for namespace in namespaces {
+            let scored = self
+                .vectors(namespace)
+                .search(self.embedder.name(), &embedding, top_k, threshold)?;
+            recalled.extend(scored.into_iter().map(|scored| Recalled {
+                namespace: namespace.clone(),
+                entry: scored.entry,
+                score: scored.score,
+            }));
+        }
+        recalled.sort_by(|a, b| b.score.total_cmp(&a.score));
+        recalled.truncate(top_k);
+        Ok(recalled)
+    }
+
+    /// Remove memories matching `query` from the vector stores and MEMORY.md
+    /// files of `namespaces`
+    ///
+    /// An entry matches when it contains `query` (ignoring case) or its
+    /// similarity reaches `threshold`; a MEMORY.md line matches when it
+    /// contains `query` or is the text of a matching entry. With `dry_run`
+    /// the matches are only reported.
+    pub async fn forget(
+        &self,
+        namespaces: &[Namespace],
+        query: &str,
+        threshold: f32,
+        dry_run: bool,
+    ) -> Result<ForgetReport> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Err(Error::tool("Nothing to forget: the query is empty"));
+        }
+
+        let embedding = self.embedder.embed(query).await?;
+        let mut report = ForgetReport {
+            dry_run,
+            ..Default::default()
+        };
+        for namespace in namespaces {
+            let vectors = self.vectors(namespace);
+            let entries: Vec<MemoryEntry> = vectors
+                .entries()?
+                .into_iter()
+                .filter(|entry| {
+                    entry.text.to_lowercase().contains(&needle)
+                        || (entry.embedder == self.embedder.name()
+                            && cosine_similarity(&embedding, &entry.embedding) >= threshold)
+                })
+                .collect();
+
+            let memory_file = self.memory_file(namespace);
+            let contents = if memory_file.exists() {
+                std::fs::read_to_string(&memory_file)?
+            } else {
+                String::new()
+            };
+            let matches_line = |line: &str| {
+                let text = line.trim().trim_start_matches("- ").trim();
+                !line.trim_start().starts_with('#')
+                    && !text.is_empty()
+                    && (text.to_lowercase().contains(&needle)
+                        || entries.iter().any(|entry| entry.text == text))
+            };
+            let lines: Vec<String> = contents
+                .lines()
+                .filter(|line| matches_line(line))
+                .map(str::to_string)
+                .collect();
+
+            if !dry_run {
+                let ids: Vec<String> = entries.iter().map(|entry| entry.id.clone()).collect();
+                vectors.remove(&ids)?;
+                if !lines.is_empty() {
+                    let mut kept: String = contents
+                        .lines()
+                        .filter(|line| !matches_line(line))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    kept.push('\n');
+                    let tmp_path = memory_file.with_extension("md.tmp");
+                    std::fs::write(&tmp_path, kept)?;
+                    std::fs::rename(&tmp_path, &memory_file)?;
+                }
+            }
+
+            report
+                .entries
+                .extend(entries.into_iter().map(|entry| (namespace.clone(), entry)));
+            report
+                .lines
+                .extend(lines.into_iter().map(|line| (namespace.clone(), line)));
+        }
+        Ok(report)
+    }
+}
+
+/// Append `text` as a bullet to MEMORY.md, creating it with a heading if missing
+fn append_line(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let exists = path.exists();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    if !exists {
+        writeln!(file, "# Long-term Memory\n")?;
+    }
+    writeln!(file, "- {}", text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path) -> MemoryStore {
+        MemoryStore::new(dir, Arc::new(HashEmbedder::default()))
+    }
+
+    #[tokio::test]
+    async fn test_remember_and_recall() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        let shared = [Namespace::Shared];
+        store.remember(&Namespace::Shared, "The garden sprinkler runs at 6am").await.unwrap();
+        store.remember(&Namespace::Shared, "Alice is allergic to peanuts").await.unwrap();
+
+        let recalled = store.recall(&shared, "when does the sprinkler run", 3, 0.3).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].entry.text, "The garden sprinkler runs at 6am");
+        assert_eq!(recalled[0].namespace, Namespace::Shared);
+
+        let memory = std::fs::read_to_string(dir.path().join(MEMORY_FILE)).unwrap();
+        assert!(memory.contains("- Alice is allergic to peanuts"));
+        assert!(store.remember(&Namespace::Shared, "   ").await.is_err());
+
+        let redactor = Redactor::new(&crate::config::RedactionConfig::default()).unwrap();
+        let store = store.with_redactor(Arc::new(redactor));
+        let entry = store.remember(&Namespace::Shared, "Vet: 020 7946 0958").await.unwrap();
+        assert_eq!(entry.text, "Vet: [REDACTED:phone]");
+    }
+
+    #[tokio::test]
+    async fn test_recall_never_crosses_users() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        let alice = Namespace::User("alice".to_string());
+        let bob = Namespace::User("bob".to_string());
+        store.remember(&alice, "My doctor appointment is on Friday").await.unwrap();
+        store.remember(&Namespace::Shared, "The family doctor is Dr. Lee").await.unwrap();
+
+        let for_bob = Namespace::visible(Some("bob"), None);
+        let recalled = store.recall(&for_bob, "doctor appointment", 5, 0.2).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].namespace, Namespace::Shared);
+
+        let for_alice = Namespace::visible(Some("alice"), None);
+        let recalled = store.recall(&for_alice, "doctor appointment", 5, 0.2).await.unwrap();
+        assert_eq!(recalled[0].namespace, alice);
+        assert_eq!(recalled.len(), 2);
+
+        assert!(store.vectors(&bob).entries().unwrap().is_empty());
+        assert_eq!(store.namespaces().unwrap(), vec![Namespace::Shared, alice]);
+    }
+
+    #[tokio::test]
+    async fn test_forget_dry_run_then_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        let alice = Namespace::User("alice".to_string());
+        store.remember(&Namespace::Shared, "Home address is 12 Elm Street").await.unwrap();
+        store.remember(&Namespace::Shared, "Alice is allergic to peanuts").await.unwrap();
+        store.remember(&alice, "Alice grew up on Elm Street").await.unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(store.memory_file(&Namespace::Shared))
+            .unwrap()
+            .write_all(b"Bob moved from Elm Street in 2020\n")
+            .unwrap();
+
+        let all = store.namespaces().unwrap();
+        let report = store.forget(&all, "elm street", DEFAULT_FORGET_THRESHOLD, true).await.unwrap();
+        assert_eq!(report.entries.len(), 2);
+        assert_eq!(report.lines.len(), 3);
+        assert_eq!(store.vectors(&Namespace::Shared).entries().unwrap().len(), 2);
+
+        store.forget(&all, "elm street", DEFAULT_FORGET_THRESHOLD, false).await.unwrap();
+        let remaining = store.vectors(&Namespace::Shared).entries().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].text, "Alice is allergic to peanuts");
+        assert!(store.vectors(&alice).entries().unwrap().is_empty());
+        let memory = std::fs::read_to_string(store.memory_file(&Namespace::Shared)).unwrap();
+        assert!(memory.starts_with("# Long-term Memory"));
+        assert!(!memory.to_lowercase().contains("elm street"));
+        assert!(store.forget(&all, "  ", DEFAULT_FORGET_THRESHOLD, true).await.is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Memory namespaces partitioning facts by user and agent
+
+use crate::error::{Error, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Directory under the workspace holding per-user and per-agent memory
+const NAMESPACES_DIR: &str = "memory";
+
+/// Whose memory a fact belongs to
+///
+/// Shared facts live in the workspace's MEMORY.md and `memory/vectors.jsonl`
+/// as before namespaces existed; user and agent facts live under
+/// `memory/users/<id>/` and `memory/agents/<name>/`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    /// Household-wide facts visible to every user and agent
+    Shared,
+    /// Facts about one user, never recalled for anyone else
+    User(String),
+    /// Facts private to one agent
+    Agent(String),
+}
+
+impl Namespace {
+    /// Namespaces a caller may read: shared, then its user's and agent's own
+    pub fn visible(user_id: Option<&str>, agent: Option<&str>) -> Vec<Namespace> {
+        let mut namespaces = vec![Namespace::Shared];
+        if let Some(user_id) = user_id.filter(|id| !id.is_empty()) {
+            namespaces.push(Namespace::User(user_id.to_string()));
+        }
+        if let Some(agent) = agent.filter(|name| !name.is_empty()) {
+            namespaces.push(Namespace::Agent(agent.to_string()));
+        }
+        namespaces
+    }
+
+    /// Short description for labeling recalled facts
+    pub fn label(&self) -> &'static str {
+        match self {
+            Namespace::Shared => "household",
+            Namespace::User(_) => "this user",
+            Namespace::Agent(_) => "this agent",
+        }
+    }
+
+    /// MEMORY.md for this namespace
+    pub fn memory_file(&self, workspace: &Path) -> PathBuf {
+        match self {
+            Namespace::Shared => workspace.join(super::MEMORY_FILE),
+            _ => self.dir(workspace).join(super::MEMORY_FILE),
+        }
+    }
+
+    /// Vector store file for this namespace
+    pub fn vectors_file(&self, workspace: &Path) -> PathBuf {
+        match self {
+            Namespace::Shared => workspace.join(super::VECTORS_FILE),
+            _ => self.dir(workspace).join("vectors.jsonl"),
+        }
+    }
+
+    fn dir(&self, workspace: &Path) -> PathBuf {
+        let base = workspace.join(NAMESPACES_DIR);
+        match self {
+            Namespace::Shared => base,
+            Namespace::User(id) => base.join("users").join(encode(id)),
+            Namespace::Agent(name) => base.join("agents").join(encode(name)),
+        }
+    }
+
+    /// User and agent namespaces with memory stored under `workspace`
+    pub fn existing(workspace: &Path) -> Result<Vec<Namespace>> {
+        let mut namespaces = Vec::new();
+        let base = workspace.join(NAMESPACES_DIR);
+        for (kind, make) in [
+            ("users", Namespace::User as fn(String) -> Namespace),
+            ("agents", Namespace::Agent),
+        ] {
+            let dir = base.join(kind);
+            if !dir.is_dir() {
+                continue;
+            }
+            let mut names: Vec<String> = std::fs::read_dir(&dir)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| decode(&entry.file_name().to_string_lossy()))
+                .collect();
+            names.sort();
+            namespaces.extend(names.into_iter().map(make));
+        }
+        Ok(namespaces)
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Namespace::Shared => write!(f, "shared"),
+            Namespace::User(id) => write!(f, "user:{}", id),
+            Namespace::Agent(name) => write!(f, "agent:{}", name),
+        }
+    }
+}
+
+impl FromStr for Namespace {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            None if s == "shared" => Ok(Namespace::Shared),
+            Some(("user", id)) if !id.is_empty() => Ok(Namespace::User(id.to_string())),
+            Some(("agent", name)) if !name.is_empty() => Ok(Namespace::Agent(name.to_string())),
+            _ => Err(Error::config(format!(
+                "Invalid memory namespace '{}' (expected shared, user:<id>, or agent:<name>)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Directory name for an id; ids that are not plain names are hex-encoded behind `~`
+fn encode(id: &str) -> String {
+    let plain = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if plain {
+        id.to_string()
+    } else {
+        format!("~{}", id.bytes().map(|b| format!("{:02x}", b)).collect::<String>())
+    }
+}
+
+fn decode(name: &str) -> Option<String> {
+    let Some(hex) = name.strip_prefix('~') else {
+        return Some(name.to_string());
+    };
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes: Option<Vec<u8>> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect();
+    String::from_utf8(bytes?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_paths_stay_in_workspace() {
+        let workspace = Path::new("/ws");
+        assert_eq!(Namespace::Shared.memory_file(workspace), Path::new("/ws/MEMORY.md"));
+        assert_eq!(
+            Namespace::User("42".to_string()).vectors_file(workspace),
+            Path::new("/ws/memory/users/42/vectors.jsonl")
+        );
+        let sneaky = Namespace::User("../../etc".to_string()).memory_file(workspace);
+        assert!(sneaky.starts_with("/ws/memory/users"));
+        assert_eq!(decode(&encode("../../etc")).as_deref(), Some("../../etc"));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for text in ["shared", "user:alice", "agent:butler"] {
+            assert_eq!(text.parse::<Namespace>().unwrap().to_string(), text);
+        }
+        assert!("user:".parse::<Namespace>().is_err());
+        assert!("everyone".parse::<Namespace>().is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Structured knowledge notes
+//!
+//! Notes are captured deliberately (a recipe, a wiring diagram, a warranty
+//! number) rather than distilled from conversation, so they are kept apart
+//! from remembered facts and never recalled automatically. Each note is a
+//! line in `notes/notes.jsonl` and is found by a mix of keyword and
+//! embedding similarity.
+
+use super::embed::{cosine_similarity, terms, Embedder};
+use crate::error::{Error, Result};
+use crate::session::Redactor;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Notes file, relative to the workspace
+pub const NOTES_FILE: &str = "notes/notes.jsonl";
+
+/// Weight of keyword overlap in a note's search score; embedding similarity gets the rest
+const KEYWORD_WEIGHT: f32 = 0.5;
+
+/// A structured note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where the note came from, e.g. a URL or "conversation"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Name of the embedder that produced `embedding`
+    pub embedder: String,
+    pub embedding: Vec<f32>,
+}
+
+/// A note matched by a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteMatch {
+    pub note: Note,
+    pub score: f32,
+}
+
+/// Notes kept in the workspace
+pub struct NoteStore {
+    path: PathBuf,
+    embedder: Arc<dyn Embedder>,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl NoteStore {
+    /// Notes in `workspace`, embedded with `embedder`
+    pub fn new(workspace: impl AsRef<Path>, embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            path: workspace.as_ref().join(NOTES_FILE),
+            embedder,
+            redactor: None,
+        }
+    }
+
+    /// Redact titles and bodies with `redactor` before they are stored
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// All notes, skipping unreadable lines
+    pub fn notes(&self) -> Result<Vec<Note>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ok(contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(note) => Some(note),
+                Err(e) => {
+                    warn!("Skipping unreadable note in {:?}: {}", self.path, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Add a note; tags are lowercased and deduplicated
+    pub async fn add(
+        &self,
+        title: &str,
+        body: &str,
+        tags: &[String],
+        source: Option<&str>,
+    ) -> Result<Note> {
+        if title.trim().is_empty() {
+            return Err(Error::tool("A note needs a title"));
+        }
+        let (title, body) = match &self.redactor {
+            Some(redactor) => (redactor.redact(title.trim()), redactor.redact(body)),
+            None => (title.trim().to_string(), body.to_string()),
+        };
+        let (title, body) = (title.as_str(), body.as_str());
+
+        let mut seen = HashSet::new();
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+            .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+            .collect();
+        let note = Note {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            embedding: self.embedder.embed(&searchable_text(title, body, &tags)).await?,
+            embedder: self.embedder.name().to_string(),
+            title: title.to_string(),
+            body: body.trim().to_string(),
+            tags,
+            source: source.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string),
+            created_at: Utc::now(),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&note)?)?;
+        Ok(note)
+    }
+
+    /// Up to `limit` notes carrying every tag in `tags`, ranked by keyword
+    /// overlap and embedding similarity with `query`
+    ///
+    /// An empty query lists the tagged notes, newest first.
+    pub async fn search(&self, query: &str, tags: &[String], limit: usize) -> Result<Vec<NoteMatch>> {
+        let tags: Vec<String> = tags.iter().map(|t| t.trim_start_matches('#').to_lowercase()).collect();
+        let mut notes: Vec<Note> = self
+            .notes()?
+            .into_iter()
+            .filter(|note| tags.iter().all(|tag| note.tags.contains(tag)))
+            .collect();
+
+        let query_terms: HashSet<String> = terms(query).into_iter().collect();
+        if query_terms.is_empty() {
+            notes.sort_by_key(|note| std::cmp::Reverse(note.created_at));
+            notes.truncate(limit);
+            return Ok(notes.into_iter().map(|note| NoteMatch { note, score: 0.0 }).collect());
+        }
+
+        let embedding = self.embedder.embed(query).await?;
+        let mut matches: Vec<NoteMatch> = notes
+            .into_iter()
+            .filter_map(|note| {
+                let note_terms: HashSet<String> =
+                    terms(&searchable_text(&note.title, &note.body, &note.tags)).into_iter().collect();
+                let keyword = query_terms.intersection(&note_terms).count() as f32 / query_terms.len() as f32;
+                let semantic = if note.embedder == self.embedder.name() {
+                    cosine_similarity(&embedding, &note.embedding).max(0.0)
+                } else {
+                    0.0
+                };
+                let score = KEYWORD_WEIGHT * keyword + (1.0 - KEYWORD_WEIGHT) * semantic;
+                (keyword > 0.0 || semantic > 0.2).then_some(NoteMatch { note, score })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+}
+
+/// Text a note is embedded and keyword-matched on
+fn searchable_text(title: &str, body: &str, tags: &[String]) -> String {
+    format!("{}\n{}\n{}", title, tags.join(" "), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::HashEmbedder;
+
+    #[tokio::test]
+    async fn test_add_and_search_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = NoteStore::new(dir.path(), Arc::new(HashEmbedder::default()));
+        let tags = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        store
+            .add("Boiler reset", "Hold the red button for 10 seconds", &tags(&["#Home", "home"]), None)
+            .await
+            .unwrap();
+        store
+            .add("Sourdough", "Feed the starter every 12 hours", &tags(&["recipes"]), Some("grandma"))
+            .await
+            .unwrap();
+
+        let found = store.search("how to reset the boiler", &[], 5).await.unwrap();
+        assert_eq!(found[0].note.title, "Boiler reset");
+        assert_eq!(found[0].note.tags, vec!["home"]);
+        assert_eq!(found.len(), 1);
+
+        let recipes = store.search("", &tags(&["recipes"]), 5).await.unwrap();
+        assert_eq!(recipes.len(), 1);
+        assert_eq!(recipes[0].note.source.as_deref(), Some("grandma"));
+        assert!(store.search("boiler", &tags(&["recipes"]), 5).await.unwrap().is_empty());
+        assert!(store.add(" ", "body", &[], None).await.is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! JSONL-backed vector store
+
+use super::embed::cosine_similarity;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A remembered fact with its embedding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub text: String,
+    /// Name of the embedder that produced `embedding`
+    pub embedder: String,
+    pub embedding: Vec<f32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A memory matched by a search, with its cosine similarity to the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredEntry {
+    pub entry: MemoryEntry,
+    pub score: f32,
+}
+
+/// Vector store keeping one entry per line in a JSONL file
+///
+/// Stores on these boards hold at most a few thousand entries, so searches
+/// scan the whole file rather than maintaining an index.
+pub struct VectorStore {
+    path: PathBuf,
+}
+
+impl VectorStore {
+    /// Open the store at `path` (created on first write)
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the backing file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All entries, skipping unreadable lines
+    pub fn entries(&self) -> Result<Vec<MemoryEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ok(contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Skipping unreadable memory entry in {:?}: {}", self.path, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Append an entry
+    pub fn add(&self, entry: &MemoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Remove entries with the given ids, returning how many were removed
+    pub fn remove(&self, ids: &[String]) -> Result<usize> {
+        let entries = self.entries()?;
+        let before = entries.len();
+        let kept: Vec<MemoryEntry> = entries
+            .into_iter()
+            .filter(|entry| !ids.contains(&entry.id))
+            .collect();
+        let removed = before - kept.len();
+        if removed > 0 {
+            self.write_all(&kept)?;
+        }
+        Ok(removed)
+    }
+
+    /// Replace the file's contents with `entries`
+    fn write_all(&self, entries: &[MemoryEntry]) -> Result<()> {
+        let mut contents = String::new();
+        for entry in entries {
+            contents.push_str(&serde_json::to_string(entry)?);
+            contents.push('\n');
+        }
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp_path, contents)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Up to `top_k` entries from `embedder` scoring at least `threshold`, best first
+    pub fn search(
+        &self,
+        embedder: &str,
+        query: &[f32],
+        top_k: usize,
+        threshold: f32,
+    ) -> Result<Vec<ScoredEntry>> {
+        let mut scored: Vec<ScoredEntry> = self
+            .entries()?
+            .into_iter()
+            .filter(|entry| entry.embedder == embedder)
+            .map(|entry| {
+                let score = cosine_similarity(query, &entry.embedding);
+                ScoredEntry { entry, score }
+            })
+            .filter(|scored| scored.score >= threshold)
+            .collect();
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scored.truncate(top_k);
+        Ok(scored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, embedding: Vec<f32>) -> MemoryEntry {
+        MemoryEntry {
+            id: id.to_string(),
+            text: id.to_string(),
+            embedder: "test".to_string(),
+            embedding,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = VectorStore::new(dir.path().join("memory/vectors.jsonl"));
+        store.add(&entry("close", vec![1.0, 0.1])).unwrap();
+        store.add(&entry("exact", vec![1.0, 0.0])).unwrap();
+        store.add(&entry("far", vec![0.0, 1.0])).unwrap();
+        let mut other = entry("other-embedder", vec![1.0, 0.0]);
+        other.embedder = "other".to_string();
+        store.add(&other).unwrap();
+
+        let results = store.search("test", &[1.0, 0.0], 5, 0.5).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.entry.id.as_str()).collect();
+        assert_eq!(ids, vec!["exact", "close"]);
+        assert_eq!(store.search("test", &[1.0, 0.0], 1, 0.5).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_remove_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = VectorStore::new(dir.path().join("vectors.jsonl"));
+        store.add(&entry("a", vec![1.0])).unwrap();
+        store.add(&entry("b", vec![1.0])).unwrap();
+
+        assert_eq!(store.remove(&["a".to_string(), "missing".to_string()]).unwrap(), 1);
+        let ids: Vec<String> = store.entries().unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["b"]);
+        assert_eq!(store.remove(&["a".to_string()]).unwrap(), 0);
+    }
+}
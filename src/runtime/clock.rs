@@ -0,0 +1,177 @@
+//! System clock sanity checks for devices without an RTC
+//!
+//! Boards like the Raspberry Pi boot with the clock at the epoch (or the last
+//! fake-hwclock save) until NTP syncs. TLS certificate validation and the
+//! scheduler's missed-job catch-up both misbehave on a wrong clock. Startup
+//! consults [`TimeSanity`] to warn that HTTPS requests may fail until the
+//! clock is synchronized, without holding them back, and the scheduler waits
+//! for the clock to become plausible before catching up on missed jobs.
+
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// Earliest plausible wall-clock time (2026-02-15, the 0.2.0 release date)
+pub const MIN_PLAUSIBLE_UNIX_SECS: u64 = 1_771_113_600;
+
+/// Marker file systemd-timesyncd creates once the clock is synchronized
+const TIMESYNC_MARKER: &str = "/run/systemd/timesync/synchronized";
+
+const STATE_FILE: &str = "clock.json";
+
+/// Result of a clock sanity check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClockStatus {
+    /// Clock is plausible
+    Sane,
+    /// Clock is before the earliest plausible time
+    BeforeMinimum,
+    /// Clock is behind the last known good time
+    BehindLastKnown { by: Duration },
+}
+
+impl ClockStatus {
+    /// Whether the clock can be trusted
+    pub fn is_sane(&self) -> bool {
+        matches!(self, ClockStatus::Sane)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ClockState {
+    last_known_good: SystemTime,
+}
+
+/// Detects a wildly wrong system clock and waits for it to be corrected
+pub struct TimeSanity {
+    state_path: PathBuf,
+    tolerance: Duration,
+}
+
+impl TimeSanity {
+    /// Create a checker persisting the last known good time in `state_dir`
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            state_path: state_dir.join(STATE_FILE),
+            tolerance: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Allowed backwards drift relative to the last known good time
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Last time the clock was known to be good
+    pub fn last_known_good(&self) -> Option<SystemTime> {
+        let contents = std::fs::read_to_string(&self.state_path).ok()?;
+        serde_json::from_str::<ClockState>(&contents)
+            .ok()
+            .map(|s| s.last_known_good)
+    }
+
+    /// Check the current system clock
+    pub fn check(&self) -> ClockStatus {
+        if is_ntp_synchronized() {
+            return ClockStatus::Sane;
+        }
+        self.check_at(SystemTime::now())
+    }
+
+    /// Check whether `now` is a plausible wall-clock time
+    pub fn check_at(&self, now: SystemTime) -> ClockStatus {
+        let min = UNIX_EPOCH + Duration::from_secs(MIN_PLAUSIBLE_UNIX_SECS);
+        if now < min {
+            return ClockStatus::BeforeMinimum;
+        }
+        if let Some(last) = self.last_known_good() {
+            if let Ok(by) = last.duration_since(now) {
+                if by > self.tolerance {
+                    return ClockStatus::BehindLastKnown { by };
+                }
+            }
+        }
+        ClockStatus::Sane
+    }
+
+    /// Persist the current time as known good if the clock is sane
+    pub fn record_good_time(&self) -> Result<()> {
+        if !self.check().is_sane() {
+            return Ok(());
+        }
+        if let Some(parent) = self.state_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let state = ClockState {
+            last_known_good: SystemTime::now(),
+        };
+        let tmp_path = self.state_path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(&state)?)?;
+        std::fs::rename(&tmp_path, &self.state_path)?;
+        debug!("Recorded last known good time");
+        Ok(())
+    }
+
+    /// Poll until the clock is sane or `timeout` elapses
+    pub async fn wait_until_sane(&self, timeout: Duration) -> ClockStatus {
+        let start = std::time::Instant::now();
+        let mut status = self.check();
+        if !status.is_sane() {
+            warn!("System clock looks wrong ({:?}), waiting for time sync", status);
+        }
+        while !status.is_sane() && start.elapsed() < timeout {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            status = self.check();
+        }
+        if status.is_sane() {
+            info!("System clock is sane");
+            if let Err(e) = self.record_good_time() {
+                warn!("Failed to record clock state: {}", e);
+            }
+        }
+        status
+    }
+}
+
+/// Whether the OS reports the clock as NTP-synchronized
+pub fn is_ntp_synchronized() -> bool {
+    Path::new(TIMESYNC_MARKER).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_epoch_clock_is_not_sane() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = TimeSanity::new(dir.path());
+        assert_eq!(clock.check_at(at(0)), ClockStatus::BeforeMinimum);
+        assert!(clock.check_at(at(MIN_PLAUSIBLE_UNIX_SECS + 60)).is_sane());
+    }
+
+    #[test]
+    fn test_clock_behind_last_known_good() {
+        let dir = tempfile::tempdir().unwrap();
+        let later = at(MIN_PLAUSIBLE_UNIX_SECS + 10 * 86400);
+        let state = ClockState {
+            last_known_good: later,
+        };
+        std::fs::write(dir.path().join(STATE_FILE), serde_json::to_string(&state).unwrap()).unwrap();
+
+        let clock = TimeSanity::new(dir.path());
+        let earlier = at(MIN_PLAUSIBLE_UNIX_SECS + 86400);
+        assert!(matches!(
+            clock.check_at(earlier),
+            ClockStatus::BehindLastKnown { .. }
+        ));
+        assert!(clock.check_at(later - Duration::from_secs(60)).is_sane());
+    }
+}
@@ -0,0 +1,283 @@
+//! Single-gateway lease on a workspace
+//!
+//! Two gateways started against one workspace would both answer every
+//! message and race each other writing sessions. The gateway therefore holds
+//! a lease file: created atomically on start, renewed while running, and
+//! removed on shutdown. A second gateway that finds a live lease refuses to
+//! start. A lease left behind by a crash is taken over, at once when its
+//! process is gone from this host, or after it has gone unrenewed for the
+//! TTL when the holder is on another host sharing the workspace.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Lease file, relative to the workspace
+pub const LEASE_FILE: &str = "state/gateway.lock";
+
+/// How long a lease stays live without renewal
+pub const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+/// The gateway holding a lease
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaseHolder {
+    pub id: String,
+    pub pid: u32,
+    pub host: String,
+    pub started_at: DateTime<Utc>,
+    pub renewed_at: DateTime<Utc>,
+}
+
+impl LeaseHolder {
+    fn current() -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            pid: std::process::id(),
+            host: hostname(),
+            started_at: now,
+            renewed_at: now,
+        }
+    }
+
+    /// Whether the holder may still be running at `now`
+    pub fn is_live(&self, now: DateTime<Utc>, ttl: Duration) -> bool {
+        let age = (now - self.renewed_at).to_std().unwrap_or_default();
+        if age > ttl {
+            return false;
+        }
+        if self.host != hostname() {
+            return true;
+        }
+        // Our own pid means a previous run of this process, e.g. pid 1 in a
+        // restarted container
+        self.pid != std::process::id() && process_running(self.pid)
+    }
+
+    /// e.g. `pid 812 on kitchen-pi since 2026-10-15 09:00:00 UTC`
+    pub fn describe(&self) -> String {
+        format!(
+            "pid {} on {} since {}",
+            self.pid,
+            self.host,
+            self.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
+}
+
+/// A held lease, released on drop
+pub struct WorkspaceLease {
+    path: PathBuf,
+    holder: LeaseHolder,
+    ttl: Duration,
+}
+
+impl WorkspaceLease {
+    /// Take the lease on `workspace`, failing if another gateway holds it
+    pub fn acquire(workspace: &Path) -> Result<Self> {
+        Self::acquire_with_ttl(workspace, DEFAULT_TTL)
+    }
+
+    pub fn acquire_with_ttl(workspace: &Path, ttl: Duration) -> Result<Self> {
+        let path = workspace.join(LEASE_FILE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let lease = Self {
+            path,
+            holder: LeaseHolder::current(),
+            ttl,
+        };
+
+        // Linking a fully written file creates the lease atomically, so a
+        // concurrent reader never sees it half written
+        let tmp_path = lease.path.with_extension(format!("{}.tmp", lease.holder.id));
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&lease.holder)?)?;
+        let linked = std::fs::hard_link(&tmp_path, &lease.path);
+        let _ = std::fs::remove_file(&tmp_path);
+        match linked {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if let Some(existing) = read(&lease.path)? {
+                    if existing.is_live(Utc::now(), ttl) {
+                        return Err(Error::runtime(format!(
+                            "Another gateway is using workspace {:?} ({}). Stop it first, or if it crashed on another host, wait {}s for its lease to expire",
+                            workspace,
+                            existing.describe(),
+                            ttl.as_secs()
+                        )));
+                    }
+                    warn!("Taking over stale gateway lease from {}", existing.describe());
+                }
+                lease.write()?;
+                // Another gateway taking over at the same moment may have won
+                if let Some(winner) = lease.taken_over()? {
+                    return Err(Error::runtime(format!(
+                        "Another gateway took over workspace {:?} first ({})",
+                        workspace,
+                        winner.describe()
+                    )));
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+        info!("Acquired gateway lease {:?}", lease.path);
+        Ok(lease)
+    }
+
+    pub fn holder(&self) -> &LeaseHolder {
+        &self.holder
+    }
+
+    /// Renew the lease every third of its TTL, returning only once another
+    /// gateway has taken it over
+    pub async fn hold(&mut self) -> Error {
+        let mut ticker = tokio::time::interval(self.ttl / 3);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match self.taken_over() {
+                Ok(Some(other)) => {
+                    return Error::runtime(format!(
+                        "Gateway lease {:?} was taken over by {}",
+                        self.path,
+                        other.describe()
+                    ))
+                }
+                Ok(None) => {
+                    self.holder.renewed_at = Utc::now();
+                    match self.write() {
+                        Ok(()) => debug!("Renewed gateway lease"),
+                        Err(e) => warn!("Failed to renew gateway lease: {}", e),
+                    }
+                }
+                Err(e) => warn!("Failed to check gateway lease: {}", e),
+            }
+        }
+    }
+
+    /// The gateway now holding the lease, if it is no longer this one
+    fn taken_over(&self) -> Result<Option<LeaseHolder>> {
+        Ok(read(&self.path)?.filter(|holder| holder.id != self.holder.id))
+    }
+
+    fn write(&self) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&self.holder)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Remove the lease file if this gateway still holds it
+    pub fn release(&self) {
+        match read(&self.path) {
+            Ok(Some(holder)) if holder.id == self.holder.id => match std::fs::remove_file(&self.path) {
+                Ok(()) => info!("Released gateway lease"),
+                Err(e) => warn!("Failed to release gateway lease: {}", e),
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Drop for WorkspaceLease {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// The live lease holder on `workspace`, if any
+pub fn current_holder(workspace: &Path) -> Result<Option<LeaseHolder>> {
+    Ok(read(&workspace.join(LEASE_FILE))?.filter(|holder| holder.is_live(Utc::now(), DEFAULT_TTL)))
+}
+
+/// Holder recorded in `path`; an unreadable lease counts as none
+fn read(path: &Path) -> Result<Option<LeaseHolder>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    match serde_json::from_str(&std::fs::read_to_string(path)?) {
+        Ok(holder) => Ok(Some(holder)),
+        Err(e) => {
+            warn!("Ignoring invalid gateway lease {:?}: {}", path, e);
+            Ok(None)
+        }
+    }
+}
+
+/// Whether `pid` is a running process, not a zombie awaiting its parent
+fn process_running(pid: u32) -> bool {
+    let Ok(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+        return false;
+    };
+    // The state follows the parenthesised command name, which may contain spaces
+    let state = stat.rfind(')').and_then(|i| stat[i + 1..].trim_start().chars().next());
+    !matches!(state, Some('Z' | 'X') | None)
+}
+
+/// This machine's hostname, or `unknown`
+pub(crate) fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lease held by another process on this host, renewed `age_secs` ago
+    fn other_holder(pid: u32, age_secs: i64) -> LeaseHolder {
+        LeaseHolder {
+            id: "other".to_string(),
+            pid,
+            host: hostname(),
+            started_at: Utc::now() - chrono::Duration::hours(1),
+            renewed_at: Utc::now() - chrono::Duration::seconds(age_secs),
+        }
+    }
+
+    fn write_holder(workspace: &Path, holder: &LeaseHolder) {
+        std::fs::create_dir_all(workspace.join("state")).unwrap();
+        std::fs::write(workspace.join(LEASE_FILE), serde_json::to_string(holder).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_live_lease_blocks_and_stale_lease_is_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let lease = WorkspaceLease::acquire(dir.path()).unwrap();
+        assert_eq!(current_holder(dir.path()).unwrap(), None, "our own pid is never live to us");
+        drop(lease);
+        assert!(!dir.path().join(LEASE_FILE).exists());
+
+        // pid 1 always exists
+        write_holder(dir.path(), &other_holder(1, 5));
+        let error = WorkspaceLease::acquire(dir.path()).err().unwrap().to_string();
+        assert!(error.contains("Another gateway"), "{}", error);
+
+        // Unrenewed past the TTL, or the process is gone
+        for stale in [other_holder(1, 60), other_holder(u32::MAX, 5)] {
+            write_holder(dir.path(), &stale);
+            let lease = WorkspaceLease::acquire(dir.path()).unwrap();
+            assert_eq!(read(&dir.path().join(LEASE_FILE)).unwrap().unwrap().id, lease.holder().id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hold_returns_when_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut lease = WorkspaceLease::acquire_with_ttl(dir.path(), Duration::from_millis(300)).unwrap();
+        write_holder(dir.path(), &other_holder(1, 0));
+
+        let error = tokio::time::timeout(Duration::from_secs(2), lease.hold()).await.unwrap();
+        assert!(error.to_string().contains("taken over"));
+        // Not ours to remove any more
+        drop(lease);
+        assert!(dir.path().join(LEASE_FILE).exists());
+    }
+}
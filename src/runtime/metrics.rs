@@ -0,0 +1,224 @@
+//! Recent throughput, latency, and error metrics
+//!
+//! Agent runs are counted into one-minute buckets held in a fixed-size ring
+//! ([`CAPACITY`] minutes, one day). The gateway saves the ring to
+//! `state/metrics.json` every minute and restores it on start, so `takobull
+//! status --history 1h` can show trends without an external metrics stack.
+
+use crate::error::Result;
+use chrono::{DateTime, DurationRound, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// Metrics file, relative to the workspace
+pub const METRICS_FILE: &str = "state/metrics.json";
+
+/// Minutes kept by the global ring
+pub const CAPACITY: usize = 24 * 60;
+
+/// Agent runs that finished within one interval
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSample {
+    /// Start of the interval
+    pub at: DateTime<Utc>,
+    pub messages: u64,
+    pub errors: u64,
+    pub total_latency_ms: u64,
+    pub max_latency_ms: u64,
+}
+
+impl MetricsSample {
+    fn empty(at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            messages: 0,
+            errors: 0,
+            total_latency_ms: 0,
+            max_latency_ms: 0,
+        }
+    }
+
+    fn add(&mut self, other: &MetricsSample) {
+        self.messages += other.messages;
+        self.errors += other.errors;
+        self.total_latency_ms += other.total_latency_ms;
+        self.max_latency_ms = self.max_latency_ms.max(other.max_latency_ms);
+    }
+
+    /// Mean run time, 0 without runs
+    pub fn avg_latency_ms(&self) -> u64 {
+        self.total_latency_ms.checked_div(self.messages).unwrap_or(0)
+    }
+}
+
+/// Bounded ring of per-minute samples
+#[derive(Clone)]
+pub struct MetricsRing {
+    samples: Arc<Mutex<VecDeque<MetricsSample>>>,
+    capacity: usize,
+}
+
+impl MetricsRing {
+    /// Ring keeping the last `capacity` minutes that saw activity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::new())),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Count a run that finished at `at` after `latency`
+    pub fn record(&self, at: DateTime<Utc>, latency: Duration, ok: bool) {
+        let minute = at.duration_trunc(chrono::Duration::minutes(1)).unwrap_or(at);
+        let latency_ms = latency.as_millis().min(u64::MAX as u128) as u64;
+        let mut samples = self.samples.lock();
+        if samples.back().is_none_or(|last| last.at < minute) {
+            if samples.len() == self.capacity {
+                samples.pop_front();
+            }
+            samples.push_back(MetricsSample::empty(minute));
+        }
+        let sample = samples.back_mut().expect("sample was just pushed");
+        sample.add(&MetricsSample {
+            at: minute,
+            messages: 1,
+            errors: u64::from(!ok),
+            total_latency_ms: latency_ms,
+            max_latency_ms: latency_ms,
+        });
+    }
+
+    /// Samples starting at or after `from`, oldest first
+    pub fn since(&self, from: DateTime<Utc>) -> Vec<MetricsSample> {
+        self.samples.lock().iter().filter(|s| s.at >= from).cloned().collect()
+    }
+
+    /// Replace the contents with `samples`, e.g. loaded from a previous run
+    pub fn restore(&self, samples: Vec<MetricsSample>) {
+        let skip = samples.len().saturating_sub(self.capacity);
+        *self.samples.lock() = samples.into_iter().skip(skip).collect();
+    }
+
+    /// Save to the workspace
+    pub async fn save(&self, workspace: &Path) -> Result<()> {
+        let json = serde_json::to_string(&*self.samples.lock())?;
+        let path = workspace.join(METRICS_FILE);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, json).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Save to `workspace` every `interval` and once more at shutdown
+    pub async fn persist(self, workspace: PathBuf, interval: Duration, mut shutdown: broadcast::Receiver<()>) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+            if let Err(e) = self.save(&workspace).await {
+                warn!("Failed to save metrics: {}", e);
+            }
+        }
+        if let Err(e) = self.save(&workspace).await {
+            warn!("Failed to save metrics: {}", e);
+        }
+        debug!("Metrics persistence stopped");
+    }
+}
+
+/// Samples saved in the workspace, oldest first
+pub async fn load(workspace: &Path) -> Result<Vec<MetricsSample>> {
+    let path = workspace.join(METRICS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?)
+}
+
+/// Ring that agent runs are recorded into
+pub fn global() -> &'static MetricsRing {
+    static RING: OnceLock<MetricsRing> = OnceLock::new();
+    RING.get_or_init(|| MetricsRing::new(CAPACITY))
+}
+
+/// Merge `samples` into consecutive intervals of `step` starting at `from`,
+/// including intervals without activity
+pub fn aggregate(samples: &[MetricsSample], from: DateTime<Utc>, to: DateTime<Utc>, step: chrono::Duration) -> Vec<MetricsSample> {
+    let mut intervals = Vec::new();
+    let mut start = from;
+    while start < to {
+        let end = start + step;
+        let mut interval = MetricsSample::empty(start);
+        for sample in samples.iter().filter(|s| s.at >= start && s.at < end) {
+            interval.add(sample);
+        }
+        intervals.push(interval);
+        start = end;
+    }
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[tokio::test]
+    async fn test_ring_buckets_by_minute() {
+        let ring = MetricsRing::new(2);
+        ring.record(at("2026-06-01T12:00:05Z"), Duration::from_millis(100), true);
+        ring.record(at("2026-06-01T12:00:50Z"), Duration::from_millis(300), false);
+        ring.record(at("2026-06-01T12:01:10Z"), Duration::from_millis(50), true);
+
+        let samples = ring.since(at("2026-06-01T12:00:00Z"));
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].messages, 2);
+        assert_eq!(samples[0].errors, 1);
+        assert_eq!(samples[0].avg_latency_ms(), 200);
+        assert_eq!(samples[0].max_latency_ms, 300);
+
+        // The oldest minute is evicted once the ring is full
+        ring.record(at("2026-06-01T12:05:00Z"), Duration::from_millis(10), true);
+        assert_eq!(ring.since(at("2026-06-01T00:00:00Z"))[0].at, at("2026-06-01T12:01:00Z"));
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(dir.path()).await.unwrap().is_empty());
+        ring.save(dir.path()).await.unwrap();
+        let restored = MetricsRing::new(2);
+        restored.restore(load(dir.path()).await.unwrap());
+        assert_eq!(restored.since(at("2026-06-01T00:00:00Z")), ring.since(at("2026-06-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_aggregate_fills_quiet_intervals() {
+        let ring = MetricsRing::new(10);
+        ring.record(at("2026-06-01T12:01:00Z"), Duration::from_millis(100), true);
+        ring.record(at("2026-06-01T12:03:00Z"), Duration::from_millis(500), true);
+        let samples = ring.since(at("2026-06-01T12:00:00Z"));
+
+        let intervals = aggregate(
+            &samples,
+            at("2026-06-01T12:00:00Z"),
+            at("2026-06-01T12:06:00Z"),
+            chrono::Duration::minutes(2),
+        );
+        let counts: Vec<u64> = intervals.iter().map(|i| i.messages).collect();
+        assert_eq!(counts, vec![1, 1, 0]);
+        assert_eq!(intervals[1].max_latency_ms, 500);
+    }
+}
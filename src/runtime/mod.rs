@@ -4,7 +4,12 @@
 //! - Tokio async runtime initialization and configuration
 //! - Graceful shutdown mechanism for all async tasks
 //! - Task pool for managing concurrent operations
-//! - Runtime metrics and monitoring
+//! - Per-minute throughput, latency, and error metrics
+//! - System clock sanity checks for devices without an RTC
+//! - Heartbeat file and hardware watchdog for external supervisors
+//! - A workspace lease so only one gateway runs per workspace
+//! - Startup phase timing against a boot-time budget
+//! - A reduced-footprint runtime on single-core or low-memory boards
 
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -15,7 +20,24 @@ use tracing::{debug, error, info, warn};
 
 use crate::error::{Error, Result};
 
+pub mod clock;
+pub mod lease;
+pub mod metrics;
+pub mod startup;
+pub mod watchdog;
+
+/// Boards with this many cores or fewer get the reduced-footprint runtime
+pub const LOW_FOOTPRINT_MAX_CORES: usize = 1;
+
+/// Boards with less memory than this get the reduced-footprint runtime
+pub const LOW_FOOTPRINT_MAX_MEMORY: u64 = 1024 * 1024 * 1024;
+
 /// Configuration for the async runtime
+///
+/// The default suits the machine it runs on: a Pi Zero (one core, 512 MB)
+/// or any board below [`LOW_FOOTPRINT_MAX_CORES`] cores or
+/// [`LOW_FOOTPRINT_MAX_MEMORY`] of RAM gets [`RuntimeConfig::low_footprint`],
+/// as does every build with the `low-footprint` feature.
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     /// Maximum number of worker threads
@@ -30,13 +52,69 @@ pub struct RuntimeConfig {
 
 impl Default for RuntimeConfig {
     fn default() -> Self {
+        if cfg!(feature = "low-footprint") {
+            return Self::low_footprint();
+        }
+        Self::for_system(num_cpus::get(), total_memory_bytes())
+    }
+}
+
+impl RuntimeConfig {
+    /// Full-size runtime with one worker per core
+    pub fn standard(cores: usize) -> Self {
         Self {
-            worker_threads: num_cpus::get(),
+            worker_threads: cores.max(1),
             max_blocking_threads: 512,
             thread_name_prefix: "takobull-worker".to_string(),
             stack_size: 2 * 1024 * 1024, // 2MB
         }
     }
+
+    /// Runtime for boards with little RAM: one worker thread, few blocking
+    /// threads (each reserves its stack), and smaller stacks
+    pub fn low_footprint() -> Self {
+        Self {
+            worker_threads: 1,
+            max_blocking_threads: 16,
+            thread_name_prefix: "takobull-worker".to_string(),
+            stack_size: 512 * 1024, // 512KB
+        }
+    }
+
+    /// Config for a machine with `cores` and `memory` bytes of RAM, when known
+    pub fn for_system(cores: usize, memory: Option<u64>) -> Self {
+        let low_memory = memory.is_some_and(|bytes| bytes < LOW_FOOTPRINT_MAX_MEMORY);
+        if cores <= LOW_FOOTPRINT_MAX_CORES || low_memory {
+            Self::low_footprint()
+        } else {
+            Self::standard(cores)
+        }
+    }
+
+    /// Whether this is the reduced-footprint config
+    pub fn is_low_footprint(&self) -> bool {
+        self.worker_threads == 1 && self.max_blocking_threads <= 16
+    }
+
+    /// Build a multi-threaded tokio runtime from this config
+    pub fn build(&self) -> Result<tokio::runtime::Runtime> {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(self.worker_threads)
+            .max_blocking_threads(self.max_blocking_threads)
+            .thread_name(self.thread_name_prefix.clone())
+            .thread_stack_size(self.stack_size)
+            .enable_all()
+            .build()
+            .map_err(|e| Error::runtime(format!("Failed to initialize tokio runtime: {}", e)))
+    }
+}
+
+/// Total RAM from `/proc/meminfo`, if it can be read
+pub fn total_memory_bytes() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
 }
 
 /// Manages the async runtime and task lifecycle
@@ -68,14 +146,7 @@ impl RuntimeManager {
     pub fn initialize(config: RuntimeConfig) -> Result<()> {
         debug!("Initializing async runtime with config: {:?}", config);
 
-        let runtime = tokio::runtime::Builder::new_multi_thread()
-            .worker_threads(config.worker_threads)
-            .max_blocking_threads(config.max_blocking_threads)
-            .thread_name("tacobot-worker")
-            .thread_stack_size(config.stack_size)
-            .enable_all()
-            .build()
-            .map_err(|e| Error::runtime(format!("Failed to initialize tokio runtime: {}", e)))?;
+        let runtime = config.build()?;
 
         // Verify runtime is ready
         let start = std::time::Instant::now();
@@ -156,8 +227,10 @@ impl RuntimeManager {
 
         info!("Initiating graceful shutdown");
 
-        // Signal all tasks to shutdown
-        if let Err(e) = self.shutdown_tx.send(()) {
+        // Signal all tasks to shutdown (there are no receivers once every task has finished)
+        if self.shutdown_tx.receiver_count() == 0 {
+            debug!("No running tasks to signal");
+        } else if let Err(e) = self.shutdown_tx.send(()) {
             warn!("Failed to broadcast shutdown signal: {}", e);
         }
 
@@ -272,6 +345,22 @@ mod property_tests;
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_low_footprint_config_on_small_boards() {
+        // Pi Zero: one core, 512 MB
+        assert!(RuntimeConfig::for_system(1, Some(512 * 1024 * 1024)).is_low_footprint());
+        // Pi Zero 2 W: four cores, but still 512 MB
+        assert!(RuntimeConfig::for_system(4, Some(512 * 1024 * 1024)).is_low_footprint());
+        let pi4 = RuntimeConfig::for_system(4, Some(4 * 1024 * 1024 * 1024));
+        assert!(!pi4.is_low_footprint());
+        assert_eq!(pi4.worker_threads, 4);
+        // Unknown memory goes by cores alone
+        assert!(!RuntimeConfig::for_system(2, None).is_low_footprint());
+
+        let runtime = RuntimeConfig::low_footprint().build().unwrap();
+        assert_eq!(runtime.block_on(async { 1 + 1 }), 2);
+    }
+
     #[tokio::test]
     async fn test_runtime_manager_creation() {
         let manager = RuntimeManager::new();
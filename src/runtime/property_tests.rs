@@ -130,7 +130,7 @@ mod tests {
                 manager.spawn_task(async move {
                     // Simulate variable execution time
                     tokio::time::sleep(Duration::from_millis(i as u64)).await;
-                    i as i32
+                    i
                 })
             })
             .collect();
@@ -148,7 +148,7 @@ mod tests {
         
         // Verify results are in expected range
         for result in results {
-            assert!(result >= 0 && result < 10);
+            assert!((0..10).contains(&result));
         }
     }
 
@@ -0,0 +1,149 @@
+//! Startup phase timing
+//!
+//! [`StartupProfile`] marks the end of each startup phase (config load, tool
+//! registry build, HTTP API, ...) and logs its duration at debug
+//! level. The gateway saves the finished [`StartupReport`] so `takobull status
+//! --verbose` can show where boot time went and whether it fit the budget.
+
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Startup report file, relative to the workspace
+pub const STARTUP_FILE: &str = "state/startup.json";
+
+/// Time spent in one startup phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupPhase {
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+/// Phase timings of a finished startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupReport {
+    pub started_at: DateTime<Utc>,
+    pub phases: Vec<StartupPhase>,
+    pub total_ms: u64,
+    #[serde(default)]
+    pub budget_ms: Option<u64>,
+}
+
+impl StartupReport {
+    /// Whether startup took longer than the budget
+    pub fn over_budget(&self) -> bool {
+        self.budget_ms.is_some_and(|budget| self.total_ms > budget)
+    }
+
+    /// Summary line, e.g. `840ms (budget 2000ms)`
+    pub fn summary(&self) -> String {
+        match self.budget_ms {
+            Some(budget) if self.over_budget() => {
+                format!("{}ms (over the {}ms budget)", self.total_ms, budget)
+            }
+            Some(budget) => format!("{}ms (budget {}ms)", self.total_ms, budget),
+            None => format!("{}ms", self.total_ms),
+        }
+    }
+
+    /// Save to the workspace
+    pub async fn save(&self, workspace: &Path) -> Result<()> {
+        let path = workspace.join(STARTUP_FILE);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+
+    /// Report saved by the last gateway startup, if any
+    pub async fn load(workspace: &Path) -> Result<Option<Self>> {
+        let path = workspace.join(STARTUP_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?))
+    }
+}
+
+/// Records how long each startup phase takes
+pub struct StartupProfile {
+    started_at: DateTime<Utc>,
+    started: Instant,
+    mark: Instant,
+    phases: Vec<StartupPhase>,
+}
+
+impl StartupProfile {
+    /// Start timing now
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started_at: Utc::now(),
+            started: now,
+            mark: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// End phase `name`, which began at the previous mark
+    pub fn phase(&mut self, name: &str) {
+        let now = Instant::now();
+        let elapsed = now - self.mark;
+        debug!("Startup phase {} took {:?}", name, elapsed);
+        self.phases.push(StartupPhase {
+            name: name.to_string(),
+            duration_ms: millis(elapsed),
+        });
+        self.mark = now;
+    }
+
+    /// Finish timing, warning when `budget_ms` is exceeded
+    pub fn finish(self, budget_ms: Option<u64>) -> StartupReport {
+        let report = StartupReport {
+            started_at: self.started_at,
+            phases: self.phases,
+            total_ms: millis(self.started.elapsed()),
+            budget_ms,
+        };
+        if report.over_budget() {
+            warn!("Startup took {}", report.summary());
+        } else {
+            debug!("Startup took {}", report.summary());
+        }
+        report
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().min(u64::MAX as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_profile_records_phases() {
+        let mut profile = StartupProfile::start();
+        profile.phase("config");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        profile.phase("tools");
+        let report = profile.finish(Some(5));
+
+        let names: Vec<&str> = report.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["config", "tools"]);
+        assert!(report.phases[1].duration_ms >= 20);
+        assert!(report.total_ms >= report.phases.iter().map(|p| p.duration_ms).sum::<u64>());
+        assert!(report.over_budget());
+        assert!(report.summary().ends_with("(over the 5ms budget)"));
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(StartupReport::load(dir.path()).await.unwrap(), None);
+        report.save(dir.path()).await.unwrap();
+        assert_eq!(StartupReport::load(dir.path()).await.unwrap(), Some(report));
+    }
+}
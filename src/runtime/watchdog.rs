@@ -0,0 +1,146 @@
+//! Heartbeat file and hardware watchdog for external supervisors
+//!
+//! While the gateway runs, [`Watchdog::run`] rewrites a heartbeat file with
+//! the current time on every tick. A supervisor (systemd, monit, a cron
+//! script) can reboot the device when the file goes stale. When a watchdog
+//! device such as `/dev/watchdog` is configured it is written on the same
+//! tick, so the SoC resets the board if the runtime stops scheduling tasks.
+//! A clean shutdown disarms the device with the magic close character.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Heartbeat file, relative to the workspace, unless configured otherwise
+pub const HEARTBEAT_FILE: &str = "state/heartbeat";
+
+/// Character that tells the Linux watchdog driver a close is intentional
+const MAGIC_CLOSE: &[u8] = b"V";
+
+/// Writes heartbeats and pets the hardware watchdog
+pub struct Watchdog {
+    heartbeat_file: PathBuf,
+    device: Option<(PathBuf, std::fs::File)>,
+}
+
+impl Watchdog {
+    /// Watchdog writing heartbeats to `heartbeat_file`
+    pub fn new(heartbeat_file: impl Into<PathBuf>) -> Self {
+        Self {
+            heartbeat_file: heartbeat_file.into(),
+            device: None,
+        }
+    }
+
+    /// Also write to the watchdog device at `path` on every beat
+    ///
+    /// Opening the device arms it: from then on the board resets if no beat
+    /// arrives within the driver's timeout.
+    pub fn with_device(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .map_err(|e| Error::device(format!("Failed to open watchdog {:?}: {}", path, e)))?;
+        info!("Hardware watchdog {:?} armed", path);
+        self.device = Some((path, file));
+        Ok(self)
+    }
+
+    /// Record a heartbeat at `now` and pet the watchdog device
+    pub async fn beat(&mut self, now: DateTime<Utc>) -> Result<()> {
+        if let Some((path, file)) = &mut self.device {
+            file.write_all(b"\0")
+                .and_then(|_| file.flush())
+                .map_err(|e| Error::device(format!("Failed to pet watchdog {:?}: {}", path, e)))?;
+        }
+        if let Some(parent) = self.heartbeat_file.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = self.heartbeat_file.with_extension("tmp");
+        tokio::fs::write(&tmp_path, format!("{}\n", now.to_rfc3339())).await?;
+        tokio::fs::rename(&tmp_path, &self.heartbeat_file).await?;
+        Ok(())
+    }
+
+    /// Disarm the watchdog device, if any
+    pub fn stop(&mut self) {
+        if let Some((path, mut file)) = self.device.take() {
+            match file.write_all(MAGIC_CLOSE).and_then(|_| file.flush()) {
+                Ok(()) => info!("Hardware watchdog {:?} disarmed", path),
+                Err(e) => warn!("Failed to disarm watchdog {:?}: {}", path, e),
+            }
+        }
+    }
+
+    /// Beat every `interval` until shutdown, then disarm
+    pub async fn run(mut self, interval: Duration, mut shutdown: broadcast::Receiver<()>) {
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_tick = Instant::now();
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let lag = last_tick.elapsed().saturating_sub(interval);
+                    if lag > interval {
+                        warn!("Watchdog tick was {:?} late; the runtime may be overloaded", lag);
+                    }
+                    last_tick = Instant::now();
+                    if let Err(e) = self.beat(Utc::now()).await {
+                        warn!("Failed to write heartbeat: {}", e);
+                    }
+                }
+                _ = shutdown.recv() => {
+                    debug!("Watchdog stopping");
+                    break;
+                }
+            }
+        }
+        self.stop();
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Time of the last heartbeat recorded in `path`, if any
+pub async fn last_heartbeat(path: &Path) -> Result<Option<DateTime<Utc>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = tokio::fs::read_to_string(path).await?;
+    DateTime::parse_from_rfc3339(contents.trim())
+        .map(|at| Some(at.with_timezone(&Utc)))
+        .map_err(|e| Error::runtime(format!("Invalid heartbeat in {:?}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_beats_and_disarms_device() {
+        let dir = tempfile::tempdir().unwrap();
+        let heartbeat = dir.path().join(HEARTBEAT_FILE);
+        // A regular file stands in for /dev/watchdog
+        let device = dir.path().join("watchdog");
+        std::fs::write(&device, "").unwrap();
+
+        assert_eq!(last_heartbeat(&heartbeat).await.unwrap(), None);
+        let mut watchdog = Watchdog::new(&heartbeat).with_device(&device).unwrap();
+        let at = DateTime::parse_from_rfc3339("2026-06-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        watchdog.beat(at).await.unwrap();
+        watchdog.beat(at).await.unwrap();
+        assert_eq!(last_heartbeat(&heartbeat).await.unwrap(), Some(at));
+
+        drop(watchdog);
+        assert_eq!(std::fs::read(&device).unwrap(), b"\0\0V");
+        assert!(Watchdog::new(&heartbeat).with_device(dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Retry backoff for failing jobs
+
+use serde::{Deserialize, Serialize};
+
+/// How failed runs are retried and when a job counts as degraded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Delay before the first retry; doubled after each further failure
+    #[serde(default = "default_base_secs")]
+    pub base_secs: u64,
+    /// Longest delay between retries
+    #[serde(default = "default_max_secs")]
+    pub max_secs: u64,
+    /// Consecutive failures after which the job is marked degraded and a
+    /// notification is sent
+    #[serde(default = "default_degraded_after")]
+    pub degraded_after: u32,
+}
+
+fn default_base_secs() -> u64 {
+    60
+}
+
+fn default_max_secs() -> u64 {
+    3600
+}
+
+fn default_degraded_after() -> u32 {
+    3
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_secs: default_base_secs(),
+            max_secs: default_max_secs(),
+            degraded_after: default_degraded_after(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after `failures` consecutive failures
+    pub fn delay(&self, failures: u32) -> chrono::Duration {
+        let doublings = failures.saturating_sub(1).min(32);
+        let secs = self
+            .base_secs
+            .saturating_mul(1u64 << doublings)
+            .min(self.max_secs);
+        chrono::Duration::seconds(secs as i64)
+    }
+}
+
+/// Change in a job's health caused by a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthChange {
+    /// The job just reached `degraded_after` consecutive failures
+    Degraded,
+    /// A degraded job succeeded again
+    Recovered,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            base_secs: 60,
+            max_secs: 300,
+            degraded_after: 3,
+        };
+        let secs: Vec<i64> = (1..=5).map(|n| policy.delay(n).num_seconds()).collect();
+        assert_eq!(secs, vec![60, 120, 240, 300, 300]);
+        assert_eq!(policy.delay(1000).num_seconds(), 300);
+    }
+}
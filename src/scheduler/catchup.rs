@@ -0,0 +1,114 @@
+//! Catch-up policies for runs missed while the device was off
+
+use super::job::CronJob;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on runs replayed by [`CatchUpPolicy::RunAll`]
+pub const MAX_CATCH_UP_RUNS: usize = 24;
+
+/// What to do with scheduled runs missed while the device was off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUpPolicy {
+    /// Drop missed runs and wait for the next scheduled time
+    Skip,
+    /// Run once to cover all missed times
+    #[default]
+    RunOnce,
+    /// Run once per missed time (capped at `MAX_CATCH_UP_RUNS`)
+    RunAll,
+}
+
+impl std::str::FromStr for CatchUpPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.replace('-', "_").as_str() {
+            "skip" => Ok(CatchUpPolicy::Skip),
+            "run_once" => Ok(CatchUpPolicy::RunOnce),
+            "run_all" => Ok(CatchUpPolicy::RunAll),
+            other => Err(format!(
+                "unknown catch-up policy '{}' (expected skip, run-once, or run-all)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for CatchUpPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // `pad` so width specifiers line up `cron list` columns
+        f.pad(match self {
+            CatchUpPolicy::Skip => "skip",
+            CatchUpPolicy::RunOnce => "run-once",
+            CatchUpPolicy::RunAll => "run-all",
+        })
+    }
+}
+
+/// Missed fire times that should be run for `job` at startup
+pub fn plan_catch_up(job: &CronJob, now: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+    if !job.enabled {
+        return Ok(Vec::new());
+    }
+    let missed = job.missed_runs(now, MAX_CATCH_UP_RUNS)?;
+    Ok(match job.catch_up {
+        CatchUpPolicy::Skip => Vec::new(),
+        CatchUpPolicy::RunOnce => missed.last().copied().into_iter().collect(),
+        CatchUpPolicy::RunAll => missed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn job_missing_hours(hours: i64, policy: CatchUpPolicy) -> CronJob {
+        let mut job = CronJob::new("0 * * * *", "hourly").unwrap().with_catch_up(policy);
+        job.last_run = Some(Utc::now() - Duration::hours(hours));
+        job
+    }
+
+    #[test]
+    fn test_policy_display_round_trips() {
+        for policy in [CatchUpPolicy::Skip, CatchUpPolicy::RunOnce, CatchUpPolicy::RunAll] {
+            assert_eq!(policy.to_string().parse::<CatchUpPolicy>().unwrap(), policy);
+        }
+        assert_eq!(CatchUpPolicy::RunOnce.to_string(), "run-once");
+        assert_eq!(format!("{:<9}|", CatchUpPolicy::Skip), "skip     |");
+    }
+
+    #[test]
+    fn test_plan_catch_up_policies() {
+        let now = Utc::now();
+        assert!(plan_catch_up(&job_missing_hours(3, CatchUpPolicy::Skip), now)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            plan_catch_up(&job_missing_hours(3, CatchUpPolicy::RunOnce), now)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(plan_catch_up(&job_missing_hours(3, CatchUpPolicy::RunAll), now)
+            .unwrap()
+            .len()
+            >= 2);
+        assert_eq!(
+            plan_catch_up(&job_missing_hours(100, CatchUpPolicy::RunAll), now)
+                .unwrap()
+                .len(),
+            MAX_CATCH_UP_RUNS
+        );
+    }
+
+    #[test]
+    fn test_policy_from_str() {
+        assert_eq!("run-once".parse::<CatchUpPolicy>(), Ok(CatchUpPolicy::RunOnce));
+        assert_eq!("run_all".parse::<CatchUpPolicy>(), Ok(CatchUpPolicy::RunAll));
+        assert!("sometimes".parse::<CatchUpPolicy>().is_err());
+    }
+}
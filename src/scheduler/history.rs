@@ -0,0 +1,185 @@
+//! Recent run history for each job
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Directory under the cron directory holding one JSONL file per job
+const HISTORY_DIR: &str = "history";
+
+/// Runs kept per job unless configured otherwise
+pub const DEFAULT_HISTORY_RUNS: usize = 20;
+
+/// Characters of output kept per run
+pub const MAX_OUTPUT_CHARS: usize = 2000;
+
+/// One run of a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub success: bool,
+    /// Agent response, or the error for a failed run, truncated
+    pub output: String,
+}
+
+impl RunRecord {
+    /// Record a run, truncating `output` to [`MAX_OUTPUT_CHARS`]
+    pub fn new(started_at: DateTime<Utc>, duration_ms: u64, success: bool, output: &str) -> Self {
+        let output = match output.char_indices().nth(MAX_OUTPUT_CHARS) {
+            Some((end, _)) => format!("{}…", &output[..end]),
+            None => output.to_string(),
+        };
+        Self {
+            started_at,
+            duration_ms,
+            success,
+            output,
+        }
+    }
+}
+
+/// Stores the last runs of each job in `<cron dir>/history/<id>.jsonl`
+pub struct RunHistory {
+    dir: PathBuf,
+    limit: usize,
+}
+
+impl RunHistory {
+    /// History kept under the cron directory
+    pub fn new(cron_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: cron_dir.into().join(HISTORY_DIR),
+            limit: DEFAULT_HISTORY_RUNS,
+        }
+    }
+
+    /// Keep the last `limit` runs of each job
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        let plain = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !plain {
+            return Err(Error::config(format!("Invalid job id: {}", id)));
+        }
+        Ok(self.dir.join(format!("{}.jsonl", id)))
+    }
+
+    /// Runs of job `id`, oldest first
+    pub async fn load(&self, id: &str) -> Result<Vec<RunRecord>> {
+        let path = self.path(id)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&path).await?;
+        Ok(contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    warn!("Skipping unreadable run record in {:?}: {}", path, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Append a run, dropping the oldest beyond the limit
+    pub async fn append(&self, id: &str, record: RunRecord) -> Result<()> {
+        let mut records = self.load(id).await?;
+        records.push(record);
+        let skip = records.len().saturating_sub(self.limit);
+
+        let mut contents = String::new();
+        for record in &records[skip..] {
+            contents.push_str(&serde_json::to_string(record)?);
+            contents.push('\n');
+        }
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.path(id)?;
+        let tmp_path = path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp_path, contents).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Cut every job's history down to its last `keep` runs, returning how many runs were dropped
+    pub async fn truncate_all(&self, keep: usize) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+        let mut dropped = 0;
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            let contents = tokio::fs::read_to_string(&path).await?;
+            let lines: Vec<&str> = contents.lines().filter(|line| !line.trim().is_empty()).collect();
+            let skip = lines.len().saturating_sub(keep);
+            if skip == 0 {
+                continue;
+            }
+            let mut kept = lines[skip..].join("\n");
+            if !kept.is_empty() {
+                kept.push('\n');
+            }
+            let tmp_path = path.with_extension("jsonl.tmp");
+            tokio::fs::write(&tmp_path, kept).await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            dropped += skip;
+        }
+        Ok(dropped)
+    }
+
+    /// Delete the history of job `id`
+    pub async fn clear(&self, id: &str) -> Result<()> {
+        let path = self.path(id)?;
+        if path.exists() {
+            tokio::fs::remove_file(&path).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_history_keeps_last_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = RunHistory::new(dir.path()).with_limit(3);
+        for i in 0..5 {
+            let record = RunRecord::new(Utc::now(), i, i % 2 == 0, &format!("run {}", i));
+            history.append("abc123", record).await.unwrap();
+        }
+
+        let runs = history.load("abc123").await.unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].output, "run 2");
+        assert_eq!(runs[2].output, "run 4");
+        assert!(history.load("other").await.unwrap().is_empty());
+        assert!(history.load("../jobs").await.is_err());
+
+        assert_eq!(history.truncate_all(1).await.unwrap(), 2);
+        assert_eq!(history.load("abc123").await.unwrap()[0].output, "run 4");
+
+        history.clear("abc123").await.unwrap();
+        assert!(history.load("abc123").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_output_is_truncated() {
+        let record = RunRecord::new(Utc::now(), 0, true, &"é".repeat(MAX_OUTPUT_CHARS + 10));
+        assert_eq!(record.output.chars().count(), MAX_OUTPUT_CHARS + 1);
+        assert!(record.output.ends_with('…'));
+    }
+}
@@ -0,0 +1,347 @@
+//! Scheduled job definitions
+
+use super::backoff::{HealthChange, RetryPolicy};
+use super::catchup::CatchUpPolicy;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// A scheduled agent job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronJob {
+    pub id: String,
+    /// Cron expression (5-field standard or 6-field with seconds); empty
+    /// for one-shot jobs
+    #[serde(default)]
+    pub expression: String,
+    /// Fire once at this time instead of following an expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<DateTime<Utc>>,
+    /// Task the agent runs when the job fires
+    pub description: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// What to do about runs missed while the device was off
+    #[serde(default)]
+    pub catch_up: CatchUpPolicy,
+    /// Up to this many seconds of random delay after each fire time, so a
+    /// fleet of devices sharing a schedule doesn't wake up at once
+    #[serde(default)]
+    pub jitter_secs: u64,
+    /// Message template the run's output is delivered through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+    /// Whether the last run succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<bool>,
+    /// How long the last run took, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_duration_ms: Option<u64>,
+    /// Failed runs since the last success
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    /// When a failing job is next attempted; replaces the schedule until a
+    /// run succeeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<DateTime<Utc>>,
+    /// Whether the job has failed too often in a row
+    #[serde(default)]
+    pub degraded: bool,
+}
+
+/// Outcome of executing a job once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOutcome {
+    pub success: bool,
+    pub duration: std::time::Duration,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl CronJob {
+    /// Create a job, validating its cron expression
+    pub fn new(expression: &str, description: &str) -> Result<Self> {
+        parse_schedule(expression)?;
+        Ok(Self::build(expression.trim().to_string(), None, description))
+    }
+
+    /// Create a job that fires once at `at`, which must be in the future
+    pub fn once(at: DateTime<Utc>, description: &str) -> Result<Self> {
+        if at <= Utc::now() {
+            return Err(Error::config(format!(
+                "One-shot time {} is in the past",
+                at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            )));
+        }
+        Ok(Self::build(String::new(), Some(at), description))
+    }
+
+    fn build(expression: String, at: Option<DateTime<Utc>>, description: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+            expression,
+            at,
+            description: description.to_string(),
+            enabled: true,
+            catch_up: CatchUpPolicy::default(),
+            jitter_secs: 0,
+            template: None,
+            created_at: Utc::now(),
+            last_run: None,
+            last_success: None,
+            last_duration_ms: None,
+            consecutive_failures: 0,
+            retry_at: None,
+            degraded: false,
+        }
+    }
+
+    /// Whether the job fires once and is then removed
+    pub fn is_one_shot(&self) -> bool {
+        self.at.is_some()
+    }
+
+    /// Cron expression, or `at <local time>` for one-shot jobs
+    pub fn schedule_label(&self) -> String {
+        match self.at {
+            Some(at) => format!("at {}", at.with_timezone(&Local).format("%Y-%m-%d %H:%M")),
+            None => self.expression.clone(),
+        }
+    }
+
+    /// Set the catch-up policy
+    pub fn with_catch_up(mut self, policy: CatchUpPolicy) -> Self {
+        self.catch_up = policy;
+        self
+    }
+
+    /// Set the maximum jitter in seconds
+    pub fn with_jitter(mut self, secs: u64) -> Self {
+        self.jitter_secs = secs;
+        self
+    }
+
+    /// Deliver the output through message template `template`
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Delay before running the fire time `scheduled_at`
+    ///
+    /// Derived from `seed`, the job and the fire time rather than drawn anew,
+    /// so every tick agrees on when a given run is due.
+    pub fn jitter_delay(&self, scheduled_at: DateTime<Utc>, seed: u64) -> chrono::Duration {
+        if self.jitter_secs == 0 {
+            return chrono::Duration::zero();
+        }
+        let mut hasher = DefaultHasher::new();
+        (seed, &self.id, scheduled_at.timestamp()).hash(&mut hasher);
+        chrono::Duration::seconds((hasher.finish() % (self.jitter_secs + 1)) as i64)
+    }
+
+    /// Record a run at `at` with its outcome, or `None` for a skipped run,
+    /// scheduling a backoff retry after a failure
+    pub fn record_run(
+        &mut self,
+        at: DateTime<Utc>,
+        outcome: Option<RunOutcome>,
+        retry: &RetryPolicy,
+    ) -> Option<HealthChange> {
+        self.last_run = Some(at);
+        self.last_success = outcome.map(|o| o.success);
+        self.last_duration_ms = outcome.map(|o| o.duration.as_millis() as u64);
+        match outcome {
+            Some(outcome) if outcome.success => {
+                self.consecutive_failures = 0;
+                self.retry_at = None;
+                std::mem::take(&mut self.degraded).then_some(HealthChange::Recovered)
+            }
+            Some(_) => {
+                self.consecutive_failures += 1;
+                self.retry_at = Some(at + retry.delay(self.consecutive_failures));
+                let degraded = !self.degraded && self.consecutive_failures >= retry.degraded_after;
+                self.degraded |= degraded;
+                degraded.then_some(HealthChange::Degraded)
+            }
+            None => None,
+        }
+    }
+
+    /// When the job is next attempted: its retry time while failing,
+    /// otherwise its next fire time
+    pub fn next_attempt(&self) -> Result<Option<DateTime<Utc>>> {
+        match self.retry_at {
+            Some(retry_at) => Ok(Some(retry_at)),
+            None => self.next_run(),
+        }
+    }
+
+    /// Parsed schedule for this job
+    pub fn schedule(&self) -> Result<Schedule> {
+        parse_schedule(&self.expression)
+    }
+
+    /// Reference point for computing the next fire time
+    pub fn last_reference(&self) -> DateTime<Utc> {
+        self.last_run.unwrap_or(self.created_at)
+    }
+
+    /// Fire times after the last run up to and including `now`, oldest first
+    pub fn missed_runs(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<DateTime<Utc>>> {
+        if let Some(at) = self.at {
+            let missed = self.last_run.is_none() && at <= now && limit > 0;
+            return Ok(if missed { vec![at] } else { Vec::new() });
+        }
+        let schedule = self.schedule()?;
+        let after = self.last_reference().with_timezone(&Local);
+        Ok(schedule
+            .after(&after)
+            .take_while(|t| t.with_timezone(&Utc) <= now)
+            .take(limit)
+            .map(|t| t.with_timezone(&Utc))
+            .collect())
+    }
+
+    /// Next fire time after the last run
+    pub fn next_run(&self) -> Result<Option<DateTime<Utc>>> {
+        if let Some(at) = self.at {
+            return Ok(self.last_run.is_none().then_some(at));
+        }
+        let schedule = self.schedule()?;
+        let after = self.last_reference().with_timezone(&Local);
+        Ok(schedule.after(&after).next().map(|t| t.with_timezone(&Utc)))
+    }
+}
+
+/// Parse a one-shot time: RFC 3339, or `YYYY-MM-DD HH:MM[:SS]` in local time
+pub fn parse_at(text: &str) -> Result<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .ok_or_else(|| {
+            Error::config(format!(
+                "Invalid time '{}' (expected YYYY-MM-DD HH:MM or RFC 3339)",
+                text
+            ))
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+        .ok_or_else(|| Error::config(format!("'{}' does not exist in the local time zone", text)))
+}
+
+/// Parse a cron expression, accepting standard 5-field syntax
+pub fn parse_schedule(expression: &str) -> Result<Schedule> {
+    let expression = expression.trim();
+    let normalized = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+    Schedule::from_str(&normalized)
+        .map_err(|e| Error::config(format!("Invalid cron expression '{}': {}", expression, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_schedule_accepts_five_fields() {
+        assert!(parse_schedule("*/5 * * * *").is_ok());
+        assert!(parse_schedule("0 */5 * * * *").is_ok());
+        assert!(parse_schedule("not a schedule").is_err());
+    }
+
+    #[test]
+    fn test_missed_runs() {
+        let mut job = CronJob::new("0 * * * *", "hourly check").unwrap();
+        let now = Utc::now();
+        job.last_run = Some(now - Duration::hours(5));
+
+        let missed = job.missed_runs(now, 100).unwrap();
+        assert!((4..=5).contains(&missed.len()));
+        assert!(missed.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(job.missed_runs(now, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_one_shot_fires_once() {
+        let now = Utc::now();
+        assert!(CronJob::once(now - Duration::minutes(1), "too late").is_err());
+
+        let mut job = CronJob::once(now + Duration::hours(1), "take out the bins").unwrap();
+        assert!(job.is_one_shot());
+        assert_eq!(job.next_run().unwrap(), job.at);
+        assert!(job.missed_runs(now, 5).unwrap().is_empty());
+        assert_eq!(job.missed_runs(now + Duration::hours(2), 5).unwrap(), vec![job.at.unwrap()]);
+
+        job.last_run = Some(now + Duration::hours(2));
+        assert!(job.missed_runs(now + Duration::hours(3), 5).unwrap().is_empty());
+        assert_eq!(job.next_run().unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_at() {
+        let local = parse_at("2025-07-01 09:00").unwrap().with_timezone(&Local);
+        assert_eq!(local.format("%Y-%m-%d %H:%M").to_string(), "2025-07-01 09:00");
+        assert_eq!(
+            parse_at("2025-07-01T09:00:00Z").unwrap(),
+            Utc.with_ymd_and_hms(2025, 7, 1, 9, 0, 0).unwrap()
+        );
+        assert!(parse_at("next tuesday").is_err());
+    }
+
+    #[test]
+    fn test_failures_back_off_then_degrade() {
+        let retry = RetryPolicy::default();
+        let mut job = CronJob::new("0 9 * * *", "digest").unwrap();
+        let failed = Some(RunOutcome {
+            success: false,
+            duration: std::time::Duration::from_secs(1),
+        });
+        let now = Utc::now();
+
+        assert_eq!(job.record_run(now, failed, &retry), None);
+        assert_eq!(job.retry_at, Some(now + Duration::seconds(60)));
+        assert_eq!(job.next_attempt().unwrap(), job.retry_at);
+        assert_eq!(job.record_run(now, failed, &retry), None);
+        assert_eq!(job.retry_at, Some(now + Duration::seconds(120)));
+        assert_eq!(job.record_run(now, failed, &retry), Some(HealthChange::Degraded));
+        assert_eq!(job.record_run(now, failed, &retry), None);
+        assert!(job.degraded);
+
+        let succeeded = failed.map(|o| RunOutcome { success: true, ..o });
+        assert_eq!(job.record_run(now, succeeded, &retry), Some(HealthChange::Recovered));
+        assert_eq!((job.consecutive_failures, job.retry_at, job.degraded), (0, None, false));
+    }
+
+    #[test]
+    fn test_jitter_delay_is_bounded_and_stable() {
+        let job = CronJob::new("0 * * * *", "hourly").unwrap().with_jitter(300);
+        let at = Utc::now();
+        let delay = job.jitter_delay(at, 7);
+        assert!(delay >= Duration::zero() && delay <= Duration::seconds(300));
+        assert_eq!(job.jitter_delay(at, 7), delay);
+
+        let seeds: std::collections::HashSet<_> = (0..20).map(|seed| job.jitter_delay(at, seed)).collect();
+        assert!(seeds.len() > 1);
+        assert_eq!(job.with_jitter(0).jitter_delay(at, 7), Duration::zero());
+    }
+}
@@ -0,0 +1,495 @@
+//! Scheduler for recurring agent jobs
+//!
+//! Jobs are persisted in `workspace/cron/jobs.json` with their last-run
+//! timestamps; one-shot jobs fire once at a fixed time and are then
+//! removed. The gateway runs [`Scheduler::catch_up`] once the system
+//! clock is trustworthy, then ticks [`Scheduler::run_due`] until shutdown.
+//! Each job keeps the outcome and duration of its last run, summarized with
+//! its next fire time by [`JobStatus`]. Jobs may be jittered to spread a
+//! fleet's wakeups, and a shared semaphore caps how many background agent
+//! runs execute at once so they can't starve interactive traffic.
+//! [`Scheduler::trigger`] runs a job on demand, e.g. from the gateway's
+//! `POST /v1/jobs/<id>/run` webhook. The last runs of each job, with
+//! their output, are kept in `cron/history/` by [`RunHistory`]. A failed
+//! job is retried with exponential backoff per [`RetryPolicy`]; after
+//! repeated failures it is marked degraded and a [`JobNotifier`] is told.
+
+pub mod backoff;
+pub mod catchup;
+pub mod history;
+pub mod job;
+pub mod status;
+pub mod store;
+
+pub use backoff::{HealthChange, RetryPolicy};
+pub use catchup::CatchUpPolicy;
+pub use history::{RunHistory, RunRecord};
+pub use job::{parse_at, CronJob, RunOutcome};
+pub use status::JobStatus;
+pub use store::JobStore;
+
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Semaphore};
+use tracing::{debug, info, warn};
+
+/// Executes the work for a job when it fires
+#[async_trait]
+pub trait JobRunner: Send + Sync {
+    /// Run `job`, returning its output
+    async fn run_job(&self, job: &CronJob) -> Result<String>;
+}
+
+/// Delivers notices about failing jobs, e.g. through the alert channel
+#[async_trait]
+pub trait JobNotifier: Send + Sync {
+    async fn notify(&self, message: &str);
+}
+
+/// Runs due jobs from a [`JobStore`]
+pub struct Scheduler {
+    store: JobStore,
+    history: RunHistory,
+    retry: RetryPolicy,
+    notifier: Option<Arc<dyn JobNotifier>>,
+    tick: Duration,
+    background: Arc<Semaphore>,
+    jitter_seed: u64,
+}
+
+impl Scheduler {
+    /// Create a scheduler over `store` running one job at a time
+    pub fn new(store: JobStore) -> Self {
+        Self {
+            history: RunHistory::new(store.cron_dir()),
+            retry: RetryPolicy::default(),
+            notifier: None,
+            store,
+            tick: Duration::from_secs(1),
+            background: Arc::new(Semaphore::new(1)),
+            jitter_seed: rand::random(),
+        }
+    }
+
+    /// Allow up to `max` background runs at once (at least one)
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.background = Arc::new(Semaphore::new(max.max(1)));
+        self
+    }
+
+    /// Keep the last `runs` runs of each job in its history
+    pub fn with_history_limit(mut self, runs: usize) -> Self {
+        self.history = self.history.with_limit(runs);
+        self
+    }
+
+    /// Retry failed jobs according to `retry`
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Tell `notifier` when a job becomes degraded or recovers
+    pub fn with_notifier(mut self, notifier: Arc<dyn JobNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Seed job jitter with `seed` instead of a random per-process value
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = seed;
+        self
+    }
+
+    /// Permits for background agent runs; other background work (alert
+    /// prompts) should hold one while it runs to share the cap with jobs
+    pub fn background_permits(&self) -> Arc<Semaphore> {
+        self.background.clone()
+    }
+
+    /// Set how often due jobs are checked
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// The underlying job store
+    pub fn store(&self) -> &JobStore {
+        &self.store
+    }
+
+    /// Recent runs of each job
+    pub fn history(&self) -> &RunHistory {
+        &self.history
+    }
+
+    /// Apply each job's catch-up policy to runs missed while the device was off
+    ///
+    /// Must only be called once the system clock is sane; on a clock reset to
+    /// the epoch nothing looks missed, and a later jump would look like a
+    /// single late run regardless of policy.
+    pub async fn catch_up(&self, runner: &dyn JobRunner, now: DateTime<Utc>) -> Result<usize> {
+        let mut ran = 0;
+        for job in self.store.load().await? {
+            // Failing jobs are left to their retry timer
+            if !job.enabled || job.retry_at.is_some() || job.missed_runs(now, 1)?.is_empty() {
+                continue;
+            }
+            let planned = catchup::plan_catch_up(&job, now)?;
+            info!(
+                "Job {} missed runs while offline; policy {:?} runs {} now",
+                job.id,
+                job.catch_up,
+                planned.len()
+            );
+            let mut outcome = None;
+            for scheduled_at in planned {
+                debug!("Catching up job {} scheduled at {}", job.id, scheduled_at);
+                outcome = Some(self.execute(runner, &job).await);
+                ran += 1;
+            }
+            self.record(&job, now, outcome).await?;
+        }
+        Ok(ran)
+    }
+
+    /// Run every enabled job whose next fire time (plus jitter) or retry
+    /// time has passed
+    ///
+    /// Due jobs run concurrently up to the background cap. While a job is
+    /// failing its retry time replaces its schedule, so frequent jobs back
+    /// off too.
+    pub async fn run_due(&self, runner: &dyn JobRunner, now: DateTime<Utc>) -> Result<usize> {
+        let mut due = Vec::new();
+        for job in self.store.load().await? {
+            if !job.enabled {
+                continue;
+            }
+            let due_at = match job.retry_at {
+                Some(retry_at) => retry_at,
+                None => {
+                    let Some(scheduled_at) = job.missed_runs(now, 1)?.first().copied() else {
+                        continue;
+                    };
+                    scheduled_at + job.jitter_delay(scheduled_at, self.jitter_seed)
+                }
+            };
+            if due_at <= now {
+                due.push(job);
+            }
+        }
+
+        let outcomes = futures::future::join_all(due.iter().map(|job| self.execute(runner, job))).await;
+        for (job, outcome) in due.iter().zip(outcomes) {
+            self.record(job, now, Some(outcome)).await?;
+        }
+        Ok(due.len())
+    }
+
+    /// Run `job` now regardless of its schedule and record the run
+    ///
+    /// Shares the background cap with scheduled runs. A triggered one-shot
+    /// job that succeeds counts as fired and is removed.
+    pub async fn trigger(&self, runner: &dyn JobRunner, job: &CronJob) -> Result<RunOutcome> {
+        info!("Job {} triggered on demand", job.id);
+        let (outcome, output) = self.execute(runner, job).await;
+        self.record(job, Utc::now(), Some((outcome, output))).await?;
+        Ok(outcome)
+    }
+
+    /// Next fire time and last outcome of every job
+    pub async fn statuses(&self) -> Result<Vec<JobStatus>> {
+        Ok(self.store.load().await?.iter().map(JobStatus::from_job).collect())
+    }
+
+    /// Tick until a shutdown signal is received
+    pub async fn run(&self, runner: &dyn JobRunner, mut shutdown: broadcast::Receiver<()>) {
+        info!("Scheduler started");
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.tick) => {
+                    if let Err(e) = self.run_due(runner, Utc::now()).await {
+                        warn!("Scheduler tick failed: {}", e);
+                    }
+                }
+                _ = shutdown.recv() => {
+                    info!("Scheduler stopped");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Record a run and send a notice if it changed the job's health
+    async fn record(&self, job: &CronJob, at: DateTime<Utc>, run: Option<(RunOutcome, String)>) -> Result<()> {
+        let outcome = run.as_ref().map(|(outcome, _)| *outcome);
+        let change = self.store.record_run(&job.id, at, outcome, &self.retry).await?;
+        let message = match change {
+            Some(HealthChange::Degraded) => {
+                let error: String = run
+                    .as_ref()
+                    .map(|(_, output)| output.chars().take(300).collect())
+                    .unwrap_or_default();
+                let next = if job.is_one_shot() {
+                    "Giving up on this one-shot job.".to_string()
+                } else {
+                    format!("Retrying with backoff of up to {}s.", self.retry.max_secs)
+                };
+                warn!("Job {} is degraded after {} failures", job.id, self.retry.degraded_after);
+                format!(
+                    "Job {} ({}) failed {} times in a row. {} Last error: {}",
+                    job.id, job.description, self.retry.degraded_after, next, error
+                )
+            }
+            Some(HealthChange::Recovered) => {
+                info!("Job {} recovered", job.id);
+                format!("Job {} ({}) is running successfully again", job.id, job.description)
+            }
+            None => return Ok(()),
+        };
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(&message).await;
+        }
+        Ok(())
+    }
+
+    /// Run `job` while holding a background permit, returning its outcome
+    /// and output (or error)
+    async fn execute(&self, runner: &dyn JobRunner, job: &CronJob) -> (RunOutcome, String) {
+        // Held for the whole run; the semaphore is never closed
+        let _permit = self.background.acquire().await.ok();
+        info!("Running job {}: {}", job.id, job.description);
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let (success, output) = match runner.run_job(job).await {
+            Ok(output) => {
+                debug!("Job {} finished: {} chars of output", job.id, output.len());
+                (true, output)
+            }
+            Err(e) => {
+                warn!("Job {} failed: {}", job.id, e);
+                (false, e.to_string())
+            }
+        };
+        let outcome = RunOutcome {
+            success,
+            duration: started.elapsed(),
+        };
+
+        let record = RunRecord::new(started_at, outcome.duration.as_millis() as u64, success, &output);
+        if let Err(e) = self.history.append(&job.id, record).await {
+            warn!("Failed to record history for job {}: {}", job.id, e);
+        }
+        (outcome, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingRunner(AtomicUsize);
+
+    /// Records the most runs seen in flight at once
+    #[derive(Default)]
+    struct OverlapRunner {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl JobRunner for OverlapRunner {
+        async fn run_job(&self, _job: &CronJob) -> Result<String> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok("done".to_string())
+        }
+    }
+
+    #[async_trait]
+    impl JobRunner for CountingRunner {
+        async fn run_job(&self, _job: &CronJob) -> Result<String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok("done".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_then_nothing_due() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let mut job = CronJob::new("0 * * * *", "hourly")
+            .unwrap()
+            .with_catch_up(CatchUpPolicy::RunAll);
+        job.last_run = Some(Utc::now() - chrono::Duration::hours(3));
+        store.add(job).await.unwrap();
+
+        let scheduler = Scheduler::new(store);
+        let runner = CountingRunner(AtomicUsize::new(0));
+        let now = Utc::now();
+
+        let ran = scheduler.catch_up(&runner, now).await.unwrap();
+        assert!(ran >= 2);
+        assert_eq!(runner.0.load(Ordering::SeqCst), ran);
+        assert_eq!(scheduler.run_due(&runner, now).await.unwrap(), 0);
+
+        let statuses = scheduler.statuses().await.unwrap();
+        assert_eq!(statuses[0].last_success, Some(true));
+        assert!(statuses[0].next_run.unwrap() > now);
+    }
+
+    #[tokio::test]
+    async fn test_skip_policy_records_run_without_executing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let mut job = CronJob::new("0 * * * *", "hourly")
+            .unwrap()
+            .with_catch_up(CatchUpPolicy::Skip);
+        job.last_run = Some(Utc::now() - chrono::Duration::hours(3));
+        let id = job.id.clone();
+        store.add(job).await.unwrap();
+
+        let scheduler = Scheduler::new(store);
+        let runner = CountingRunner(AtomicUsize::new(0));
+        let now = Utc::now();
+
+        assert_eq!(scheduler.catch_up(&runner, now).await.unwrap(), 0);
+        let job = scheduler.store().get(&id).await.unwrap().unwrap();
+        assert_eq!(job.last_run, Some(now));
+        assert_eq!(job.last_success, None);
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_job_is_removed_after_firing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let job = CronJob::once(Utc::now() + chrono::Duration::minutes(5), "reminder").unwrap();
+        store.add(job).await.unwrap();
+        store.add(CronJob::new("0 9 * * *", "daily").unwrap()).await.unwrap();
+
+        let scheduler = Scheduler::new(store);
+        let runner = CountingRunner(AtomicUsize::new(0));
+        assert_eq!(scheduler.run_due(&runner, Utc::now()).await.unwrap(), 0);
+
+        let later = Utc::now() + chrono::Duration::minutes(6);
+        assert_eq!(scheduler.run_due(&runner, later).await.unwrap(), 1);
+        let jobs = scheduler.store().load().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].description, "daily");
+    }
+
+    #[tokio::test]
+    async fn test_trigger_runs_and_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let job = CronJob::new("0 9 * * *", "daily").unwrap();
+        let id = job.id.clone();
+        store.add(job.clone()).await.unwrap();
+
+        let scheduler = Scheduler::new(store);
+        let runner = CountingRunner(AtomicUsize::new(0));
+        let outcome = scheduler.trigger(&runner, &job).await.unwrap();
+        assert!(outcome.success);
+        assert_eq!(runner.0.load(Ordering::SeqCst), 1);
+        let job = scheduler.store().get(&id).await.unwrap().unwrap();
+        assert!(job.last_run.is_some());
+        assert_eq!(job.last_success, Some(true));
+
+        let runs = scheduler.history().load(&id).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].output, "done");
+        assert!(runs[0].success);
+    }
+
+    struct FailingRunner;
+
+    #[async_trait]
+    impl JobRunner for FailingRunner {
+        async fn run_job(&self, _job: &CronJob) -> Result<String> {
+            Err(crate::error::Error::tool("provider unreachable"))
+        }
+    }
+
+    #[derive(Default)]
+    struct Notices(std::sync::Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl JobNotifier for Notices {
+        async fn notify(&self, message: &str) {
+            self.0.lock().unwrap().push(message.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_job_backs_off_and_notifies_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let mut job = CronJob::new("0 9 * * *", "nightly digest").unwrap();
+        job.last_run = Some(Utc::now() - chrono::Duration::days(1));
+        let id = job.id.clone();
+        store.add(job).await.unwrap();
+
+        let notices = Arc::new(Notices::default());
+        let scheduler = Scheduler::new(store).with_notifier(notices.clone());
+        let mut now = Utc::now();
+        assert_eq!(scheduler.run_due(&FailingRunner, now).await.unwrap(), 1);
+        for expected_delay in [60, 120, 240] {
+            let job = scheduler.store().get(&id).await.unwrap().unwrap();
+            assert_eq!(job.retry_at, Some(now + chrono::Duration::seconds(expected_delay)));
+            assert_eq!(scheduler.run_due(&FailingRunner, now).await.unwrap(), 0);
+            now = job.retry_at.unwrap();
+            assert_eq!(scheduler.run_due(&FailingRunner, now).await.unwrap(), 1);
+        }
+
+        let status = &scheduler.statuses().await.unwrap()[0];
+        assert!(status.degraded && status.retrying);
+        assert_eq!(status.consecutive_failures, 4);
+        let notices = notices.0.lock().unwrap();
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].contains("nightly digest") && notices[0].contains("provider unreachable"));
+    }
+
+    #[tokio::test]
+    async fn test_background_runs_are_capped() {
+        for max in [1, 2] {
+            let dir = tempfile::tempdir().unwrap();
+            let store = JobStore::new(dir.path());
+            for _ in 0..3 {
+                let mut job = CronJob::new("0 * * * *", "hourly").unwrap();
+                job.last_run = Some(Utc::now() - chrono::Duration::hours(2));
+                store.add(job).await.unwrap();
+            }
+
+            let scheduler = Scheduler::new(store).with_max_concurrent(max);
+            let runner = OverlapRunner::default();
+            assert_eq!(scheduler.run_due(&runner, Utc::now()).await.unwrap(), 3);
+            assert_eq!(runner.peak.load(Ordering::SeqCst), max);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jitter_delays_due_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let mut job = CronJob::new("0 * * * *", "hourly").unwrap().with_jitter(600);
+        job.last_run = Some(Utc::now() - chrono::Duration::hours(2));
+        let scheduled_at = job.missed_runs(Utc::now(), 1).unwrap()[0];
+        let seed = (0..)
+            .find(|seed| job.jitter_delay(scheduled_at, *seed) >= chrono::Duration::seconds(2))
+            .unwrap();
+        let due_at = scheduled_at + job.jitter_delay(scheduled_at, seed);
+        store.add(job).await.unwrap();
+
+        let scheduler = Scheduler::new(store).with_jitter_seed(seed);
+        let runner = CountingRunner(AtomicUsize::new(0));
+        let early = due_at - chrono::Duration::seconds(1);
+        assert_eq!(scheduler.run_due(&runner, early).await.unwrap(), 0);
+        assert_eq!(scheduler.run_due(&runner, due_at).await.unwrap(), 1);
+    }
+}
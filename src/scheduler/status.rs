@@ -0,0 +1,129 @@
+//! Per-job schedule summaries for status output
+
+use super::job::CronJob;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+/// When a job fires next and how its last run went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    /// Cron expression, or `at <time>` for one-shot jobs
+    pub expression: String,
+    pub description: String,
+    pub enabled: bool,
+    /// Maximum random delay after each fire time
+    #[serde(default)]
+    pub jitter_secs: u64,
+    /// Next attempt (a retry while failing); `None` when disabled or the
+    /// schedule never fires again
+    pub next_run: Option<DateTime<Utc>>,
+    /// Whether `next_run` is a backoff retry
+    #[serde(default)]
+    pub retrying: bool,
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    /// Whether the job has failed too often in a row
+    #[serde(default)]
+    pub degraded: bool,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_success: Option<bool>,
+    pub last_duration_ms: Option<u64>,
+}
+
+impl JobStatus {
+    /// Summarize `job`
+    pub fn from_job(job: &CronJob) -> Self {
+        let next_run = if job.enabled {
+            job.next_attempt().ok().flatten()
+        } else {
+            None
+        };
+        Self {
+            id: job.id.clone(),
+            expression: job.schedule_label(),
+            description: job.description.clone(),
+            enabled: job.enabled,
+            jitter_secs: job.jitter_secs,
+            next_run,
+            retrying: job.retry_at.is_some(),
+            consecutive_failures: job.consecutive_failures,
+            degraded: job.degraded,
+            last_run: job.last_run,
+            last_success: job.last_success,
+            last_duration_ms: job.last_duration_ms,
+        }
+    }
+
+    /// One-line schedule summary in local time, e.g.
+    /// `next 2025-07-01 09:00 · last 2025-06-30 09:00 ok in 3.2s`
+    pub fn schedule_line(&self) -> String {
+        let next = match (self.enabled, self.next_run) {
+            (false, _) => "disabled".to_string(),
+            (true, Some(next)) if self.retrying => format!(
+                "{}retry {} after {} failures",
+                if self.degraded { "DEGRADED · " } else { "" },
+                local(next),
+                self.consecutive_failures
+            ),
+            (true, Some(next)) if self.jitter_secs > 0 => {
+                format!("next {} (+ up to {}s jitter)", local(next), self.jitter_secs)
+            }
+            (true, Some(next)) => format!("next {}", local(next)),
+            (true, None) => "no upcoming runs".to_string(),
+        };
+        let last = match self.last_run {
+            None => "never run".to_string(),
+            Some(at) => {
+                let mut text = format!("last {}", local(at));
+                match self.last_success {
+                    Some(true) => text.push_str(" ok"),
+                    Some(false) => text.push_str(" failed"),
+                    None => text.push_str(" skipped"),
+                }
+                if let Some(ms) = self.last_duration_ms.filter(|_| self.last_success.is_some()) {
+                    text.push_str(&format!(" in {:.1}s", ms as f64 / 1000.0));
+                }
+                text
+            }
+        };
+        format!("{} · {}", next, last)
+    }
+}
+
+fn local(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_line() {
+        let mut job = CronJob::new("0 9 * * *", "morning digest").unwrap();
+        let status = JobStatus::from_job(&job);
+        assert!(status.next_run.is_some());
+        assert!(status.schedule_line().starts_with("next "));
+        assert!(status.schedule_line().ends_with("· never run"));
+
+        job.last_run = Some(Utc::now());
+        job.last_success = Some(false);
+        job.last_duration_ms = Some(3200);
+        assert!(JobStatus::from_job(&job).schedule_line().ends_with(" failed in 3.2s"));
+
+        job.retry_at = Some(Utc::now());
+        job.consecutive_failures = 3;
+        job.degraded = true;
+        assert!(JobStatus::from_job(&job).schedule_line().starts_with("DEGRADED · retry "));
+        job.retry_at = None;
+
+        job.jitter_secs = 90;
+        assert!(JobStatus::from_job(&job).schedule_line().contains("(+ up to 90s jitter)"));
+
+        job.enabled = false;
+        let status = JobStatus::from_job(&job);
+        assert_eq!(status.next_run, None);
+        assert!(status.schedule_line().starts_with("disabled · last "));
+    }
+}
@@ -0,0 +1,98 @@
+//! Persistent job storage
+
+use super::backoff::{HealthChange, RetryPolicy};
+use super::job::{CronJob, RunOutcome};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+const JOBS_FILE: &str = "jobs.json";
+
+/// Stores scheduled jobs in `<cron dir>/jobs.json`
+pub struct JobStore {
+    cron_dir: PathBuf,
+}
+
+impl JobStore {
+    /// Create a store rooted at the workspace cron directory
+    pub fn new(cron_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cron_dir: cron_dir.into(),
+        }
+    }
+
+    /// Directory the store lives in
+    pub fn cron_dir(&self) -> &Path {
+        &self.cron_dir
+    }
+
+    /// Load all jobs
+    pub async fn load(&self) -> Result<Vec<CronJob>> {
+        let path = self.cron_dir.join(JOBS_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&path).await?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Replace all jobs
+    pub async fn save(&self, jobs: &[CronJob]) -> Result<()> {
+        tokio::fs::create_dir_all(&self.cron_dir).await?;
+        let path = self.cron_dir.join(JOBS_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string_pretty(jobs)?).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Add a job
+    pub async fn add(&self, job: CronJob) -> Result<()> {
+        let mut jobs = self.load().await?;
+        jobs.push(job);
+        self.save(&jobs).await
+    }
+
+    /// Remove a job by id
+    pub async fn remove(&self, id: &str) -> Result<CronJob> {
+        let mut jobs = self.load().await?;
+        let index = jobs
+            .iter()
+            .position(|j| j.id == id)
+            .ok_or_else(|| Error::config(format!("Job not found: {}", id)))?;
+        let job = jobs.remove(index);
+        self.save(&jobs).await?;
+        Ok(job)
+    }
+
+    /// Get a job by id
+    pub async fn get(&self, id: &str) -> Result<Option<CronJob>> {
+        Ok(self.load().await?.into_iter().find(|j| j.id == id))
+    }
+
+    /// Record that a job ran at `at`, with its outcome if it was executed
+    /// rather than skipped, returning any change in the job's health
+    ///
+    /// One-shot jobs are removed once they succeed, are skipped, or become
+    /// degraded; until then a failed one is retried like any other job.
+    /// Reloads before writing so jobs added concurrently by the CLI are kept.
+    pub async fn record_run(
+        &self,
+        id: &str,
+        at: DateTime<Utc>,
+        outcome: Option<RunOutcome>,
+        retry: &RetryPolicy,
+    ) -> Result<Option<HealthChange>> {
+        let mut jobs = self.load().await?;
+        let Some(index) = jobs.iter().position(|j| j.id == id) else {
+            return Ok(None);
+        };
+        let change = jobs[index].record_run(at, outcome, retry);
+        let job = &jobs[index];
+        if job.is_one_shot() && (job.retry_at.is_none() || job.degraded) {
+            jobs.remove(index);
+        }
+        self.save(&jobs).await?;
+        Ok(change)
+    }
+}
@@ -0,0 +1,281 @@
+//! Cold storage for idle sessions
+//!
+//! Sessions idle for longer than the configured age are gzip-compressed into
+//! the archive directory and removed from the hot sessions directory, keeping
+//! startup scans fast on slow SD cards. An `index.jsonl` file keeps archived
+//! sessions searchable without decompressing them.
+
+use super::manager::SessionManager;
+use super::store::Session;
+use crate::agent::context::MessageRole;
+use crate::error::{Error, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+const INDEX_FILE: &str = "index.jsonl";
+const SNIPPET_CHARS: usize = 120;
+
+/// Index entry describing an archived session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub id: String,
+    pub user_id: String,
+    pub title: Option<String>,
+    pub created_at: SystemTime,
+    pub last_activity: SystemTime,
+    pub archived_at: SystemTime,
+    pub message_count: usize,
+    /// Start of the first user message
+    pub snippet: String,
+}
+
+impl ArchiveEntry {
+    fn from_session(session: &Session) -> Self {
+        let snippet = session
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.chars().take(SNIPPET_CHARS).collect())
+            .unwrap_or_default();
+        Self {
+            id: session.id.clone(),
+            user_id: session.user_id.clone(),
+            title: session.metadata.title.clone(),
+            created_at: session.created_at,
+            last_activity: session.last_activity,
+            archived_at: SystemTime::now(),
+            message_count: session.messages.len(),
+            snippet,
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.id.contains(&query)
+            || self
+                .title
+                .as_deref()
+                .is_some_and(|t| t.to_lowercase().contains(&query))
+            || self.snippet.to_lowercase().contains(&query)
+    }
+}
+
+/// Compressed archive of idle sessions
+pub struct SessionArchive {
+    archive_dir: PathBuf,
+}
+
+impl SessionArchive {
+    /// Create an archive rooted at `archive_dir`
+    pub fn new(archive_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            archive_dir: archive_dir.into(),
+        }
+    }
+
+    /// Archive directory for a sessions directory (`<sessions>/archive`)
+    pub fn for_sessions_dir(sessions_dir: &Path) -> Self {
+        Self::new(sessions_dir.join("archive"))
+    }
+
+    /// Archive every session idle for longer than `max_idle`, returning the archived ids
+    pub async fn archive_idle(
+        &self,
+        manager: &SessionManager,
+        max_idle: Duration,
+    ) -> Result<Vec<String>> {
+        let now = SystemTime::now();
+        let mut archived = Vec::new();
+        for session in manager.list_sessions().await? {
+            let idle = now
+                .duration_since(session.last_activity)
+                .unwrap_or(Duration::ZERO);
+            if idle < max_idle {
+                continue;
+            }
+            self.archive_session(manager, &session).await?;
+            archived.push(session.id);
+        }
+        if !archived.is_empty() {
+            info!("Archived {} idle sessions", archived.len());
+        }
+        Ok(archived)
+    }
+
+    /// Compress `session` into the archive and remove it from the hot directory
+    pub async fn archive_session(&self, manager: &SessionManager, session: &Session) -> Result<()> {
+        tokio::fs::create_dir_all(&self.archive_dir).await?;
+
+        let json = serde_json::to_vec(session)?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&json)?;
+        let compressed = encoder.finish()?;
+
+        let path = self.archive_path(&session.id);
+        let tmp_path = path.with_extension("gz.tmp");
+        tokio::fs::write(&tmp_path, compressed).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        let mut line = serde_json::to_string(&ArchiveEntry::from_session(session))?;
+        line.push('\n');
+        let mut index = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.archive_dir.join(INDEX_FILE))
+            .await?;
+        tokio::io::AsyncWriteExt::write_all(&mut index, line.as_bytes()).await?;
+
+        manager.delete_session(&session.id).await
+    }
+
+    /// Load an archived session
+    pub async fn load(&self, session_id: &str) -> Result<Session> {
+        let path = self.archive_path(session_id);
+        if !path.exists() {
+            return Err(Error::session(format!("Archived session not found: {}", session_id)));
+        }
+        let compressed = tokio::fs::read(&path).await?;
+        let mut json = String::new();
+        GzDecoder::new(compressed.as_slice()).read_to_string(&mut json)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Move an archived session back into the hot sessions directory
+    pub async fn restore(&self, manager: &SessionManager, session_id: &str) -> Result<Session> {
+        let session = self.load(session_id).await?;
+        manager.save_session(&session).await?;
+        tokio::fs::remove_file(self.archive_path(session_id)).await?;
+        self.remove_from_index(session_id).await?;
+        Ok(session)
+    }
+
+    /// Rewrite the index without the lines for `session_id`
+    async fn remove_from_index(&self, session_id: &str) -> Result<()> {
+        let index_path = self.archive_dir.join(INDEX_FILE);
+        if !index_path.exists() {
+            return Ok(());
+        }
+        let contents = tokio::fs::read_to_string(&index_path).await?;
+        let kept: String = contents
+            .lines()
+            .filter(|line| {
+                serde_json::from_str::<ArchiveEntry>(line).map_or(true, |entry| entry.id != session_id)
+            })
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        let tmp_path = index_path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp_path, kept).await?;
+        tokio::fs::rename(&tmp_path, &index_path).await?;
+        Ok(())
+    }
+
+    /// All index entries for sessions still present in the archive
+    ///
+    /// A session archived more than once is listed once, from its latest line.
+    pub async fn entries(&self) -> Result<Vec<ArchiveEntry>> {
+        let index_path = self.archive_dir.join(INDEX_FILE);
+        if !index_path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&index_path).await?;
+        let mut entries = Vec::new();
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<ArchiveEntry>(line) {
+                Ok(entry) if self.archive_path(&entry.id).exists() => {
+                    entries.retain(|e: &ArchiveEntry| e.id != entry.id);
+                    entries.push(entry);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Skipping malformed archive index line: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Search archived sessions by id, title, or first message
+    pub async fn search(&self, query: &str) -> Result<Vec<ArchiveEntry>> {
+        Ok(self
+            .entries()
+            .await?
+            .into_iter()
+            .filter(|e| e.matches(query))
+            .collect())
+    }
+
+    fn archive_path(&self, session_id: &str) -> PathBuf {
+        self.archive_dir.join(format!("{}.json.gz", session_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_archive_and_restore_idle_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = SessionManager::new(dir.path());
+        let archive = SessionArchive::for_sessions_dir(dir.path());
+
+        let mut session = manager.create_session("user-1").await.unwrap();
+        session.add_message(MessageRole::User, "How do I prune tomatoes?");
+        session.last_activity = SystemTime::now() - Duration::from_secs(40 * 86400);
+        manager.save_session(&session).await.unwrap();
+        manager.create_session("user-2").await.unwrap();
+
+        let archived = archive
+            .archive_idle(&manager, Duration::from_secs(30 * 86400))
+            .await
+            .unwrap();
+        assert_eq!(archived, vec![session.id.clone()]);
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 1);
+
+        let found = archive.search("tomatoes").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message_count, 1);
+
+        let restored = archive.restore(&manager, &session.id).await.unwrap();
+        assert_eq!(restored.messages[0].content, "How do I prune tomatoes?");
+        assert!(archive.entries().await.unwrap().is_empty());
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rearchived_session_listed_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = SessionManager::new(dir.path());
+        let archive = SessionArchive::for_sessions_dir(dir.path());
+
+        let mut session = manager.create_session("user-1").await.unwrap();
+        session.add_message(MessageRole::User, "Repot the basil?");
+        archive.archive_session(&manager, &session).await.unwrap();
+        archive.restore(&manager, &session.id).await.unwrap();
+        let index = std::fs::read_to_string(dir.path().join("archive").join(INDEX_FILE)).unwrap();
+        assert!(!index.contains(&session.id));
+
+        session.add_message(MessageRole::User, "And the mint?");
+        archive.archive_session(&manager, &session).await.unwrap();
+        let entries = archive.entries().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message_count, 2);
+
+        // Indexes written before restore pruned its lines may still repeat an id
+        let stale = serde_json::to_string(&ArchiveEntry {
+            message_count: 1,
+            ..entries[0].clone()
+        })
+        .unwrap();
+        let index_path = dir.path().join("archive").join(INDEX_FILE);
+        let index = std::fs::read_to_string(&index_path).unwrap();
+        std::fs::write(&index_path, format!("{}\n{}", stale, index)).unwrap();
+        let entries = archive.entries().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message_count, 2);
+    }
+}
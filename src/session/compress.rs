@@ -0,0 +1,316 @@
+//! Merging of near-duplicate old exchanges
+//!
+//! Long-lived household sessions collect the same exchanges over and over
+//! ("is the porch light on?"). Compression embeds each exchange (a user
+//! message and the replies up to the next one) older than the configured
+//! age and clusters them greedily: an exchange joins the first cluster whose
+//! first exchange is similar enough, or starts a new one. Each cluster of two
+//! or more is replaced by a single system message holding a canonical
+//! summary, placed where its newest exchange was. Summaries are system
+//! messages, so later passes never merge them again.
+
+use super::manager::SessionManager;
+use super::store::Session;
+use crate::agent::context::{Message, MessageRole};
+use crate::config::CompressionConfig;
+use crate::error::Result;
+use crate::llm::ModelRouter;
+use crate::memory::{cosine_similarity, Embedder};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Start of every summary message
+pub const SUMMARY_PREFIX: &str = "[Summary of";
+
+const SUMMARY_INSTRUCTION: &str = "The following exchanges from one conversation are near-duplicates. \
+Write a single short canonical version of them: what was asked and the answer that holds now, \
+preferring the most recent answer where they differ. Reply with the summary only.";
+
+/// Outcome of compressing every session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionReport {
+    /// Sessions that had exchanges merged
+    pub sessions: usize,
+    /// Exchanges removed, not counting the ones kept as summaries
+    pub exchanges_removed: usize,
+}
+
+/// A user message and the replies to it
+struct Exchange {
+    range: Range<usize>,
+    text: String,
+    at: SystemTime,
+}
+
+/// Merges near-duplicate old exchanges in sessions
+pub struct SessionCompressor {
+    embedder: Arc<dyn Embedder>,
+    router: Option<ModelRouter>,
+    config: CompressionConfig,
+}
+
+impl SessionCompressor {
+    /// Compressor comparing exchanges with `embedder`
+    ///
+    /// Without a router, a cluster's newest exchange is kept verbatim as its
+    /// canonical version.
+    pub fn new(embedder: Arc<dyn Embedder>, config: CompressionConfig) -> Self {
+        Self {
+            embedder,
+            router: None,
+            config,
+        }
+    }
+
+    /// Write canonical summaries on `router`'s summary route
+    pub fn with_router(mut self, router: ModelRouter) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Merge near-duplicate exchanges in `session` older than the configured
+    /// age at `now`, returning how many exchanges were removed
+    pub async fn compress(&self, session: &mut Session, now: SystemTime) -> Result<usize> {
+        let cutoff = now
+            .checked_sub(Duration::from_secs(self.config.after_days * 24 * 60 * 60))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let exchanges: Vec<Exchange> = exchanges(&session.messages)
+            .into_iter()
+            .filter(|exchange| exchange.at < cutoff)
+            .collect();
+        if exchanges.len() < 2 {
+            return Ok(0);
+        }
+
+        let mut clusters: Vec<(Vec<f32>, Vec<&Exchange>)> = Vec::new();
+        for exchange in &exchanges {
+            let vector = self.embedder.embed(&exchange.text).await?;
+            match clusters
+                .iter_mut()
+                .find(|(first, _)| cosine_similarity(first, &vector) >= self.config.similarity)
+            {
+                Some((_, members)) => members.push(exchange),
+                None => clusters.push((vector, vec![exchange])),
+            }
+        }
+
+        // Summaries keyed by where the newest exchange of their cluster starts
+        let mut summaries: HashMap<usize, Message> = HashMap::new();
+        let mut removed = vec![false; session.messages.len()];
+        let mut merged = 0;
+        for (_, members) in clusters.iter().filter(|(_, members)| members.len() > 1) {
+            let newest = members[members.len() - 1];
+            let summary = self.summarize(&session.id, members).await;
+            summaries.insert(
+                newest.range.start,
+                Message {
+                    role: MessageRole::System,
+                    content: format!(
+                        "{} {} similar exchanges, {} to {}]\n{}",
+                        SUMMARY_PREFIX,
+                        members.len(),
+                        date(members[0].at),
+                        date(newest.at),
+                        summary
+                    ),
+                    timestamp: newest.at,
+                },
+            );
+            for exchange in members {
+                removed[exchange.range.clone()].iter_mut().for_each(|r| *r = true);
+            }
+            merged += members.len() - 1;
+        }
+        if merged == 0 {
+            return Ok(0);
+        }
+
+        let messages = std::mem::take(&mut session.messages);
+        for (i, message) in messages.into_iter().enumerate() {
+            if let Some(summary) = summaries.remove(&i) {
+                session.messages.push(summary);
+            }
+            if !removed[i] {
+                session.messages.push(message);
+            }
+        }
+        debug!("Merged {} exchanges in session {}", merged, session.id);
+        Ok(merged)
+    }
+
+    /// Compress every session in `manager`
+    ///
+    /// Messages added while a session was being compressed are kept: the
+    /// result is only saved if the session still starts with the messages
+    /// that were compressed, with anything newer appended.
+    pub async fn compress_all(&self, manager: &SessionManager) -> Result<CompressionReport> {
+        let mut report = CompressionReport::default();
+        for session in manager.list_sessions().await? {
+            let mut compressed = session.clone();
+            let merged = self.compress(&mut compressed, SystemTime::now()).await?;
+            if merged == 0 {
+                continue;
+            }
+            let current = manager.load_session(&session.id).await?;
+            let unchanged = current.messages.len() >= session.messages.len()
+                && current
+                    .messages
+                    .iter()
+                    .zip(&session.messages)
+                    .all(|(a, b)| a.timestamp == b.timestamp && a.content == b.content);
+            if !unchanged {
+                warn!("Session {} changed while compressing it; skipping", session.id);
+                continue;
+            }
+            let mut updated = current;
+            let newer = updated.messages.split_off(session.messages.len());
+            updated.messages = compressed.messages;
+            updated.messages.extend(newer);
+            manager.save_session(&updated).await?;
+            report.sessions += 1;
+            report.exchanges_removed += merged;
+        }
+        if report.sessions > 0 {
+            info!(
+                "Compressed {} sessions, merging {} exchanges",
+                report.sessions, report.exchanges_removed
+            );
+        }
+        Ok(report)
+    }
+
+    /// Compress every session in `manager` every `interval_hours` until shutdown
+    pub async fn run(self, manager: SessionManager, mut shutdown: broadcast::Receiver<()>) {
+        let interval = Duration::from_secs(self.config.interval_hours.max(1) * 60 * 60);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if let Err(e) = self.compress_all(&manager).await {
+                        warn!("Session compression failed: {}", e);
+                    }
+                }
+                _ = shutdown.recv() => {
+                    debug!("Session compressor stopping");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Canonical version of `members`, oldest first
+    async fn summarize(&self, session_id: &str, members: &[&Exchange]) -> String {
+        let newest = &members[members.len() - 1].text;
+        let Some(router) = &self.router else {
+            return newest.clone();
+        };
+        let text: Vec<&str> = members.iter().map(|exchange| exchange.text.as_str()).collect();
+        match router.summarize(SUMMARY_INSTRUCTION, &text.join("\n\n")).await {
+            Ok(summary) if !summary.trim().is_empty() => summary.trim().to_string(),
+            Ok(_) => newest.clone(),
+            Err(e) => {
+                warn!("Summarizing exchanges in {} failed, keeping the newest: {}", session_id, e);
+                newest.clone()
+            }
+        }
+    }
+}
+
+/// Exchanges in `messages`; system messages belong to none
+fn exchanges(messages: &[Message]) -> Vec<Exchange> {
+    let mut exchanges: Vec<Exchange> = Vec::new();
+    let mut open = false;
+    for (i, message) in messages.iter().enumerate() {
+        let line = match message.role {
+            MessageRole::User => {
+                exchanges.push(Exchange {
+                    range: i..i,
+                    text: String::new(),
+                    at: message.timestamp,
+                });
+                open = true;
+                format!("User: {}", message.content)
+            }
+            MessageRole::Assistant if open => format!("Assistant: {}", message.content),
+            _ => {
+                open = false;
+                continue;
+            }
+        };
+        let exchange = exchanges.last_mut().expect("an exchange is open");
+        if !exchange.text.is_empty() {
+            exchange.text.push('\n');
+        }
+        exchange.text.push_str(&line);
+        exchange.range.end = i + 1;
+        exchange.at = message.timestamp;
+    }
+    exchanges
+}
+
+fn date(at: SystemTime) -> String {
+    DateTime::<Local>::from(at).format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::HashEmbedder;
+
+    fn message(role: MessageRole, content: &str, days_ago: u64) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            timestamp: SystemTime::now() - Duration::from_secs(days_ago * 24 * 60 * 60),
+        }
+    }
+
+    fn porch(days_ago: u64, answer: &str) -> [Message; 2] {
+        [
+            message(MessageRole::User, "Is the porch light on?", days_ago),
+            message(MessageRole::Assistant, answer, days_ago),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_merges_old_near_duplicates_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = SessionManager::new(dir.path());
+        let mut session = manager.create_session("alice").await.unwrap();
+        session.messages.extend(porch(30, "Yes, the porch light is on."));
+        session.messages.push(message(MessageRole::User, "Add milk to the shopping list", 25));
+        session.messages.push(message(MessageRole::Assistant, "Added milk.", 25));
+        session.messages.extend(porch(20, "No, the porch light is off."));
+        session.messages.push(message(MessageRole::System, "Persona switched", 15));
+        session.messages.extend(porch(10, "Yes, the porch light is on."));
+        // Recent enough to be left alone
+        session.messages.extend(porch(1, "Yes, the porch light is on."));
+        manager.save_session(&session).await.unwrap();
+
+        let config = CompressionConfig {
+            similarity: 0.8,
+            ..Default::default()
+        };
+        let compressor = SessionCompressor::new(Arc::new(HashEmbedder::default()), config);
+        let report = compressor.compress_all(&manager).await.unwrap();
+        assert_eq!(report, CompressionReport { sessions: 1, exchanges_removed: 2 });
+
+        let session = manager.load_session(&session.id).await.unwrap();
+        let contents: Vec<&str> = session.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents.len(), 6);
+        assert_eq!(contents[0], "Add milk to the shopping list");
+        assert_eq!(contents[2], "Persona switched");
+        assert!(contents[3].starts_with("[Summary of 3 similar exchanges, "), "{}", contents[3]);
+        assert!(contents[3].ends_with("\nUser: Is the porch light on?\nAssistant: Yes, the porch light is on."));
+        assert_eq!(session.messages[3].role, MessageRole::System);
+        assert_eq!(contents[4], "Is the porch light on?");
+
+        // A second pass finds nothing left to merge
+        assert_eq!(compressor.compress_all(&manager).await.unwrap(), CompressionReport::default());
+    }
+}
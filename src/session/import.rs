@@ -0,0 +1,349 @@
+//! Import conversation history exported from other assistants
+//!
+//! Supported formats:
+//! - `chatgpt`: the `conversations.json` file from a ChatGPT data export
+//! - `generic-jsonl`: one message per line as
+//!   `{"conversation": "...", "title": "...", "role": "user", "content": "...", "timestamp": ...}`
+//!   where `conversation`, `title`, and `timestamp` (RFC 3339 or Unix seconds) are optional
+
+use super::manager::SessionManager;
+use super::store::{Session, SessionMetadata};
+use crate::agent::context::{Message, MessageRole};
+use crate::error::{Error, Result};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, info};
+
+/// `custom_data` key recording the source conversation id of an imported session
+pub const IMPORT_ID_KEY: &str = "import_id";
+
+/// Format of an exported conversation archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    ChatGpt,
+    GenericJsonl,
+}
+
+impl ImportFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            ImportFormat::ChatGpt => "chatgpt",
+            ImportFormat::GenericJsonl => "generic-jsonl",
+        }
+    }
+}
+
+impl std::str::FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.replace('_', "-").to_lowercase().as_str() {
+            "chatgpt" => Ok(ImportFormat::ChatGpt),
+            "generic-jsonl" | "jsonl" => Ok(ImportFormat::GenericJsonl),
+            other => Err(format!(
+                "unknown import format '{}' (expected chatgpt or generic-jsonl)",
+                other
+            )),
+        }
+    }
+}
+
+/// A conversation read from an export
+#[derive(Debug, Clone)]
+pub struct ImportedConversation {
+    /// Conversation id in the source system
+    pub source_id: String,
+    pub title: Option<String>,
+    pub created_at: SystemTime,
+    pub messages: Vec<Message>,
+}
+
+/// Parse a ChatGPT `conversations.json` export
+pub fn parse_chatgpt(contents: &str) -> Result<Vec<ImportedConversation>> {
+    let data: Value = serde_json::from_str(contents)?;
+    let conversations = data
+        .as_array()
+        .ok_or_else(|| Error::serialization("Expected a JSON array of conversations"))?;
+
+    let mut imported = Vec::new();
+    for (index, conversation) in conversations.iter().enumerate() {
+        let mapping = match conversation["mapping"].as_object() {
+            Some(mapping) => mapping,
+            None => continue,
+        };
+
+        // Follow the active branch from the current node back to the root
+        let mut node_id = conversation["current_node"].as_str().map(str::to_string);
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        while let Some(id) = node_id {
+            if !seen.insert(id.clone()) {
+                break;
+            }
+            let Some(node) = mapping.get(&id) else {
+                break;
+            };
+            chain.push(&node["message"]);
+            node_id = node["parent"].as_str().map(str::to_string);
+        }
+        chain.reverse();
+
+        let messages: Vec<Message> = chain.into_iter().filter_map(chatgpt_message).collect();
+        if messages.is_empty() {
+            continue;
+        }
+
+        imported.push(ImportedConversation {
+            source_id: conversation["id"]
+                .as_str()
+                .or_else(|| conversation["conversation_id"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("chatgpt-{}", index)),
+            title: conversation["title"]
+                .as_str()
+                .filter(|t| !t.trim().is_empty())
+                .map(str::to_string),
+            created_at: conversation["create_time"]
+                .as_f64()
+                .and_then(unix_time)
+                .unwrap_or(messages[0].timestamp),
+            messages,
+        });
+    }
+    Ok(imported)
+}
+
+fn chatgpt_message(message: &Value) -> Option<Message> {
+    let role = match message["author"]["role"].as_str()? {
+        "user" => MessageRole::User,
+        "assistant" => MessageRole::Assistant,
+        _ => return None,
+    };
+    let content: Vec<&str> = message["content"]["parts"]
+        .as_array()?
+        .iter()
+        .filter_map(|part| part.as_str())
+        .collect();
+    let content = content.join("\n");
+    if content.trim().is_empty() {
+        return None;
+    }
+    Some(Message {
+        role,
+        content,
+        timestamp: message["create_time"]
+            .as_f64()
+            .and_then(unix_time)
+            .unwrap_or(UNIX_EPOCH),
+    })
+}
+
+/// Parse generic JSONL, grouping lines into conversations by their `conversation` field
+pub fn parse_generic_jsonl(contents: &str) -> Result<Vec<ImportedConversation>> {
+    let mut order = Vec::new();
+    let mut conversations: HashMap<String, ImportedConversation> = HashMap::new();
+
+    for (line_no, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Value = serde_json::from_str(line).map_err(|e| {
+            Error::serialization(format!("Invalid JSON on line {}: {}", line_no + 1, e))
+        })?;
+        let role = match record["role"].as_str() {
+            Some("user") => MessageRole::User,
+            Some("assistant") => MessageRole::Assistant,
+            Some("system") => MessageRole::System,
+            other => {
+                return Err(Error::serialization(format!(
+                    "Invalid role {:?} on line {}",
+                    other,
+                    line_no + 1
+                )))
+            }
+        };
+        let content = record["content"].as_str().ok_or_else(|| {
+            Error::serialization(format!("Missing content on line {}", line_no + 1))
+        })?;
+        let timestamp = parse_timestamp(&record["timestamp"]).unwrap_or_else(SystemTime::now);
+        let id = record["conversation"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| record["conversation"].as_u64().map(|n| n.to_string()))
+            .unwrap_or_else(|| "default".to_string());
+
+        let conversation = conversations.entry(id.clone()).or_insert_with(|| {
+            order.push(id.clone());
+            ImportedConversation {
+                source_id: id,
+                title: None,
+                created_at: timestamp,
+                messages: Vec::new(),
+            }
+        });
+        if let Some(title) = record["title"].as_str() {
+            conversation.title = Some(title.to_string());
+        }
+        conversation.messages.push(Message {
+            role,
+            content: content.to_string(),
+            timestamp,
+        });
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|id| conversations.remove(&id))
+        .collect())
+}
+
+fn parse_timestamp(value: &Value) -> Option<SystemTime> {
+    if let Some(secs) = value.as_f64() {
+        return unix_time(secs);
+    }
+    let parsed = chrono::DateTime::parse_from_rfc3339(value.as_str()?).ok()?;
+    Some(parsed.with_timezone(&chrono::Utc).into())
+}
+
+/// `secs` after the epoch, or `None` for a bogus value (negative, NaN, or
+/// past what a timestamp can hold) instead of overflowing `SystemTime`
+fn unix_time(secs: f64) -> Option<SystemTime> {
+    if secs.is_nan() || secs < 0.0 {
+        return None;
+    }
+    // The float-to-int cast saturates, and out-of-range millis give None
+    chrono::DateTime::from_timestamp_millis((secs * 1000.0) as i64).map(SystemTime::from)
+}
+
+/// Import `path` into sessions, skipping conversations imported previously
+///
+/// Returns the newly created sessions.
+pub async fn import_file(
+    manager: &SessionManager,
+    path: &Path,
+    format: ImportFormat,
+) -> Result<Vec<Session>> {
+    let contents = tokio::fs::read_to_string(path).await?;
+    let conversations = match format {
+        ImportFormat::ChatGpt => parse_chatgpt(&contents)?,
+        ImportFormat::GenericJsonl => parse_generic_jsonl(&contents)?,
+    };
+    info!("Read {} conversations from {:?}", conversations.len(), path);
+
+    let already_imported: HashSet<String> = manager
+        .list_sessions()
+        .await?
+        .into_iter()
+        .filter_map(|s| s.metadata.custom_data.get(IMPORT_ID_KEY).cloned())
+        .collect();
+
+    let mut sessions = Vec::new();
+    for conversation in conversations {
+        let import_id = format!("{}:{}", format.name(), conversation.source_id);
+        if already_imported.contains(&import_id) {
+            debug!("Skipping already imported conversation {}", import_id);
+            continue;
+        }
+        let session = to_session(conversation, format, import_id);
+        manager.save_session(&session).await?;
+        sessions.push(session);
+    }
+    Ok(sessions)
+}
+
+fn to_session(conversation: ImportedConversation, format: ImportFormat, import_id: String) -> Session {
+    let last_activity = conversation
+        .messages
+        .iter()
+        .map(|m| m.timestamp)
+        .max()
+        .unwrap_or(conversation.created_at);
+    Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: "import".to_string(),
+        created_at: conversation.created_at,
+        last_activity,
+        messages: conversation.messages,
+        metadata: SessionMetadata {
+            channel: format!("import:{}", format.name()),
+            tags: vec!["imported".to_string()],
+            custom_data: HashMap::from([(IMPORT_ID_KEY.to_string(), import_id)]),
+            title: conversation.title,
+            persona: None,
+            system_prompt: None,
+            context_events: Vec::new(),
+            experiments: Default::default(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHATGPT_EXPORT: &str = r#"[{
+        "id": "conv-1",
+        "title": "Tomato seedlings",
+        "create_time": 1700000000.5,
+        "current_node": "n3",
+        "mapping": {
+            "root": {"id": "root", "message": null, "parent": null, "children": ["n1"]},
+            "n1": {"id": "n1", "parent": "root", "message": {"author": {"role": "system"}, "content": {"content_type": "text", "parts": [""]}}},
+            "n2": {"id": "n2", "parent": "n1", "message": {"author": {"role": "user"}, "create_time": 1700000001.0, "content": {"content_type": "text", "parts": ["When should I transplant?"]}}},
+            "n2b": {"id": "n2b", "parent": "n2", "message": {"author": {"role": "assistant"}, "content": {"content_type": "text", "parts": ["Abandoned branch"]}}},
+            "n3": {"id": "n3", "parent": "n2", "message": {"author": {"role": "assistant"}, "create_time": 1700000002.0, "content": {"content_type": "text", "parts": ["After the first true leaves."]}}}
+        }
+    }]"#;
+
+    #[test]
+    fn test_parse_chatgpt_follows_active_branch() {
+        let conversations = parse_chatgpt(CHATGPT_EXPORT).unwrap();
+        assert_eq!(conversations.len(), 1);
+        let conversation = &conversations[0];
+        assert_eq!(conversation.title.as_deref(), Some("Tomato seedlings"));
+        assert_eq!(conversation.messages.len(), 2);
+        assert_eq!(conversation.messages[0].role, MessageRole::User);
+        assert_eq!(conversation.messages[1].content, "After the first true leaves.");
+    }
+
+    #[test]
+    fn test_parse_generic_jsonl_groups_conversations() {
+        let jsonl = r#"{"conversation": "a", "title": "Trip", "role": "user", "content": "Pack list?", "timestamp": "2025-06-01T10:00:00Z"}
+{"conversation": "b", "role": "user", "content": "Hello", "timestamp": 1748772000}
+{"conversation": "a", "role": "assistant", "content": "Sunscreen."}
+"#;
+        let conversations = parse_generic_jsonl(jsonl).unwrap();
+        assert_eq!(conversations.len(), 2);
+        assert_eq!(conversations[0].source_id, "a");
+        assert_eq!(conversations[0].title.as_deref(), Some("Trip"));
+        assert_eq!(conversations[0].messages.len(), 2);
+
+        assert!(parse_generic_jsonl(r#"{"role": "robot", "content": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn test_unix_time_rejects_bogus_values() {
+        assert_eq!(unix_time(1.5), Some(UNIX_EPOCH + std::time::Duration::from_millis(1500)));
+        for bogus in [-1.0, f64::NAN, f64::INFINITY, 1e300] {
+            assert_eq!(unix_time(bogus), None, "{}", bogus);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let export = dir.path().join("conversations.json");
+        std::fs::write(&export, CHATGPT_EXPORT).unwrap();
+        let manager = SessionManager::new(dir.path().join("sessions"));
+
+        let imported = import_file(&manager, &export, ImportFormat::ChatGpt).await.unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].metadata.channel, "import:chatgpt");
+
+        let again = import_file(&manager, &export, ImportFormat::ChatGpt).await.unwrap();
+        assert!(again.is_empty());
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 1);
+    }
+}
@@ -1,40 +1,192 @@
 //! Session manager implementation
 
-use crate::error::Result;
-use super::store::Session;
+use crate::error::{Error, Result};
+use super::redact::Redactor;
+use super::store::{Session, SessionMetadata};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tracing::{debug, warn};
 
 /// Session manager for managing conversation sessions
+///
+/// Sessions are stored as one JSON file per session in the sessions directory.
 pub struct SessionManager {
-    // TODO: Add fields for session management
+    sessions_dir: PathBuf,
+    redactor: Option<Arc<Redactor>>,
 }
 
 impl SessionManager {
-    /// Create a new session manager
-    pub fn new() -> Self {
-        SessionManager {}
+    /// Create a new session manager backed by `sessions_dir`
+    pub fn new(sessions_dir: impl Into<PathBuf>) -> Self {
+        SessionManager {
+            sessions_dir: sessions_dir.into(),
+            redactor: None,
+        }
+    }
+
+    /// Redact message contents with `redactor` when saving
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Directory sessions are stored in
+    pub fn sessions_dir(&self) -> &Path {
+        &self.sessions_dir
     }
 
     /// Create a new session
-    pub async fn create_session(&mut self, _user_id: &str) -> Result<Session> {
-        // TODO: Implement session creation
-        todo!()
+    pub async fn create_session(&mut self, user_id: &str) -> Result<Session> {
+        let now = SystemTime::now();
+        let session = Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            created_at: now,
+            last_activity: now,
+            messages: Vec::new(),
+            metadata: SessionMetadata {
+                channel: String::new(),
+                tags: Vec::new(),
+                custom_data: HashMap::new(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        };
+        self.save_session(&session).await?;
+        debug!("Created session {}", session.id);
+        Ok(session)
     }
 
     /// Load a session
-    pub async fn load_session(&self, _session_id: &str) -> Result<Session> {
-        // TODO: Implement session loading
-        todo!()
+    pub async fn load_session(&self, session_id: &str) -> Result<Session> {
+        let path = self.session_path(session_id)?;
+        if !path.exists() {
+            return Err(Error::session(format!("Session not found: {}", session_id)));
+        }
+        let contents = tokio::fs::read_to_string(&path).await?;
+        Ok(serde_json::from_str(&contents)?)
     }
 
     /// Save a session
-    pub async fn save_session(&self, _session: &Session) -> Result<()> {
-        // TODO: Implement session saving
+    ///
+    /// Writes to a temporary file and renames it so a power loss never leaves
+    /// a half-written session behind. With a redactor, only the stored copy
+    /// is redacted; `session` itself is unchanged.
+    pub async fn save_session(&self, session: &Session) -> Result<()> {
+        tokio::fs::create_dir_all(&self.sessions_dir).await?;
+        let path = self.session_path(&session.id)?;
+        let tmp_path = path.with_extension("json.tmp");
+        let contents = match &self.redactor {
+            Some(redactor) => serde_json::to_string_pretty(&redactor.redact_session(session))?,
+            None => serde_json::to_string_pretty(session)?,
+        };
+        tokio::fs::write(&tmp_path, contents).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Delete a session
+    pub async fn delete_session(&self, session_id: &str) -> Result<()> {
+        let path = self.session_path(session_id)?;
+        if !path.exists() {
+            return Err(Error::session(format!("Session not found: {}", session_id)));
+        }
+        tokio::fs::remove_file(&path).await?;
         Ok(())
     }
+
+    /// List all sessions, most recently active first
+    pub async fn list_sessions(&self) -> Result<Vec<Session>> {
+        let mut sessions = Vec::new();
+        if !self.sessions_dir.exists() {
+            return Ok(sessions);
+        }
+
+        let mut entries = tokio::fs::read_dir(&self.sessions_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let contents = tokio::fs::read_to_string(&path).await?;
+            match serde_json::from_str::<Session>(&contents) {
+                Ok(session) => sessions.push(session),
+                Err(e) => warn!("Skipping unreadable session {:?}: {}", path, e),
+            }
+        }
+
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
+        Ok(sessions)
+    }
+
+    fn session_path(&self, session_id: &str) -> Result<PathBuf> {
+        if session_id.is_empty()
+            || !session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::session(format!("Invalid session id: {}", session_id)));
+        }
+        Ok(self.sessions_dir.join(format!("{}.json", session_id)))
+    }
 }
 
-impl Default for SessionManager {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = SessionManager::new(dir.path());
+
+        let mut session = manager.create_session("user-1").await.unwrap();
+        session.metadata.title = Some("Garden watering".to_string());
+        manager.save_session(&session).await.unwrap();
+
+        let loaded = manager.load_session(&session.id).await.unwrap();
+        assert_eq!(loaded.user_id, "user-1");
+        assert_eq!(loaded.metadata.title.as_deref(), Some("Garden watering"));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = SessionManager::new(dir.path());
+
+        let first = manager.create_session("user-1").await.unwrap();
+        manager.create_session("user-2").await.unwrap();
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 2);
+
+        manager.delete_session(&first.id).await.unwrap();
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 1);
+        assert!(manager.load_session(&first.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redacts_stored_copy_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = crate::config::RedactionConfig::default();
+        let mut manager = SessionManager::new(dir.path()).with_redactor(Arc::new(Redactor::new(&config).unwrap()));
+
+        let mut session = manager.create_session("user-1").await.unwrap();
+        session.add_message(crate::agent::context::MessageRole::User, "My number is 07700 900123");
+        manager.save_session(&session).await.unwrap();
+
+        assert_eq!(session.messages[0].content, "My number is 07700 900123");
+        let stored = manager.load_session(&session.id).await.unwrap();
+        assert_eq!(stored.messages[0].content, "My number is [REDACTED:phone]");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_path_traversal_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(dir.path());
+        assert!(manager.load_session("../config").await.is_err());
     }
 }
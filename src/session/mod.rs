@@ -1,7 +1,15 @@
 //! Session management for TakoBull
 
+pub mod archive;
+pub mod compress;
+pub mod import;
 pub mod manager;
+pub mod redact;
 pub mod store;
+pub mod title;
 
+pub use archive::SessionArchive;
+pub use compress::SessionCompressor;
 pub use manager::SessionManager;
+pub use redact::Redactor;
 pub use store::Session;
@@ -0,0 +1,197 @@
+//! Redaction of sensitive text before it is stored
+//!
+//! A [`Redactor`] replaces card numbers, phone numbers, and configured
+//! patterns with placeholders such as `[REDACTED:credit_card]`. It is applied
+//! where sessions, memories, and notes are written, so the running
+//! conversation keeps the original text while what lives on the device long
+//! term does not.
+
+use super::store::Session;
+use crate::config::RedactionConfig;
+use crate::error::{Error, Result};
+use regex::Regex;
+
+/// Digit runs of card length, optionally grouped by spaces or dashes
+const CARD_PATTERN: &str = r"\b\d(?:[ -]?\d){12,18}\b";
+
+/// Optional country code and area code, then three to five digit groups
+const PHONE_PATTERN: &str = r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?)?\d{2,4}(?:[ .-]?\d{2,4}){2,4}\b";
+
+struct Rule {
+    name: String,
+    regex: Regex,
+    /// Whether a match really is sensitive
+    accept: fn(&str) -> bool,
+}
+
+/// Replaces sensitive substrings with placeholders
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+impl std::fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Redactor")
+            .field("rules", &self.rules.iter().map(|r| r.name.as_str()).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Redactor {
+    /// Redactor for the rules enabled in `config`
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        let mut rules = Vec::new();
+        if config.credit_cards {
+            rules.push(Rule {
+                name: "credit_card".to_string(),
+                regex: Regex::new(CARD_PATTERN).expect("card pattern is valid"),
+                accept: luhn_valid,
+            });
+        }
+        if config.phone_numbers {
+            rules.push(Rule {
+                name: "phone".to_string(),
+                regex: Regex::new(PHONE_PATTERN).expect("phone pattern is valid"),
+                accept: looks_like_phone,
+            });
+        }
+        for pattern in &config.patterns {
+            let regex = Regex::new(&pattern.regex).map_err(|e| {
+                Error::config(format!("Invalid redaction pattern '{}': {}", pattern.name, e))
+            })?;
+            rules.push(Rule {
+                name: pattern.name.clone(),
+                regex,
+                accept: |_| true,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Redactor from `config`, or `None` when redaction is disabled
+    pub fn from_config(config: &RedactionConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Self::new(config).map(Some)
+    }
+
+    /// `text` with every sensitive match replaced
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in &self.rules {
+            let placeholder = format!("[REDACTED:{}]", rule.name);
+            text = rule
+                .regex
+                .replace_all(&text, |caps: &regex::Captures| {
+                    let found = &caps[0];
+                    if (rule.accept)(found) {
+                        placeholder.clone()
+                    } else {
+                        found.to_string()
+                    }
+                })
+                .into_owned();
+        }
+        text
+    }
+
+    /// Copy of `session` with message contents and the title redacted
+    pub fn redact_session(&self, session: &Session) -> Session {
+        let mut redacted = session.clone();
+        for message in &mut redacted.messages {
+            message.content = self.redact(&message.content);
+        }
+        if let Some(title) = &mut redacted.metadata.title {
+            *title = self.redact(title);
+        }
+        redacted
+    }
+}
+
+fn digits(text: &str) -> Vec<u32> {
+    text.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// Whether the digits in `text` pass the Luhn checksum used by card numbers
+fn luhn_valid(text: &str) -> bool {
+    let digits = digits(text);
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Whether `text` has a phone number's digit count and is not a date
+fn looks_like_phone(text: &str) -> bool {
+    let count = digits(text).len();
+    let date = text.len() >= 10
+        && text.as_bytes()[..10]
+            .iter()
+            .enumerate()
+            .all(|(i, b)| if i == 4 || i == 7 { *b == b'-' } else { b.is_ascii_digit() });
+    (9..=15).contains(&count) && !date
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RedactionPattern;
+
+    fn redactor() -> Redactor {
+        Redactor::new(&RedactionConfig {
+            enabled: true,
+            patterns: vec![RedactionPattern {
+                name: "api_key".to_string(),
+                regex: r"sk-[A-Za-z0-9]{8,}".to_string(),
+            }],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_redacts_cards_phones_and_patterns() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.redact("Card 4111 1111 1111 1111, call +1 (555) 123-4567, key sk-abcdef123456"),
+            "Card [REDACTED:credit_card], call [REDACTED:phone], key [REDACTED:api_key]"
+        );
+        // Numbers that only look sensitive are kept
+        for text in [
+            "Order 4111 1111 1111 1112 shipped",
+            "Meeting on 2026-10-15 12:30",
+            "The tank holds 1500 litres",
+        ] {
+            assert_eq!(redactor.redact(text), text);
+        }
+    }
+
+    #[test]
+    fn test_config_controls_rules() {
+        assert!(Redactor::from_config(&RedactionConfig::default()).unwrap().is_none());
+
+        let phones_only = Redactor::new(&RedactionConfig {
+            credit_cards: false,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(phones_only.redact("4111111111111111"), "4111111111111111");
+
+        let invalid = RedactionConfig {
+            patterns: vec![RedactionPattern {
+                name: "broken".to_string(),
+                regex: "(".to_string(),
+            }],
+            ..Default::default()
+        };
+        assert!(Redactor::new(&invalid).is_err());
+    }
+}
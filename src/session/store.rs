@@ -1,8 +1,9 @@
 //! Session storage and persistence
 
-use crate::agent::context::Message;
+use crate::agent::context::{Message, MessageRole};
+use crate::agent::ContextDegradation;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::SystemTime;
 
 /// Session metadata
@@ -11,6 +12,28 @@ pub struct SessionMetadata {
     pub channel: String,
     pub tags: Vec<String>,
     pub custom_data: HashMap<String, String>,
+    /// Short human-readable title, generated after the first few turns
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Persona selected with `/persona`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// System prompt override for this session only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Turns that overflowed the model's context window and were retried reduced
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_events: Vec<ContextEvent>,
+    /// Variant this session was bucketed into, per prompt experiment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub experiments: BTreeMap<String, String>,
+}
+
+/// A turn that was retried with a reduced request after a context-length error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextEvent {
+    pub timestamp: SystemTime,
+    pub degradation: ContextDegradation,
 }
 
 /// Session structure
@@ -23,3 +46,97 @@ pub struct Session {
     pub messages: Vec<Message>,
     pub metadata: SessionMetadata,
 }
+
+impl Session {
+    /// Title if one has been generated, otherwise the session id
+    pub fn display_name(&self) -> &str {
+        self.metadata.title.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Append a message and update the last activity time
+    pub fn add_message(&mut self, role: MessageRole, content: impl Into<String>) {
+        let now = SystemTime::now();
+        self.messages.push(Message {
+            role,
+            content: content.into(),
+            timestamp: now,
+        });
+        self.last_activity = now;
+    }
+
+    /// Replace the content of the last user message, dropping any replies to it
+    ///
+    /// Used when the user edits their last message. Returns false if the
+    /// session has no user message.
+    pub fn correct_last_user_message(&mut self, content: impl Into<String>) -> bool {
+        let Some(index) = self.messages.iter().rposition(|m| m.role == MessageRole::User) else {
+            return false;
+        };
+        self.messages.truncate(index + 1);
+        self.messages[index].content = content.into();
+        self.last_activity = SystemTime::now();
+        true
+    }
+
+    /// Override the system prompt for this session; `None` restores the default
+    pub fn set_system_prompt(&mut self, prompt: Option<String>) {
+        self.metadata.persona = None;
+        self.metadata.system_prompt = prompt;
+    }
+
+    /// Switch this session to a named persona with the given prompt
+    pub fn set_persona(&mut self, name: impl Into<String>, prompt: impl Into<String>) {
+        self.metadata.persona = Some(name.into());
+        self.metadata.system_prompt = Some(prompt.into());
+    }
+
+    /// Record that a turn was retried with a reduced request
+    pub fn record_context_event(&mut self, degradation: ContextDegradation) {
+        self.metadata.context_events.push(ContextEvent {
+            timestamp: SystemTime::now(),
+            degradation,
+        });
+    }
+
+    /// Number of user messages in the session
+    pub fn user_turns(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|m| m.role == MessageRole::User)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correct_last_user_message_drops_reply() {
+        let mut session = Session {
+            id: "s1".to_string(),
+            user_id: "user-1".to_string(),
+            created_at: SystemTime::now(),
+            last_activity: SystemTime::now(),
+            messages: Vec::new(),
+            metadata: SessionMetadata {
+                channel: "telegram".to_string(),
+                tags: Vec::new(),
+                custom_data: HashMap::new(),
+                title: None,
+                persona: None,
+                system_prompt: None,
+                context_events: Vec::new(),
+                experiments: Default::default(),
+            },
+        };
+        session.add_message(MessageRole::User, "first");
+        session.add_message(MessageRole::Assistant, "reply");
+        session.add_message(MessageRole::User, "whats the wether");
+        session.add_message(MessageRole::Assistant, "Which city?");
+
+        assert!(session.correct_last_user_message("what's the weather in Oslo"));
+        assert_eq!(session.messages.len(), 3);
+        assert_eq!(session.messages[2].content, "what's the weather in Oslo");
+    }
+}
@@ -0,0 +1,106 @@
+//! Session title generation
+
+use super::store::Session;
+use crate::agent::context::MessageRole;
+use crate::error::Result;
+use crate::llm::ModelRouter;
+
+/// Number of user turns after which a session gets a title
+pub const TITLE_AFTER_TURNS: usize = 2;
+
+/// Maximum title length in characters
+pub const MAX_TITLE_CHARS: usize = 60;
+
+const TITLE_INSTRUCTION: &str = "Write a short title (at most 6 words) for the following conversation. \
+Reply with the title only, without quotes or punctuation at the end.";
+
+/// Whether `session` has enough turns and no title yet
+pub fn needs_title(session: &Session) -> bool {
+    session.metadata.title.is_none() && session.user_turns() >= TITLE_AFTER_TURNS
+}
+
+/// Generate a title for `session` on the summary route
+///
+/// Falls back to a title derived from the first user message when the
+/// model call fails or returns nothing usable.
+pub async fn generate_title(router: &ModelRouter, session: &Session) -> Result<String> {
+    let transcript: String = session
+        .messages
+        .iter()
+        .take(TITLE_AFTER_TURNS * 2)
+        .map(|m| {
+            let role = match m.role {
+                MessageRole::User => "User",
+                MessageRole::Assistant => "Assistant",
+                MessageRole::System => "System",
+            };
+            format!("{}: {}\n", role, m.content)
+        })
+        .collect();
+
+    let title = match router.summarize(TITLE_INSTRUCTION, &transcript).await {
+        Ok(raw) => clean_title(&raw),
+        Err(e) => {
+            tracing::warn!("Title generation failed for {}: {}", session.id, e);
+            None
+        }
+    };
+
+    Ok(title.unwrap_or_else(|| fallback_title(session)))
+}
+
+/// Normalize a model-generated title to a single short line
+pub fn clean_title(raw: &str) -> Option<String> {
+    let line = raw.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .trim_start_matches(|c: char| c == '#' || c.is_whitespace())
+        .trim_start_matches("Title:")
+        .trim()
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '*' || c == '`')
+        .trim_end_matches(['.', '!', ':'])
+        .trim();
+    if line.is_empty() {
+        return None;
+    }
+    Some(truncate_chars(line, MAX_TITLE_CHARS))
+}
+
+/// Title built from the first user message
+pub fn fallback_title(session: &Session) -> String {
+    session
+        .messages
+        .iter()
+        .find(|m| m.role == MessageRole::User)
+        .and_then(|m| clean_title(&m.content))
+        .unwrap_or_else(|| session.id.clone())
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated = truncated.trim_end().to_string();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(clean_title("\"Watering the garden.\"").as_deref(), Some("Watering the garden"));
+        assert_eq!(clean_title("Title: Pi setup\nextra").as_deref(), Some("Pi setup"));
+        assert_eq!(clean_title("   \n  "), None);
+    }
+
+    #[test]
+    fn test_clean_title_truncates() {
+        let long = "word ".repeat(40);
+        let title = clean_title(&long).unwrap();
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+        assert!(title.ends_with('…'));
+    }
+}
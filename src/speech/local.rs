@@ -0,0 +1,200 @@
+//! Local speech engines run as child processes
+//!
+//! Audio passes through a temporary file because both binaries work on
+//! files; it is removed once the engine exits.
+
+use super::{AudioClip, AudioFormat, SpeechToText, TextToSpeech};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tracing::debug;
+
+/// whisper.cpp command-line transcription
+pub struct WhisperCpp {
+    binary: String,
+    model: String,
+    language: Option<String>,
+}
+
+impl WhisperCpp {
+    /// Transcribe with the ggml model file at `model`
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            binary: "whisper-cli".to_string(),
+            model: model.into(),
+            language: None,
+        }
+    }
+
+    /// Run `binary` instead of `whisper-cli` from PATH
+    pub fn with_binary(mut self, binary: impl Into<String>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
+    /// Expect speech in `language` instead of letting the model detect it
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+}
+
+#[async_trait]
+impl SpeechToText for WhisperCpp {
+    async fn transcribe(&self, audio: &AudioClip) -> Result<String> {
+        // whisper.cpp only decodes WAV without an ffmpeg build
+        if audio.format != AudioFormat::Wav {
+            return Err(Error::runtime(format!(
+                "whisper.cpp needs WAV audio, got {}",
+                audio.format.extension()
+            )));
+        }
+        let input = TempAudio::new(AudioFormat::Wav);
+        tokio::fs::write(&input.0, &audio.data).await?;
+
+        let mut command = tokio::process::Command::new(&self.binary);
+        command
+            .arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(&input.0)
+            .args(["--no-timestamps", "--no-prints"]);
+        if let Some(language) = &self.language {
+            command.arg("-l").arg(language);
+        }
+        let output = command
+            .output()
+            .await
+            .map_err(|e| Error::runtime(format!("Failed to run {}: {}", self.binary, e)))?;
+        if !output.status.success() {
+            return Err(Error::runtime(format!(
+                "{} failed: {}",
+                self.binary,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let text = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        debug!("whisper.cpp transcribed {} bytes into {} chars", audio.data.len(), text.len());
+        Ok(text)
+    }
+
+    fn name(&self) -> &str {
+        "whisper.cpp"
+    }
+}
+
+/// Piper neural text-to-speech
+pub struct Piper {
+    binary: String,
+    model: String,
+}
+
+impl Piper {
+    /// Speak with the `.onnx` voice file at `model`
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            binary: "piper".to_string(),
+            model: model.into(),
+        }
+    }
+
+    /// Run `binary` instead of `piper` from PATH
+    pub fn with_binary(mut self, binary: impl Into<String>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+}
+
+#[async_trait]
+impl TextToSpeech for Piper {
+    async fn synthesize(&self, text: &str) -> Result<AudioClip> {
+        let output = TempAudio::new(AudioFormat::Wav);
+        let mut child = tokio::process::Command::new(&self.binary)
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--output_file")
+            .arg(&output.0)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::runtime(format!("Failed to run {}: {}", self.binary, e)))?;
+        // A write error means piper exited early; its status explains why
+        let written = match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(text.as_bytes()).await,
+            None => Ok(()),
+        };
+        let result = child.wait_with_output().await?;
+        if !result.status.success() {
+            return Err(Error::runtime(format!(
+                "{} failed: {}",
+                self.binary,
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+        written?;
+        Ok(AudioClip::new(tokio::fs::read(&output.0).await?, AudioFormat::Wav))
+    }
+
+    fn name(&self) -> &str {
+        "piper"
+    }
+}
+
+/// Uniquely named file in the system temp directory, removed on drop
+struct TempAudio(PathBuf);
+
+impl TempAudio {
+    fn new(format: AudioFormat) -> Self {
+        let name = format!("takobull-speech-{}.{}", uuid::Uuid::new_v4(), format.extension());
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for TempAudio {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn script(dir: &std::path::Path, name: &str, body: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_local_engines_run_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Echo the model and the input file's contents like a transcript
+        let whisper = WhisperCpp::new("base.bin")
+            .with_binary(script(dir.path(), "whisper", "echo \" [$2]\"; echo; cat \"$4\""))
+            .with_language("en");
+        let clip = AudioClip::new(b"hello there".to_vec(), AudioFormat::Wav);
+        assert_eq!(whisper.transcribe(&clip).await.unwrap(), "[base.bin] hello there");
+        let ogg = AudioClip::new(Vec::new(), AudioFormat::Ogg);
+        assert!(whisper.transcribe(&ogg).await.is_err());
+
+        // Write the spoken text as the "audio"
+        let piper = Piper::new("voice.onnx").with_binary(script(dir.path(), "piper", "cat > \"$4\""));
+        let spoken = piper.synthesize("good morning").await.unwrap();
+        assert_eq!(spoken, AudioClip::new(b"good morning".to_vec(), AudioFormat::Wav));
+
+        let failing = Piper::new("voice.onnx").with_binary(script(dir.path(), "broken", "echo no voice >&2; exit 1"));
+        assert!(failing.synthesize("hi").await.unwrap_err().to_string().contains("no voice"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Speech recognition and synthesis
+//!
+//! Voice features go through the [`SpeechToText`] and [`TextToSpeech`]
+//! traits, so the engine is a config choice rather than a dependency: the
+//! local whisper.cpp and Piper binaries keep audio on the device, while the
+//! OpenAI-compatible backends need no local models. [`speech_to_text`] and
+//! [`text_to_speech`] build the engines configured under `speech`.
+
+pub mod local;
+pub mod openai;
+
+pub use local::{Piper, WhisperCpp};
+pub use openai::{OpenAiSpeech, OpenAiTranscription};
+
+use crate::config::{SpeechConfig, SttBackendKind, SttConfig, TtsBackendKind, TtsConfig};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use std::path::Path;
+
+/// Container format of an audio clip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    Ogg,
+}
+
+impl AudioFormat {
+    /// Format for a file extension such as `wav` or `oga`
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "wav" => Some(AudioFormat::Wav),
+            "mp3" => Some(AudioFormat::Mp3),
+            "ogg" | "oga" | "opus" => Some(AudioFormat::Ogg),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Ogg => "ogg",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Ogg => "audio/ogg",
+        }
+    }
+}
+
+/// Encoded audio held in memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioClip {
+    pub data: Vec<u8>,
+    pub format: AudioFormat,
+}
+
+impl AudioClip {
+    pub fn new(data: Vec<u8>, format: AudioFormat) -> Self {
+        Self { data, format }
+    }
+
+    /// Read a clip from `path`, taking the format from its extension
+    pub async fn read(path: &Path) -> Result<Self> {
+        let format = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(AudioFormat::from_extension)
+            .ok_or_else(|| Error::config(format!("Unsupported audio file: {}", path.display())))?;
+        Ok(Self::new(tokio::fs::read(path).await?, format))
+    }
+}
+
+/// Turns recorded speech into text
+#[async_trait]
+pub trait SpeechToText: Send + Sync {
+    /// Transcribe `audio`
+    async fn transcribe(&self, audio: &AudioClip) -> Result<String>;
+
+    /// Engine name for logs
+    fn name(&self) -> &str;
+}
+
+/// Turns text into speech
+#[async_trait]
+pub trait TextToSpeech: Send + Sync {
+    /// Speak `text`
+    async fn synthesize(&self, text: &str) -> Result<AudioClip>;
+
+    /// Engine name for logs
+    fn name(&self) -> &str;
+}
+
+/// The `speech` section of config.yaml, with API credentials defaulting to
+/// those under `providers.openai`
+pub fn load_config(config: &serde_yaml::Value) -> SpeechConfig {
+    let mut speech: SpeechConfig = serde_yaml::from_value(config["speech"].clone()).unwrap_or_default();
+    let openai = &config["providers"]["openai"];
+    for engine in [&mut speech.stt.engine, &mut speech.tts.engine] {
+        if engine.api_key.is_none() {
+            engine.api_key = openai["api_key"].as_str().filter(|k| !k.is_empty()).map(str::to_string);
+        }
+        if engine.api_base.is_none() {
+            engine.api_base = openai["api_base"].as_str().map(str::to_string);
+        }
+    }
+    speech
+}
+
+/// The configured speech recognizer, or `None` when disabled
+pub fn speech_to_text(config: &SttConfig) -> Result<Option<Box<dyn SpeechToText>>> {
+    let engine = &config.engine;
+    Ok(match config.backend {
+        SttBackendKind::None => None,
+        SttBackendKind::WhisperCpp => {
+            let model = engine
+                .model
+                .as_deref()
+                .ok_or_else(|| Error::config("speech.stt.model must name a whisper.cpp model file"))?;
+            let mut whisper = WhisperCpp::new(model);
+            if let Some(binary) = &engine.binary {
+                whisper = whisper.with_binary(binary);
+            }
+            if let Some(language) = &engine.language {
+                whisper = whisper.with_language(language);
+            }
+            Some(Box::new(whisper))
+        }
+        SttBackendKind::Openai => {
+            let mut api = OpenAiTranscription::new(api_key(engine.api_key.as_deref(), "stt")?);
+            if let Some(api_base) = &engine.api_base {
+                api = api.with_api_base(api_base);
+            }
+            if let Some(model) = &engine.model {
+                api = api.with_model(model);
+            }
+            if let Some(language) = &engine.language {
+                api = api.with_language(language);
+            }
+            Some(Box::new(api))
+        }
+    })
+}
+
+/// The configured speech synthesizer, or `None` when disabled
+pub fn text_to_speech(config: &TtsConfig) -> Result<Option<Box<dyn TextToSpeech>>> {
+    let engine = &config.engine;
+    Ok(match config.backend {
+        TtsBackendKind::None => None,
+        TtsBackendKind::Piper => {
+            let model = engine
+                .model
+                .as_deref()
+                .ok_or_else(|| Error::config("speech.tts.model must name a Piper voice file"))?;
+            let mut piper = Piper::new(model);
+            if let Some(binary) = &engine.binary {
+                piper = piper.with_binary(binary);
+            }
+            Some(Box::new(piper))
+        }
+        TtsBackendKind::Openai => {
+            let mut api = OpenAiSpeech::new(api_key(engine.api_key.as_deref(), "tts")?);
+            if let Some(api_base) = &engine.api_base {
+                api = api.with_api_base(api_base);
+            }
+            if let Some(model) = &engine.model {
+                api = api.with_model(model);
+            }
+            if let Some(voice) = &engine.voice {
+                api = api.with_voice(voice);
+            }
+            Some(Box::new(api))
+        }
+    })
+}
+
+fn api_key<'a>(key: Option<&'a str>, section: &str) -> Result<&'a str> {
+    key.filter(|k| !k.is_empty()).ok_or_else(|| {
+        Error::config(format!(
+            "speech.{} uses the OpenAI backend but no API key is set (speech.{}.api_key or providers.openai.api_key)",
+            section, section
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engines_from_config() {
+        let config: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+providers:
+  openai:
+    api_key: "sk-test"
+speech:
+  stt:
+    backend: whisper_cpp
+    model: /models/ggml-base.en.bin
+  tts:
+    backend: openai
+    voice: nova
+"#,
+        )
+        .unwrap();
+        let speech = load_config(&config);
+        assert_eq!(speech.tts.engine.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(speech_to_text(&speech.stt).unwrap().unwrap().name(), "whisper.cpp");
+        assert_eq!(text_to_speech(&speech.tts).unwrap().unwrap().name(), "openai");
+
+        let mut missing_model = speech.stt.clone();
+        missing_model.engine.model = None;
+        assert!(speech_to_text(&missing_model).is_err());
+
+        let disabled = load_config(&serde_yaml::Value::Null);
+        assert!(speech_to_text(&disabled.stt).unwrap().is_none());
+        assert!(text_to_speech(&disabled.tts).unwrap().is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! OpenAI-compatible speech APIs
+//!
+//! `/audio/transcriptions` and `/audio/speech` are also served by several
+//! self-hosted and third-party servers, so `api_base` can point elsewhere.
+
+use super::{AudioClip, AudioFormat, SpeechToText, TextToSpeech};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+const DEFAULT_API_BASE: &str = "https://api.openai.com/v1";
+
+/// Transcription via `POST /audio/transcriptions`
+pub struct OpenAiTranscription {
+    api_key: String,
+    api_base: String,
+    model: String,
+    language: Option<String>,
+}
+
+impl OpenAiTranscription {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            api_base: DEFAULT_API_BASE.to_string(),
+            model: "whisper-1".to_string(),
+            language: None,
+        }
+    }
+
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Expect speech in `language` (ISO-639-1) instead of detecting it
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+}
+
+#[async_trait]
+impl SpeechToText for OpenAiTranscription {
+    async fn transcribe(&self, audio: &AudioClip) -> Result<String> {
+        let boundary = format!("takobull-{}", uuid::Uuid::new_v4().simple());
+        let mut fields = vec![("model", self.model.as_str())];
+        if let Some(language) = &self.language {
+            fields.push(("language", language.as_str()));
+        }
+        let response = reqwest::Client::new()
+            .post(format!("{}/audio/transcriptions", self.api_base))
+            .bearer_auth(&self.api_key)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(multipart(&boundary, &fields, audio))
+            .send()
+            .await
+            .map_err(|e| Error::http(format!("Transcription request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::http(format!("Transcription failed ({}): {}", status, text)));
+        }
+        let data: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| Error::serialization(format!("Failed to parse transcription: {}", e)))?;
+        data["text"]
+            .as_str()
+            .map(|text| text.trim().to_string())
+            .ok_or_else(|| Error::serialization("No text in transcription response"))
+    }
+
+    fn name(&self) -> &str {
+        "openai"
+    }
+}
+
+/// Synthesis via `POST /audio/speech`, returning WAV
+pub struct OpenAiSpeech {
+    api_key: String,
+    api_base: String,
+    model: String,
+    voice: String,
+}
+
+impl OpenAiSpeech {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            api_base: DEFAULT_API_BASE.to_string(),
+            model: "tts-1".to_string(),
+            voice: "alloy".to_string(),
+        }
+    }
+
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = voice.into();
+        self
+    }
+}
+
+#[async_trait]
+impl TextToSpeech for OpenAiSpeech {
+    async fn synthesize(&self, text: &str) -> Result<AudioClip> {
+        let response = reqwest::Client::new()
+            .post(format!("{}/audio/speech", self.api_base))
+            .bearer_auth(&self.api_key)
+            .json(&json!({
+                "model": self.model,
+                "voice": self.voice,
+                "input": text,
+                "response_format": "wav",
+            }))
+            .send()
+            .await
+            .map_err(|e| Error::http(format!("Speech request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::http(format!("Speech synthesis failed ({}): {}", status, text)));
+        }
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| Error::http(format!("Failed to read speech audio: {}", e)))?;
+        Ok(AudioClip::new(data.to_vec(), AudioFormat::Wav))
+    }
+
+    fn name(&self) -> &str {
+        "openai"
+    }
+}
+
+/// `multipart/form-data` body with text `fields` and `audio` as the `file` part
+fn multipart(boundary: &str, fields: &[(&str, &str)], audio: &AudioClip) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            audio.format.extension(),
+            audio.format.mime_type()
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(&audio.data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_body() {
+        let clip = AudioClip::new(b"RIFF".to_vec(), AudioFormat::Wav);
+        let body = String::from_utf8(multipart("b", &[("model", "whisper-1")], &clip)).unwrap();
+        assert_eq!(
+            body,
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.wav\"\r\n\
+             Content-Type: audio/wav\r\n\r\nRIFF\r\n--b--\r\n"
+        );
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_api_round_trip() {
+        let api = axum::Router::new()
+            .route(
+                "/audio/transcriptions",
+                axum::routing::post(|body: String| async move {
+                    let heard = if body.contains("name=\"language\"\r\n\r\nde") { "hallo" } else { "?" };
+                    axum::Json(json!({ "text": format!(" {} ", heard) }))
+                }),
+            )
+            .route(
+                "/audio/speech",
+                axum::routing::post(|axum::Json(request): axum::Json<serde_json::Value>| async move {
+                    format!("{}:{}", request["voice"].as_str().unwrap(), request["input"].as_str().unwrap())
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api).await });
+
+        let stt = OpenAiTranscription::new("key").with_api_base(&api_base).with_language("de");
+        let clip = AudioClip::new(b"RIFF".to_vec(), AudioFormat::Wav);
+        assert_eq!(stt.transcribe(&clip).await.unwrap(), "hallo");
+
+        let tts = OpenAiSpeech::new("key").with_api_base(&api_base).with_voice("nova");
+        assert_eq!(tts.synthesize("hi").await.unwrap().data, b"nova:hi");
+
+        let missing = OpenAiSpeech::new("key").with_api_base(format!("{}/v2", api_base));
+        assert!(missing.synthesize("hi").await.is_err());
+    }
+}
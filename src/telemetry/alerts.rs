@@ -0,0 +1,285 @@
+//! Threshold alert rules evaluated on sensor readings
+//!
+//! Rules are checked by the collector as readings arrive, without any LLM
+//! involvement, so an alert fires even when the provider is unreachable.
+
+use super::series::Reading;
+use crate::channels::Templates;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Comparison between a reading and a rule's threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparator {
+    #[serde(rename = ">", alias = "gt")]
+    Above,
+    #[serde(rename = ">=", alias = "gte")]
+    AtLeast,
+    #[serde(rename = "<", alias = "lt")]
+    Below,
+    #[serde(rename = "<=", alias = "lte")]
+    AtMost,
+}
+
+impl Comparator {
+    /// Whether `value` crosses `threshold`
+    pub fn matches(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Above => value > threshold,
+            Comparator::AtLeast => value >= threshold,
+            Comparator::Below => value < threshold,
+            Comparator::AtMost => value <= threshold,
+        }
+    }
+}
+
+/// What to do when a rule fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AlertAction {
+    /// Send `message` directly to the user
+    Notify { message: String },
+    /// Run the agent with `prompt`
+    Prompt { prompt: String },
+    /// Send the message template `template` to the user; the engine renders
+    /// it into a `Notify` with the rule's variables and `variables`
+    Template {
+        template: String,
+        #[serde(default)]
+        variables: HashMap<String, String>,
+    },
+}
+
+/// Alert rule from the `telemetry.alerts` config section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub sensor: String,
+    pub comparator: Comparator,
+    pub threshold: f64,
+    /// Seconds the condition must hold before firing
+    #[serde(default)]
+    pub debounce_secs: u64,
+    #[serde(flatten)]
+    pub action: AlertAction,
+}
+
+/// A fired alert with its action text rendered
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub sensor: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub timestamp: DateTime<Utc>,
+    pub action: AlertAction,
+}
+
+/// Receives fired alerts
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    /// Deliver a fired alert
+    async fn dispatch(&self, event: &AlertEvent);
+}
+
+struct RuleState {
+    rule: AlertRule,
+    breaching_since: Option<DateTime<Utc>>,
+    fired: bool,
+}
+
+/// Tracks rule state across readings and fires alerts on sustained crossings
+pub struct AlertEngine {
+    rules: Vec<RuleState>,
+    templates: Arc<Templates>,
+}
+
+impl AlertEngine {
+    /// Create an engine for `rules`
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| RuleState {
+                    rule,
+                    breaching_since: None,
+                    fired: false,
+                })
+                .collect(),
+            templates: Arc::new(Templates::default()),
+        }
+    }
+
+    /// Message templates for `template` actions
+    pub fn with_templates(mut self, templates: Arc<Templates>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Whether any rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluate a reading, returning alerts that fire as a result
+    ///
+    /// A rule fires once when its condition has held for `debounce_secs`,
+    /// and re-arms after a reading no longer matches.
+    pub fn evaluate(&mut self, sensor: &str, reading: Reading) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+        for state in self.rules.iter_mut().filter(|s| s.rule.sensor == sensor) {
+            let rule = &state.rule;
+            if !rule.comparator.matches(reading.value, rule.threshold) {
+                if state.fired {
+                    info!("Alert {} resolved ({} = {})", rule.name, sensor, reading.value);
+                }
+                state.breaching_since = None;
+                state.fired = false;
+                continue;
+            }
+
+            let since = *state.breaching_since.get_or_insert(reading.timestamp);
+            let held = (reading.timestamp - since).num_seconds().max(0) as u64;
+            if state.fired || held < rule.debounce_secs {
+                continue;
+            }
+
+            state.fired = true;
+            warn!(
+                "Alert {} fired: {} = {} ({:?} {})",
+                rule.name, sensor, reading.value, rule.comparator, rule.threshold
+            );
+            events.push(AlertEvent {
+                rule: rule.name.clone(),
+                sensor: sensor.to_string(),
+                value: reading.value,
+                threshold: rule.threshold,
+                timestamp: reading.timestamp,
+                action: render_action(&rule.action, rule, reading.value, &self.templates),
+            });
+        }
+        events
+    }
+}
+
+fn render_action(action: &AlertAction, rule: &AlertRule, value: f64, templates: &Templates) -> AlertAction {
+    let render = |text: &str| {
+        text.replace("{name}", &rule.name)
+            .replace("{sensor}", &rule.sensor)
+            .replace("{value}", &format!("{:.2}", value))
+            .replace("{threshold}", &format!("{}", rule.threshold))
+    };
+    match action {
+        AlertAction::Notify { message } => AlertAction::Notify {
+            message: render(message),
+        },
+        AlertAction::Prompt { prompt } => AlertAction::Prompt {
+            prompt: render(prompt),
+        },
+        AlertAction::Template { template, variables } => {
+            let mut values: HashMap<String, String> =
+                variables.iter().map(|(k, v)| (k.clone(), render(v))).collect();
+            values.insert("name".to_string(), rule.name.clone());
+            values.insert("sensor".to_string(), rule.sensor.clone());
+            values.insert("value".to_string(), format!("{:.2}", value));
+            values.insert("threshold".to_string(), format!("{}", rule.threshold));
+            let message = templates.render(template, &values).unwrap_or_else(|e| {
+                warn!("Alert {} could not use its template: {}", rule.name, e);
+                format!("{}: {} = {:.2}", rule.name, rule.sensor, value)
+            });
+            AlertAction::Notify { message }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(debounce_secs: u64) -> AlertRule {
+        serde_yaml::from_str(&format!(
+            r#"
+name: freezer_warm
+sensor: freezer
+comparator: ">"
+threshold: -10
+debounce_secs: {}
+action: notify
+message: "Freezer at {{value}}°C"
+"#,
+            debounce_secs
+        ))
+        .unwrap()
+    }
+
+    fn at(secs: i64, value: f64) -> Reading {
+        Reading {
+            timestamp: DateTime::<Utc>::from_timestamp(1_800_000_000 + secs, 0).unwrap(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_debounce_and_rearm() {
+        let mut engine = AlertEngine::new(vec![rule(120)]);
+
+        assert!(engine.evaluate("freezer", at(0, -5.0)).is_empty());
+        assert!(engine.evaluate("freezer", at(60, -4.0)).is_empty());
+        let fired = engine.evaluate("freezer", at(120, -3.0));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(
+            fired[0].action,
+            AlertAction::Notify {
+                message: "Freezer at -3.00°C".to_string()
+            }
+        );
+
+        // Fires once per breach
+        assert!(engine.evaluate("freezer", at(180, -2.0)).is_empty());
+
+        // Re-arms after recovering
+        assert!(engine.evaluate("freezer", at(240, -18.0)).is_empty());
+        assert!(engine.evaluate("freezer", at(300, -1.0)).is_empty());
+        assert_eq!(engine.evaluate("freezer", at(420, -1.0)).len(), 1);
+    }
+
+    #[test]
+    fn test_ignores_other_sensors() {
+        let mut engine = AlertEngine::new(vec![rule(0)]);
+        assert!(engine.evaluate("fridge", at(0, 20.0)).is_empty());
+        assert_eq!(engine.evaluate("freezer", at(0, 20.0)).len(), 1);
+    }
+
+    #[test]
+    fn test_template_action_renders_to_notify() {
+        let mut rule = rule(0);
+        rule.action = serde_yaml::from_str("action: template\ntemplate: warning\nvariables:\n  place: garage").unwrap();
+        let templates = Templates::new(HashMap::from([(
+            "warning".to_string(),
+            crate::config::MessageTemplateConfig {
+                text: "⚠️ {sensor} in the {place} is {value} (limit {threshold})".to_string(),
+                ..Default::default()
+            },
+        )]));
+        let mut engine = AlertEngine::new(vec![rule.clone()]).with_templates(Arc::new(templates));
+        assert_eq!(
+            engine.evaluate("freezer", at(0, -3.0))[0].action,
+            AlertAction::Notify {
+                message: "⚠️ freezer in the garage is -3.00 (limit -10)".to_string()
+            }
+        );
+
+        // An unknown template still notifies
+        let mut engine = AlertEngine::new(vec![rule]);
+        assert_eq!(
+            engine.evaluate("freezer", at(0, -3.0))[0].action,
+            AlertAction::Notify {
+                message: "freezer_warm: freezer = -3.00".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Polls sensors on their intervals and records readings
+
+use super::alerts::{AlertEngine, AlertEvent, AlertSink};
+use super::sensor::{build_source, SensorConfig, SensorSource};
+use super::series::{Reading, SeriesStore};
+use crate::config::TelemetryConfig;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
+
+struct PolledSensor {
+    config: SensorConfig,
+    source: Box<dyn SensorSource>,
+    last_polled: Option<DateTime<Utc>>,
+}
+
+/// Alert rules and where their events go
+///
+/// Events are queued to a task that dispatches them in order, so a slow
+/// action (a `prompt` alert waits for an agent run) never holds up polling.
+struct Alerts {
+    engine: AlertEngine,
+    sink: Arc<dyn AlertSink>,
+    queue: Option<mpsc::UnboundedSender<AlertEvent>>,
+}
+
+impl Alerts {
+    fn send(&mut self, event: AlertEvent) {
+        let queue = self.queue.get_or_insert_with(|| {
+            let (tx, mut rx) = mpsc::unbounded_channel::<AlertEvent>();
+            let sink = self.sink.clone();
+            tokio::spawn(async move {
+                while let Some(event) = rx.recv().await {
+                    sink.dispatch(&event).await;
+                }
+            });
+            tx
+        });
+        if queue.send(event).is_err() {
+            warn!("Alert dispatcher stopped; dropping alert");
+        }
+    }
+}
+
+/// Telemetry collector polling configured sensors into a [`SeriesStore`]
+pub struct TelemetryCollector {
+    sensors: Vec<PolledSensor>,
+    store: Arc<SeriesStore>,
+    retention_days: u32,
+    last_pruned: Option<DateTime<Utc>>,
+    alerts: Option<Alerts>,
+}
+
+impl TelemetryCollector {
+    /// Create a collector with no sensors
+    pub fn new(store: Arc<SeriesStore>, retention_days: u32) -> Self {
+        Self {
+            sensors: Vec::new(),
+            store,
+            retention_days,
+            last_pruned: None,
+            alerts: None,
+        }
+    }
+
+    /// Evaluate threshold rules on each reading and dispatch fired alerts to `sink`
+    pub fn with_alerts(mut self, engine: AlertEngine, sink: Arc<dyn AlertSink>) -> Self {
+        self.alerts = Some(Alerts {
+            engine,
+            sink,
+            queue: None,
+        });
+        self
+    }
+
+    /// Create a collector for the sensors in `config`
+    pub fn from_config(config: &TelemetryConfig, store: Arc<SeriesStore>) -> Self {
+        let mut collector = Self::new(store, config.retention_days);
+        for sensor in &config.sensors {
+            collector.add_sensor(sensor.clone(), build_source(&sensor.source));
+        }
+        collector
+    }
+
+    /// Add a sensor with an explicit source
+    pub fn add_sensor(&mut self, config: SensorConfig, source: Box<dyn SensorSource>) {
+        self.sensors.push(PolledSensor {
+            config,
+            source,
+            last_polled: None,
+        });
+    }
+
+    /// Number of configured sensors
+    pub fn sensor_count(&self) -> usize {
+        self.sensors.len()
+    }
+
+    /// Read every sensor whose interval has elapsed, returning the recorded readings
+    pub async fn poll_once(&mut self, now: DateTime<Utc>) -> Vec<(String, Reading)> {
+        let mut recorded = Vec::new();
+        for sensor in &mut self.sensors {
+            let interval = chrono::Duration::seconds(sensor.config.interval_secs.max(1) as i64);
+            if matches!(sensor.last_polled, Some(last) if now - last < interval) {
+                continue;
+            }
+            sensor.last_polled = Some(now);
+
+            let value = match sensor.source.read().await {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("Sensor {} read failed: {}", sensor.config.name, e);
+                    continue;
+                }
+            };
+            let reading = Reading {
+                timestamp: now,
+                value,
+            };
+            if let Err(e) = self.store.append(&sensor.config.name, reading).await {
+                warn!("Failed to record {} reading: {}", sensor.config.name, e);
+                continue;
+            }
+            debug!("Sensor {} = {}{}", sensor.config.name, value, sensor.config.unit);
+            recorded.push((sensor.config.name.clone(), reading));
+        }
+
+        if let Some(alerts) = &mut self.alerts {
+            for (name, reading) in &recorded {
+                for event in alerts.engine.evaluate(name, *reading) {
+                    alerts.send(event);
+                }
+            }
+        }
+
+        if self.last_pruned.is_none_or(|last| now - last >= chrono::Duration::days(1)) {
+            self.last_pruned = Some(now);
+            match self.store.prune(self.retention_days, now).await {
+                Ok(0) => {}
+                Ok(n) => info!("Pruned {} expired telemetry files", n),
+                Err(e) => warn!("Telemetry prune failed: {}", e),
+            }
+        }
+        recorded
+    }
+
+    /// Poll until a shutdown signal is received
+    pub async fn run(&mut self, mut shutdown: broadcast::Receiver<()>) {
+        info!("Telemetry collector started with {} sensors", self.sensors.len());
+        loop {
+            self.poll_once(Utc::now()).await;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                _ = shutdown.recv() => {
+                    info!("Telemetry collector stopped");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::telemetry::sensor::SensorSourceConfig;
+    use async_trait::async_trait;
+
+    struct FixedSource(f64);
+
+    /// Reports each alert, then never finishes dispatching it
+    struct StuckSink(mpsc::UnboundedSender<String>);
+
+    #[async_trait]
+    impl AlertSink for StuckSink {
+        async fn dispatch(&self, event: &AlertEvent) {
+            let _ = self.0.send(event.rule.clone());
+            std::future::pending::<()>().await;
+        }
+    }
+
+    fn sensor(name: &str) -> SensorConfig {
+        SensorConfig {
+            name: name.to_string(),
+            unit: "%".to_string(),
+            interval_secs: 60,
+            source: SensorSourceConfig::Command {
+                command: String::new(),
+            },
+        }
+    }
+
+    #[async_trait]
+    impl SensorSource for FixedSource {
+        async fn read(&self) -> Result<f64> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_respects_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(SeriesStore::new(dir.path()));
+        let mut collector = TelemetryCollector::new(store.clone(), 7);
+        collector.add_sensor(sensor("humidity"), Box::new(FixedSource(41.0)));
+
+        let now = Utc::now();
+        assert_eq!(collector.poll_once(now).await.len(), 1);
+        assert!(collector.poll_once(now + chrono::Duration::seconds(30)).await.is_empty());
+        assert_eq!(collector.poll_once(now + chrono::Duration::seconds(61)).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_slow_alert_does_not_block_polling() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = serde_yaml::from_str(
+            "name: damp\nsensor: humidity\ncomparator: \">\"\nthreshold: 40\naction: notify\nmessage: Damp",
+        )
+        .unwrap();
+        let (tx, mut dispatched) = mpsc::unbounded_channel();
+        let mut collector = TelemetryCollector::new(Arc::new(SeriesStore::new(dir.path())), 7)
+            .with_alerts(AlertEngine::new(vec![rule]), Arc::new(StuckSink(tx)));
+        collector.add_sensor(sensor("humidity"), Box::new(FixedSource(41.0)));
+
+        let now = Utc::now();
+        let polled = tokio::time::timeout(Duration::from_secs(5), collector.poll_once(now)).await;
+        assert_eq!(polled.expect("polling waited on the alert").len(), 1);
+        assert_eq!(dispatched.recv().await.as_deref(), Some("damp"));
+
+        let next = now + chrono::Duration::seconds(61);
+        assert!(tokio::time::timeout(Duration::from_secs(5), collector.poll_once(next)).await.is_ok());
+    }
+}
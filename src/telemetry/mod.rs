@@ -0,0 +1,11 @@
+//! Sensor telemetry collection and time-series logging
+
+pub mod alerts;
+pub mod collector;
+pub mod sensor;
+pub mod series;
+
+pub use alerts::{AlertEngine, AlertEvent, AlertRule, AlertSink};
+pub use collector::TelemetryCollector;
+pub use sensor::{SensorConfig, SensorSource};
+pub use series::{Reading, SeriesStats, SeriesStore};
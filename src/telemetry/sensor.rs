@@ -0,0 +1,115 @@
+//! Sensor sources polled by the telemetry collector
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Configuration for a polled sensor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorConfig {
+    /// Unique sensor name (e.g. "cpu_temp")
+    pub name: String,
+    /// Unit for display (e.g. "°C")
+    #[serde(default)]
+    pub unit: String,
+    /// Seconds between readings
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(flatten)]
+    pub source: SensorSourceConfig,
+}
+
+fn default_interval_secs() -> u64 {
+    60
+}
+
+/// Where a sensor's readings come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum SensorSourceConfig {
+    /// Numeric file such as `/sys/class/thermal/thermal_zone0/temp`
+    File {
+        path: PathBuf,
+        /// Multiplier applied to the raw value (e.g. 0.001 for millidegrees)
+        #[serde(default = "default_scale")]
+        scale: f64,
+    },
+    /// Shell command printing a number on stdout
+    Command { command: String },
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// A source of numeric readings
+#[async_trait]
+pub trait SensorSource: Send + Sync {
+    /// Take a reading
+    async fn read(&self) -> Result<f64>;
+}
+
+/// Reads a number from a file
+pub struct FileSource {
+    path: PathBuf,
+    scale: f64,
+}
+
+#[async_trait]
+impl SensorSource for FileSource {
+    async fn read(&self) -> Result<f64> {
+        let contents = tokio::fs::read_to_string(&self.path).await?;
+        parse_reading(&contents).map(|v| v * self.scale)
+    }
+}
+
+/// Runs a command and parses the number it prints
+pub struct CommandSource {
+    command: String,
+}
+
+#[async_trait]
+impl SensorSource for CommandSource {
+    async fn read(&self) -> Result<f64> {
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(Error::device(format!(
+                "Sensor command failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        parse_reading(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// Build the source described by `config`
+pub fn build_source(config: &SensorSourceConfig) -> Box<dyn SensorSource> {
+    match config {
+        SensorSourceConfig::File { path, scale } => Box::new(FileSource {
+            path: path.clone(),
+            scale: *scale,
+        }),
+        SensorSourceConfig::Command { command } => Box::new(CommandSource {
+            command: command.clone(),
+        }),
+    }
+}
+
+/// Parse the first whitespace-separated token of `raw` as a number
+pub fn parse_reading(raw: &str) -> Result<f64> {
+    let token = raw
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| Error::device("Empty sensor reading"))?;
+    token
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| Error::device(format!("Invalid sensor reading: {}", token)))
+}
@@ -0,0 +1,225 @@
+//! Compact time-series storage for sensor readings
+//!
+//! Readings are appended to one CSV file per sensor per UTC day
+//! (`<telemetry dir>/<sensor>/<YYYY-MM-DD>.csv`, lines of `unix_secs,value`),
+//! so retention is a matter of deleting old day files.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// A single sensor reading
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Reading {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// Summary statistics over a set of readings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SeriesStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub last: f64,
+}
+
+impl SeriesStats {
+    /// Compute statistics, or `None` for an empty series
+    pub fn from_readings(readings: &[Reading]) -> Option<Self> {
+        let last = readings.last()?.value;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+        for r in readings {
+            min = min.min(r.value);
+            max = max.max(r.value);
+            sum += r.value;
+        }
+        Some(Self {
+            count: readings.len(),
+            min,
+            max,
+            mean: sum / readings.len() as f64,
+            last,
+        })
+    }
+}
+
+/// Per-sensor daily CSV files under a telemetry directory
+pub struct SeriesStore {
+    dir: PathBuf,
+}
+
+impl SeriesStore {
+    /// Create a store rooted at `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Root directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Append a reading for `sensor`
+    pub async fn append(&self, sensor: &str, reading: Reading) -> Result<()> {
+        let sensor_dir = self.sensor_dir(sensor)?;
+        tokio::fs::create_dir_all(&sensor_dir).await?;
+        let path = sensor_dir.join(format!("{}.csv", reading.timestamp.format("%Y-%m-%d")));
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        let line = format!("{},{}\n", reading.timestamp.timestamp(), reading.value);
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Readings for `sensor` between `from` and `to` (inclusive), oldest first
+    pub async fn query(
+        &self,
+        sensor: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Reading>> {
+        let sensor_dir = self.sensor_dir(sensor)?;
+        let mut readings = Vec::new();
+        let mut day = from.date_naive();
+        while day <= to.date_naive() {
+            let path = sensor_dir.join(format!("{}.csv", day.format("%Y-%m-%d")));
+            if let Ok(contents) = tokio::fs::read_to_string(&path).await {
+                readings.extend(
+                    contents
+                        .lines()
+                        .filter_map(parse_line)
+                        .filter(|r| r.timestamp >= from && r.timestamp <= to),
+                );
+            }
+            day = match day.succ_opt() {
+                Some(d) => d,
+                None => break,
+            };
+        }
+        readings.sort_by_key(|r| r.timestamp);
+        Ok(readings)
+    }
+
+    /// Names of sensors with stored readings
+    pub async fn sensors(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if !self.dir.exists() {
+            return Ok(names);
+        }
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.path().is_dir() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Delete day files older than `retention_days`, returning how many were removed
+    pub async fn prune(&self, retention_days: u32, now: DateTime<Utc>) -> Result<usize> {
+        let cutoff = now.date_naive() - chrono::Duration::days(retention_days as i64);
+        let mut removed = 0;
+        for sensor in self.sensors().await? {
+            let mut entries = tokio::fs::read_dir(self.dir.join(&sensor)).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let day = name
+                    .strip_suffix(".csv")
+                    .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+                if matches!(day, Some(d) if d < cutoff) {
+                    tokio::fs::remove_file(entry.path()).await?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    fn sensor_dir(&self, sensor: &str) -> Result<PathBuf> {
+        if sensor.is_empty()
+            || !sensor
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::device(format!("Invalid sensor name: {}", sensor)));
+        }
+        Ok(self.dir.join(sensor))
+    }
+}
+
+fn parse_line(line: &str) -> Option<Reading> {
+    let (ts, value) = line.split_once(',')?;
+    Some(Reading {
+        timestamp: Utc.timestamp_opt(ts.trim().parse().ok()?, 0).single()?,
+        value: value.trim().parse().ok()?,
+    })
+}
+
+/// Reduce `readings` to at most `max_points` by averaging consecutive buckets
+pub fn downsample(readings: &[Reading], max_points: usize) -> Vec<Reading> {
+    if max_points == 0 || readings.len() <= max_points {
+        return readings.to_vec();
+    }
+    let bucket = readings.len().div_ceil(max_points);
+    readings
+        .chunks(bucket)
+        .map(|chunk| Reading {
+            timestamp: chunk[chunk.len() / 2].timestamp,
+            value: chunk.iter().map(|r| r.value).sum::<f64>() / chunk.len() as f64,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(secs_ago: i64, value: f64, now: DateTime<Utc>) -> Reading {
+        Reading {
+            timestamp: now - chrono::Duration::seconds(secs_ago),
+            value,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_query_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SeriesStore::new(dir.path());
+        let now = Utc.timestamp_opt(Utc::now().timestamp(), 0).unwrap();
+
+        store.append("cpu_temp", reading(3 * 86400, 40.0, now)).await.unwrap();
+        store.append("cpu_temp", reading(3600, 50.0, now)).await.unwrap();
+        store.append("cpu_temp", reading(60, 52.5, now)).await.unwrap();
+
+        let recent = store
+            .query("cpu_temp", now - chrono::Duration::hours(2), now)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+        let stats = SeriesStats::from_readings(&recent).unwrap();
+        assert_eq!(stats.max, 52.5);
+        assert_eq!(stats.last, 52.5);
+
+        assert_eq!(store.prune(1, now).await.unwrap(), 1);
+        assert!(store.append("../etc", reading(0, 1.0, now)).await.is_err());
+    }
+
+    #[test]
+    fn test_downsample() {
+        let now = Utc::now();
+        let readings: Vec<Reading> = (0..100).map(|i| reading(100 - i, i as f64, now)).collect();
+        let reduced = downsample(&readings, 10);
+        assert_eq!(reduced.len(), 10);
+        assert_eq!(reduced[0].value, 4.5);
+    }
+}
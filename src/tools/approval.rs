@@ -0,0 +1,495 @@
+//! Approval of sensitive tool calls
+//!
+//! Tools listed in `tools.approval.required` only run once the call is
+//! approved. A call is described by its subject (the command, path, or URL
+//! it acts on) and the pattern a rule for it would use: the command exactly
+//! as given, since approving `rm foo.txt` must not approve `rm -rf ~`, or the
+//! directory for a path, `notes/*` for `notes/todo.md`. Once a user has
+//! approved the same tool and pattern `suggest_after` times, they are offered
+//! a rule approving such calls for them automatically.
+//!
+//! Rules are kept in `state/approval_rules.json` and every decision, rule
+//! change included, is appended to `state/approvals.jsonl`, so a call that
+//! ran without asking can be traced to its rule and the approvals behind it.
+//! `state/` is reserved by the path policy, so the filesystem tools cannot
+//! write a rule the user never approved. The shell is not confined that way,
+//! which is why `exec` needs approval unless configured otherwise. In rules written by hand, a
+//! wildcard never matches shell control characters or `..`, so `git *` does
+//! not approve `git pull; rm -rf ~`.
+
+use crate::config::ApprovalConfig;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Rules file, relative to the workspace
+pub const RULES_FILE: &str = "state/approval_rules.json";
+
+/// Audit log, relative to the workspace
+pub const AUDIT_FILE: &str = "state/approvals.jsonl";
+
+/// Arguments naming what a call acts on, in order of preference
+const SUBJECT_KEYS: &[&str] = &["command", "path", "url", "query"];
+
+/// What a wildcard may not cover
+const WILDCARD: &str = r"[^;&|`$<>()\n]*";
+
+/// A standing approval for calls to `tool` whose subject matches `pattern`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub id: String,
+    pub tool: String,
+    pub pattern: String,
+    /// User whose calls the rule approves; `None` for everyone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApprovalRule {
+    /// Whether the rule approves `tool` acting on `subject` for `user_id`
+    pub fn matches(&self, tool: &str, subject: &str, user_id: Option<&str>) -> bool {
+        self.tool == tool
+            && (self.user_id.is_none() || self.user_id.as_deref() == user_id)
+            && pattern_matches(&self.pattern, subject)
+    }
+}
+
+/// What happened to a call or rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approved,
+    Denied,
+    /// Ran without asking because a rule matched
+    AutoApproved,
+    RuleAdded,
+    RuleRevoked,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub at: DateTime<Utc>,
+    pub decision: ApprovalDecision,
+    pub tool: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Rule that approved the call, or was added or revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+/// A call waiting for approval
+#[derive(Debug, Clone)]
+pub struct ApprovalRequest {
+    pub tool: String,
+    pub subject: String,
+    /// Pattern a rule created from this call would use; `None` when no
+    /// pattern covers exactly this call, which is then never offered a rule
+    pub pattern: Option<String>,
+    pub user_id: Option<String>,
+}
+
+/// Someone who can approve calls, such as the user at the terminal
+#[async_trait]
+pub trait Approver: Send + Sync {
+    /// Whether `request` may run
+    async fn approve(&self, request: &ApprovalRequest) -> bool;
+
+    /// Whether calls like `request`, approved `approvals` times so far,
+    /// should be approved automatically from now on
+    async fn offer_rule(&self, request: &ApprovalRequest, approvals: usize) -> bool;
+}
+
+/// Checks calls to tools that need approval against rules and the approver
+pub struct ApprovalGate {
+    config: ApprovalConfig,
+    rules_path: PathBuf,
+    audit_path: PathBuf,
+    approver: RwLock<Option<Arc<dyn Approver>>>,
+    lock: Mutex<()>,
+}
+
+impl ApprovalGate {
+    /// Gate keeping its rules and audit log in `workspace`
+    pub fn new(workspace: impl AsRef<Path>, config: ApprovalConfig) -> Self {
+        Self {
+            config,
+            rules_path: workspace.as_ref().join(RULES_FILE),
+            audit_path: workspace.as_ref().join(AUDIT_FILE),
+            approver: RwLock::new(None),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Ask `approver` about calls no rule covers; without one they are refused
+    pub fn set_approver(&self, approver: Arc<dyn Approver>) {
+        *self.approver.write().unwrap_or_else(|e| e.into_inner()) = Some(approver);
+    }
+
+    /// Whether calls to `tool` need approval
+    pub fn requires_approval(&self, tool: &str) -> bool {
+        self.config.required.iter().any(|t| t == tool)
+    }
+
+    /// Decide whether a call to `tool` with `args` for `user_id` may run,
+    /// returning the reason when it may not
+    pub async fn authorize(
+        &self,
+        tool: &str,
+        args: &HashMap<String, Value>,
+        user_id: Option<&str>,
+    ) -> std::result::Result<(), String> {
+        if !self.requires_approval(tool) {
+            return Ok(());
+        }
+        let request = ApprovalRequest {
+            tool: tool.to_string(),
+            pattern: pattern_for(args),
+            subject: subject(args),
+            user_id: user_id.map(str::to_string),
+        };
+
+        let rules = self.rules().await.unwrap_or_else(|e| {
+            warn!("Failed to load approval rules: {}", e);
+            Vec::new()
+        });
+        if let Some(rule) = rules.iter().find(|r| r.matches(tool, &request.subject, user_id)) {
+            info!("Tool call {} auto-approved by rule {}", tool, rule.id);
+            self.log(&request, ApprovalDecision::AutoApproved, Some(&rule.id)).await;
+            return Ok(());
+        }
+
+        let approver = self.approver.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let Some(approver) = approver else {
+            self.log(&request, ApprovalDecision::Denied, None).await;
+            return Err(format!(
+                "Tool '{}' needs approval, which can only be given from `takobull agent`; \
+calls matching an auto-approve rule run without asking",
+                tool
+            ));
+        };
+        if !approver.approve(&request).await {
+            self.log(&request, ApprovalDecision::Denied, None).await;
+            return Err(format!("The user declined to run '{}' on {}", tool, request.subject));
+        }
+        self.log(&request, ApprovalDecision::Approved, None).await;
+
+        // Offered again every `suggest_after` approvals if declined
+        let Some(pattern) = &request.pattern else {
+            return Ok(());
+        };
+        let approvals = self.approvals(&request).await;
+        let suggest_after = self.config.suggest_after;
+        let offer = suggest_after > 0 && approvals % suggest_after == 0;
+        if offer && approver.offer_rule(&request, approvals).await {
+            match self.add_rule(tool, pattern, user_id).await {
+                Ok(rule) => info!("Added approval rule {}: {} {}", rule.id, rule.tool, rule.pattern),
+                Err(e) => warn!("Failed to add approval rule: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Approval rules, oldest first
+    pub async fn rules(&self) -> Result<Vec<ApprovalRule>> {
+        if !self.rules_path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&self.rules_path).await?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Approve calls to `tool` matching `pattern` for `user_id` from now on
+    pub async fn add_rule(&self, tool: &str, pattern: &str, user_id: Option<&str>) -> Result<ApprovalRule> {
+        let rule = ApprovalRule {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            tool: tool.to_string(),
+            pattern: pattern.to_string(),
+            user_id: user_id.map(str::to_string),
+            created_at: Utc::now(),
+        };
+        {
+            let _guard = self.lock.lock().await;
+            let mut rules = self.rules().await?;
+            rules.push(rule.clone());
+            self.save_rules(&rules).await?;
+        }
+        self.log_rule(&rule, ApprovalDecision::RuleAdded).await;
+        Ok(rule)
+    }
+
+    /// Remove the rule with `id`, returning it
+    pub async fn revoke(&self, id: &str) -> Result<ApprovalRule> {
+        let rule = {
+            let _guard = self.lock.lock().await;
+            let mut rules = self.rules().await?;
+            let index = rules
+                .iter()
+                .position(|r| r.id == id)
+                .ok_or_else(|| Error::tool(format!("No approval rule with id {}", id)))?;
+            let rule = rules.remove(index);
+            self.save_rules(&rules).await?;
+            rule
+        };
+        self.log_rule(&rule, ApprovalDecision::RuleRevoked).await;
+        Ok(rule)
+    }
+
+    /// The audit log, oldest first
+    pub async fn audit(&self) -> Result<Vec<ApprovalRecord>> {
+        if !self.audit_path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&self.audit_path).await?;
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Calls like `request` the same user has approved
+    async fn approvals(&self, request: &ApprovalRequest) -> usize {
+        let audit = self.audit().await.unwrap_or_default();
+        audit
+            .iter()
+            .filter(|r| {
+                r.decision == ApprovalDecision::Approved
+                    && r.tool == request.tool
+                    && request.pattern.as_ref() == Some(&r.pattern)
+                    && r.user_id == request.user_id
+            })
+            .count()
+    }
+
+    async fn save_rules(&self, rules: &[ApprovalRule]) -> Result<()> {
+        if let Some(parent) = self.rules_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = self.rules_path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string_pretty(rules)?).await?;
+        tokio::fs::rename(&tmp_path, &self.rules_path).await?;
+        Ok(())
+    }
+
+    async fn log(&self, request: &ApprovalRequest, decision: ApprovalDecision, rule: Option<&str>) {
+        self.append(ApprovalRecord {
+            at: Utc::now(),
+            decision,
+            tool: request.tool.clone(),
+            subject: request.subject.clone(),
+            pattern: request.pattern.clone().unwrap_or_default(),
+            user_id: request.user_id.clone(),
+            rule: rule.map(str::to_string),
+        })
+        .await;
+    }
+
+    async fn log_rule(&self, rule: &ApprovalRule, decision: ApprovalDecision) {
+        self.append(ApprovalRecord {
+            at: Utc::now(),
+            decision,
+            tool: rule.tool.clone(),
+            subject: String::new(),
+            pattern: rule.pattern.clone(),
+            user_id: rule.user_id.clone(),
+            rule: Some(rule.id.clone()),
+        })
+        .await;
+    }
+
+    /// Append `record` to the audit log; a failure is logged, not returned
+    async fn append(&self, record: ApprovalRecord) {
+        let result: Result<()> = async {
+            if let Some(parent) = self.audit_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut line = serde_json::to_string(&record)?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.audit_path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("Failed to write approval audit log: {}", e);
+        }
+    }
+}
+
+/// What a call acts on: its command, path, URL, or query, else all its arguments
+pub fn subject(args: &HashMap<String, Value>) -> String {
+    SUBJECT_KEYS
+        .iter()
+        .find_map(|key| args.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let sorted: BTreeMap<&String, &Value> = args.iter().collect();
+            serde_json::to_string(&sorted).unwrap_or_default()
+        })
+}
+
+/// Pattern for a rule approving calls like one with `args`: the directory
+/// for a path, otherwise the subject exactly
+///
+/// A command keeps its subcommand and every argument, so the rule for
+/// `git status` does not approve `git -c core.sshCommand=... fetch`. A `*`
+/// in the subject would act as a wildcard, so such calls get no pattern.
+pub fn pattern_for(args: &HashMap<String, Value>) -> Option<String> {
+    let subject = subject(args);
+    let subject = subject.trim();
+    if subject.is_empty() || subject.contains('*') {
+        return None;
+    }
+    if args.contains_key("command") {
+        return Some(subject.to_string());
+    }
+    match subject.rfind('/') {
+        Some(index) if index > 0 && !subject.contains("://") && args.contains_key("path") => {
+            Some(format!("{}/*", &subject[..index]))
+        }
+        _ => Some(subject.to_string()),
+    }
+}
+
+/// Whether `subject` matches `pattern`, where `*` stands for any text
+/// without shell control characters
+pub fn pattern_matches(pattern: &str, subject: &str) -> bool {
+    if subject.contains("..") && !pattern.contains("..") {
+        return false;
+    }
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    Regex::new(&format!("^{}$", parts.join(WILDCARD)))
+        .map(|regex| regex.is_match(subject.trim()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Approves everything and accepts every rule offered
+    #[derive(Default)]
+    struct Yes {
+        offers: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Approver for Yes {
+        async fn approve(&self, _request: &ApprovalRequest) -> bool {
+            true
+        }
+
+        async fn offer_rule(&self, _request: &ApprovalRequest, _approvals: usize) -> bool {
+            self.offers.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+    }
+
+    fn exec(command: &str) -> HashMap<String, Value> {
+        HashMap::from([("command".to_string(), json!(command))])
+    }
+
+    fn args(key: &str, value: &str) -> HashMap<String, Value> {
+        HashMap::from([(key.to_string(), json!(value))])
+    }
+
+    #[test]
+    fn test_patterns() {
+        assert_eq!(pattern_for(&exec("git pull --rebase")).unwrap(), "git pull --rebase");
+        assert_eq!(pattern_for(&exec(" ls /tmp/a/b ")).unwrap(), "ls /tmp/a/b");
+        assert_eq!(pattern_for(&exec("rm *.txt")), None);
+        assert_eq!(pattern_for(&args("path", "notes/2025/todo.md")).unwrap(), "notes/2025/*");
+        assert_eq!(pattern_for(&args("url", "https://example.com/a")).unwrap(), "https://example.com/a");
+        assert!(pattern_matches("git *", "git status"));
+        assert!(!pattern_matches("git *", "git pull; rm -rf ~"));
+        assert!(!pattern_matches("git *", "git log $(whoami)"));
+        assert!(!pattern_matches("notes/*", "notes/../.env"));
+        assert!(!pattern_matches("git *", "gitk"));
+    }
+
+    #[test]
+    fn test_suggested_rules_do_not_cover_dangerous_variants() {
+        let covers = |approved: &str, other: &str| pattern_matches(&pattern_for(&exec(approved)).unwrap(), other);
+        assert!(covers("rm foo.txt", "rm foo.txt"));
+        for other in ["rm -rf ~", "rm -rf foo.txt", "rm foo.txt ~", "rm foo.txt; rm -rf ~", "rm foo.txt.bak"] {
+            assert!(!covers("rm foo.txt", other), "rm foo.txt rule covers {}", other);
+        }
+        for other in ["git -c core.sshCommand=evil fetch", "git status --porcelain", "git push --force"] {
+            assert!(!covers("git status", other), "git status rule covers {}", other);
+        }
+        assert!(!covers("/usr/bin/ls", "/usr/bin/rm -rf ~"));
+    }
+
+    #[tokio::test]
+    async fn test_rule_offered_after_repeated_approvals() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ApprovalConfig {
+            required: vec!["exec".to_string()],
+            suggest_after: 2,
+        };
+        let gate = ApprovalGate::new(dir.path(), config);
+
+        // Without an approver nothing runs, but other tools are unaffected
+        assert!(gate.authorize("exec", &exec("git status"), Some("alice")).await.is_err());
+        assert!(gate.authorize("read_file", &HashMap::new(), Some("alice")).await.is_ok());
+
+        let approver = Arc::new(Yes::default());
+        gate.set_approver(approver.clone());
+        gate.authorize("exec", &exec("git status"), Some("alice")).await.unwrap();
+        assert_eq!(approver.offers.load(Ordering::SeqCst), 0);
+        gate.authorize("exec", &exec("git log"), Some("alice")).await.unwrap();
+        assert_eq!(approver.offers.load(Ordering::SeqCst), 0);
+        gate.authorize("exec", &exec("git status"), Some("alice")).await.unwrap();
+        assert_eq!(approver.offers.load(Ordering::SeqCst), 1);
+
+        let rules = gate.rules().await.unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!((rules[0].pattern.as_str(), rules[0].user_id.as_deref()), ("git status", Some("alice")));
+
+        // The rule covers alice's `git status` only
+        gate.authorize("exec", &exec("git status"), Some("alice")).await.unwrap();
+        let audit = gate.audit().await.unwrap();
+        let last = audit.last().unwrap();
+        assert_eq!((last.decision, last.rule.as_deref()), (ApprovalDecision::AutoApproved, Some(rules[0].id.as_str())));
+        assert!(!rules[0].matches("exec", "git status", Some("bob")));
+        assert!(!rules[0].matches("exec", "git log", Some("alice")));
+
+        gate.revoke(&rules[0].id).await.unwrap();
+        assert!(gate.rules().await.unwrap().is_empty());
+        assert!(gate.revoke(&rules[0].id).await.is_err());
+        let decisions: Vec<ApprovalDecision> = gate.audit().await.unwrap().iter().map(|r| r.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                ApprovalDecision::Denied,
+                ApprovalDecision::Approved,
+                ApprovalDecision::Approved,
+                ApprovalDecision::Approved,
+                ApprovalDecision::RuleAdded,
+                ApprovalDecision::AutoApproved,
+                ApprovalDecision::RuleRevoked,
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Base tool trait and types for TacoBot
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -17,6 +18,10 @@ pub struct ToolResult {
     pub silent: bool,
     /// Whether execution is async
     pub async_exec: bool,
+    /// URLs the result was drawn from, cited under the final answer
+    pub sources: Vec<String>,
+    /// Files the tool read or wrote, listed in tool transcripts
+    pub files: Vec<String>,
 }
 
 impl ToolResult {
@@ -28,6 +33,8 @@ impl ToolResult {
             is_error: false,
             silent: false,
             async_exec: false,
+            sources: Vec::new(),
+            files: Vec::new(),
         }
     }
 
@@ -39,6 +46,8 @@ impl ToolResult {
             is_error: true,
             silent: false,
             async_exec: false,
+            sources: Vec::new(),
+            files: Vec::new(),
         }
     }
 
@@ -48,6 +57,18 @@ impl ToolResult {
         self
     }
 
+    /// Record a URL the result was drawn from
+    pub fn with_source(mut self, url: impl Into<String>) -> Self {
+        self.sources.push(url.into());
+        self
+    }
+
+    /// Record a file the tool read or wrote
+    pub fn with_file(mut self, path: impl Into<String>) -> Self {
+        self.files.push(path.into());
+        self
+    }
+
     /// Mark as silent (don't notify user)
     pub fn silent(mut self) -> Self {
         self.silent = true;
@@ -61,6 +82,60 @@ impl ToolResult {
     }
 }
 
+/// Relative cost of running a tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCost {
+    Cheap,
+    Expensive,
+}
+
+/// Cost and latency annotation shown to the LLM in the tool description
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostHint {
+    pub cost: ToolCost,
+    /// Typical run time in seconds
+    #[serde(default)]
+    pub typical_secs: Option<u64>,
+}
+
+impl CostHint {
+    pub fn cheap() -> Self {
+        Self {
+            cost: ToolCost::Cheap,
+            typical_secs: None,
+        }
+    }
+
+    pub fn expensive() -> Self {
+        Self {
+            cost: ToolCost::Expensive,
+            typical_secs: None,
+        }
+    }
+
+    pub fn with_typical_secs(mut self, secs: u64) -> Self {
+        self.typical_secs = Some(secs);
+        self
+    }
+
+    pub fn is_expensive(&self) -> bool {
+        self.cost == ToolCost::Expensive
+    }
+
+    /// Suffix appended to the tool description, e.g. `[cost: expensive, typically ~30s]`
+    pub fn describe(&self) -> String {
+        let cost = match self.cost {
+            ToolCost::Cheap => "cheap",
+            ToolCost::Expensive => "expensive, use sparingly",
+        };
+        match self.typical_secs {
+            Some(secs) => format!("[cost: {}, typically ~{}s]", cost, secs),
+            None => format!("[cost: {}]", cost),
+        }
+    }
+}
+
 /// Tool call from LLM response
 #[derive(Debug, Clone)]
 pub struct ToolCall {
@@ -81,8 +156,62 @@ pub trait Tool: Send + Sync {
     /// Tool parameters schema (JSON Schema format)
     fn parameters(&self) -> Value;
 
+    /// Cost/latency annotation (none by default)
+    fn cost_hint(&self) -> Option<CostHint> {
+        None
+    }
+
+    /// Whether the tool changes anything outside the conversation (files,
+    /// processes, memory, hardware); such tools are disabled in safe mode
+    fn mutates(&self) -> bool {
+        false
+    }
+
     /// Execute the tool
     async fn execute(&self, args: HashMap<String, Value>) -> ToolResult;
+
+    /// Execute on behalf of the caller described by `ctx`
+    ///
+    /// Tools that start processes or keep per-user state override this to
+    /// use `ctx`; the default ignores it.
+    async fn execute_with_context(&self, args: HashMap<String, Value>, _ctx: ToolContext) -> ToolResult {
+        self.execute(args).await
+    }
+}
+
+/// Who a tool call is made for, passed to [`Tool::execute_with_context`]
+#[derive(Debug, Clone, Default)]
+pub struct ToolContext {
+    /// Environment variables configured under `tools.env`, resolved
+    pub env: HashMap<String, String>,
+    /// User the agent is acting for
+    pub user_id: Option<String>,
+    /// Name of the agent making the call
+    pub agent: Option<String>,
+    /// Where to send output for the user while the call runs
+    pub output: Option<tokio::sync::mpsc::UnboundedSender<String>>,
+}
+
+impl ToolContext {
+    /// Context for a call made for `user_id`
+    pub fn for_user(user_id: Option<&str>) -> Self {
+        Self {
+            user_id: user_id.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// Set the calling agent
+    pub fn with_agent(mut self, agent: Option<&str>) -> Self {
+        self.agent = agent.map(str::to_string);
+        self
+    }
+
+    /// Stream progress, such as command output, to `tx` as the call runs
+    pub fn with_output(mut self, tx: tokio::sync::mpsc::UnboundedSender<String>) -> Self {
+        self.output = Some(tx);
+        self
+    }
 }
 
 /// Optional trait for tools that need context
@@ -95,6 +224,9 @@ pub trait ContextualTool: Tool {
 pub struct ToolDefinition {
     pub r#type: String,
     pub function: ToolFunctionDefinition,
+    /// Cost annotation, already folded into the function description
+    #[serde(skip)]
+    pub cost: Option<CostHint>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -106,13 +238,23 @@ pub struct ToolFunctionDefinition {
 
 impl ToolDefinition {
     pub fn from_tool(tool: &dyn Tool) -> Self {
+        Self::with_cost(tool, tool.cost_hint())
+    }
+
+    /// Definition with `cost` overriding the tool's own hint
+    pub fn with_cost(tool: &dyn Tool, cost: Option<CostHint>) -> Self {
+        let description = match &cost {
+            Some(hint) => format!("{} {}", tool.description(), hint.describe()),
+            None => tool.description().to_string(),
+        };
         Self {
             r#type: "function".to_string(),
             function: ToolFunctionDefinition {
                 name: tool.name().to_string(),
-                description: tool.description().to_string(),
+                description,
                 parameters: tool.parameters(),
             },
+            cost,
         }
     }
 }
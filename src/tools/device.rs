@@ -0,0 +1,144 @@
+//! Device tool for reading sensors and switching GPIO outputs
+
+use super::base::{CostHint, Tool, ToolResult};
+use crate::device::DeviceManager;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Lists, reads, and writes devices registered with the device manager
+pub struct DeviceTool {
+    devices: Arc<DeviceManager>,
+}
+
+impl DeviceTool {
+    pub fn new(devices: Arc<DeviceManager>) -> Self {
+        Self { devices }
+    }
+}
+
+#[async_trait]
+impl Tool for DeviceTool {
+    fn name(&self) -> &str {
+        "device"
+    }
+
+    fn description(&self) -> &str {
+        "Work with attached hardware: 'list' devices, 'read' a sensor or pin, or 'write' a value (GPIO pins take 0 or 1)"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "read", "write"],
+                    "description": "What to do"
+                },
+                "device": {
+                    "type": "string",
+                    "description": "Device id, for read and write"
+                },
+                "value": {
+                    "type": "number",
+                    "description": "Value to write"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("list");
+        if action == "list" {
+            let devices = self.devices.devices();
+            if devices.is_empty() {
+                return ToolResult::success("No devices are attached");
+            }
+            let lines: Vec<String> = devices
+                .iter()
+                .map(|d| match d.config.address.as_str() {
+                    "" => format!("- {} ({:?})", d.id, d.device_type),
+                    address => format!("- {} ({:?} at {})", d.id, d.device_type, address),
+                })
+                .collect();
+            return ToolResult::success(lines.join("\n"));
+        }
+
+        let Some(id) = args.get("device").and_then(|v| v.as_str()) else {
+            return ToolResult::error("Missing 'device' parameter");
+        };
+        match action {
+            "read" => match self.devices.read(id).await {
+                Ok(value) => ToolResult::success(format!("{} = {}", id, value)),
+                Err(e) => ToolResult::error(format!("Failed to read {}: {}", id, e)),
+            },
+            "write" => {
+                let Some(value) = args.get("value").and_then(|v| v.as_f64()) else {
+                    return ToolResult::error("Missing 'value' parameter");
+                };
+                match self.devices.write(id, value).await {
+                    Ok(()) => ToolResult::success(format!("Set {} to {}", id, value)),
+                    Err(e) => ToolResult::error(format!("Failed to write {}: {}", id, e)),
+                }
+            }
+            other => ToolResult::error(format!("Unknown action: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::SimulatedBackend;
+
+    #[tokio::test]
+    async fn test_device_tool_against_simulated_board() {
+        let yaml = r#"
+- id: fan
+  type: gpio
+  address: "18"
+- id: cpu_temp
+  type: i2c
+  signal: { kind: constant, value: 61.5 }
+"#;
+        let backend = Arc::new(SimulatedBackend::new(serde_yaml::from_str(yaml).unwrap()));
+        let mut manager = DeviceManager::new().with_backend(backend.clone());
+        for device in manager.discover_devices().await.unwrap() {
+            manager.register_device(device).await.unwrap();
+        }
+        let tool = DeviceTool::new(Arc::new(manager));
+
+        let args = |pairs: &[(&str, Value)]| -> HashMap<String, Value> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+        };
+        let listed = tool.execute(args(&[("action", json!("list"))])).await;
+        assert_eq!(listed.for_llm, "- cpu_temp (I2C)\n- fan (GPIO at 18)");
+
+        let read = tool
+            .execute(args(&[("action", json!("read")), ("device", json!("cpu_temp"))]))
+            .await;
+        assert_eq!(read.for_llm, "cpu_temp = 61.5");
+
+        let write = tool
+            .execute(args(&[("action", json!("write")), ("device", json!("fan")), ("value", json!(1))]))
+            .await;
+        assert!(!write.is_error);
+        assert_eq!(backend.writes(), vec![("fan".to_string(), 1.0)]);
+
+        let missing = tool
+            .execute(args(&[("action", json!("read")), ("device", json!("heater"))]))
+            .await;
+        assert!(missing.is_error);
+    }
+}
@@ -0,0 +1,387 @@
+//! Background shell jobs started by the `exec` tool
+
+use super::base::{Tool, ToolResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Output kept per job; older output is dropped
+const MAX_JOB_OUTPUT: usize = 16 * 1024;
+
+/// State of a background job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Exited(i32),
+    Killed,
+    Failed(String),
+}
+
+impl std::fmt::Display for JobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobState::Running => write!(f, "running"),
+            JobState::Exited(code) => write!(f, "exited with code {}", code),
+            JobState::Killed => write!(f, "killed"),
+            JobState::Failed(e) => write!(f, "failed: {}", e),
+        }
+    }
+}
+
+/// Snapshot of a background job
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: String,
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    pub state: JobState,
+    /// Most recent output (stdout and stderr interleaved)
+    pub output: String,
+}
+
+struct Job {
+    info: Mutex<JobInfo>,
+    cancel: CancellationToken,
+}
+
+/// Tracks background shell jobs
+pub struct JobManager {
+    jobs: Mutex<HashMap<String, Arc<Job>>>,
+    working_dir: PathBuf,
+    max_running: usize,
+}
+
+impl JobManager {
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            working_dir: working_dir.into(),
+            max_running: 4,
+        }
+    }
+
+    /// Maximum number of jobs running at once
+    pub fn with_max_running(mut self, max_running: usize) -> Self {
+        self.max_running = max_running;
+        self
+    }
+
+    /// Start `command` in the background with extra `env` and return its job id
+    pub fn spawn(&self, command: &str, env: &HashMap<String, String>) -> crate::error::Result<String> {
+        let running = self
+            .jobs
+            .lock()
+            .values()
+            .filter(|j| j.info.lock().state == JobState::Running)
+            .count();
+        if running >= self.max_running {
+            return Err(crate::error::Error::tool(format!(
+                "Too many background jobs running ({}), kill one first",
+                running
+            )));
+        }
+
+        let mut child = spawn_shell(command, &self.working_dir, env)?;
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        let job = Arc::new(Job {
+            info: Mutex::new(JobInfo {
+                id: id.clone(),
+                command: command.to_string(),
+                started_at: Utc::now(),
+                state: JobState::Running,
+                output: String::new(),
+            }),
+            cancel: CancellationToken::new(),
+        });
+        self.jobs.lock().insert(id.clone(), job.clone());
+        info!("Started background job {}: {}", id, command);
+
+        let mut lines = forward_lines(&mut child);
+        let collector = {
+            let job = job.clone();
+            tokio::spawn(async move {
+                while let Some(line) = lines.recv().await {
+                    push_tail(&mut job.info.lock().output, &line, MAX_JOB_OUTPUT);
+                }
+            })
+        };
+        tokio::spawn(async move {
+            let exited = tokio::select! {
+                status = child.wait() => Some(status),
+                _ = job.cancel.cancelled() => None,
+            };
+            let state = match exited {
+                Some(Ok(status)) => JobState::Exited(status.code().unwrap_or(-1)),
+                Some(Err(e)) => JobState::Failed(e.to_string()),
+                None => {
+                    if let Err(e) = child.kill().await {
+                        warn!("Failed to kill job: {}", e);
+                    }
+                    JobState::Killed
+                }
+            };
+            // Let the last lines drain; a detached grandchild may hold the pipes open
+            let _ = tokio::time::timeout(std::time::Duration::from_secs(1), collector).await;
+            let mut info = job.info.lock();
+            info!("Background job {} {}", info.id, state);
+            info.state = state;
+        });
+
+        Ok(id)
+    }
+
+    /// Snapshot of a job
+    pub fn status(&self, id: &str) -> Option<JobInfo> {
+        self.jobs.lock().get(id).map(|j| j.info.lock().clone())
+    }
+
+    /// All jobs, oldest first
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self.jobs.lock().values().map(|j| j.info.lock().clone()).collect();
+        jobs.sort_by_key(|j| j.started_at);
+        jobs
+    }
+
+    /// Kill a running job; returns false if there is no such job
+    pub fn kill(&self, id: &str) -> bool {
+        match self.jobs.lock().get(id) {
+            Some(job) => {
+                job.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Spawn `sh -c command` with piped output and extra environment
+pub(crate) fn spawn_shell(
+    command: &str,
+    working_dir: &std::path::Path,
+    env: &HashMap<String, String>,
+) -> crate::error::Result<tokio::process::Child> {
+    tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(working_dir)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| crate::error::Error::tool(format!("Failed to start command: {}", e)))
+}
+
+/// Merge the child's stdout and stderr into one stream of lines
+pub(crate) fn forward_lines(child: &mut tokio::process::Child) -> mpsc::UnboundedReceiver<String> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(read_lines(stdout, tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(read_lines(stderr, tx));
+    }
+    rx
+}
+
+async fn read_lines(reader: impl AsyncRead + Unpin, tx: mpsc::UnboundedSender<String>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if tx.send(line).is_err() {
+            break;
+        }
+    }
+}
+
+/// Append a line to `buf`, dropping the oldest output beyond `max` bytes
+pub(crate) fn push_tail(buf: &mut String, line: &str, max: usize) {
+    buf.push_str(line);
+    buf.push('\n');
+    if buf.len() > max {
+        let mut cut = buf.len() - max;
+        while !buf.is_char_boundary(cut) {
+            cut += 1;
+        }
+        buf.drain(..cut);
+    }
+}
+
+/// Job status tool
+pub struct JobStatusTool {
+    jobs: Arc<JobManager>,
+}
+
+impl JobStatusTool {
+    pub fn new(jobs: Arc<JobManager>) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for JobStatusTool {
+    fn name(&self) -> &str {
+        "job_status"
+    }
+
+    fn description(&self) -> &str {
+        "Check a background job started by exec: its state and recent output. Omit 'job_id' to list all jobs."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "Job ID returned by exec"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let id = match args.get("job_id").and_then(|v| v.as_str()) {
+            Some(id) => id,
+            None => {
+                let jobs = self.jobs.list();
+                if jobs.is_empty() {
+                    return ToolResult::success("No background jobs");
+                }
+                let lines: Vec<String> = jobs
+                    .iter()
+                    .map(|j| format!("{} [{}] {}", j.id, j.state, j.command))
+                    .collect();
+                return ToolResult::success(lines.join("\n"));
+            }
+        };
+
+        match self.jobs.status(id) {
+            Some(job) => ToolResult::success(format!(
+                "Job {} ({}) {}, started {}\nOutput:\n{}",
+                job.id,
+                job.command,
+                job.state,
+                job.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                job.output
+            )),
+            None => ToolResult::error(format!("No such job: {}", id)),
+        }
+    }
+}
+
+/// Job kill tool
+pub struct JobKillTool {
+    jobs: Arc<JobManager>,
+}
+
+impl JobKillTool {
+    pub fn new(jobs: Arc<JobManager>) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for JobKillTool {
+    fn name(&self) -> &str {
+        "job_kill"
+    }
+
+    fn description(&self) -> &str {
+        "Kill a background job started by exec"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "Job ID returned by exec"
+                }
+            },
+            "required": ["job_id"]
+        })
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let id = match args.get("job_id").and_then(|v| v.as_str()) {
+            Some(id) => id,
+            None => return ToolResult::error("Missing 'job_id' parameter"),
+        };
+        match self.jobs.status(id) {
+            Some(job) if job.state != JobState::Running => {
+                ToolResult::success(format!("Job {} already {}", id, job.state))
+            }
+            Some(_) => {
+                self.jobs.kill(id);
+                ToolResult::success(format!("Killed job {}", id))
+            }
+            None => ToolResult::error(format!("No such job: {}", id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_for_exit(jobs: &JobManager, id: &str) -> JobInfo {
+        for _ in 0..100 {
+            let job = jobs.status(id).unwrap();
+            if job.state != JobState::Running {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn test_job_runs_and_captures_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = JobManager::new(dir.path());
+        let id = jobs.spawn("echo hello; echo oops >&2; exit 3", &HashMap::new()).unwrap();
+
+        let job = wait_for_exit(&jobs, &id).await;
+        assert_eq!(job.state, JobState::Exited(3));
+        assert!(job.output.contains("hello"));
+        assert!(job.output.contains("oops"));
+    }
+
+    #[tokio::test]
+    async fn test_kill_job_and_running_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = JobManager::new(dir.path()).with_max_running(1);
+        let id = jobs.spawn("sleep 30", &HashMap::new()).unwrap();
+        assert!(jobs.spawn("sleep 30", &HashMap::new()).is_err());
+
+        assert!(jobs.kill(&id));
+        assert_eq!(wait_for_exit(&jobs, &id).await.state, JobState::Killed);
+        assert!(!jobs.kill("missing"));
+    }
+
+    #[test]
+    fn test_push_tail_keeps_recent_output() {
+        let mut buf = String::new();
+        for i in 0..10 {
+            push_tail(&mut buf, &format!("line {}", i), 20);
+        }
+        assert!(buf.len() <= 20);
+        assert!(buf.ends_with("line 9\n"));
+    }
+}
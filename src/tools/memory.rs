@@ -0,0 +1,226 @@
+//! Long-term memory tools
+
+use super::base::{CostHint, Tool, ToolContext, ToolResult};
+use crate::memory::{ForgetReport, MemoryStore, Namespace, DEFAULT_FORGET_THRESHOLD};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Stores a fact in long-term memory
+pub struct RememberTool {
+    memory: Arc<MemoryStore>,
+}
+
+impl RememberTool {
+    pub fn new(memory: Arc<MemoryStore>) -> Self {
+        Self { memory }
+    }
+}
+
+#[async_trait]
+impl Tool for RememberTool {
+    fn name(&self) -> &str {
+        "remember"
+    }
+
+    fn description(&self) -> &str {
+        "Save a lasting fact to long-term memory. Relevant memories are recalled automatically in later conversations."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "fact": {
+                    "type": "string",
+                    "description": "A single self-contained fact, e.g. 'Alice is allergic to peanuts'"
+                },
+                "scope": {
+                    "type": "string",
+                    "enum": ["user", "shared", "agent"],
+                    "description": "Who may recall it: only the current user (default), the whole household, or only this agent"
+                }
+            },
+            "required": ["fact"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        self.execute_with_context(args, ToolContext::default()).await
+    }
+
+    async fn execute_with_context(&self, args: HashMap<String, Value>, ctx: ToolContext) -> ToolResult {
+        let fact = match args.get("fact").and_then(|v| v.as_str()) {
+            Some(f) => f,
+            None => return ToolResult::error("Missing 'fact' parameter"),
+        };
+        let namespace = match (args.get("scope").and_then(|v| v.as_str()), &ctx) {
+            (Some("shared"), _) => Namespace::Shared,
+            (Some("agent"), ToolContext { agent: Some(agent), .. }) => Namespace::Agent(agent.clone()),
+            (Some("agent"), _) => return ToolResult::error("No agent name is configured; use scope 'user' or 'shared'"),
+            (Some("user") | None, ToolContext { user_id: Some(user_id), .. }) => Namespace::User(user_id.clone()),
+            (Some("user"), _) => return ToolResult::error("The current user is unknown; use scope 'shared'"),
+            (None, _) => Namespace::Shared,
+            (Some(other), _) => return ToolResult::error(format!("Unknown scope '{}'", other)),
+        };
+
+        match self.memory.remember(&namespace, fact).await {
+            Ok(entry) => ToolResult::success(format!("Remembered ({}): {}", namespace.label(), entry.text)),
+            Err(e) => ToolResult::error(format!("Failed to remember: {}", e)),
+        }
+    }
+}
+
+/// Removes matching facts from long-term memory
+pub struct ForgetTool {
+    memory: Arc<MemoryStore>,
+}
+
+impl ForgetTool {
+    pub fn new(memory: Arc<MemoryStore>) -> Self {
+        Self { memory }
+    }
+}
+
+#[async_trait]
+impl Tool for ForgetTool {
+    fn name(&self) -> &str {
+        "forget"
+    }
+
+    fn description(&self) -> &str {
+        "Permanently delete facts matching a query from long-term memory. Run with dry_run first to see what would be deleted."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Text identifying the facts to delete, e.g. 'home address'"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Only list matching memories without deleting them (default false)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        self.execute_with_context(args, ToolContext::default()).await
+    }
+
+    /// Only the caller's own and shared memory can be forgotten from a conversation
+    async fn execute_with_context(&self, args: HashMap<String, Value>, ctx: ToolContext) -> ToolResult {
+        let query = match args.get("query").and_then(|v| v.as_str()) {
+            Some(q) => q,
+            None => return ToolResult::error("Missing 'query' parameter"),
+        };
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+        let namespaces = Namespace::visible(ctx.user_id.as_deref(), ctx.agent.as_deref());
+
+        match self.memory.forget(&namespaces, query, DEFAULT_FORGET_THRESHOLD, dry_run).await {
+            Ok(report) => ToolResult::success(describe_forget(&report)),
+            Err(e) => ToolResult::error(format!("Failed to forget: {}", e)),
+        }
+    }
+}
+
+/// Human-readable summary of a forget report
+pub fn describe_forget(report: &ForgetReport) -> String {
+    if report.is_empty() {
+        return "No matching memories".to_string();
+    }
+    let verb = if report.dry_run { "Would delete" } else { "Deleted" };
+    let mut text = format!(
+        "{} {} stored memories and {} MEMORY.md lines:",
+        verb,
+        report.entries.len(),
+        report.lines.len()
+    );
+    for (namespace, entry) in &report.entries {
+        text.push_str(&format!("\n- [{} {}] {}", namespace, entry.id, entry.text));
+    }
+    for (namespace, line) in &report.lines {
+        text.push_str(&format!("\n- [{} MEMORY.md] {}", namespace, line.trim()));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::HashEmbedder;
+
+    #[tokio::test]
+    async fn test_remember_scopes() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory = Arc::new(MemoryStore::new(dir.path(), Arc::new(HashEmbedder::default())));
+        let tool = RememberTool::new(memory.clone());
+        let args = |scope: Option<&str>| {
+            let mut args = HashMap::from([("fact".to_string(), json!("Likes jazz"))]);
+            if let Some(scope) = scope {
+                args.insert("scope".to_string(), json!(scope));
+            }
+            args
+        };
+
+        let alice = ToolContext::for_user(Some("alice"));
+        assert!(!tool.execute_with_context(args(None), alice.clone()).await.is_error);
+        assert!(!tool.execute_with_context(args(Some("shared")), alice.clone()).await.is_error);
+        assert!(tool.execute_with_context(args(Some("agent")), alice).await.is_error);
+        assert!(tool.execute_with_context(args(Some("user")), ToolContext::default()).await.is_error);
+
+        let user = Namespace::User("alice".to_string());
+        assert_eq!(memory.vectors(&user).entries().unwrap().len(), 1);
+        assert_eq!(memory.vectors(&Namespace::Shared).entries().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_forget_tool_is_scoped_to_caller() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory = Arc::new(MemoryStore::new(dir.path(), Arc::new(HashEmbedder::default())));
+        let alice = Namespace::User("alice".to_string());
+        let bob = Namespace::User("bob".to_string());
+        memory.remember(&alice, "Door code is 4321").await.unwrap();
+        memory.remember(&bob, "Door code is 9999").await.unwrap();
+        let tool = ForgetTool::new(memory.clone());
+
+        let args = |dry_run: bool| {
+            HashMap::from([
+                ("query".to_string(), json!("door code")),
+                ("dry_run".to_string(), json!(dry_run)),
+            ])
+        };
+        let ctx = ToolContext::for_user(Some("alice"));
+        let result = tool.execute_with_context(args(true), ctx.clone()).await;
+        assert!(result.for_llm.starts_with("Would delete 1 stored memories and 1 MEMORY.md lines"));
+        assert_eq!(memory.vectors(&alice).entries().unwrap().len(), 1);
+
+        let result = tool.execute_with_context(args(false), ctx.clone()).await;
+        assert!(result.for_llm.starts_with("Deleted"));
+        assert!(memory.vectors(&alice).entries().unwrap().is_empty());
+        assert_eq!(memory.vectors(&bob).entries().unwrap().len(), 1);
+        assert_eq!(tool.execute_with_context(args(false), ctx).await.for_llm, "No matching memories");
+    }
+}
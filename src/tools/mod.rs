@@ -1,9 +1,31 @@
 //! Tool framework and implementations
 
+pub mod approval;
 pub mod base;
+pub mod device;
+pub mod jobs;
+pub mod memory;
+pub mod notes;
+pub mod notify;
+pub mod path_policy;
+pub mod query_metrics;
+pub mod read_file;
 pub mod registry;
+pub mod shell;
+pub mod todo;
 pub mod write_file;
 
-pub use base::{Tool, ToolCall, ToolDefinition, ToolResult};
+pub use approval::{ApprovalGate, ApprovalRequest, ApprovalRule, Approver};
+pub use base::{CostHint, Tool, ToolCall, ToolContext, ToolCost, ToolDefinition, ToolResult};
+pub use device::DeviceTool;
+pub use jobs::{JobInfo, JobKillTool, JobManager, JobState, JobStatusTool};
+pub use memory::{ForgetTool, RememberTool};
+pub use notes::{NoteAddTool, NoteSearchTool};
+pub use notify::NotifyTool;
+pub use path_policy::{Access, PathPolicy, SymlinkPolicy};
+pub use query_metrics::QueryMetricsTool;
+pub use read_file::ReadFileTool;
 pub use registry::ToolRegistry;
+pub use shell::{OutputStreamer, ShellTool};
+pub use todo::{TodoAddTool, TodoDoneTool, TodoItem, TodoListTool, TodoStore};
 pub use write_file::WriteFileTool;
@@ -0,0 +1,216 @@
+//! Knowledge note tools
+
+use super::base::{CostHint, Tool, ToolResult};
+use crate::memory::NoteStore;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Most notes returned by a search
+const MAX_RESULTS: usize = 20;
+
+/// String array argument, ignoring non-string items
+fn string_list(args: &HashMap<String, Value>, key: &str) -> Vec<String> {
+    args.get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|v| v.as_str()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Saves a structured note
+pub struct NoteAddTool {
+    notes: Arc<NoteStore>,
+}
+
+impl NoteAddTool {
+    pub fn new(notes: Arc<NoteStore>) -> Self {
+        Self { notes }
+    }
+}
+
+#[async_trait]
+impl Tool for NoteAddTool {
+    fn name(&self) -> &str {
+        "note_add"
+    }
+
+    fn description(&self) -> &str {
+        "Save a structured knowledge note (instructions, reference info, recipes) when the user asks to note or file something. For facts about people use remember instead."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "Short title"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Note content"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Tags for filtering, e.g. [\"garden\", \"howto\"]"
+                },
+                "source": {
+                    "type": "string",
+                    "description": "Where the information came from, e.g. a URL"
+                }
+            },
+            "required": ["title", "body"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let (Some(title), Some(body)) = (
+            args.get("title").and_then(|v| v.as_str()),
+            args.get("body").and_then(|v| v.as_str()),
+        ) else {
+            return ToolResult::error("Missing 'title' or 'body' parameter");
+        };
+        let tags = string_list(&args, "tags");
+        let source = args.get("source").and_then(|v| v.as_str());
+
+        match self.notes.add(title, body, &tags, source).await {
+            Ok(note) => ToolResult::success(format!("Saved note {} \"{}\"", note.id, note.title)),
+            Err(e) => ToolResult::error(format!("Failed to save note: {}", e)),
+        }
+    }
+}
+
+/// Searches structured notes
+pub struct NoteSearchTool {
+    notes: Arc<NoteStore>,
+}
+
+impl NoteSearchTool {
+    pub fn new(notes: Arc<NoteStore>) -> Self {
+        Self { notes }
+    }
+}
+
+#[async_trait]
+impl Tool for NoteSearchTool {
+    fn name(&self) -> &str {
+        "note_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search saved knowledge notes by keywords and meaning, optionally filtered by tags"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What to look for; empty lists the newest notes"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Only notes carrying all of these tags"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum notes to return (default 5)"
+                }
+            }
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+        let tags = string_list(&args, "tags");
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|l| usize::try_from(l).unwrap_or(MAX_RESULTS).clamp(1, MAX_RESULTS))
+            .unwrap_or(5);
+
+        let matches = match self.notes.search(query, &tags, limit).await {
+            Ok(matches) => matches,
+            Err(e) => return ToolResult::error(format!("Failed to search notes: {}", e)),
+        };
+        if matches.is_empty() {
+            return ToolResult::success("No matching notes");
+        }
+        let results: Vec<String> = matches
+            .iter()
+            .map(|m| {
+                let mut text = format!("## {} [{}]", m.note.title, m.note.id);
+                if !m.note.tags.is_empty() {
+                    text.push_str(&format!("\ntags: {}", m.note.tags.join(", ")));
+                }
+                if let Some(source) = &m.note.source {
+                    text.push_str(&format!("\nsource: {}", source));
+                }
+                text.push_str(&format!("\n{}", m.note.body));
+                text
+            })
+            .collect();
+        matches
+            .iter()
+            .filter_map(|m| m.note.source.as_deref())
+            .fold(ToolResult::success(results.join("\n\n")), |result, source| {
+                result.with_source(source)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::HashEmbedder;
+
+    #[tokio::test]
+    async fn test_note_add_then_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = Arc::new(NoteStore::new(dir.path(), Arc::new(HashEmbedder::default())));
+        let add = NoteAddTool::new(notes.clone());
+        let search = NoteSearchTool::new(notes);
+
+        let result = add
+            .execute(HashMap::from([
+                ("title".to_string(), json!("Wifi extender")),
+                ("body".to_string(), json!("Pair with WPS, admin page at 192.168.0.254")),
+                ("tags".to_string(), json!(["network"])),
+                ("source".to_string(), json!("https://example.org/extender-manual")),
+            ]))
+            .await;
+        assert!(result.for_llm.starts_with("Saved note"));
+        assert!(add.execute(HashMap::new()).await.is_error);
+
+        let found = search
+            .execute(HashMap::from([("query".to_string(), json!("extender admin page"))]))
+            .await;
+        assert!(found.for_llm.contains("## Wifi extender"));
+        assert!(found.for_llm.contains("tags: network"));
+        assert_eq!(found.sources, vec!["https://example.org/extender-manual"]);
+
+        let none = search
+            .execute(HashMap::from([
+                ("query".to_string(), json!("extender")),
+                ("tags".to_string(), json!(["kitchen"])),
+            ]))
+            .await;
+        assert_eq!(none.for_llm, "No matching notes");
+    }
+}
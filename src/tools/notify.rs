@@ -0,0 +1,104 @@
+//! Templated notification tool
+
+use super::base::{CostHint, Tool, ToolResult};
+use crate::channels::template::{values_from_json, Templates};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Sends the user a message rendered from a configured template
+pub struct NotifyTool {
+    templates: Arc<Templates>,
+    description: String,
+}
+
+impl NotifyTool {
+    pub fn new(templates: Arc<Templates>) -> Self {
+        let description = format!(
+            "Send the user a notification using one of these message templates, filling in its variables:\n{}",
+            templates.describe()
+        );
+        Self { templates, description }
+    }
+}
+
+#[async_trait]
+impl Tool for NotifyTool {
+    fn name(&self) -> &str {
+        "notify"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "template": {
+                    "type": "string",
+                    "enum": self.templates.names().collect::<Vec<_>>(),
+                    "description": "Template name"
+                },
+                "variables": {
+                    "type": "object",
+                    "description": "Template variables; strings, or arrays of strings for lists"
+                }
+            },
+            "required": ["template", "variables"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let Some(template) = args.get("template").and_then(|v| v.as_str()) else {
+            return ToolResult::error("Missing 'template' parameter");
+        };
+        let values = args.get("variables").map(values_from_json).unwrap_or_default();
+        match self.templates.render(template, &values) {
+            Ok(message) => ToolResult::success(format!("Sent the '{}' notification", template)).with_user_content(message),
+            Err(e) => ToolResult::error(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MessageTemplateConfig;
+
+    #[tokio::test]
+    async fn test_notify_renders_template_for_user() {
+        let templates = Templates::new(HashMap::from([(
+            "chores".to_string(),
+            MessageTemplateConfig {
+                text: "Chores for {day}:\n{tasks}".to_string(),
+                ..Default::default()
+            },
+        )]));
+        let tool = NotifyTool::new(Arc::new(templates));
+        assert_eq!(tool.parameters()["properties"]["template"]["enum"], json!(["chores"]));
+        // Outbound messages are off in safe mode
+        assert!(tool.mutates());
+
+        let args = HashMap::from([
+            ("template".to_string(), json!("chores")),
+            ("variables".to_string(), json!({"day": "Saturday", "tasks": ["bins", "lawn"]})),
+        ]);
+        let result = tool.execute(args).await;
+        assert!(!result.is_error);
+        assert_eq!(result.for_user.as_deref(), Some("Chores for Saturday:\n- bins\n- lawn"));
+
+        let missing = HashMap::from([("template".to_string(), json!("chores"))]);
+        assert!(tool.execute(missing).await.is_error);
+    }
+}
@@ -0,0 +1,341 @@
+//! Path permissions shared by the filesystem tools
+//!
+//! Requested paths are resolved against the workspace, normalized, and
+//! canonicalized (following symlinks) before being checked against the
+//! configured roots, so `..` segments and symlinks cannot escape them.
+//! Directories in [`RESERVED`] are refused whatever the configuration says.
+
+use crate::config::FilesystemConfig;
+use crate::error::{Error, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Workspace directories the filesystem tools may neither read nor write:
+/// TakoBull's own state, such as approval rules, and per-user and per-agent
+/// memory, which is only recalled into its own namespace's conversations
+pub const RESERVED: &[&str] = &["state", "memory/users", "memory/agents"];
+
+/// What a tool wants to do with a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// How symlinks inside allowed roots are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Follow symlinks as long as the target is itself allowed
+    #[default]
+    FollowWithin,
+    /// Reject any path that passes through a symlink
+    Deny,
+}
+
+struct Root {
+    lexical: PathBuf,
+    canonical: PathBuf,
+}
+
+impl Root {
+    fn new(path: PathBuf) -> Self {
+        let canonical = canonicalize_existing(&path);
+        Self {
+            lexical: path,
+            canonical,
+        }
+    }
+}
+
+/// Resolves and authorizes paths requested by filesystem tools
+pub struct PathPolicy {
+    workspace: Root,
+    writable: Vec<Root>,
+    read_only: Vec<Root>,
+    deny: Vec<(String, Regex)>,
+    symlinks: SymlinkPolicy,
+}
+
+impl PathPolicy {
+    /// Policy allowing reads and writes inside `workspace` only
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace: Root::new(normalize(&workspace.into())),
+            writable: Vec::new(),
+            read_only: Vec::new(),
+            deny: Vec::new(),
+            symlinks: SymlinkPolicy::default(),
+        }
+    }
+
+    /// Policy for `workspace` with the `tools.filesystem` permissions
+    pub fn from_config(workspace: impl Into<PathBuf>, permissions: &FilesystemConfig) -> Result<Self> {
+        let mut policy = Self::new(workspace);
+        policy.writable = permissions
+            .writable
+            .iter()
+            .map(|p| Root::new(policy.config_path(p)))
+            .collect();
+        policy.read_only = permissions
+            .read_only
+            .iter()
+            .map(|p| Root::new(policy.config_path(p)))
+            .collect();
+        policy.deny = permissions
+            .deny
+            .iter()
+            .map(|pattern| Ok((pattern.clone(), glob_to_regex(pattern)?)))
+            .collect::<Result<_>>()?;
+        policy.symlinks = permissions.symlinks;
+        Ok(policy)
+    }
+
+    /// Workspace root
+    pub fn workspace(&self) -> &Path {
+        &self.workspace.lexical
+    }
+
+    /// Resolve `requested` (relative to the workspace, or absolute) and check `access`
+    ///
+    /// Returns the canonical path to operate on.
+    pub fn resolve(&self, requested: &str, access: Access) -> Result<PathBuf> {
+        let requested_path = Path::new(requested);
+        let lexical = normalize(&self.workspace.lexical.join(requested_path));
+        if self.symlinks == SymlinkPolicy::Deny {
+            self.reject_symlinks(&lexical)?;
+        }
+        let resolved = canonicalize_existing(&lexical);
+
+        let in_read_only = self.read_only.iter().any(|r| resolved.starts_with(&r.canonical));
+        let in_writable = resolved.starts_with(&self.workspace.canonical)
+            || self.writable.iter().any(|r| resolved.starts_with(&r.canonical));
+        let allowed = match access {
+            Access::Read => in_writable || in_read_only,
+            Access::Write => in_writable && !in_read_only,
+        };
+        if !allowed {
+            return Err(Error::tool(match access {
+                Access::Write if in_read_only => format!("Path is read-only: {}", requested),
+                _ => format!("Path is outside allowed directories: {}", requested),
+            }));
+        }
+
+        if let Some(dir) = self.reserved(&resolved) {
+            return Err(Error::tool(format!("Path is reserved ({}/): {}", dir, requested)));
+        }
+        if let Some(pattern) = self.denied_by(&resolved) {
+            return Err(Error::tool(format!(
+                "Path matches denied pattern '{}': {}",
+                pattern, requested
+            )));
+        }
+        Ok(resolved)
+    }
+
+    fn reserved(&self, path: &Path) -> Option<&'static str> {
+        let relative = path.strip_prefix(&self.workspace.canonical).ok()?;
+        RESERVED.iter().copied().find(|dir| relative.starts_with(dir))
+    }
+
+    fn denied_by(&self, path: &Path) -> Option<&str> {
+        let relative = path
+            .strip_prefix(&self.workspace.canonical)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let absolute = path.to_string_lossy().replace('\\', "/");
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        self.deny
+            .iter()
+            .find(|(pattern, regex)| {
+                if pattern.contains('/') {
+                    regex.is_match(&relative) || regex.is_match(&absolute)
+                } else {
+                    // Patterns without a slash match any path component, like .gitignore
+                    regex.is_match(&file_name)
+                        || relative.split('/').any(|component| regex.is_match(component))
+                }
+            })
+            .map(|(pattern, _)| pattern.as_str())
+    }
+
+    fn reject_symlinks(&self, lexical: &Path) -> Result<()> {
+        let base = std::iter::once(&self.workspace)
+            .chain(&self.writable)
+            .chain(&self.read_only)
+            .map(|r| &r.lexical)
+            .filter(|root| lexical.starts_with(root))
+            .max_by_key(|root| root.components().count());
+        let mut current = match base {
+            Some(root) => root.clone(),
+            None => return Ok(()),
+        };
+        for component in lexical.strip_prefix(&current).unwrap_or(lexical).components() {
+            current.push(component);
+            match std::fs::symlink_metadata(&current) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    return Err(Error::tool(format!("Symlinks are not allowed: {:?}", current)));
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn config_path(&self, path: &str) -> PathBuf {
+        let expanded = match (path.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(path),
+        };
+        normalize(&self.workspace.lexical.join(expanded))
+    }
+}
+
+/// Lexically resolve `.` and `..` components
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Canonicalize the longest existing prefix of `path` and append the rest
+fn canonicalize_existing(path: &Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest.iter().rev().fold(canonical, |acc, c| acc.join(c));
+        }
+        match (existing.file_name().map(|n| n.to_os_string()), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                rest.push(name);
+                existing = parent.to_path_buf();
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Translate a glob (`*`, `?`, `**`) into an anchored regex
+fn glob_to_regex(pattern: &str) -> Result<Regex> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| Error::config(format!("Invalid glob '{}': {}", pattern, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("skills")).unwrap();
+        std::fs::create_dir_all(dir.path().join("outside")).unwrap();
+        (dir, workspace)
+    }
+
+    #[test]
+    fn test_rejects_escape_via_parent_dir() {
+        let (_dir, workspace) = setup();
+        let policy = PathPolicy::new(&workspace);
+        assert!(policy.resolve("notes/todo.md", Access::Write).is_ok());
+        assert!(policy.resolve("../outside/x", Access::Write).is_err());
+        assert!(policy.resolve("skills/../../outside/x", Access::Read).is_err());
+        // A sibling directory sharing the workspace name prefix is not inside it
+        let sibling = format!("{}-evil/x", workspace.display());
+        assert!(policy.resolve(&sibling, Access::Write).is_err());
+    }
+
+    #[test]
+    fn test_read_only_and_deny() {
+        let (dir, workspace) = setup();
+        let permissions = FilesystemConfig {
+            read_only: vec!["skills".to_string(), dir.path().join("outside").display().to_string()],
+            deny: vec!["**/.env".to_string(), "*.key".to_string()],
+            ..Default::default()
+        };
+        let policy = PathPolicy::from_config(&workspace, &permissions).unwrap();
+
+        assert!(policy.resolve("skills/a/SKILL.md", Access::Read).is_ok());
+        assert!(policy.resolve("skills/a/SKILL.md", Access::Write).is_err());
+        assert!(policy
+            .resolve(&dir.path().join("outside/data.txt").display().to_string(), Access::Read)
+            .is_ok());
+        assert!(policy.resolve("config/.env", Access::Read).is_err());
+        assert!(policy.resolve("certs/server.key", Access::Read).is_err());
+    }
+
+    #[test]
+    fn test_reserved_dirs_refused_without_config() {
+        let (_dir, workspace) = setup();
+        let policy = PathPolicy::new(&workspace);
+        assert!(policy.resolve("state/approval_rules.json", Access::Write).is_err());
+        assert!(policy.resolve("./notes/../state/x", Access::Read).is_err());
+        assert!(policy.resolve("memory/users/bob/MEMORY.md", Access::Read).is_err());
+        assert!(policy.resolve("memory/agents/coder/vectors.jsonl", Access::Read).is_err());
+        assert!(policy.resolve("statements/x.md", Access::Write).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policies() {
+        let (dir, workspace) = setup();
+        std::os::unix::fs::symlink(dir.path().join("outside"), workspace.join("escape")).unwrap();
+        std::os::unix::fs::symlink(workspace.join("skills"), workspace.join("alias")).unwrap();
+
+        let follow = PathPolicy::new(&workspace);
+        assert!(follow.resolve("escape/x", Access::Write).is_err());
+        assert!(follow.resolve("alias/x", Access::Write).is_ok());
+
+        let deny = PathPolicy::from_config(
+            &workspace,
+            &FilesystemConfig {
+                symlinks: SymlinkPolicy::Deny,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(deny.resolve("alias/x", Access::Write).is_err());
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        assert!(glob_to_regex("**/.env").unwrap().is_match(".env"));
+        assert!(glob_to_regex("**/.env").unwrap().is_match("a/b/.env"));
+        assert!(glob_to_regex("secrets/*").unwrap().is_match("secrets/a"));
+        assert!(!glob_to_regex("secrets/*").unwrap().is_match("secrets/a/b"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Query metrics tool for sensor time series
+
+use super::base::{CostHint, Tool, ToolResult};
+use crate::config::parse_duration;
+use crate::telemetry::series::{downsample, SeriesStats, SeriesStore};
+use async_trait::async_trait;
+use chrono::{DateTime, Local, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_POINTS: usize = 24;
+
+/// Days of readings assumed kept when no retention is set
+const DEFAULT_RETENTION_DAYS: u32 = 14;
+
+/// Query metrics tool
+pub struct QueryMetricsTool {
+    store: Arc<SeriesStore>,
+    retention: Duration,
+}
+
+impl QueryMetricsTool {
+    pub fn new(store: Arc<SeriesStore>) -> Self {
+        Self {
+            store,
+            retention: days(DEFAULT_RETENTION_DAYS),
+        }
+    }
+
+    /// Look back no further than `retention_days`, since older readings are pruned
+    pub fn with_retention_days(mut self, retention_days: u32) -> Self {
+        self.retention = days(retention_days);
+        self
+    }
+}
+
+fn days(n: u32) -> Duration {
+    Duration::from_secs(u64::from(n) * 24 * 60 * 60)
+}
+
+/// The window from `since` ago to `until` ago, with `since` capped at `retention`
+fn window(
+    now: DateTime<Utc>,
+    since: Duration,
+    until: Duration,
+    retention: Duration,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let since = since.min(retention);
+    if until > since {
+        return Err("'until' must be more recent than 'since'".to_string());
+    }
+    let ago = |d: Duration| {
+        chrono::Duration::from_std(d)
+            .ok()
+            .and_then(|d| now.checked_sub_signed(d))
+            .ok_or_else(|| "Duration is too far back".to_string())
+    };
+    Ok((ago(since)?, ago(until)?))
+}
+
+#[async_trait]
+impl Tool for QueryMetricsTool {
+    fn name(&self) -> &str {
+        "query_metrics"
+    }
+
+    fn description(&self) -> &str {
+        "Query recorded sensor readings (e.g. temperature overnight). Returns min/max/mean/last and a downsampled series. Omit 'sensor' to list available sensors."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "sensor": {
+                    "type": "string",
+                    "description": "Sensor name"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "How far back to look, e.g. '30m', '8h', '2d' (default '24h'), up to the retention period"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "End of the window as a duration ago, e.g. '6h' (default now)"
+                },
+                "max_points": {
+                    "type": "integer",
+                    "description": "Maximum number of series points to return (default 24)"
+                }
+            }
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let sensor = match args.get("sensor").and_then(|v| v.as_str()) {
+            Some(s) => s,
+            None => {
+                return match self.store.sensors().await {
+                    Ok(sensors) if sensors.is_empty() => {
+                        ToolResult::success("No sensor readings have been recorded")
+                    }
+                    Ok(sensors) => {
+                        ToolResult::success(format!("Available sensors: {}", sensors.join(", ")))
+                    }
+                    Err(e) => ToolResult::error(format!("Failed to list sensors: {}", e)),
+                };
+            }
+        };
+
+        let since = args.get("since").and_then(|v| v.as_str()).unwrap_or("24h");
+        let since = match parse_duration(since) {
+            Some(d) => d,
+            None => return ToolResult::error(format!("Invalid 'since' duration: {}", since)),
+        };
+        let until = match args.get("until").and_then(|v| v.as_str()) {
+            Some(u) => match parse_duration(u) {
+                Some(d) => d,
+                None => return ToolResult::error(format!("Invalid 'until' duration: {}", u)),
+            },
+            None => Duration::ZERO,
+        };
+        let max_points = args
+            .get("max_points")
+            .and_then(|v| v.as_u64())
+            .map(|n| usize::try_from(n).unwrap_or(usize::MAX))
+            .unwrap_or(DEFAULT_POINTS);
+
+        let (from, to) = match window(Utc::now(), since, until, self.retention) {
+            Ok(window) => window,
+            Err(e) => return ToolResult::error(e),
+        };
+
+        let readings = match self.store.query(sensor, from, to).await {
+            Ok(r) => r,
+            Err(e) => return ToolResult::error(format!("Failed to query {}: {}", sensor, e)),
+        };
+        let stats = match SeriesStats::from_readings(&readings) {
+            Some(s) => s,
+            None => return ToolResult::success(format!("No readings for {} in that window", sensor)),
+        };
+
+        let series: Vec<String> = downsample(&readings, max_points)
+            .iter()
+            .map(|r| {
+                format!(
+                    "{} {:.2}",
+                    r.timestamp.with_timezone(&Local).format("%m-%d %H:%M"),
+                    r.value
+                )
+            })
+            .collect();
+
+        ToolResult::success(format!(
+            "{}: {} readings from {} to {}\nmin {:.2}, max {:.2}, mean {:.2}, last {:.2}\nSeries:\n{}",
+            sensor,
+            stats.count,
+            from.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            to.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            stats.min,
+            stats.max,
+            stats.mean,
+            stats.last,
+            series.join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::series::Reading;
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), json!(v))).collect()
+    }
+
+    #[test]
+    fn test_window_clamps_to_retention() {
+        let now = Utc::now();
+        let hour = Duration::from_secs(60 * 60);
+        let (from, to) = window(now, hour * 8, hour * 2, days(14)).unwrap();
+        assert_eq!((now - from, now - to), (chrono::Duration::hours(8), chrono::Duration::hours(2)));
+
+        let huge = parse_duration("14000000w").unwrap();
+        let (from, to) = window(now, huge, Duration::ZERO, days(14)).unwrap();
+        assert_eq!((now - from, to), (chrono::Duration::days(14), now));
+
+        assert!(window(now, hour, hour * 2, days(14)).is_err());
+        assert!(window(now, huge, huge, Duration::MAX).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(SeriesStore::new(dir.path()));
+        let now = Utc::now();
+        for (hours_ago, value) in [(30, 5.0), (3, 19.5), (1, 20.5)] {
+            let reading = Reading {
+                timestamp: now - chrono::Duration::hours(hours_ago),
+                value,
+            };
+            store.append("greenhouse", reading).await.unwrap();
+        }
+        let tool = QueryMetricsTool::new(store).with_retention_days(7);
+
+        let recent = tool.execute(args(&[("sensor", "greenhouse"), ("since", "8h")])).await;
+        assert!(!recent.is_error);
+        assert!(recent.for_llm.starts_with("greenhouse: 2 readings"), "{}", recent.for_llm);
+        assert!(recent.for_llm.contains("min 19.50, max 20.50"));
+
+        let all = tool.execute(args(&[("sensor", "greenhouse"), ("since", "1000000d")])).await;
+        assert!(all.for_llm.starts_with("greenhouse: 3 readings"), "{}", all.for_llm);
+
+        let invalid = tool.execute(args(&[("sensor", "greenhouse"), ("since", "yesterday")])).await;
+        assert!(invalid.is_error);
+        let backwards = tool.execute(args(&[("sensor", "greenhouse"), ("since", "1h"), ("until", "2h")])).await;
+        assert!(backwards.is_error);
+        let too_far = tool.execute(args(&[("sensor", "greenhouse"), ("until", "14000000w")])).await;
+        assert!(too_far.is_error);
+
+        let listed = tool.execute(HashMap::new()).await;
+        assert_eq!(listed.for_llm, "Available sensors: greenhouse");
+    }
+}
@@ -0,0 +1,97 @@
+//! Read file tool for TacoBot
+
+use super::base::{Tool, ToolResult};
+use super::path_policy::{Access, PathPolicy};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Largest file returned to the model, in bytes
+const MAX_READ_BYTES: usize = 64 * 1024;
+
+/// Read file tool
+pub struct ReadFileTool {
+    policy: Arc<PathPolicy>,
+}
+
+impl ReadFileTool {
+    pub fn new(policy: Arc<PathPolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl Tool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file from the workspace or another readable directory"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File path relative to workspace, or an absolute path"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let path = match args.get("path").and_then(|v| v.as_str()) {
+            Some(p) => p,
+            None => return ToolResult::error("Missing 'path' parameter"),
+        };
+
+        let full_path = match self.policy.resolve(path, Access::Read) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+
+        match tokio::fs::read(&full_path).await {
+            Ok(bytes) if bytes.len() > MAX_READ_BYTES => {
+                let text = String::from_utf8_lossy(&bytes[..MAX_READ_BYTES]);
+                ToolResult::success(format!(
+                    "{}\n[truncated: showing {} of {} bytes]",
+                    text,
+                    MAX_READ_BYTES,
+                    bytes.len()
+                ))
+                .with_file(path)
+            }
+            Ok(bytes) => ToolResult::success(String::from_utf8_lossy(&bytes).to_string()).with_file(path),
+            Err(e) => ToolResult::error(format!("Failed to read file: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilesystemConfig;
+
+    #[tokio::test]
+    async fn test_read_respects_deny_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.md"), "hello").unwrap();
+        std::fs::write(dir.path().join(".env"), "SECRET=1").unwrap();
+        let config = FilesystemConfig {
+            deny: vec!["**/.env".to_string()],
+            ..Default::default()
+        };
+        let tool = ReadFileTool::new(Arc::new(PathPolicy::from_config(dir.path(), &config).unwrap()));
+
+        let read = |path: &str| HashMap::from([("path".to_string(), json!(path))]);
+        let result = tool.execute(read("notes.md")).await;
+        assert_eq!(result.for_llm, "hello");
+        assert_eq!(result.files, vec!["notes.md"]);
+        assert!(tool.execute(read(".env")).await.is_error);
+    }
+}
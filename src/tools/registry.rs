@@ -1,15 +1,22 @@
 //! Tool registry for managing and executing tools
 
-use super::base::{Tool, ToolDefinition, ToolResult};
+use super::approval::ApprovalGate;
+use super::base::{CostHint, Tool, ToolContext, ToolDefinition, ToolResult};
+use crate::auth::SecretStore;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 /// Registry for managing tools
 pub struct ToolRegistry {
     tools: Arc<RwLock<HashMap<String, Arc<dyn Tool>>>>,
+    env: HashMap<String, HashMap<String, String>>,
+    secrets: Option<Arc<SecretStore>>,
+    cost_hints: HashMap<String, CostHint>,
+    read_only: bool,
+    approvals: Option<Arc<ApprovalGate>>,
 }
 
 impl ToolRegistry {
@@ -17,9 +24,70 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: Arc::new(RwLock::new(HashMap::new())),
+            env: HashMap::new(),
+            secrets: None,
+            cost_hints: HashMap::new(),
+            read_only: false,
+            approvals: None,
         }
     }
 
+    /// Hide and refuse tools that mutate state, as in safe mode
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Whether mutating tools are disabled
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Run tools that need approval only once `approvals` allows the call
+    pub fn with_approvals(mut self, approvals: Arc<ApprovalGate>) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
+    /// The approval gate, if calls are checked against one
+    pub fn approvals(&self) -> Option<&Arc<ApprovalGate>> {
+        self.approvals.as_ref()
+    }
+
+    /// Override tools' own cost hints by tool name
+    pub fn with_cost_hints(mut self, cost_hints: HashMap<String, CostHint>) -> Self {
+        self.cost_hints = cost_hints;
+        self
+    }
+
+    /// Effective cost hint for a tool
+    pub async fn cost_hint(&self, name: &str) -> Option<CostHint> {
+        if let Some(hint) = self.cost_hints.get(name) {
+            return Some(*hint);
+        }
+        self.get(name).await.and_then(|tool| tool.cost_hint())
+    }
+
+    /// Inject environment variables into tools by name
+    ///
+    /// Values are `secret:NAME`, `env:NAME`, or literals, resolved on every
+    /// execution so they never appear in tool definitions.
+    pub fn with_env(mut self, env: HashMap<String, HashMap<String, String>>, secrets: Arc<SecretStore>) -> Self {
+        self.env = env;
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Resolve the configured environment for a tool
+    fn resolve_env(&self, name: &str) -> crate::error::Result<HashMap<String, String>> {
+        let (Some(vars), Some(secrets)) = (self.env.get(name), &self.secrets) else {
+            return Ok(HashMap::new());
+        };
+        vars.iter()
+            .map(|(key, reference)| Ok((key.clone(), secrets.resolve(reference)?)))
+            .collect()
+    }
+
     /// Register a tool
     pub async fn register(&self, tool: Arc<dyn Tool>) {
         let mut tools = self.tools.write().await;
@@ -34,6 +102,11 @@ impl ToolRegistry {
 
     /// Execute a tool
     pub async fn execute(&self, name: &str, args: HashMap<String, Value>) -> ToolResult {
+        self.execute_in(name, args, ToolContext::default()).await
+    }
+
+    /// Execute a tool on behalf of the caller in `ctx`, adding its configured environment
+    pub async fn execute_in(&self, name: &str, args: HashMap<String, Value>, mut ctx: ToolContext) -> ToolResult {
         info!("Tool execution started: {}", name);
 
         let tool = match self.get(name).await {
@@ -43,9 +116,33 @@ impl ToolRegistry {
                 return ToolResult::error(format!("Tool '{}' not found", name));
             }
         };
+        if self.read_only && tool.mutates() {
+            info!("Tool refused in safe mode: {}", name);
+            return ToolResult::error(format!("Tool '{}' is disabled in safe mode", name));
+        }
+        if let Some(approvals) = &self.approvals {
+            if let Err(refusal) = approvals.authorize(name, &args, ctx.user_id.as_deref()).await {
+                info!("Tool call not approved: {}", name);
+                return ToolResult::error(refusal);
+            }
+        }
+
+        let env = match self.resolve_env(name) {
+            Ok(env) => env,
+            Err(e) => {
+                error!("Tool environment unavailable: {} ({})", name, e);
+                return ToolResult::error(format!("Tool '{}' is missing configured credentials: {}", name, e));
+            }
+        };
+        if !env.is_empty() {
+            let keys: Vec<&str> = env.keys().map(String::as_str).collect();
+            debug!("Injecting environment into {}: {:?}", name, keys);
+        }
+
+        ctx.env = env;
 
         let start = std::time::Instant::now();
-        let result = tool.execute(args).await;
+        let result = tool.execute_with_context(args, ctx).await;
         let duration = start.elapsed();
 
         if result.is_error {
@@ -71,7 +168,11 @@ impl ToolRegistry {
         let tools = self.tools.read().await;
         tools
             .values()
-            .map(|tool| ToolDefinition::from_tool(tool.as_ref()))
+            .filter(|tool| !(self.read_only && tool.mutates()))
+            .map(|tool| {
+                let cost = self.cost_hints.get(tool.name()).copied().or_else(|| tool.cost_hint());
+                ToolDefinition::with_cost(tool.as_ref(), cost)
+            })
             .collect()
     }
 
@@ -93,3 +194,117 @@ impl Default for ToolRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    struct EnvEcho;
+
+    #[async_trait]
+    impl Tool for EnvEcho {
+        fn name(&self) -> &str {
+            "env_echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo injected environment"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn execute(&self, _args: HashMap<String, Value>) -> ToolResult {
+            ToolResult::success("")
+        }
+
+        async fn execute_with_context(&self, _args: HashMap<String, Value>, ctx: ToolContext) -> ToolResult {
+            ToolResult::success(ctx.env.get("TOKEN").cloned().unwrap_or_default())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cost_hint_override_in_definitions() {
+        let registry = ToolRegistry::new().with_cost_hints(HashMap::from([(
+            "env_echo".to_string(),
+            CostHint::expensive().with_typical_secs(30),
+        )]));
+        registry.register(Arc::new(EnvEcho)).await;
+
+        let definitions = registry.get_definitions().await;
+        assert_eq!(
+            definitions[0].function.description,
+            "Echo injected environment [cost: expensive, use sparingly, typically ~30s]"
+        );
+        assert!(registry.cost_hint("env_echo").await.unwrap().is_expensive());
+        assert!(registry.cost_hint("missing").await.is_none());
+    }
+
+    struct Wipe;
+
+    #[async_trait]
+    impl Tool for Wipe {
+        fn name(&self) -> &str {
+            "wipe"
+        }
+
+        fn description(&self) -> &str {
+            "Delete everything"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        fn mutates(&self) -> bool {
+            true
+        }
+
+        async fn execute(&self, _args: HashMap<String, Value>) -> ToolResult {
+            ToolResult::success("wiped")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_only_hides_and_refuses_mutating_tools() {
+        let registry = ToolRegistry::new().with_read_only(true);
+        registry.register(Arc::new(EnvEcho)).await;
+        registry.register(Arc::new(Wipe)).await;
+
+        let names: Vec<String> = registry
+            .get_definitions()
+            .await
+            .into_iter()
+            .map(|d| d.function.name)
+            .collect();
+        assert_eq!(names, vec!["env_echo"]);
+        let refused = registry.execute("wipe", HashMap::new()).await;
+        assert!(refused.is_error);
+        assert!(refused.for_llm.contains("safe mode"));
+        assert!(!registry.execute("env_echo", HashMap::new()).await.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_env_resolved_at_execution_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = Arc::new(SecretStore::new(dir.path().join("secrets.yaml")));
+        let env = HashMap::from([(
+            "env_echo".to_string(),
+            HashMap::from([("TOKEN".to_string(), "secret:token".to_string())]),
+        )]);
+        let registry = ToolRegistry::new().with_env(env, secrets.clone());
+        registry.register(Arc::new(EnvEcho)).await;
+
+        // Missing secret fails the call without running the tool
+        assert!(registry.execute("env_echo", HashMap::new()).await.is_error);
+
+        secrets.set("token", "abc").unwrap();
+        assert_eq!(registry.execute("env_echo", HashMap::new()).await.for_llm, "abc");
+
+        let definitions = serde_json::to_string(&registry.get_definitions().await).unwrap();
+        assert!(!definitions.contains("abc"));
+    }
+}
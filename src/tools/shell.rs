@@ -0,0 +1,326 @@
+//! Shell command tool with streamed output and background jobs
+
+use super::base::{CostHint, Tool, ToolContext, ToolResult};
+use super::jobs::{forward_lines, push_tail, spawn_shell, JobManager};
+use crate::config::ShellConfig;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Output returned to the model for a foreground command
+const MAX_RESULT_OUTPUT: usize = 8 * 1024;
+
+/// Batches command output into rate-limited chunks for a channel
+///
+/// At most one chunk is sent per interval; output beyond the chunk size
+/// within one interval is dropped from the front and marked as skipped.
+pub struct OutputStreamer {
+    tx: mpsc::UnboundedSender<String>,
+    interval: Duration,
+    max_chunk: usize,
+    buf: String,
+    skipped: usize,
+    last_flush: Option<Instant>,
+}
+
+impl OutputStreamer {
+    pub fn new(tx: mpsc::UnboundedSender<String>, interval: Duration, max_chunk: usize) -> Self {
+        Self {
+            tx,
+            interval,
+            max_chunk: max_chunk.max(1),
+            buf: String::new(),
+            skipped: 0,
+            last_flush: None,
+        }
+    }
+
+    /// Buffer a line of output, flushing if the interval has passed
+    pub fn push(&mut self, line: &str) {
+        let before = self.buf.len();
+        push_tail(&mut self.buf, line, self.max_chunk);
+        if self.buf.len() < before + line.len() + 1 {
+            self.skipped += before + line.len() + 1 - self.buf.len();
+        }
+        self.tick();
+    }
+
+    /// Flush buffered output if the interval has passed
+    pub fn tick(&mut self) {
+        if self.last_flush.is_none_or(|t| t.elapsed() >= self.interval) {
+            self.flush();
+        }
+    }
+
+    /// Send any buffered output now
+    pub fn flush(&mut self) {
+        if self.buf.is_empty() {
+            return;
+        }
+        let mut chunk = std::mem::take(&mut self.buf);
+        if self.skipped > 0 {
+            chunk.insert_str(0, &format!("[… {} bytes skipped]\n", self.skipped));
+            self.skipped = 0;
+        }
+        let _ = self.tx.send(chunk.trim_end().to_string());
+        self.last_flush = Some(Instant::now());
+    }
+}
+
+/// Shell command tool
+pub struct ShellTool {
+    working_dir: PathBuf,
+    jobs: Arc<JobManager>,
+    config: ShellConfig,
+}
+
+impl ShellTool {
+    pub fn new(working_dir: impl Into<PathBuf>, jobs: Arc<JobManager>, config: ShellConfig) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            jobs,
+            config,
+        }
+    }
+
+    /// Run `command`, streaming its output to `stream` while it runs
+    async fn run_foreground(
+        &self,
+        command: &str,
+        timeout: Duration,
+        env: &HashMap<String, String>,
+        stream: Option<&mpsc::UnboundedSender<String>>,
+    ) -> ToolResult {
+        let mut child = match spawn_shell(command, &self.working_dir, env) {
+            Ok(c) => c,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+        let mut lines = forward_lines(&mut child);
+        let mut streamer = stream.filter(|_| self.config.stream).map(|tx| {
+            OutputStreamer::new(
+                tx.clone(),
+                Duration::from_millis(self.config.stream_interval_ms),
+                self.config.stream_chunk_chars,
+            )
+        });
+
+        let mut output = String::new();
+        let deadline = tokio::time::sleep(timeout);
+        tokio::pin!(deadline);
+        let mut tick = tokio::time::interval(Duration::from_millis(self.config.stream_interval_ms.max(100)));
+        let mut timed_out = false;
+        loop {
+            tokio::select! {
+                line = lines.recv() => match line {
+                    Some(line) => {
+                        push_tail(&mut output, &line, MAX_RESULT_OUTPUT);
+                        if let Some(s) = streamer.as_mut() {
+                            s.push(&line);
+                        }
+                    }
+                    None => break,
+                },
+                _ = tick.tick() => {
+                    if let Some(s) = streamer.as_mut() {
+                        s.tick();
+                    }
+                }
+                _ = &mut deadline => {
+                    timed_out = true;
+                    break;
+                }
+            }
+        }
+        if let Some(s) = streamer.as_mut() {
+            s.flush();
+        }
+
+        if timed_out {
+            let _ = child.kill().await;
+            return ToolResult::error(format!(
+                "Command timed out after {}s (use background=true for long-running commands)\n{}",
+                timeout.as_secs(),
+                output
+            ));
+        }
+        match child.wait().await {
+            Ok(status) if status.success() => ToolResult::success(if output.is_empty() {
+                "Command completed with no output".to_string()
+            } else {
+                output
+            }),
+            Ok(status) => ToolResult::error(format!(
+                "Command exited with code {}\n{}",
+                status.code().unwrap_or(-1),
+                output
+            )),
+            Err(e) => ToolResult::error(format!("Failed to wait for command: {}", e)),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ShellTool {
+    fn name(&self) -> &str {
+        "exec"
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command in the workspace. Set background=true for long-running commands: it returns a job ID to check with job_status or stop with job_kill."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Shell command to run"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Run as a background job and return its job ID immediately"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Timeout for foreground commands in seconds"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::expensive())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        self.execute_with_context(args, ToolContext::default()).await
+    }
+
+    async fn execute_with_context(&self, args: HashMap<String, Value>, ctx: ToolContext) -> ToolResult {
+        let env = ctx.env;
+        let command = match args.get("command").and_then(|v| v.as_str()) {
+            Some(c) => c,
+            None => return ToolResult::error("Missing 'command' parameter"),
+        };
+
+        if args.get("background").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return match self.jobs.spawn(command, &env) {
+                Ok(id) => ToolResult::success(format!(
+                    "Started background job {}. Check it with job_status or stop it with job_kill.",
+                    id
+                ))
+                .with_user_content(format!("⏳ Started job {}: {}", id, command))
+                .async_result(),
+                Err(e) => ToolResult::error(e.to_string()),
+            };
+        }
+
+        let timeout = args
+            .get("timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(self.config.timeout_secs);
+        self.run_foreground(command, Duration::from_secs(timeout), &env, ctx.output.as_ref())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(dir: &std::path::Path) -> ShellTool {
+        ShellTool::new(dir, Arc::new(JobManager::new(dir)), ShellConfig::default())
+    }
+
+    fn args(command: &str) -> HashMap<String, Value> {
+        HashMap::from([("command".to_string(), json!(command))])
+    }
+
+    #[tokio::test]
+    async fn test_foreground_command_streams_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ctx = ToolContext::default().with_output(tx);
+
+        let result = tool(dir.path()).execute_with_context(args("echo one; echo two"), ctx).await;
+        assert!(!result.is_error);
+        assert_eq!(result.for_llm, "one\ntwo\n");
+
+        let mut streamed = String::new();
+        while let Ok(chunk) = rx.try_recv() {
+            streamed.push_str(&chunk);
+            streamed.push('\n');
+        }
+        assert_eq!(streamed, "one\ntwo\n");
+    }
+
+    #[tokio::test]
+    async fn test_foreground_timeout_and_exit_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let shell = tool(dir.path());
+
+        let mut timeout = args("sleep 5");
+        timeout.insert("timeout_secs".to_string(), json!(0));
+        assert!(shell.execute(timeout).await.for_llm.contains("timed out"));
+
+        let failed = shell.execute(args("exit 2")).await;
+        assert!(failed.is_error);
+        assert!(failed.for_llm.contains("code 2"));
+    }
+
+    #[tokio::test]
+    async fn test_env_is_passed_to_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = HashMap::from([("GITHUB_TOKEN".to_string(), "ghp_x".to_string())]);
+        let ctx = ToolContext {
+            env,
+            ..Default::default()
+        };
+        let result = tool(dir.path())
+            .execute_with_context(args("echo $GITHUB_TOKEN"), ctx)
+            .await;
+        assert_eq!(result.for_llm, "ghp_x\n");
+    }
+
+    #[tokio::test]
+    async fn test_background_returns_job_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = Arc::new(JobManager::new(dir.path()));
+        let shell = ShellTool::new(dir.path(), jobs.clone(), ShellConfig::default());
+
+        let mut background = args("sleep 30");
+        background.insert("background".to_string(), json!(true));
+        let result = shell.execute(background).await;
+        assert!(result.async_exec);
+        assert_eq!(jobs.list().len(), 1);
+        assert!(jobs.kill(&jobs.list()[0].id));
+    }
+
+    #[test]
+    fn test_streamer_rate_limits_and_truncates() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut streamer = OutputStreamer::new(tx, Duration::from_secs(60), 16);
+        streamer.push("first");
+        for i in 0..10 {
+            streamer.push(&format!("line {}", i));
+        }
+        assert_eq!(rx.try_recv().unwrap(), "first");
+        assert!(rx.try_recv().is_err());
+
+        streamer.flush();
+        let chunk = rx.try_recv().unwrap();
+        assert!(chunk.starts_with("[… "));
+        assert!(chunk.ends_with("line 9"));
+    }
+}
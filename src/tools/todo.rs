@@ -0,0 +1,368 @@
+//! Todo list and its tools
+//!
+//! Todos are kept in `todo/todo.json`. They are added by the agent when the
+//! user asks, and by the email-to-task pipeline for action items found in
+//! incoming mail, which records the sender in `source`.
+
+use super::base::{CostHint, Tool, ToolResult};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Todo file, relative to the workspace
+pub const TODO_FILE: &str = "todo/todo.json";
+
+/// A todo item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// Where the todo came from, e.g. the email it was filed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done_at: Option<DateTime<Utc>>,
+}
+
+impl TodoItem {
+    /// One-line listing, e.g. `[1a2b3c4d] Pay the invoice (due 2026-10-20)`
+    pub fn summary(&self) -> String {
+        let mut line = format!("[{}] {}", self.id, self.title);
+        if let Some(due) = self.due {
+            line.push_str(&format!(" (due {})", due));
+        }
+        if self.done_at.is_some() {
+            line.push_str(" (done)");
+        }
+        line
+    }
+}
+
+/// Todos kept in the workspace
+pub struct TodoStore {
+    path: PathBuf,
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl TodoStore {
+    pub fn new(workspace: impl AsRef<Path>) -> Self {
+        Self {
+            path: workspace.as_ref().join(TODO_FILE),
+            lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// All todos, oldest first
+    pub async fn items(&self) -> Result<Vec<TodoItem>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&self.path).await?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Add an open todo
+    pub async fn add(
+        &self,
+        title: &str,
+        notes: Option<&str>,
+        due: Option<NaiveDate>,
+        source: Option<&str>,
+    ) -> Result<TodoItem> {
+        if title.trim().is_empty() {
+            return Err(Error::tool("A todo needs a title"));
+        }
+        let non_empty = |s: Option<&str>| s.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+        let item = TodoItem {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            title: title.trim().to_string(),
+            notes: non_empty(notes),
+            due,
+            source: non_empty(source),
+            created_at: Utc::now(),
+            done_at: None,
+        };
+        let _guard = self.lock.lock().await;
+        let mut items = self.items().await?;
+        items.push(item.clone());
+        self.save(&items).await?;
+        Ok(item)
+    }
+
+    /// Mark the todo `id` done, returning it if it exists
+    pub async fn complete(&self, id: &str) -> Result<Option<TodoItem>> {
+        let _guard = self.lock.lock().await;
+        let mut items = self.items().await?;
+        let Some(item) = items.iter_mut().find(|item| item.id == id) else {
+            return Ok(None);
+        };
+        item.done_at.get_or_insert_with(Utc::now);
+        let item = item.clone();
+        self.save(&items).await?;
+        Ok(Some(item))
+    }
+
+    async fn save(&self, items: &[TodoItem]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string_pretty(items)?).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+/// Adds a todo
+pub struct TodoAddTool {
+    todos: Arc<TodoStore>,
+}
+
+impl TodoAddTool {
+    pub fn new(todos: Arc<TodoStore>) -> Self {
+        Self { todos }
+    }
+}
+
+#[async_trait]
+impl Tool for TodoAddTool {
+    fn name(&self) -> &str {
+        "todo_add"
+    }
+
+    fn description(&self) -> &str {
+        "Add an item to the user's todo list when they ask to be reminded of or to track a task"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "The task, phrased as an action"
+                },
+                "notes": {
+                    "type": "string",
+                    "description": "Details worth keeping with the task"
+                },
+                "due": {
+                    "type": "string",
+                    "description": "Due date as YYYY-MM-DD"
+                }
+            },
+            "required": ["title"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let Some(title) = args.get("title").and_then(|v| v.as_str()) else {
+            return ToolResult::error("Missing 'title' parameter");
+        };
+        let due = match args.get("due").and_then(|v| v.as_str()) {
+            Some(due) => match NaiveDate::parse_from_str(due, "%Y-%m-%d") {
+                Ok(due) => Some(due),
+                Err(_) => return ToolResult::error(format!("Invalid due date '{}', expected YYYY-MM-DD", due)),
+            },
+            None => None,
+        };
+        let notes = args.get("notes").and_then(|v| v.as_str());
+
+        match self.todos.add(title, notes, due, None).await {
+            Ok(item) => ToolResult::success(format!("Added todo {}", item.summary())),
+            Err(e) => ToolResult::error(format!("Failed to add todo: {}", e)),
+        }
+    }
+}
+
+/// Lists todos
+pub struct TodoListTool {
+    todos: Arc<TodoStore>,
+}
+
+impl TodoListTool {
+    pub fn new(todos: Arc<TodoStore>) -> Self {
+        Self { todos }
+    }
+}
+
+#[async_trait]
+impl Tool for TodoListTool {
+    fn name(&self) -> &str {
+        "todo_list"
+    }
+
+    fn description(&self) -> &str {
+        "List the user's todos, including those filed from incoming email"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "include_done": {
+                    "type": "boolean",
+                    "description": "Also list completed todos (default false)"
+                }
+            }
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let include_done = args.get("include_done").and_then(|v| v.as_bool()).unwrap_or(false);
+        let items = match self.todos.items().await {
+            Ok(items) => items,
+            Err(e) => return ToolResult::error(format!("Failed to read todos: {}", e)),
+        };
+        let lines: Vec<String> = items
+            .iter()
+            .filter(|item| include_done || item.done_at.is_none())
+            .map(|item| {
+                let mut line = format!("- {}", item.summary());
+                if let Some(source) = &item.source {
+                    line.push_str(&format!("\n  from: {}", source));
+                }
+                if let Some(notes) = &item.notes {
+                    line.push_str(&format!("\n  {}", notes));
+                }
+                line
+            })
+            .collect();
+        if lines.is_empty() {
+            return ToolResult::success("No open todos");
+        }
+        ToolResult::success(lines.join("\n"))
+    }
+}
+
+/// Marks a todo done
+pub struct TodoDoneTool {
+    todos: Arc<TodoStore>,
+}
+
+impl TodoDoneTool {
+    pub fn new(todos: Arc<TodoStore>) -> Self {
+        Self { todos }
+    }
+}
+
+#[async_trait]
+impl Tool for TodoDoneTool {
+    fn name(&self) -> &str {
+        "todo_done"
+    }
+
+    fn description(&self) -> &str {
+        "Mark a todo as done by its id, as shown by todo_list"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Todo id"
+                }
+            },
+            "required": ["id"]
+        })
+    }
+
+    fn cost_hint(&self) -> Option<CostHint> {
+        Some(CostHint::cheap())
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
+        let Some(id) = args.get("id").and_then(|v| v.as_str()) else {
+            return ToolResult::error("Missing 'id' parameter");
+        };
+        match self.todos.complete(id.trim()).await {
+            Ok(Some(item)) => ToolResult::success(format!("Done: {}", item.title)),
+            Ok(None) => ToolResult::error(format!("No todo with id '{}'", id)),
+            Err(e) => ToolResult::error(format!("Failed to update todo: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_todo_add_list_done() {
+        let dir = tempfile::tempdir().unwrap();
+        let todos = Arc::new(TodoStore::new(dir.path()));
+        let (add, list, done) = (
+            TodoAddTool::new(todos.clone()),
+            TodoListTool::new(todos.clone()),
+            TodoDoneTool::new(todos.clone()),
+        );
+        assert!(add.mutates() && done.mutates() && !list.mutates());
+
+        let result = add
+            .execute(HashMap::from([
+                ("title".to_string(), json!("Renew the car insurance")),
+                ("due".to_string(), json!("2026-11-01")),
+            ]))
+            .await;
+        assert!(result.for_llm.ends_with("Renew the car insurance (due 2026-11-01)"));
+        assert!(add
+            .execute(HashMap::from([
+                ("title".to_string(), json!("x")),
+                ("due".to_string(), json!("next week"))
+            ]))
+            .await
+            .is_error);
+        todos
+            .add("Pay the invoice", None, None, Some("billing@power.example"))
+            .await
+            .unwrap();
+
+        let listed = list.execute(HashMap::new()).await.for_llm;
+        assert!(listed.contains("Renew the car insurance"));
+        assert!(listed.contains("Pay the invoice\n  from: billing@power.example"));
+
+        let id = todos.items().await.unwrap()[0].id.clone();
+        let result = done.execute(HashMap::from([("id".to_string(), json!(id))])).await;
+        assert_eq!(result.for_llm, "Done: Renew the car insurance");
+        assert!(done
+            .execute(HashMap::from([("id".to_string(), json!("missing"))]))
+            .await
+            .is_error);
+
+        let open = list.execute(HashMap::new()).await.for_llm;
+        assert!(!open.contains("Renew the car insurance"));
+        let all = list
+            .execute(HashMap::from([("include_done".to_string(), json!(true))]))
+            .await
+            .for_llm;
+        assert!(all.contains("Renew the car insurance (due 2026-11-01) (done)"));
+    }
+}
@@ -1,19 +1,26 @@
 //! Write file tool for TacoBot
 
 use super::base::{Tool, ToolResult};
+use super::path_policy::{Access, PathPolicy};
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::info;
 
 /// Write file tool
 pub struct WriteFileTool {
-    workspace: String,
+    policy: Arc<PathPolicy>,
 }
 
 impl WriteFileTool {
     pub fn new(workspace: String) -> Self {
-        Self { workspace }
+        Self::with_policy(Arc::new(PathPolicy::new(workspace)))
+    }
+
+    /// Create a tool that checks paths against a shared policy
+    pub fn with_policy(policy: Arc<PathPolicy>) -> Self {
+        Self { policy }
     }
 }
 
@@ -44,6 +51,10 @@ impl Tool for WriteFileTool {
         })
     }
 
+    fn mutates(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> ToolResult {
         let path = match args.get("path").and_then(|v| v.as_str()) {
             Some(p) => p,
@@ -55,9 +66,11 @@ impl Tool for WriteFileTool {
             None => return ToolResult::error("Missing 'content' parameter"),
         };
 
-        // Validate path is within workspace
-        let full_path = std::path::PathBuf::from(&self.workspace).join(path);
-        
+        let full_path = match self.policy.resolve(path, Access::Write) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+
         // Create parent directories if needed
         if let Some(parent) = full_path.parent() {
             if !parent.exists() {
@@ -67,23 +80,55 @@ impl Tool for WriteFileTool {
             }
         }
 
-        // Check if path is within workspace
-        let workspace_path = std::path::Path::new(&self.workspace);
-        let full_path_str = full_path.to_string_lossy();
-        let workspace_str = workspace_path.to_string_lossy();
-        
-        if !full_path_str.starts_with(workspace_str.as_ref()) {
-            return ToolResult::error("Path is outside workspace");
-        }
-
         // Write file
         match std::fs::write(&full_path, content) {
             Ok(_) => {
                 info!("File written: {}", path);
                 ToolResult::success(format!("File written successfully: {}", path))
                     .with_user_content(format!("✓ Created file: {}", path))
+                    .with_file(path)
             }
             Err(e) => ToolResult::error(format!("Failed to write file: {}", e)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_outside_workspace_creates_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let tool = WriteFileTool::new(workspace.display().to_string());
+
+        let args = |path: &str| {
+            HashMap::from([
+                ("path".to_string(), json!(path)),
+                ("content".to_string(), json!("hi")),
+            ])
+        };
+        assert!(!tool.execute(args("notes/a.md")).await.is_error);
+        assert!(workspace.join("notes/a.md").exists());
+
+        assert!(tool.execute(args("../escape/a.md")).await.is_error);
+        assert!(!dir.path().join("escape").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cannot_write_approval_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = WriteFileTool::new(dir.path().display().to_string());
+        let rule = json!([{"id": "r1", "tool": "exec", "pattern": "*"}]).to_string();
+        let args = HashMap::from([
+            ("path".to_string(), json!(crate::tools::approval::RULES_FILE)),
+            ("content".to_string(), json!(rule)),
+        ]);
+        assert!(tool.execute(args).await.is_error);
+
+        let gate = crate::tools::ApprovalGate::new(dir.path(), Default::default());
+        assert!(gate.rules().await.unwrap().is_empty());
+    }
+}
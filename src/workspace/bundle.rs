@@ -0,0 +1,227 @@
+//! Portable persona bundles
+//!
+//! A bundle is a single gzip-compressed YAML file holding what makes up the
+//! assistant's personality: the identity files at the workspace root,
+//! `skills/`, `personas/`, and the message templates from config.yaml. Only
+//! those are read, so sessions, memory, state, and anything from config.yaml
+//! besides the templates (API keys included) never end up in a bundle.
+
+use super::template::{self, ApplyReport, TemplatePack, MAX_FILES};
+use crate::agent::persona::PERSONAS_DIR;
+use crate::config::MessageTemplateConfig;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Bundle format written by this version
+const BUNDLE_VERSION: u32 = 1;
+
+/// Largest uncompressed bundle accepted on import
+const MAX_BUNDLE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Workspace root files that make up a persona
+pub const PERSONA_FILES: &[&str] = &["IDENTITY.md", "SOUL.md", "USER.md", "AGENTS.md", "TOOLS.md"];
+
+/// Workspace directories exported whole
+pub const PERSONA_DIRS: &[&str] = &["skills", PERSONAS_DIR];
+
+/// An exported persona
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaBundle {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Contents keyed by path relative to the workspace, `/`-separated
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// Message templates from the `templates` section of config.yaml
+    #[serde(default)]
+    pub templates: BTreeMap<String, MessageTemplateConfig>,
+}
+
+impl PersonaBundle {
+    /// Collect the persona files in `workspace` along with `templates`
+    pub fn export(workspace: &Path, templates: BTreeMap<String, MessageTemplateConfig>) -> Result<Self> {
+        let mut collected = Vec::new();
+        for file in PERSONA_FILES {
+            let path = workspace.join(file);
+            if path.is_file() {
+                collected.push((PathBuf::from(file), std::fs::read_to_string(path)?));
+            }
+        }
+        for dir in PERSONA_DIRS {
+            let path = workspace.join(dir);
+            if path.is_dir() {
+                template::collect_files(workspace, &path, &mut collected)?;
+            }
+        }
+        let files = collected
+            .into_iter()
+            .map(|(path, contents)| (bundle_path(&path), contents))
+            .collect();
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            files,
+            templates,
+        })
+    }
+
+    /// Write the bundle to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let yaml = serde_yaml::to_string(self)?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(yaml.as_bytes())?;
+        let compressed = encoder.finish()?;
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, compressed)?;
+        std::fs::rename(&tmp_path, path)?;
+        info!("Exported persona with {} files to {:?}", self.files.len(), path);
+        Ok(())
+    }
+
+    /// Read and check a bundle written by [`save`](Self::save)
+    pub fn load(path: &Path) -> Result<Self> {
+        let compressed = std::fs::read(path)?;
+        let mut yaml = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .take(MAX_BUNDLE_BYTES + 1)
+            .read_to_string(&mut yaml)
+            .map_err(|e| Error::config(format!("Not a persona bundle: {:?}: {}", path, e)))?;
+        if yaml.len() as u64 > MAX_BUNDLE_BYTES {
+            return Err(Error::config(format!(
+                "Persona bundle is larger than {} MB",
+                MAX_BUNDLE_BYTES / 1024 / 1024
+            )));
+        }
+
+        let bundle: Self = serde_yaml::from_str(&yaml)?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(Error::config(format!(
+                "Persona bundle version {} is newer than this build supports ({})",
+                bundle.version, BUNDLE_VERSION
+            )));
+        }
+        if bundle.files.len() > MAX_FILES {
+            return Err(Error::config(format!(
+                "Persona bundle has {} files (max {})",
+                bundle.files.len(),
+                MAX_FILES
+            )));
+        }
+        for path in bundle.files.keys() {
+            let path = template::validate_path(Path::new(path))?;
+            if !is_persona_path(&path) {
+                return Err(Error::config(format!("Persona bundle contains {:?}, which is not a persona file", path)));
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Write the bundle's files into `workspace`, keeping existing files
+    /// unless `overwrite` is set
+    ///
+    /// Templates are left to the caller, since they belong in config.yaml.
+    pub fn apply(&self, workspace: &Path, overwrite: bool) -> Result<ApplyReport> {
+        let pack = TemplatePack {
+            name: "persona bundle".to_string(),
+            description: None,
+            files: self
+                .files
+                .iter()
+                .map(|(path, contents)| (PathBuf::from(path), contents.clone()))
+                .collect(),
+        };
+        pack.apply(workspace, overwrite)
+    }
+}
+
+/// `path` with `/` separators on every platform
+fn bundle_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether `path` is one of the files a bundle may carry
+pub(crate) fn is_persona_path(path: &Path) -> bool {
+    let mut components = path.components();
+    let first = components.next().map(|c| c.as_os_str().to_string_lossy().to_string());
+    match (first, components.next()) {
+        (Some(file), None) => PERSONA_FILES.contains(&file.as_str()),
+        (Some(dir), Some(_)) => PERSONA_DIRS.contains(&dir.as_str()),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip_leaves_out_private_files() {
+        let source = tempfile::tempdir().unwrap();
+        write(source.path(), "SOUL.md", "Warm and brief");
+        write(source.path(), "USER.md", "Alice, gardener");
+        write(source.path(), "skills/watering/SKILL.md", "Water at dawn");
+        write(source.path(), "skills/watering/.env", "TOKEN=abc");
+        write(source.path(), "personas/pirate.md", "Arr");
+        write(source.path(), "MEMORY.md", "Alice's door code");
+        write(source.path(), "sessions/s1.json", "{}");
+        write(source.path(), "state/approval_rules.json", "[]");
+        let templates = BTreeMap::from([(
+            "digest".to_string(),
+            MessageTemplateConfig {
+                text: "Good morning {name}".to_string(),
+                ..Default::default()
+            },
+        )]);
+
+        let file = source.path().join("persona.takobull");
+        PersonaBundle::export(source.path(), templates).unwrap().save(&file).unwrap();
+        let bundle = PersonaBundle::load(&file).unwrap();
+        assert_eq!(
+            bundle.files.keys().collect::<Vec<_>>(),
+            vec!["SOUL.md", "USER.md", "personas/pirate.md", "skills/watering/SKILL.md"]
+        );
+        assert_eq!(bundle.templates["digest"].text, "Good morning {name}");
+
+        let target = tempfile::tempdir().unwrap();
+        write(target.path(), "SOUL.md", "mine");
+        let report = bundle.apply(target.path(), false).unwrap();
+        assert_eq!(report.skipped, vec![PathBuf::from("SOUL.md")]);
+        assert_eq!(std::fs::read_to_string(target.path().join("personas/pirate.md")).unwrap(), "Arr");
+        bundle.apply(target.path(), true).unwrap();
+        assert_eq!(std::fs::read_to_string(target.path().join("SOUL.md")).unwrap(), "Warm and brief");
+    }
+
+    #[test]
+    fn test_load_rejects_files_outside_the_persona() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["../SOUL.md", "state/approval_rules.json", "MEMORY.md", "skills"] {
+            let mut bundle = PersonaBundle::export(dir.path(), BTreeMap::new()).unwrap();
+            bundle.files.insert(path.to_string(), "x".to_string());
+            let file = dir.path().join("bundle");
+            bundle.save(&file).unwrap();
+            assert!(PersonaBundle::load(&file).is_err(), "{} accepted", path);
+        }
+
+        std::fs::write(dir.path().join("plain"), "not gzip").unwrap();
+        assert!(PersonaBundle::load(&dir.path().join("plain")).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Low disk space protection
+//!
+//! A full SD card leaves the assistant unable to save sessions or even its
+//! own state files. [`DiskGuard`] checks free space on the workspace
+//! filesystem and, below the configured threshold, frees space aggressively:
+//! quarantined corrupt files are purged, old telemetry is pruned, job run
+//! history is truncated, and idle sessions are archived. The user is alerted
+//! once each time space runs low. In safe mode nothing is deleted; the guard
+//! only alerts.
+
+use super::integrity::CORRUPT_DIR;
+use crate::about::format_bytes;
+use crate::config::DiskConfig;
+use crate::error::{Error, Result};
+use crate::scheduler::{JobNotifier, RunHistory};
+use crate::session::{SessionArchive, SessionManager};
+use crate::telemetry::SeriesStore;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Bytes available to unprivileged users on the filesystem holding `path`
+pub async fn free_bytes(path: &Path) -> Result<u64> {
+    let output = tokio::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| Error::runtime(format!("Failed to run df: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::runtime(format!(
+            "df failed for {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    parse_df(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| Error::runtime(format!("Unexpected df output for {:?}", path)))
+}
+
+/// Available bytes from POSIX `df -Pk` output
+fn parse_df(output: &str) -> Option<u64> {
+    // Filesystem 1024-blocks Used Available Capacity Mounted-on
+    let kib: u64 = output.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// What a cleanup pass removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    pub corrupt_files: usize,
+    pub telemetry_files: usize,
+    pub history_runs: usize,
+    pub archived_sessions: usize,
+}
+
+impl CleanupReport {
+    /// Summary, e.g. `archived 3 sessions, dropped 12 job runs`
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            (self.archived_sessions, "archived", "sessions"),
+            (self.history_runs, "dropped", "job runs"),
+            (self.telemetry_files, "pruned", "telemetry files"),
+            (self.corrupt_files, "purged", "corrupt files"),
+        ]
+        .iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|(count, verb, what)| format!("{} {} {}", verb, count, what))
+        .collect();
+        if parts.is_empty() {
+            "found nothing to remove".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Watches free space and cleans up the workspace when it runs low
+pub struct DiskGuard {
+    workspace: PathBuf,
+    config: DiskConfig,
+    low: bool,
+    /// Only alert, without deleting or archiving anything
+    read_only: bool,
+}
+
+impl DiskGuard {
+    /// Guard the filesystem holding `workspace`
+    pub fn new(workspace: impl Into<PathBuf>, config: DiskConfig) -> Self {
+        Self {
+            workspace: workspace.into(),
+            config,
+            low: false,
+            read_only: false,
+        }
+    }
+
+    /// Leave the workspace untouched when space runs low, e.g. in safe mode
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Free space below which cleanup runs
+    pub fn threshold(&self) -> u64 {
+        self.config.min_free_mb * 1024 * 1024
+    }
+
+    /// Remove what the workspace can spare
+    ///
+    /// Steps that only delete run before those that write, so the later
+    /// steps have room to work on a full disk, and every step is attempted
+    /// even if an earlier one fails.
+    pub async fn cleanup(&self) -> CleanupReport {
+        let mut report = CleanupReport::default();
+
+        match purge_dir(&self.workspace.join(CORRUPT_DIR)).await {
+            Ok(n) => report.corrupt_files = n,
+            Err(e) => warn!("Failed to purge quarantined files: {}", e),
+        }
+        let telemetry = SeriesStore::new(self.workspace.join("telemetry"));
+        match telemetry.prune(self.config.telemetry_days, chrono::Utc::now()).await {
+            Ok(n) => report.telemetry_files = n,
+            Err(e) => warn!("Failed to prune telemetry: {}", e),
+        }
+        let history = RunHistory::new(self.workspace.join("cron"));
+        match history.truncate_all(self.config.history_runs).await {
+            Ok(n) => report.history_runs = n,
+            Err(e) => warn!("Failed to truncate job history: {}", e),
+        }
+        let sessions = SessionManager::new(self.workspace.join("sessions"));
+        let archive = SessionArchive::for_sessions_dir(sessions.sessions_dir());
+        let max_idle = Duration::from_secs(self.config.archive_after_days * 86400);
+        match archive.archive_idle(&sessions, max_idle).await {
+            Ok(ids) => report.archived_sessions = ids.len(),
+            Err(e) => warn!("Failed to archive sessions: {}", e),
+        }
+
+        info!("Low disk cleanup: {}", report.summary());
+        report
+    }
+
+    /// React to `free` bytes of free space, cleaning up when below the
+    /// threshold and returning an alert when space has just run low
+    pub async fn check(&mut self, free: u64) -> Option<String> {
+        if free >= self.threshold() {
+            if self.low {
+                info!("Disk space recovered: {} free", format_bytes(free));
+            }
+            self.low = false;
+            return None;
+        }
+
+        warn!(
+            "Low disk space: {} free, below the {} minimum",
+            format_bytes(free),
+            format_bytes(self.threshold())
+        );
+        let cleanup = if self.read_only {
+            info!("Skipping low disk cleanup in safe mode");
+            "skipped in safe mode".to_string()
+        } else {
+            self.cleanup().await.summary()
+        };
+        let was_low = std::mem::replace(&mut self.low, true);
+        if was_low {
+            return None;
+        }
+        let now = free_bytes(&self.workspace).await.unwrap_or(free);
+        Some(format!(
+            "Low disk space: {} free on the workspace filesystem (minimum {}). Cleanup {}; {} free now.",
+            format_bytes(free),
+            format_bytes(self.threshold()),
+            cleanup,
+            format_bytes(now)
+        ))
+    }
+
+    /// Check free space every `check_interval_secs` until shutdown, sending
+    /// alerts to `notifier`
+    pub async fn run(mut self, notifier: Arc<dyn JobNotifier>, mut shutdown: broadcast::Receiver<()>) {
+        let interval = Duration::from_secs(self.config.check_interval_secs.max(1));
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    match free_bytes(&self.workspace).await {
+                        Ok(free) => {
+                            if let Some(alert) = self.check(free).await {
+                                notifier.notify(&alert).await;
+                            }
+                        }
+                        Err(e) => warn!("Failed to check free disk space: {}", e),
+                    }
+                }
+                _ = shutdown.recv() => {
+                    debug!("Disk guard stopping");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Delete everything under `dir`, returning how many files were removed
+async fn purge_dir(dir: &Path) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                pending.push(entry.path());
+            } else {
+                removed += 1;
+            }
+        }
+    }
+    tokio::fs::remove_dir_all(dir).await?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::RunRecord;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_parse_df() {
+        let output = "Filesystem     1024-blocks    Used Available Capacity Mounted on\n\
+                      /dev/mmcblk0p2    29502728 2150312  26124464       8% /\n";
+        assert_eq!(parse_df(output), Some(26124464 * 1024));
+        assert_eq!(parse_df("Filesystem\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_low_space_cleans_up_and_alerts_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path();
+        std::fs::create_dir_all(workspace.join("corrupt/sessions")).unwrap();
+        std::fs::write(workspace.join("corrupt/sessions/a.json"), "{").unwrap();
+
+        let history = RunHistory::new(workspace.join("cron"));
+        for i in 0..5 {
+            let record = RunRecord::new(chrono::Utc::now(), i, true, "ok");
+            history.append("job1", record).await.unwrap();
+        }
+
+        let mut sessions = SessionManager::new(workspace.join("sessions"));
+        let mut idle = sessions.create_session("user-1").await.unwrap();
+        idle.last_activity = SystemTime::now() - Duration::from_secs(3 * 86400);
+        sessions.save_session(&idle).await.unwrap();
+        sessions.create_session("user-2").await.unwrap();
+
+        let mut guard = DiskGuard::new(workspace, DiskConfig::default());
+        assert_eq!(guard.check(guard.threshold()).await, None);
+
+        let alert = guard.check(1024).await.unwrap();
+        assert!(alert.contains("archived 1 sessions, dropped 2 job runs, purged 1 corrupt files"));
+        assert!(!workspace.join(CORRUPT_DIR).exists());
+        assert_eq!(history.load("job1").await.unwrap().len(), 3);
+        assert_eq!(sessions.list_sessions().await.unwrap().len(), 1);
+
+        // Still low: cleanup runs again but the user was already told
+        assert_eq!(guard.check(1024).await, None);
+        guard.check(guard.threshold()).await;
+        assert!(guard.check(1024).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_guard_only_alerts() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path();
+        std::fs::create_dir_all(workspace.join("corrupt/sessions")).unwrap();
+        std::fs::write(workspace.join("corrupt/sessions/a.json"), "{").unwrap();
+
+        let mut guard = DiskGuard::new(workspace, DiskConfig::default()).with_read_only(true);
+        let alert = guard.check(1024).await.unwrap();
+        assert!(alert.contains("Cleanup skipped in safe mode"));
+        assert!(workspace.join("corrupt/sessions/a.json").exists());
+    }
+}
@@ -0,0 +1,160 @@
+//! Startup integrity check for workspace files
+//!
+//! Power loss on embedded boards regularly leaves truncated or zero-length
+//! files behind. On boot every JSON/YAML file under the persisted state
+//! directories is parsed; corrupt files are moved into `workspace/corrupt/`
+//! so startup can continue, and stale temporary files from interrupted
+//! atomic writes are removed.
+
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Workspace subdirectories holding machine-written state
+pub const CHECKED_DIRS: &[&str] = &["sessions", "state", "cron"];
+
+/// Directory corrupt files are moved into
+pub const CORRUPT_DIR: &str = "corrupt";
+
+/// Outcome of a workspace integrity check
+#[derive(Debug, Default)]
+pub struct IntegrityReport {
+    /// Number of files parsed
+    pub checked: usize,
+    /// Corrupt files and where they were moved
+    pub quarantined: Vec<(PathBuf, PathBuf)>,
+    /// Leftover temporary files that were removed
+    pub removed_temp: Vec<PathBuf>,
+}
+
+impl IntegrityReport {
+    /// Whether every file was intact
+    pub fn is_clean(&self) -> bool {
+        self.quarantined.is_empty() && self.removed_temp.is_empty()
+    }
+}
+
+/// Validate the workspace state files, quarantining any that fail to parse
+pub fn check_workspace(workspace: &Path) -> Result<IntegrityReport> {
+    let mut report = IntegrityReport::default();
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S").to_string();
+
+    for dir in CHECKED_DIRS {
+        let root = workspace.join(dir);
+        if root.is_dir() {
+            check_dir(workspace, &root, &stamp, &mut report)?;
+        }
+    }
+
+    if report.is_clean() {
+        debug!("Workspace integrity check passed ({} files)", report.checked);
+    } else {
+        info!(
+            "Workspace integrity check: {} files checked, {} quarantined, {} temp files removed",
+            report.checked,
+            report.quarantined.len(),
+            report.removed_temp.len()
+        );
+    }
+    Ok(report)
+}
+
+fn check_dir(workspace: &Path, dir: &Path, stamp: &str, report: &mut IntegrityReport) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            check_dir(workspace, &path, stamp, report)?;
+            continue;
+        }
+
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if ext == "tmp" {
+            warn!("Removing interrupted write: {:?}", path);
+            std::fs::remove_file(&path)?;
+            report.removed_temp.push(path);
+            continue;
+        }
+        if !matches!(ext, "json" | "yaml" | "yml") {
+            continue;
+        }
+
+        report.checked += 1;
+        if let Err(reason) = validate_file(&path, ext) {
+            let target = quarantine(workspace, &path, stamp)?;
+            warn!("Quarantined corrupt file {:?} -> {:?}: {}", path, target, reason);
+            report.quarantined.push((path, target));
+        }
+    }
+    Ok(())
+}
+
+fn validate_file(path: &Path, ext: &str) -> std::result::Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if bytes.iter().all(|b| b.is_ascii_whitespace() || *b == 0) {
+        return Err("file is empty".to_string());
+    }
+    let contents = std::str::from_utf8(&bytes).map_err(|e| e.to_string())?;
+    match ext {
+        "json" => serde_json::from_str::<serde_json::Value>(contents)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str::<serde_yaml::Value>(contents)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+fn quarantine(workspace: &Path, path: &Path, stamp: &str) -> Result<PathBuf> {
+    let relative = path.strip_prefix(workspace).unwrap_or(path);
+    let mut target = workspace.join(CORRUPT_DIR).join(relative);
+    let file_name = format!(
+        "{}.{}",
+        target.file_name().and_then(|n| n.to_str()).unwrap_or("file"),
+        stamp
+    );
+    target.set_file_name(file_name);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantines_corrupt_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = dir.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::write(sessions.join("good.json"), r#"{"id": "good"}"#).unwrap();
+        std::fs::write(sessions.join("truncated.json"), r#"{"id": "tru"#).unwrap();
+        std::fs::write(sessions.join("empty.json"), "").unwrap();
+        std::fs::write(sessions.join("partial.json.tmp"), "{").unwrap();
+
+        let report = check_workspace(dir.path()).unwrap();
+
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.quarantined.len(), 2);
+        assert_eq!(report.removed_temp.len(), 1);
+        assert!(sessions.join("good.json").exists());
+        assert!(!sessions.join("truncated.json").exists());
+        for (_, target) in &report.quarantined {
+            assert!(target.starts_with(dir.path().join(CORRUPT_DIR).join("sessions")));
+            assert!(target.exists());
+        }
+    }
+
+    #[test]
+    fn test_clean_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("cron")).unwrap();
+        std::fs::write(dir.path().join("cron/jobs.yaml"), "jobs: []\n").unwrap();
+
+        let report = check_workspace(dir.path()).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.checked, 1);
+    }
+}
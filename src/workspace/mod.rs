@@ -0,0 +1,11 @@
+//! Workspace directory management
+
+pub mod bundle;
+pub mod disk;
+pub mod integrity;
+pub mod template;
+
+pub use bundle::PersonaBundle;
+pub use disk::DiskGuard;
+pub use integrity::{check_workspace, IntegrityReport};
+pub use template::TemplatePack;
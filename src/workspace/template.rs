@@ -0,0 +1,402 @@
+//! Workspace template packs
+//!
+//! A template pack seeds IDENTITY.md, SOUL.md, HEARTBEAT.md, and skills with
+//! content for a common use case. Packs are built in, loaded from a local
+//! directory, or loaded from a manifest file or URL:
+//!
+//! ```yaml
+//! name: greenhouse
+//! description: Greenhouse monitoring
+//! files:
+//!   IDENTITY.md: "# Identity\n..."
+//!   skills/watering/SKILL.md: "..."
+//! ```
+//!
+//! A pack may only write the persona files, HEARTBEAT.md, `skills/`, and
+//! `personas/`, so a pack from a URL cannot touch state, memory, or jobs.
+
+use super::bundle::is_persona_path;
+use crate::error::{Error, Result};
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Largest file accepted from a template pack
+pub(crate) const MAX_FILE_BYTES: u64 = 256 * 1024;
+
+/// Most files accepted from a template pack
+pub(crate) const MAX_FILES: usize = 200;
+
+/// Largest manifest accepted from a URL
+const MAX_MANIFEST_BYTES: usize = 8 * 1024 * 1024;
+
+/// How long fetching a manifest from a URL may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Workspace root files a pack may seed besides the persona files
+const TEMPLATE_FILES: &[&str] = &["HEARTBEAT.md"];
+
+macro_rules! template_files {
+    ($dir:literal: $($file:literal),+ $(,)?) => {
+        &[$(($file, include_str!(concat!("../../templates/", $dir, "/", $file)))),+]
+    };
+}
+
+type BuiltinPack = (&'static str, &'static str, &'static [(&'static str, &'static str)]);
+
+const BUILTIN: &[BuiltinPack] = &[
+    (
+        "home-automation",
+        "Sensors, devices, and household routines",
+        template_files!("home-automation":
+            "IDENTITY.md",
+            "SOUL.md",
+            "HEARTBEAT.md",
+            "skills/device-control/SKILL.md",
+            "skills/sensor-report/SKILL.md",
+        ),
+    ),
+    (
+        "coding",
+        "Pair programming, code review, and repository summaries",
+        template_files!("coding":
+            "IDENTITY.md",
+            "SOUL.md",
+            "HEARTBEAT.md",
+            "skills/code-review/SKILL.md",
+            "skills/git-summary/SKILL.md",
+        ),
+    ),
+    (
+        "journal",
+        "Private daily journaling and weekly reviews",
+        template_files!("journal":
+            "IDENTITY.md",
+            "SOUL.md",
+            "HEARTBEAT.md",
+            "skills/daily-entry/SKILL.md",
+            "skills/weekly-review/SKILL.md",
+        ),
+    ),
+];
+
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    files: BTreeMap<String, String>,
+}
+
+/// A set of workspace files to seed
+#[derive(Debug, Clone)]
+pub struct TemplatePack {
+    pub name: String,
+    pub description: Option<String>,
+    /// Paths relative to the workspace root and their contents
+    pub files: Vec<(PathBuf, String)>,
+}
+
+/// Outcome of applying a template pack
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub written: Vec<PathBuf>,
+    /// Files left alone because they already existed
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Names and descriptions of the built-in packs
+pub fn builtin_templates() -> Vec<(&'static str, &'static str)> {
+    BUILTIN.iter().map(|(name, desc, _)| (*name, *desc)).collect()
+}
+
+/// Get a built-in pack by name
+pub fn builtin(name: &str) -> Option<TemplatePack> {
+    BUILTIN
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(name, description, files)| TemplatePack {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            files: files
+                .iter()
+                .map(|(path, contents)| (PathBuf::from(path), contents.to_string()))
+                .collect(),
+        })
+}
+
+/// Resolve a template spec: a built-in name, an `http(s)://` manifest URL,
+/// a manifest file, or a directory
+pub async fn resolve(spec: &str) -> Result<TemplatePack> {
+    if let Some(pack) = builtin(spec) {
+        return Ok(pack);
+    }
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+        return from_manifest(&fetch_manifest(spec).await?);
+    }
+
+    let path = Path::new(spec);
+    if path.is_dir() {
+        return from_dir(path);
+    }
+    if path.is_file() {
+        return from_manifest(&std::fs::read_to_string(path)?);
+    }
+
+    let names: Vec<&str> = BUILTIN.iter().map(|(n, _, _)| *n).collect();
+    Err(Error::config(format!(
+        "Unknown template '{}' (built-in: {})",
+        spec,
+        names.join(", ")
+    )))
+}
+
+/// Download a manifest, refusing bodies larger than [`MAX_MANIFEST_BYTES`]
+async fn fetch_manifest(url: &str) -> Result<String> {
+    info!("Fetching template pack from {}", url);
+    let too_large = || Error::config(format!("Template {} is larger than {} MB", url, MAX_MANIFEST_BYTES / 1024 / 1024));
+    let response = reqwest::Client::new()
+        .get(url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| Error::http(format!("Failed to fetch template {}: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(Error::http(format!(
+            "Failed to fetch template {}: {}",
+            url,
+            response.status()
+        )));
+    }
+    if response.content_length().is_some_and(|len| len > MAX_MANIFEST_BYTES as u64) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|e| Error::http(format!("Failed to fetch template {}: {}", url, e)))?;
+        if body.len() + chunk.len() > MAX_MANIFEST_BYTES {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    String::from_utf8(body).map_err(|_| Error::config(format!("Template {} is not text", url)))
+}
+
+/// Parse a YAML or JSON manifest listing the pack's files
+pub fn from_manifest(contents: &str) -> Result<TemplatePack> {
+    let manifest: Manifest = serde_yaml::from_str(contents)?;
+    if manifest.files.len() > MAX_FILES {
+        return Err(Error::config(format!(
+            "Template has {} files (max {})",
+            manifest.files.len(),
+            MAX_FILES
+        )));
+    }
+    let files = manifest
+        .files
+        .into_iter()
+        .map(|(path, contents)| Ok((validate_template_path(Path::new(&path))?, contents)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TemplatePack {
+        name: manifest.name,
+        description: manifest.description,
+        files,
+    })
+}
+
+/// Load every text file under `dir` as a pack named after the directory
+pub fn from_dir(dir: &Path) -> Result<TemplatePack> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.retain(|(path, _)| {
+        let keep = validate_template_path(path).is_ok();
+        if !keep {
+            warn!("Skipping template file {:?}, which is not a persona file or skill", path);
+        }
+        keep
+    });
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(TemplatePack {
+        name: dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "custom".to_string()),
+        description: None,
+        files,
+    })
+}
+
+pub(crate) fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, String)>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &path, files)?;
+            continue;
+        }
+        if !file_type.is_file() {
+            continue;
+        }
+        if entry.metadata()?.len() > MAX_FILE_BYTES {
+            warn!("Skipping large template file {:?}", path);
+            continue;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                files.push((relative, contents));
+            }
+            Err(_) => warn!("Skipping non-text template file {:?}", path),
+        }
+        if files.len() > MAX_FILES {
+            return Err(Error::config(format!("Template has more than {} files", MAX_FILES)));
+        }
+    }
+    Ok(())
+}
+
+/// Ensure a template path stays inside the workspace
+pub(crate) fn validate_path(path: &Path) -> Result<PathBuf> {
+    if path.as_os_str().is_empty()
+        || path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(Error::config(format!("Invalid template path: {:?}", path)));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Ensure a template path is a persona file, HEARTBEAT.md, or under
+/// `skills/` or `personas/`
+fn validate_template_path(path: &Path) -> Result<PathBuf> {
+    let path = validate_path(path)?;
+    if !is_persona_path(&path) && !TEMPLATE_FILES.iter().any(|file| path == Path::new(file)) {
+        return Err(Error::config(format!(
+            "Template path {:?} is not a persona file or skill",
+            path
+        )));
+    }
+    Ok(path)
+}
+
+impl TemplatePack {
+    /// Write the pack into `workspace`, keeping existing files unless `overwrite` is set
+    pub fn apply(&self, workspace: &Path, overwrite: bool) -> Result<ApplyReport> {
+        let mut report = ApplyReport::default();
+        for (relative, contents) in &self.files {
+            let relative = validate_template_path(relative)?;
+            let target = workspace.join(&relative);
+            if target.exists() && !overwrite {
+                debug!("Keeping existing {:?}", target);
+                report.skipped.push(relative);
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, contents)?;
+            report.written.push(relative);
+        }
+        info!(
+            "Applied template {}: {} written, {} kept",
+            self.name,
+            report.written.len(),
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_templates_seed_core_files() {
+        for (name, _) in builtin_templates() {
+            let pack = builtin(name).unwrap();
+            for file in ["IDENTITY.md", "SOUL.md", "HEARTBEAT.md"] {
+                assert!(pack.files.iter().any(|(p, _)| p == Path::new(file)), "{} lacks {}", name, file);
+            }
+        }
+    }
+
+    #[test]
+    fn test_manifest_rejects_escaping_paths() {
+        let ok = from_manifest("name: t\nfiles:\n  skills/a/SKILL.md: hi\n").unwrap();
+        assert_eq!(ok.files[0].0, PathBuf::from("skills/a/SKILL.md"));
+        assert!(from_manifest("name: t\nfiles:\n  ../evil.md: x\n").is_err());
+        assert!(from_manifest("name: t\nfiles:\n  /etc/passwd: x\n").is_err());
+    }
+
+    #[test]
+    fn test_manifest_limited_to_persona_files_and_skills() {
+        let ok = from_manifest("name: t\nfiles:\n  HEARTBEAT.md: a\n  SOUL.md: b\n  personas/pirate.md: c\n").unwrap();
+        assert_eq!(ok.files.len(), 3);
+        for path in ["state/approval_rules.json", "cron/jobs.json", "memory/users/alice.json", "MEMORY.md", "skills"] {
+            let manifest = format!("name: t\nfiles:\n  {}: x\n", path);
+            assert!(from_manifest(&manifest).is_err(), "{} accepted", path);
+        }
+
+        let pack = TemplatePack {
+            name: "t".to_string(),
+            description: None,
+            files: vec![(PathBuf::from("state/approval_rules.json"), "[]".to_string())],
+        };
+        let workspace = tempfile::tempdir().unwrap();
+        assert!(pack.apply(workspace.path(), true).is_err());
+        assert!(!workspace.path().join("state").exists());
+    }
+
+    #[test]
+    fn test_apply_keeps_existing_files() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(workspace.path().join("SOUL.md"), "mine").unwrap();
+        let pack = builtin("journal").unwrap();
+
+        let report = pack.apply(workspace.path(), false).unwrap();
+        assert_eq!(report.skipped, vec![PathBuf::from("SOUL.md")]);
+        assert_eq!(std::fs::read_to_string(workspace.path().join("SOUL.md")).unwrap(), "mine");
+        assert!(workspace.path().join("skills/daily-entry/SKILL.md").exists());
+
+        pack.apply(workspace.path(), true).unwrap();
+        assert_ne!(std::fs::read_to_string(workspace.path().join("SOUL.md")).unwrap(), "mine");
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_oversized_manifest_refused() {
+        let huge = format!("name: t\nfiles:\n  SOUL.md: \"{}\"\n", "x".repeat(MAX_MANIFEST_BYTES));
+        let server = axum::Router::new().route("/pack.yaml", axum::routing::get(move || async move { huge }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/pack.yaml", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, server).await });
+
+        let error = resolve(&url).await.unwrap_err();
+        assert!(error.to_string().contains("larger than 8 MB"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("skills/x")).unwrap();
+        std::fs::write(dir.path().join("IDENTITY.md"), "# Me").unwrap();
+        std::fs::write(dir.path().join("skills/x/SKILL.md"), "skill").unwrap();
+        std::fs::write(dir.path().join(".hidden"), "no").unwrap();
+        std::fs::create_dir_all(dir.path().join("cron")).unwrap();
+        std::fs::write(dir.path().join("cron/jobs.json"), "[]").unwrap();
+
+        let pack = resolve(dir.path().to_str().unwrap()).await.unwrap();
+        assert_eq!(pack.files.len(), 2);
+        assert!(resolve("no-such-template").await.is_err());
+    }
+}